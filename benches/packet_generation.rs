@@ -29,7 +29,7 @@ fn bench_tcp_packet_generation(c: &mut Criterion) {
     c.bench_function("tcp_syn_packet_generation", |b| {
         b.iter(|| {
             builder.build_packet(
-                black_box(PacketType::TcpSyn),
+                black_box(PacketType::TCP_SYN),
                 black_box(target_ip),
                 black_box(443)
             )
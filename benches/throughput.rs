@@ -48,6 +48,9 @@ fn bench_mixed_protocol_throughput(c: &mut Criterion) {
         tcp_ack_ratio: 0.2,
         icmp_ratio: 0.1,
         custom_ratio: 0.0,
+        arp_ratio: 0.0,
+        ipv6: None,
+        tcp_flags: Default::default(),
     };
     
     let mut builder = PacketBuilder::new(PacketSizeRange::new(64, 1400), protocol_mix);
@@ -59,8 +62,8 @@ fn bench_mixed_protocol_throughput(c: &mut Criterion) {
             for i in 0..1000 {
                 let packet_type = match i % 10 {
                     0..=3 => PacketType::Udp,
-                    4..=6 => PacketType::TcpSyn,
-                    7..=8 => PacketType::TcpAck,
+                    4..=6 => PacketType::TCP_SYN,
+                    7..=8 => PacketType::TCP_ACK,
                     _ => PacketType::Icmp,
                 };
                 
@@ -138,6 +138,9 @@ fn main() -> Result<()> {
         tcp_rst_ratio: 0.10,  // 10% TCP RST (connection resets)
         icmp_ratio: 0.05,     // 5% ICMP (ping, traceroute)
         custom_ratio: 0.0,
+        arp_ratio: 0.0,
+        ipv6: None,
+        tcp_flags: Default::default(),
     };
     
     println!("   UDP: {}% - Simulates DNS, VoIP, streaming", (realistic_mix.udp_ratio * 100.0) as u8);
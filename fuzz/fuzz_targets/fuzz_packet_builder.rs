@@ -95,11 +95,12 @@ fuzz_target!(|input: FuzzInput| {
     // Test with specific packet types to ensure robustness
     let packet_types = [
         PacketType::Udp,
-        PacketType::TcpSyn,
-        PacketType::TcpAck,
+        PacketType::TCP_SYN,
+        PacketType::TCP_ACK,
         PacketType::Icmp,
         PacketType::Ipv6Udp,
-        PacketType::Ipv6Tcp,
+        PacketType::IPV6_TCP_SYN,
+        PacketType::IPV6_TCP_ACK,
         PacketType::Ipv6Icmp,
         PacketType::Arp,
     ];
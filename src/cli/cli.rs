@@ -8,8 +8,11 @@ use std::str::FromStr;
 use tracing::info;
 
 use crate::constants::{defaults, MAX_THREADS};
-use crate::error::{ConfigError, Result, RouterFloodError};
-use crate::config::{Config, ExportFormat};
+use crate::error::{ConfigError, Result};
+use crate::config::{
+    load_scenario, load_trace, parse_field_override, validate_config, Config, ExportFormat, FrameTable, ImixProfile, RateProfile,
+    PortSelection, PortSpec, SizeDistribution, SourcePort, TcpOptions,
+};
 
 /// Generate comprehensive help text with examples
 fn get_long_help() -> &'static str {
@@ -38,6 +41,32 @@ fn get_long_help() -> &'static str {
   With configuration file and export:
     sudo ./router-flood --config my_test.yaml --export json
 
+  Multi-phase scenario (warmup, sustain, spike, cooldown):
+    sudo ./router-flood --target 192.168.1.1 --scenario regression.yaml --export csv
+
+  Replay the rate shape of a recorded incident (offset_seconds,pps CSV):
+    sudo ./router-flood --target 192.168.1.1 --trace incident.csv --threads 4
+
+  Send exactly 10 million packets for a reproducible comparison:
+    sudo ./router-flood --target 192.168.1.1 --ports 80 --rate 10000 --max-packets 10000000
+
+  Cisco IMIX frame sizes, or a custom frame-size table:
+    sudo ./router-flood --target 192.168.1.1 --ports 53 --rate 1000 --imix cisco
+    sudo ./router-flood --target 192.168.1.1 --ports 53 --rate 1000 --imix "64:7,594:4,1518:1"
+
+  Linux-like SYN options (MSS, SACK, timestamps, window scale):
+    sudo ./router-flood --target 192.168.1.1 --ports 443 --tcp-options linux
+
+  Sequential source ports for a NAT port allocation test:
+    sudo ./router-flood --target 192.168.1.1 --ports 53 --source-port sequential:40000-40999
+
+  TTL 1 with incrementing IP IDs, to probe TTL-expiry handling:
+    sudo ./router-flood --target 192.168.1.1 --ports 53 --field ipv4_ttl=1 --field ipv4_id=sequential
+
+  Sweep thousands of destination ports through a firewall ACL:
+    sudo ./router-flood --target 192.168.1.1 --ports 1000-5000,http,https --port-selection sequential
+    sudo ./router-flood --target 192.168.1.1 --ports "http:5,https:3,dns:1"
+
   List available interfaces:
     ./router-flood --list-interfaces
 
@@ -79,10 +108,24 @@ pub fn parse_arguments() -> ArgMatches {
                 .long("ports")
                 .short('p')
                 .value_name("PORTS")
-                .help("Target ports (comma-separated, e.g., 80,443,22)")
-                .long_help("Target ports for testing (comma-separated).\nCommon ports: 80 (HTTP), 443 (HTTPS), 22 (SSH), 53 (DNS), 21 (FTP), 25 (SMTP)")
+                .help("Target ports (comma-separated, e.g., 80,443,1000-2000,dns)")
+                .long_help("Target ports for testing (comma-separated).\n\
+                            Entries are ports, ranges (1000-2000), 'all' (1-65535) or service names\n\
+                            (http, https, dns, ssh, ...). Append :WEIGHT for weighted selection,\n\
+                            e.g. http:5,dns:1.\n\
+                            Common ports: 80 (HTTP), 443 (HTTPS), 22 (SSH), 53 (DNS), 21 (FTP), 25 (SMTP)")
                 .required_unless_present_any(["config", "list-interfaces"]),
         )
+        .arg(
+            Arg::new("port-selection")
+                .long("port-selection")
+                .value_name("POLICY")
+                .help("Destination port order: round-robin, random, weighted or sequential")
+                .long_help("How workers pick the next destination port: round-robin in the order\n\
+                            listed (default), random, weighted by the :WEIGHT of each entry, or a\n\
+                            sequential sweep through the distinct ports in ascending order.\n\
+                            Weights in --ports select weighted unless a policy is given."),
+        )
         .arg(
             Arg::new("threads")
                 .long("threads")
@@ -106,6 +149,28 @@ pub fn parse_arguments() -> ArgMatches {
                 .value_name("SECONDS")
                 .help("Test duration in seconds (default: unlimited)"),
         )
+        .arg(
+            Arg::new("max-packets")
+                .long("max-packets")
+                .value_name("COUNT")
+                .help("Stop after exactly this many packets have been sent"),
+        )
+        .arg(
+            Arg::new("max-bytes")
+                .long("max-bytes")
+                .value_name("BYTES")
+                .help("Stop before the bytes sent would exceed this limit"),
+        )
+        .arg(
+            Arg::new("imix")
+                .long("imix")
+                .value_name("PROFILE|TABLE")
+                .help("IMIX frame sizes: simple, cisco or size:weight pairs (e.g. 64:7,594:4,1518:1)")
+                .long_help("Draw packet sizes from an IMIX mix of Ethernet frame sizes (including FCS)\n\
+                            for every protocol. Presets: simple (64:7, 594:4, 1518:1) and\n\
+                            cisco (64:7, 570:4, 1518:1). Custom tables list size:weight pairs;\n\
+                            frames must be between 64 and 1518 bytes."),
+        )
         .arg(
             Arg::new("config")
                 .long("config")
@@ -113,6 +178,59 @@ pub fn parse_arguments() -> ArgMatches {
                 .value_name("FILE")
                 .help("YAML configuration file path"),
         )
+        .arg(
+            Arg::new("scenario")
+                .long("scenario")
+                .value_name("FILE")
+                .help("YAML scenario file with phases to run back to back")
+                .long_help("Run an ordered list of phases (e.g. warmup, sustain, spike, cooldown)\n\
+                            without restarting workers. Each phase sets its own duration and may\n\
+                            override rate, rate profile, protocol mix, ports and payload size.\n\
+                            Overrides --duration."),
+        )
+        .arg(
+            Arg::new("trace")
+                .long("trace")
+                .value_name("FILE")
+                .help("CSV rate trace to replay (offset_seconds, pps[, protocol mix ratios])")
+                .long_help("Follow a recorded rate timeline, interpolating between points.\n\
+                            Rates are aggregate pps split across threads; optional protocol mix\n\
+                            ratio columns (udp_ratio, tcp_syn_ratio, ...) switch the mix over time.\n\
+                            Without --duration the run ends at the last point."),
+        )
+        .arg(
+            Arg::new("tcp-options")
+                .long("tcp-options")
+                .value_name("PRESET|LIST")
+                .help("TCP options: none, linux, windows, macos or a list (e.g. mss:1460,sack,ts,nop,ws:7)")
+                .long_help("Options of generated TCP segments. Presets mimic the SYN options of\n\
+                            linux, windows and macos. Lists name options in order: mss:N,\n\
+                            window_scale:N (ws), sack_permitted (sack), timestamps (ts), nop, eol.\n\
+                            SYNs carry every option; other segments only the timestamps."),
+        )
+        .arg(
+            Arg::new("source-port")
+                .long("source-port")
+                .value_name("POLICY")
+                .help("UDP/TCP source ports: random, PORT, MIN-MAX, sequential or sequential:MIN-MAX")
+                .long_help("How UDP and TCP source ports are chosen: random ephemeral ports (default),\n\
+                            one fixed PORT, random ports within MIN-MAX, or ports counting up\n\
+                            through 1024-65535 or MIN-MAX. Each protocol of each worker counts\n\
+                            on its own."),
+        )
+        .arg(
+            Arg::new("field")
+                .long("field")
+                .value_name("FIELD=RULE")
+                .action(clap::ArgAction::Append)
+                .help("Header field rule, repeatable (e.g. ipv4_ttl=1, ipv4_id=sequential, tcp_window=0)")
+                .long_help("Set an IPv4, IPv6, TCP, UDP or ICMP header field for every packet. RULE is\n\
+                            random, a fixed VALUE, random within MIN-MAX, sequential[:START[:STEP]]\n\
+                            or a list VALUE,VALUE,... cycled in order. Fields: ipv4_tos, ipv4_id,\n\
+                            ipv4_flags, ipv4_ttl, ipv6_traffic_class, ipv6_flow_label,\n\
+                            ipv6_hop_limit, tcp_source_port, tcp_sequence, tcp_ack, tcp_window,\n\
+                            tcp_urgent, udp_source_port, icmp_id, icmp_sequence."),
+        )
         .arg(
             Arg::new("interface")
                 .long("interface")
@@ -182,7 +300,16 @@ pub fn process_cli_config(matches: &ArgMatches, mut config: Config) -> Result<Co
     }
 
     if let Some(ports_str) = matches.get_one::<String>("ports") {
-        config.target.ports = parse_ports(ports_str)?;
+        let spec = parse_ports(ports_str)?;
+        if !spec.weights.is_empty() {
+            config.target.port_selection = PortSelection::Weighted;
+        }
+        config.target.ports = spec.ports;
+        config.target.port_weights = spec.weights;
+    }
+
+    if let Some(selection_str) = matches.get_one::<String>("port-selection") {
+        config.target.port_selection = selection_str.parse::<PortSelection>()?;
     }
 
     if let Some(threads_str) = matches.get_one::<String>("threads") {
@@ -197,6 +324,33 @@ pub fn process_cli_config(matches: &ArgMatches, mut config: Config) -> Result<Co
         config.attack.duration = Some(parse_positive_number(duration_str, "duration")?);
     }
 
+    if let Some(packets_str) = matches.get_one::<String>("max-packets") {
+        config.attack.max_packets = Some(parse_positive_number(packets_str, "max-packets")?);
+    }
+
+    if let Some(bytes_str) = matches.get_one::<String>("max-bytes") {
+        config.attack.max_bytes = Some(parse_positive_number(bytes_str, "max-bytes")?);
+    }
+
+    if let Some(imix_str) = matches.get_one::<String>("imix") {
+        config.attack.payload_sizes.default = Some(parse_imix(imix_str)?);
+    }
+
+    if let Some(options_str) = matches.get_one::<String>("tcp-options") {
+        config.attack.tcp_options = options_str.parse::<TcpOptions>()?;
+    }
+
+    if let Some(port_str) = matches.get_one::<String>("source-port") {
+        config.source.port = port_str.parse::<SourcePort>()?;
+    }
+
+    if let Some(overrides) = matches.get_many::<String>("field") {
+        for spec in overrides {
+            let (field, rule) = parse_field_override(spec)?;
+            config.attack.header_fields.insert(field, rule);
+        }
+    }
+
     if let Some(interface) = matches.get_one::<String>("interface") {
         config.target.interface = Some(interface.to_string());
     }
@@ -234,6 +388,21 @@ pub fn process_cli_config(matches: &ArgMatches, mut config: Config) -> Result<Co
         info!("📡 BROADCAST MODE ENABLED - Broadcast addresses allowed");
     }
 
+    if let Some(trace_path) = matches.get_one::<String>("trace") {
+        let points = load_trace(trace_path)?;
+        info!("📈 Rate trace loaded from {} ({} points)", trace_path, points.len());
+        config.attack.rate_profile = RateProfile::Trace { points };
+    }
+
+    if let Some(scenario_path) = matches.get_one::<String>("scenario") {
+        config.scenario = Some(load_scenario(scenario_path)?);
+        info!("🎬 Scenario loaded from {}", scenario_path);
+    }
+
+    // Check the merged settings once every override is in place; scenario
+    // phases inherit them
+    validate_config(&config)?;
+
     Ok(config)
 }
 
@@ -246,19 +415,9 @@ pub fn handle_pre_execution_commands(matches: &ArgMatches) -> bool {
     false
 }
 
-/// Parse comma-separated ports
-pub fn parse_ports(ports_str: &str) -> Result<Vec<u16>> {
-    ports_str
-        .split(',')
-        .map(|s| {
-            s.trim()
-                .parse::<u16>()
-                .map_err(|_| ConfigError::new(
-                    format!("Invalid port value '{}': must be a valid port number (1-65535)", s.trim())
-                ))
-        })
-        .collect::<std::result::Result<Vec<_>, _>>()
-        .map_err(RouterFloodError::from)
+/// Parse comma-separated ports, ranges, service names and `port:weight` entries
+pub fn parse_ports(ports_str: &str) -> Result<PortSpec> {
+    ports_str.parse::<PortSpec>()
 }
 
 /// Parse positive numbers with field context
@@ -280,6 +439,19 @@ where
     Ok(value)
 }
 
+/// Parse an IMIX preset name or a `size:weight` frame table
+pub fn parse_imix(imix_str: &str) -> Result<SizeDistribution> {
+    if let Ok(profile) = imix_str.parse::<ImixProfile>() {
+        return Ok(SizeDistribution::imix(profile));
+    }
+    if !imix_str.contains(':') {
+        // Report unknown preset names rather than a table syntax error
+        return Err(imix_str.parse::<ImixProfile>().unwrap_err());
+    }
+    let frames = imix_str.parse::<FrameTable>()?;
+    Ok(SizeDistribution::Imix { profile: None, frames: Some(frames) })
+}
+
 /// Parse export format string
 pub fn parse_export_format(format_str: &str) -> Result<ExportFormat> {
     match format_str.to_lowercase().as_str() {
@@ -11,5 +11,6 @@ pub use cli::{
     handle_pre_execution_commands,
     parse_ports,
     parse_positive_number,
+    parse_imix,
     parse_export_format
 };
\ No newline at end of file
@@ -0,0 +1,138 @@
+//! Adaptive rate search configuration
+//!
+//! In adaptive mode the engine raises the per-thread rate step by step until
+//! a health signal degrades, then binary-searches between the last healthy
+//! and the first unhealthy rate. The run ends once the highest sustainable
+//! rate is known to within `precision`.
+
+use serde::{Deserialize, Serialize};
+
+use super::Config;
+use crate::constants::{defaults, MAX_PACKET_RATE};
+use crate::error::{ConfigError, Result};
+
+/// Closed-loop search for the highest sustainable rate
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AdaptiveSearch {
+    /// First per-thread rate to try
+    pub start_rate: f64,
+    /// Per-thread rate the search never exceeds
+    pub max_rate: f64,
+    /// Rate multiplier between healthy steps before the first degradation
+    #[serde(default = "default_growth_factor")]
+    pub growth_factor: f64,
+    /// How long each rate is held before its health is judged
+    #[serde(default = "default_step_duration_ms")]
+    pub step_duration_ms: u64,
+    /// Stop once the healthy/unhealthy bracket is narrower than this fraction
+    #[serde(default = "default_precision")]
+    pub precision: f64,
+    #[serde(default)]
+    pub health: HealthSignal,
+    /// Port for TCP health probes; defaults to the first target port
+    #[serde(default)]
+    pub probe_port: Option<u16>,
+    #[serde(default = "default_probe_interval_ms")]
+    pub probe_interval_ms: u64,
+    #[serde(default = "default_probe_timeout_ms")]
+    pub probe_timeout_ms: u64,
+}
+
+/// Signal that decides whether the target copes with a rate
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HealthSignal {
+    /// Local send failures as a fraction of send attempts
+    FailureRate { max_ratio: f64 },
+    /// Median round-trip time of TCP connection probes
+    ProbeRtt { max_rtt_ms: f64 },
+    /// Fraction of TCP connection probes the target answers (accept or reset)
+    ResponseRatio { min_ratio: f64 },
+}
+
+impl Default for HealthSignal {
+    fn default() -> Self {
+        HealthSignal::FailureRate { max_ratio: defaults::ADAPTIVE_MAX_FAILURE_RATIO }
+    }
+}
+
+impl HealthSignal {
+    /// Whether the signal is measured with TCP probes
+    pub fn uses_probes(&self) -> bool {
+        !matches!(self, HealthSignal::FailureRate { .. })
+    }
+}
+
+fn default_growth_factor() -> f64 {
+    defaults::ADAPTIVE_GROWTH_FACTOR
+}
+
+fn default_step_duration_ms() -> u64 {
+    defaults::ADAPTIVE_STEP_DURATION_MS
+}
+
+fn default_precision() -> f64 {
+    defaults::ADAPTIVE_PRECISION
+}
+
+fn default_probe_interval_ms() -> u64 {
+    defaults::ADAPTIVE_PROBE_INTERVAL_MS
+}
+
+fn default_probe_timeout_ms() -> u64 {
+    defaults::ADAPTIVE_PROBE_TIMEOUT_MS
+}
+
+/// Validate search parameters against the base configuration
+pub(super) fn validate_adaptive(search: &AdaptiveSearch, config: &Config) -> Result<()> {
+    if config.scenario.is_some() {
+        return Err(ConfigError::new("Adaptive rate search cannot be combined with a scenario").into());
+    }
+
+    let valid_rate = |rate: f64| rate.is_finite() && rate > 0.0 && rate <= MAX_PACKET_RATE as f64;
+    if !valid_rate(search.start_rate) || !valid_rate(search.max_rate) {
+        return Err(ConfigError::new(
+            format!("Adaptive start_rate and max_rate must be between 0 and {}", MAX_PACKET_RATE)
+        ).into());
+    }
+    if search.max_rate < search.start_rate {
+        return Err(ConfigError::new("Adaptive max_rate must be at least start_rate").into());
+    }
+
+    if !search.growth_factor.is_finite() || search.growth_factor <= 1.0 {
+        return Err(ConfigError::new("Adaptive growth_factor must be greater than 1.0").into());
+    }
+    if search.step_duration_ms == 0 {
+        return Err(ConfigError::new("Adaptive step_duration_ms must be greater than 0").into());
+    }
+    if !search.precision.is_finite() || search.precision <= 0.0 || search.precision >= 1.0 {
+        return Err(ConfigError::new("Adaptive precision must be between 0.0 and 1.0").into());
+    }
+
+    match search.health {
+        HealthSignal::FailureRate { max_ratio } if !(0.0..1.0).contains(&max_ratio) => {
+            return Err(ConfigError::new("Health max_ratio must be between 0.0 and 1.0").into());
+        }
+        HealthSignal::ProbeRtt { max_rtt_ms } if !max_rtt_ms.is_finite() || max_rtt_ms <= 0.0 => {
+            return Err(ConfigError::new("Health max_rtt_ms must be a positive number").into());
+        }
+        HealthSignal::ResponseRatio { min_ratio } if !(0.0..=1.0).contains(&min_ratio) || min_ratio == 0.0 => {
+            return Err(ConfigError::new("Health min_ratio must be between 0.0 and 1.0").into());
+        }
+        _ => {}
+    }
+
+    if search.health.uses_probes() {
+        if search.probe_interval_ms == 0 || search.probe_timeout_ms == 0 {
+            return Err(ConfigError::new("Probe interval and timeout must be greater than 0 ms").into());
+        }
+        if search.probe_interval_ms >= search.step_duration_ms {
+            return Err(ConfigError::new("Probe interval must be shorter than the search step").into());
+        }
+        if search.probe_port == Some(0) {
+            return Err(ConfigError::new("Probe port must not be 0").into());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+//! ARP settings
+//!
+//! `arp.types` weights the ARP variants of generated ARP frames; without it
+//! every frame is a request for the target. The remaining fields override
+//! the sender and target fields of every variant, e.g. to claim another
+//! host's address when testing dynamic ARP inspection.
+
+use pnet::util::MacAddr;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr};
+
+use crate::error::{ConfigError, Result};
+use crate::security::validation::is_private_ip;
+
+/// ARP variants that can be generated
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ArpKind {
+    /// Broadcast request for the target address
+    Request,
+    /// Unsolicited reply to the target, claiming the sender address
+    Reply,
+    /// Broadcast announcement of the sender address (sender IP = target IP)
+    Gratuitous,
+    /// RFC 5227 probe for the target address, with sender IP 0.0.0.0
+    Probe,
+}
+
+/// ARP variant weights and field overrides
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ArpConfig {
+    /// Weights of ARP variants, e.g. `reply: 0.5`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub types: BTreeMap<ArpKind, f64>,
+    /// Sender hardware address; defaults to the interface MAC or a random
+    /// locally administered one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_mac: Option<String>,
+    /// Sender protocol address; defaults to the IPv4 source address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_ip: Option<Ipv4Addr>,
+    /// Target hardware address of replies; defaults to broadcast
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_mac: Option<String>,
+    /// Target protocol address; defaults to the target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_ip: Option<Ipv4Addr>,
+}
+
+impl ArpConfig {
+    /// Whether nothing is configured: requests with default fields
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Parse a MAC address such as `02:00:00:00:00:01`
+pub fn parse_mac(field: &str, mac: &str) -> Result<MacAddr> {
+    mac.trim().parse::<MacAddr>().map_err(|_| {
+        ConfigError::new(format!("Invalid ARP {} '{}': expected six hex bytes like 02:00:00:00:00:01", field, mac)).into()
+    })
+}
+
+/// Check that ARP weights, addresses and MACs are usable
+pub(super) fn validate_arp(arp: &ArpConfig) -> Result<()> {
+    super::icmp::validate_weights("ARP", arp.types.values())?;
+
+    if let Some(ref mac) = arp.sender_mac {
+        parse_mac("sender_mac", mac)?;
+    }
+    if let Some(ref mac) = arp.target_mac {
+        parse_mac("target_mac", mac)?;
+    }
+    for (field, ip) in [("sender_ip", arp.sender_ip), ("target_ip", arp.target_ip)] {
+        if let Some(ip) = ip.filter(|ip| !is_private_ip(&IpAddr::V4(*ip))) {
+            return Err(ConfigError::new(format!("ARP {} '{}' must be a private address", field, ip)).into());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,240 @@
+//! Per-field header override rules
+//!
+//! `header_fields` maps IPv4, IPv6, TCP, UDP and ICMP header fields to a
+//! rule that replaces the value the strategies would otherwise pick. Fields
+//! without a rule keep their usual values. On the command line a rule is
+//! written `FIELD=RULE`, as in `ipv4_ttl=1` or `ipv4_id=sequential`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::{ConfigError, Result, RouterFloodError};
+
+/// A header field that rules can set
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderField {
+    /// IPv4 type of service (DSCP and ECN)
+    Ipv4Tos,
+    Ipv4Id,
+    /// The three IPv4 flag bits; 2 is don't-fragment
+    Ipv4Flags,
+    Ipv4Ttl,
+    Ipv6TrafficClass,
+    Ipv6FlowLabel,
+    Ipv6HopLimit,
+    TcpSourcePort,
+    TcpSequence,
+    /// Acknowledgment number, written even without ACK when set
+    TcpAck,
+    TcpWindow,
+    /// Urgent pointer, written even without URG when set
+    TcpUrgent,
+    UdpSourcePort,
+    /// Identifier of ICMP and ICMPv6 queries
+    IcmpId,
+    /// Sequence number of ICMP and ICMPv6 queries
+    IcmpSequence,
+}
+
+impl HeaderField {
+    pub const ALL: [HeaderField; 15] = [
+        HeaderField::Ipv4Tos,
+        HeaderField::Ipv4Id,
+        HeaderField::Ipv4Flags,
+        HeaderField::Ipv4Ttl,
+        HeaderField::Ipv6TrafficClass,
+        HeaderField::Ipv6FlowLabel,
+        HeaderField::Ipv6HopLimit,
+        HeaderField::TcpSourcePort,
+        HeaderField::TcpSequence,
+        HeaderField::TcpAck,
+        HeaderField::TcpWindow,
+        HeaderField::TcpUrgent,
+        HeaderField::UdpSourcePort,
+        HeaderField::IcmpId,
+        HeaderField::IcmpSequence,
+    ];
+
+    /// Name used in configuration files and on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            HeaderField::Ipv4Tos => "ipv4_tos",
+            HeaderField::Ipv4Id => "ipv4_id",
+            HeaderField::Ipv4Flags => "ipv4_flags",
+            HeaderField::Ipv4Ttl => "ipv4_ttl",
+            HeaderField::Ipv6TrafficClass => "ipv6_traffic_class",
+            HeaderField::Ipv6FlowLabel => "ipv6_flow_label",
+            HeaderField::Ipv6HopLimit => "ipv6_hop_limit",
+            HeaderField::TcpSourcePort => "tcp_source_port",
+            HeaderField::TcpSequence => "tcp_sequence",
+            HeaderField::TcpAck => "tcp_ack",
+            HeaderField::TcpWindow => "tcp_window",
+            HeaderField::TcpUrgent => "tcp_urgent",
+            HeaderField::UdpSourcePort => "udp_source_port",
+            HeaderField::IcmpId => "icmp_id",
+            HeaderField::IcmpSequence => "icmp_sequence",
+        }
+    }
+
+    /// Largest value the field holds
+    pub fn max_value(self) -> u32 {
+        match self {
+            HeaderField::Ipv4Flags => 0x7,
+            HeaderField::Ipv6FlowLabel => 0xf_ffff,
+            HeaderField::Ipv4Tos | HeaderField::Ipv4Ttl | HeaderField::Ipv6TrafficClass | HeaderField::Ipv6HopLimit => {
+                u8::MAX as u32
+            }
+            HeaderField::TcpSequence | HeaderField::TcpAck => u32::MAX,
+            HeaderField::Ipv4Id
+            | HeaderField::TcpSourcePort
+            | HeaderField::TcpWindow
+            | HeaderField::TcpUrgent
+            | HeaderField::UdpSourcePort
+            | HeaderField::IcmpId
+            | HeaderField::IcmpSequence => u16::MAX as u32,
+        }
+    }
+}
+
+impl fmt::Display for HeaderField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for HeaderField {
+    type Err = RouterFloodError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        let name = name.trim().to_lowercase().replace('-', "_");
+        HeaderField::ALL.into_iter().find(|field| field.name() == name).ok_or_else(|| {
+            let names: Vec<&str> = HeaderField::ALL.iter().map(|field| field.name()).collect();
+            ConfigError::new(format!("Unknown header field '{}': must be one of {}", name, names.join(", "))).into()
+        })
+    }
+}
+
+/// How the values of a header field are chosen
+///
+/// Each protocol of each worker is a stream with its own sequential counter
+/// and list position.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FieldRule {
+    /// Uniformly random over the whole field for every packet
+    #[default]
+    Random,
+    /// The same value for every packet
+    Fixed { value: u32 },
+    /// Random value within `min..=max` for every packet
+    Range { min: u32, max: u32 },
+    /// Counts up from `start` by `step`, wrapping at the field's maximum
+    Sequential {
+        #[serde(default)]
+        start: u32,
+        #[serde(default = "default_step")]
+        step: u32,
+    },
+    /// Cycles through the values in the order listed
+    List { values: Vec<u32> },
+}
+
+fn default_step() -> u32 {
+    1
+}
+
+/// Parses `random`, `VALUE`, `MIN-MAX`, `sequential[:START[:STEP]]` or
+/// `VALUE,VALUE,...`
+impl FromStr for FieldRule {
+    type Err = RouterFloodError;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid = || -> RouterFloodError {
+            ConfigError::new(format!(
+                "Invalid field rule '{}': expected random, VALUE, MIN-MAX, sequential[:START[:STEP]] or VALUE,VALUE,...",
+                spec
+            )).into()
+        };
+        let value = |value: &str| value.trim().parse::<u32>().map_err(|_| invalid());
+
+        let spec_lower = spec.trim().to_lowercase();
+        match spec_lower.as_str() {
+            "random" => Ok(FieldRule::Random),
+            "sequential" => Ok(FieldRule::Sequential { start: 0, step: default_step() }),
+            other => {
+                if let Some(bounds) = other.strip_prefix("sequential:") {
+                    let (start, step) = match bounds.split_once(':') {
+                        Some((start, step)) => (value(start)?, value(step)?),
+                        None => (value(bounds)?, default_step()),
+                    };
+                    Ok(FieldRule::Sequential { start, step })
+                } else if other.contains(',') {
+                    let values = other.split(',').map(value).collect::<Result<Vec<u32>>>()?;
+                    Ok(FieldRule::List { values })
+                } else if let Some((min, max)) = other.split_once('-') {
+                    Ok(FieldRule::Range { min: value(min)?, max: value(max)? })
+                } else {
+                    Ok(FieldRule::Fixed { value: value(other)? })
+                }
+            }
+        }
+    }
+}
+
+/// Parse a `FIELD=RULE` override, as given on the command line
+pub fn parse_field_override(spec: &str) -> Result<(HeaderField, FieldRule)> {
+    let (field, rule) = spec.split_once('=').ok_or_else(|| {
+        ConfigError::new(format!("Invalid header field override '{}': expected FIELD=RULE", spec))
+    })?;
+    Ok((field.parse()?, rule.parse()?))
+}
+
+/// Check that every rule stays within its field
+pub(super) fn validate_header_fields(rules: &BTreeMap<HeaderField, FieldRule>) -> Result<()> {
+    for (&field, rule) in rules {
+        let max = field.max_value();
+        let out_of_range = |value: u32| -> RouterFloodError {
+            ConfigError::new(format!("Value {} of header field {} is above its maximum of {}", value, field, max)).into()
+        };
+        match rule {
+            FieldRule::Random => {}
+            FieldRule::Fixed { value } => {
+                if *value > max {
+                    return Err(out_of_range(*value));
+                }
+            }
+            FieldRule::Range { min, max: range_max } => {
+                if min > range_max {
+                    return Err(ConfigError::new(format!(
+                        "Range of header field {} starts above its end: {}-{}", field, min, range_max
+                    )).into());
+                }
+                if *range_max > max {
+                    return Err(out_of_range(*range_max));
+                }
+            }
+            FieldRule::Sequential { start, step } => {
+                if *start > max {
+                    return Err(out_of_range(*start));
+                }
+                if *step == 0 || *step > max {
+                    return Err(ConfigError::new(format!(
+                        "Step of header field {} must be between 1 and {}, got {}", field, max, step
+                    )).into());
+                }
+            }
+            FieldRule::List { values } => {
+                if values.is_empty() {
+                    return Err(ConfigError::new(format!("Value list of header field {} is empty", field)).into());
+                }
+                if let Some(&value) = values.iter().find(|&&value| value > max) {
+                    return Err(out_of_range(value));
+                }
+            }
+        }
+    }
+    Ok(())
+}
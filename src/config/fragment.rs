@@ -0,0 +1,78 @@
+//! IPv4 fragmentation settings
+//!
+//! `fragmentation` splits IPv4 datagrams into fragments with real offsets
+//! and more-fragments bits, sent in the order or shape of a pattern, to
+//! exercise reassembly buffers and fragment-attack defenses.
+
+use serde::{Deserialize, Serialize};
+
+use crate::constants::fragment;
+use crate::error::{ConfigError, Result};
+
+/// Order and shape of the fragments of a datagram
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FragmentPattern {
+    /// Fragments in offset order
+    #[default]
+    InOrder,
+    /// Last fragment first
+    Reversed,
+    /// Fragments shuffled per datagram
+    Random,
+    /// Every fragment after the first repeats the last 8 bytes of the one
+    /// before, with those bytes inverted
+    Overlapping,
+    /// A first fragment of 8 bytes, splitting the transport header (RFC 1858)
+    TinyFirst,
+    /// In offset order, but the last fragment is never sent
+    MissingLast,
+}
+
+/// Fragmentation of IPv4 datagrams
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct FragmentationConfig {
+    #[serde(default)]
+    pub pattern: FragmentPattern,
+    /// Payload bytes per fragment, a multiple of 8
+    #[serde(default = "default_fragment_size")]
+    pub fragment_size: usize,
+    /// Share of IPv4 datagrams that are fragmented
+    #[serde(default = "default_ratio")]
+    pub ratio: f64,
+}
+
+fn default_fragment_size() -> usize {
+    fragment::DEFAULT_FRAGMENT_SIZE
+}
+
+fn default_ratio() -> f64 {
+    1.0
+}
+
+impl Default for FragmentationConfig {
+    fn default() -> Self {
+        Self {
+            pattern: FragmentPattern::default(),
+            fragment_size: default_fragment_size(),
+            ratio: default_ratio(),
+        }
+    }
+}
+
+/// Check that fragments fall on 8-byte offsets and the ratio is a fraction
+pub(super) fn validate_fragmentation(fragmentation: &FragmentationConfig) -> Result<()> {
+    let size = fragmentation.fragment_size;
+    if size == 0 || !size.is_multiple_of(fragment::OFFSET_UNIT) || size > fragment::MAX_FRAGMENT_SIZE {
+        return Err(ConfigError::new(format!(
+            "Fragment size {} must be a multiple of {} between {} and {}",
+            size, fragment::OFFSET_UNIT, fragment::OFFSET_UNIT, fragment::MAX_FRAGMENT_SIZE
+        )).into());
+    }
+    if !(0.0..=1.0).contains(&fragmentation.ratio) {
+        return Err(ConfigError::new(format!(
+            "Fragmentation ratio must be between 0.0 and 1.0, got {}", fragmentation.ratio
+        )).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,212 @@
+//! ICMP message settings
+//!
+//! `icmp_types` weights the ICMP messages of generated IPv4 ICMP packets,
+//! keyed by message name with an optional code, such as `timestamp` or
+//! `dest_unreachable:3`. Without it, every ICMP packet is an echo request.
+//!
+//! `icmpv6_types` does the same for IPv6 ICMP packets, adding Neighbor
+//! Discovery and MLD messages to echo requests.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::constants::{icmp, icmpv6};
+use crate::error::{ConfigError, Result, RouterFloodError};
+
+/// ICMP message types that can be generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum IcmpKind {
+    EchoRequest,
+    /// Timestamp request with the originate timestamp set
+    Timestamp,
+    /// Address mask request
+    AddressMask,
+    /// Destination unreachable, quoting a datagram from the target
+    DestUnreachable,
+    /// Time exceeded, quoting a datagram from the target
+    TimeExceeded,
+    /// Parameter problem, quoting a datagram from the target
+    ParameterProblem,
+}
+
+impl IcmpKind {
+    const NAMES: &'static [(&'static str, IcmpKind)] = &[
+        ("echo_request", IcmpKind::EchoRequest),
+        ("timestamp", IcmpKind::Timestamp),
+        ("address_mask", IcmpKind::AddressMask),
+        ("dest_unreachable", IcmpKind::DestUnreachable),
+        ("time_exceeded", IcmpKind::TimeExceeded),
+        ("parameter_problem", IcmpKind::ParameterProblem),
+    ];
+
+    /// Type number in the ICMP header
+    pub const fn type_number(self) -> u8 {
+        match self {
+            IcmpKind::EchoRequest => icmp::TYPE_ECHO_REQUEST,
+            IcmpKind::Timestamp => icmp::TYPE_TIMESTAMP,
+            IcmpKind::AddressMask => icmp::TYPE_ADDRESS_MASK,
+            IcmpKind::DestUnreachable => icmp::TYPE_DEST_UNREACHABLE,
+            IcmpKind::TimeExceeded => icmp::TYPE_TIME_EXCEEDED,
+            IcmpKind::ParameterProblem => icmp::TYPE_PARAMETER_PROBLEM,
+        }
+    }
+
+    /// Highest code defined for the type
+    pub const fn max_code(self) -> u8 {
+        match self {
+            IcmpKind::DestUnreachable => icmp::MAX_UNREACHABLE_CODE,
+            IcmpKind::TimeExceeded => 1,
+            IcmpKind::ParameterProblem => 2,
+            _ => 0,
+        }
+    }
+
+    /// Whether the message is an error that quotes the offending datagram
+    pub const fn is_error(self) -> bool {
+        matches!(self, IcmpKind::DestUnreachable | IcmpKind::TimeExceeded | IcmpKind::ParameterProblem)
+    }
+
+    fn name(self) -> &'static str {
+        Self::NAMES.iter()
+            .find(|&&(_, kind)| kind == self)
+            .map(|&(name, _)| name)
+            .unwrap_or("echo_request")
+    }
+}
+
+/// An ICMP message type with an optional fixed code
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct IcmpMessage {
+    pub kind: IcmpKind,
+    /// Without a code, error messages pick a random defined code per packet
+    pub code: Option<u8>,
+}
+
+impl IcmpMessage {
+    /// Common messages with a name of their own
+    const ALIASES: &'static [(&'static str, IcmpKind, u8)] = &[
+        ("net_unreachable", IcmpKind::DestUnreachable, 0),
+        ("host_unreachable", IcmpKind::DestUnreachable, 1),
+        ("protocol_unreachable", IcmpKind::DestUnreachable, 2),
+        ("port_unreachable", IcmpKind::DestUnreachable, 3),
+        ("frag_needed", IcmpKind::DestUnreachable, icmp::CODE_FRAG_NEEDED),
+        ("admin_prohibited", IcmpKind::DestUnreachable, 13),
+        ("ttl_exceeded", IcmpKind::TimeExceeded, 0),
+        ("reassembly_exceeded", IcmpKind::TimeExceeded, 1),
+    ];
+
+    pub const fn new(kind: IcmpKind) -> Self {
+        Self { kind, code: None }
+    }
+
+    pub const fn with_code(kind: IcmpKind, code: u8) -> Self {
+        Self { kind, code: Some(code) }
+    }
+}
+
+/// Parses `name` or `name:code`, e.g. `timestamp`, `dest_unreachable:3` or
+/// an alias such as `port_unreachable`
+impl FromStr for IcmpMessage {
+    type Err = RouterFloodError;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let spec = spec.trim().to_lowercase().replace('-', "_");
+        if let Some(&(_, kind, code)) = Self::ALIASES.iter().find(|(name, _, _)| *name == spec) {
+            return Ok(Self::with_code(kind, code));
+        }
+
+        let (name, code) = match spec.split_once(':') {
+            Some((name, code)) => (name.trim(), Some(code.trim())),
+            None => (spec.as_str(), None),
+        };
+        let kind = IcmpKind::NAMES.iter()
+            .find(|(known, _)| *known == name)
+            .map(|&(_, kind)| kind)
+            .ok_or_else(|| ConfigError::new(format!(
+                "Unknown ICMP message '{}': expected echo_request, timestamp, address_mask, \
+                 dest_unreachable, time_exceeded or parameter_problem, optionally with :code",
+                spec
+            )))?;
+
+        let code = match code {
+            None => None,
+            Some(code) => Some(code.parse::<u8>().ok().filter(|&code| code <= kind.max_code()).ok_or_else(|| {
+                ConfigError::new(format!(
+                    "Invalid ICMP code '{}' for {}: must be between 0 and {}", code, name, kind.max_code()
+                ))
+            })?),
+        };
+        Ok(Self { kind, code })
+    }
+}
+
+impl TryFrom<String> for IcmpMessage {
+    type Error = RouterFloodError;
+
+    fn try_from(spec: String) -> Result<Self> {
+        spec.parse()
+    }
+}
+
+impl From<IcmpMessage> for String {
+    fn from(message: IcmpMessage) -> Self {
+        message.to_string()
+    }
+}
+
+impl fmt::Display for IcmpMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "{}:{}", self.kind.name(), code),
+            None => write!(f, "{}", self.kind.name()),
+        }
+    }
+}
+
+/// ICMPv6 message types that can be generated
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Icmpv6Kind {
+    EchoRequest,
+    /// Neighbor Solicitation for the target, sent to its solicited-node group
+    NeighborSolicitation,
+    /// Router Solicitation, sent to all-routers
+    RouterSolicitation,
+    /// MLDv2 report joining a random group, sent to all MLDv2 routers
+    MldReport,
+}
+
+impl Icmpv6Kind {
+    /// Type number in the ICMPv6 header
+    pub const fn type_number(self) -> u8 {
+        match self {
+            Icmpv6Kind::EchoRequest => icmpv6::TYPE_ECHO_REQUEST,
+            Icmpv6Kind::NeighborSolicitation => icmpv6::TYPE_NEIGHBOR_SOLICITATION,
+            Icmpv6Kind::RouterSolicitation => icmpv6::TYPE_ROUTER_SOLICITATION,
+            Icmpv6Kind::MldReport => icmpv6::TYPE_MLDV2_REPORT,
+        }
+    }
+}
+
+/// Check that ICMP and ICMPv6 message weights are usable
+pub(super) fn validate_icmp_types(
+    types: &BTreeMap<IcmpMessage, f64>,
+    icmpv6_types: &BTreeMap<Icmpv6Kind, f64>,
+) -> Result<()> {
+    validate_weights("ICMP", types.values())?;
+    validate_weights("ICMPv6", icmpv6_types.values())
+}
+
+/// Check that message weights are non-negative and not all 0
+pub(super) fn validate_weights<'a>(family: &str, weights: impl Iterator<Item = &'a f64> + Clone) -> Result<()> {
+    if weights.clone().any(|weight| !weight.is_finite() || *weight < 0.0) {
+        return Err(ConfigError::new(format!("{} message weights must be non-negative numbers", family)).into());
+    }
+    if weights.clone().next().is_some() && weights.sum::<f64>() <= 0.0 {
+        return Err(ConfigError::new(format!("{} message weights must not all be 0", family)).into());
+    }
+    Ok(())
+}
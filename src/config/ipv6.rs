@@ -0,0 +1,201 @@
+//! IPv6 extension header settings
+//!
+//! `ipv6_extension_headers` puts a chain of extension headers between the
+//! IPv6 header and the upper-layer header of every IPv6 packet, in the order
+//! listed, e.g. `hop_by_hop, routing:4:3, fragment, destination_options:2`.
+//! A `*N` suffix repeats an entry, so `destination_options*100` builds a
+//! chain of 100 headers. Routers often hand such packets to their CPU.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::constants::ipv6_extension;
+use crate::error::{ConfigError, Result, RouterFloodError};
+
+/// A single IPv6 extension header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ipv6Extension {
+    /// Hop-by-Hop Options header of the given number of 8-byte units, padded
+    HopByHop(u16),
+    /// Destination Options header of the given number of 8-byte units, padded
+    DestinationOptions(u16),
+    /// Routing header of type 0, 2 or 4 listing `segments` addresses
+    Routing { routing_type: u8, segments: u8 },
+    /// Atomic fragment: offset 0, no more fragments
+    Fragment,
+}
+
+impl Ipv6Extension {
+    /// Protocol number naming the header in the preceding next header field
+    pub fn protocol(self) -> u8 {
+        match self {
+            Ipv6Extension::HopByHop(_) => ipv6_extension::HOP_BY_HOP,
+            Ipv6Extension::DestinationOptions(_) => ipv6_extension::DESTINATION_OPTIONS,
+            Ipv6Extension::Routing { .. } => ipv6_extension::ROUTING,
+            Ipv6Extension::Fragment => ipv6_extension::FRAGMENT,
+        }
+    }
+
+    /// Encoded length in bytes
+    pub fn encoded_len(self) -> usize {
+        match self {
+            Ipv6Extension::HopByHop(units) | Ipv6Extension::DestinationOptions(units) => {
+                units as usize * ipv6_extension::UNIT
+            }
+            Ipv6Extension::Routing { segments, .. } => ipv6_extension::UNIT + segments as usize * 16,
+            Ipv6Extension::Fragment => ipv6_extension::FRAGMENT_SIZE,
+        }
+    }
+}
+
+impl FromStr for Ipv6Extension {
+    type Err = RouterFloodError;
+
+    fn from_str(entry: &str) -> Result<Self> {
+        let entry = entry.trim();
+        let mut parts = entry.split(':').map(str::trim);
+        let name = parts.next().unwrap_or_default().to_lowercase();
+        let values: Vec<&str> = parts.collect();
+        let invalid = |reason: &str| -> RouterFloodError {
+            ConfigError::new(format!("Invalid IPv6 extension header '{}': {}", entry, reason)).into()
+        };
+        let units = |values: &[&str]| -> Result<u16> {
+            match values {
+                [] => Ok(1),
+                [units] => units.parse::<u16>().ok()
+                    .filter(|units| (1..=ipv6_extension::MAX_OPTIONS_UNITS).contains(units))
+                    .ok_or_else(|| invalid(&format!(
+                        "length must be 1 to {} units of 8 bytes", ipv6_extension::MAX_OPTIONS_UNITS
+                    ))),
+                _ => Err(invalid("expected at most one length")),
+            }
+        };
+
+        match name.as_str() {
+            "hop_by_hop" | "hbh" => units(&values).map(Ipv6Extension::HopByHop),
+            "destination_options" | "dest_opts" => units(&values).map(Ipv6Extension::DestinationOptions),
+            "fragment" | "frag" if values.is_empty() => Ok(Ipv6Extension::Fragment),
+            "routing" => {
+                let (routing_type, segments) = match values.as_slice() {
+                    [routing_type] => (*routing_type, "1"),
+                    [routing_type, segments] => (*routing_type, *segments),
+                    _ => return Err(invalid("expected routing:TYPE or routing:TYPE:SEGMENTS")),
+                };
+                let routing_type = match routing_type.parse::<u8>() {
+                    Ok(routing_type @ (ipv6_extension::ROUTING_TYPE_SOURCE_ROUTE
+                        | ipv6_extension::ROUTING_TYPE_MOBILE_IPV6
+                        | ipv6_extension::ROUTING_TYPE_SEGMENT_ROUTING)) => routing_type,
+                    _ => return Err(invalid("routing type must be 0, 2 or 4")),
+                };
+                let segments = segments.parse::<u8>().ok()
+                    .filter(|segments| (1..=ipv6_extension::MAX_ROUTING_SEGMENTS).contains(segments))
+                    .ok_or_else(|| invalid(&format!(
+                        "routing headers list 1 to {} addresses", ipv6_extension::MAX_ROUTING_SEGMENTS
+                    )))?;
+                if routing_type == ipv6_extension::ROUTING_TYPE_MOBILE_IPV6 && segments != 1 {
+                    return Err(invalid("type 2 routing headers carry exactly one address"));
+                }
+                Ok(Ipv6Extension::Routing { routing_type, segments })
+            }
+            _ => Err(invalid(
+                "expected hop_by_hop[:UNITS], destination_options[:UNITS], routing:TYPE[:SEGMENTS] or fragment",
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Ipv6Extension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Ipv6Extension::HopByHop(1) => write!(f, "hop_by_hop"),
+            Ipv6Extension::HopByHop(units) => write!(f, "hop_by_hop:{}", units),
+            Ipv6Extension::DestinationOptions(1) => write!(f, "destination_options"),
+            Ipv6Extension::DestinationOptions(units) => write!(f, "destination_options:{}", units),
+            Ipv6Extension::Routing { routing_type, segments: 1 } => write!(f, "routing:{}", routing_type),
+            Ipv6Extension::Routing { routing_type, segments } => write!(f, "routing:{}:{}", routing_type, segments),
+            Ipv6Extension::Fragment => write!(f, "fragment"),
+        }
+    }
+}
+
+/// Extension headers written as a comma-separated list, e.g. `hop_by_hop, fragment*3`
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Ipv6ExtensionChain(pub Vec<Ipv6Extension>);
+
+impl Ipv6ExtensionChain {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Encoded length of the whole chain
+    pub fn encoded_len(&self) -> usize {
+        self.0.iter().map(|header| header.encoded_len()).sum()
+    }
+}
+
+impl FromStr for Ipv6ExtensionChain {
+    type Err = RouterFloodError;
+
+    fn from_str(list: &str) -> Result<Self> {
+        let mut chain = Vec::new();
+        for entry in list.split(',').filter(|entry| !entry.trim().is_empty()) {
+            let (header, count) = match entry.split_once('*') {
+                Some((header, count)) => {
+                    let count = count.trim().parse::<usize>().ok().filter(|&count| count > 0).ok_or_else(|| {
+                        ConfigError::new(format!(
+                            "Invalid IPv6 extension header '{}': repeat count must be a positive number", entry.trim()
+                        ))
+                    })?;
+                    (header, count)
+                }
+                None => (entry, 1),
+            };
+            let header: Ipv6Extension = header.parse()?;
+            chain.extend(std::iter::repeat_n(header, count));
+        }
+        Ok(Ipv6ExtensionChain(chain))
+    }
+}
+
+impl TryFrom<String> for Ipv6ExtensionChain {
+    type Error = RouterFloodError;
+
+    fn try_from(list: String) -> Result<Self> {
+        list.parse()
+    }
+}
+
+impl From<Ipv6ExtensionChain> for String {
+    fn from(chain: Ipv6ExtensionChain) -> Self {
+        chain.to_string()
+    }
+}
+
+/// Writes runs of the same header once, with a `*N` count
+impl fmt::Display for Ipv6ExtensionChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, run) in self.0.chunk_by(|a, b| a == b).enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", run[0])?;
+            if run.len() > 1 {
+                write!(f, "*{}", run.len())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check that the chain leaves room for the rest of the packet
+pub(super) fn validate_ipv6_extensions(chain: &Ipv6ExtensionChain) -> Result<()> {
+    if chain.encoded_len() > ipv6_extension::MAX_CHAIN_SIZE {
+        return Err(ConfigError::new(format!(
+            "IPv6 extension headers take {} bytes, more than the maximum of {}",
+            chain.encoded_len(), ipv6_extension::MAX_CHAIN_SIZE
+        )).into());
+    }
+    Ok(())
+}
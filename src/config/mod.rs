@@ -3,37 +3,79 @@
 //! This module provides configuration structures and validation.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 use tracing::{info, warn};
 
 use crate::constants::{
     defaults, MAX_THREADS, MAX_PACKET_RATE, RECOMMENDED_MAX_RATE, MIN_PAYLOAD_SIZE, MAX_PAYLOAD_SIZE,
-    DEFAULT_CONFIG_FILE, DEFAULT_EXPORT_INTERVAL,
+    DEFAULT_CONFIG_FILE, DEFAULT_EXPORT_INTERVAL, MAX_SPIN_THRESHOLD_US,
 };
 use crate::error::{ConfigError, Result};
 
+pub mod adaptive;
+pub mod arp;
+pub mod fields;
+pub mod fragment;
+pub mod icmp;
+pub mod ipv6;
+pub mod payload;
+pub mod ports;
+pub mod scenario;
+pub mod source;
+pub mod tcp;
+pub mod trace;
+
+pub use adaptive::{AdaptiveSearch, HealthSignal};
+pub use arp::{ArpConfig, ArpKind};
+pub use fields::{parse_field_override, FieldRule, HeaderField};
+pub use fragment::{FragmentPattern, FragmentationConfig};
+pub use icmp::{IcmpKind, IcmpMessage, Icmpv6Kind};
+pub use ipv6::{Ipv6Extension, Ipv6ExtensionChain};
+pub use payload::{FrameTable, ImixProfile, PayloadPattern, PayloadSizes, SizeDistribution, WeightedSize};
+pub use ports::{format_ports, service_port, PortSelection, PortSpec};
+pub use scenario::{load_scenario, Scenario, ScenarioPhase};
+pub use source::{SourceAddress, SourceConfig, SourcePort, SourceSelection};
+pub use tcp::{TcpFlagSet, TcpOption, TcpOptionList, TcpOptions, TcpOsProfile, TcpPayload};
+pub use trace::{load_trace, TracePoint};
+
 /// Main configuration structure
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     pub target: Target,
+    /// Source addresses of generated packets
+    #[serde(default)]
+    pub source: SourceConfig,
     pub attack: LoadConfig,
     pub safety: Safety,
     pub monitoring: Monitoring,
     pub export: Export,
     pub audit: Audit,
+    #[serde(default)]
+    pub scenario: Option<Scenario>,
+    #[serde(default)]
+    pub adaptive: Option<AdaptiveSearch>,
 }
 
 /// Target configuration
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Target {
     pub ip: String,
+    /// Destination ports: numbers, ranges, `all` or service names
+    #[serde(deserialize_with = "ports::deserialize_ports")]
     pub ports: Vec<u16>,
+    /// How workers pick the next destination port
+    #[serde(default)]
+    pub port_selection: PortSelection,
+    /// Relative weights for weighted selection; unlisted ports weigh 1
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub port_weights: BTreeMap<u16, u32>,
     pub protocol_mix: ProtocolMix,
     pub interface: Option<String>,
 }
 
 /// Protocol distribution configuration
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct ProtocolMix {
     pub udp_ratio: f64,
     pub tcp_syn_ratio: f64,
@@ -42,6 +84,72 @@ pub struct ProtocolMix {
     pub tcp_rst_ratio: f64,
     pub icmp_ratio: f64,
     pub custom_ratio: f64,
+    /// Ratio of ARP frames, sent through the layer-2 channel
+    #[serde(default)]
+    pub arp_ratio: f64,
+    /// Ratios of further TCP flag sets, e.g. `syn_ack: 0.1` or `"syn+fin": 0.01`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tcp_flags: BTreeMap<TcpFlagSet, f64>,
+    /// Ratios for IPv6 targets; without them IPv6 targets follow the IPv4 ratios
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipv6: Option<Ipv6ProtocolMix>,
+}
+
+/// Protocol distribution for IPv6 targets
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Ipv6ProtocolMix {
+    #[serde(default)]
+    pub udp_ratio: f64,
+    #[serde(default)]
+    pub tcp_syn_ratio: f64,
+    #[serde(default)]
+    pub tcp_ack_ratio: f64,
+    #[serde(default)]
+    pub tcp_fin_ratio: f64,
+    #[serde(default)]
+    pub tcp_rst_ratio: f64,
+    #[serde(default)]
+    pub icmp_ratio: f64,
+    /// Ratios of further TCP flag sets, as in the IPv4 mix
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tcp_flags: BTreeMap<TcpFlagSet, f64>,
+}
+
+impl Ipv6ProtocolMix {
+    /// Ratio of every TCP flag set in the mix, the SYN/ACK/FIN/RST fields included
+    pub fn tcp_ratios(&self) -> Vec<(TcpFlagSet, f64)> {
+        merge_tcp_ratios(
+            [self.tcp_syn_ratio, self.tcp_ack_ratio, self.tcp_fin_ratio, self.tcp_rst_ratio],
+            &self.tcp_flags,
+        )
+    }
+
+    /// Sum of all ratios (1.0 for a valid mix)
+    pub fn total(&self) -> f64 {
+        self.udp_ratio
+            + self.tcp_syn_ratio
+            + self.tcp_ack_ratio
+            + self.tcp_fin_ratio
+            + self.tcp_rst_ratio
+            + self.icmp_ratio
+            + self.tcp_flags.values().sum::<f64>()
+    }
+}
+
+/// Ratios of SYN, ACK, FIN and RST merged with further flag sets, dropping zeros
+fn merge_tcp_ratios(basic: [f64; 4], tcp_flags: &BTreeMap<TcpFlagSet, f64>) -> Vec<(TcpFlagSet, f64)> {
+    let mut ratios: Vec<(TcpFlagSet, f64)> = [TcpFlagSet::SYN, TcpFlagSet::ACK, TcpFlagSet::FIN, TcpFlagSet::RST]
+        .into_iter()
+        .zip(basic)
+        .collect();
+    for (&flags, &ratio) in tcp_flags {
+        match ratios.iter_mut().find(|(existing, _)| *existing == flags) {
+            Some((_, total)) => *total += ratio,
+            None => ratios.push((flags, ratio)),
+        }
+    }
+    ratios.retain(|&(_, ratio)| ratio > 0.0);
+    ratios
 }
 
 impl Default for ProtocolMix {
@@ -54,19 +162,117 @@ impl Default for ProtocolMix {
             tcp_rst_ratio: 0.10,
             icmp_ratio: 0.10,
             custom_ratio: 0.0,
+            arp_ratio: 0.0,
+            tcp_flags: BTreeMap::new(),
+            ipv6: None,
         }
     }
 }
 
+impl ProtocolMix {
+    /// All ratios zero, to be filled in field by field
+    fn empty() -> Self {
+        Self {
+            udp_ratio: 0.0,
+            tcp_syn_ratio: 0.0,
+            tcp_ack_ratio: 0.0,
+            tcp_fin_ratio: 0.0,
+            tcp_rst_ratio: 0.0,
+            icmp_ratio: 0.0,
+            custom_ratio: 0.0,
+            arp_ratio: 0.0,
+            tcp_flags: BTreeMap::new(),
+            ipv6: None,
+        }
+    }
+
+    /// Ratio of every TCP flag set in the mix, the SYN/ACK/FIN/RST fields included
+    pub fn tcp_ratios(&self) -> Vec<(TcpFlagSet, f64)> {
+        merge_tcp_ratios(
+            [self.tcp_syn_ratio, self.tcp_ack_ratio, self.tcp_fin_ratio, self.tcp_rst_ratio],
+            &self.tcp_flags,
+        )
+    }
+
+    /// Ratios used for IPv6 targets: the `ipv6` mix, or else the IPv4 ratios
+    /// with custom and ARP traffic sent as UDP
+    pub fn ipv6_mix(&self) -> Ipv6ProtocolMix {
+        self.ipv6.clone().unwrap_or_else(|| Ipv6ProtocolMix {
+            udp_ratio: self.udp_ratio + self.custom_ratio + self.arp_ratio,
+            tcp_syn_ratio: self.tcp_syn_ratio,
+            tcp_ack_ratio: self.tcp_ack_ratio,
+            tcp_fin_ratio: self.tcp_fin_ratio,
+            tcp_rst_ratio: self.tcp_rst_ratio,
+            icmp_ratio: self.icmp_ratio,
+            tcp_flags: self.tcp_flags.clone(),
+        })
+    }
+
+    /// Sum of all ratios (1.0 for a valid mix)
+    pub fn total(&self) -> f64 {
+        self.udp_ratio
+            + self.tcp_syn_ratio
+            + self.tcp_ack_ratio
+            + self.tcp_fin_ratio
+            + self.tcp_rst_ratio
+            + self.icmp_ratio
+            + self.custom_ratio
+            + self.arp_ratio
+            + self.tcp_flags.values().sum::<f64>()
+    }
+}
+
 /// Load/attack configuration
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LoadConfig {
     pub threads: usize,
     pub packet_rate: f64,
     pub payload_size: usize,
+    /// Per-protocol payload size distributions; override `payload_size`
+    #[serde(default)]
+    pub payload_sizes: PayloadSizes,
+    /// Content of UDP, ICMP and TCP payloads
+    #[serde(default)]
+    pub payload_pattern: PayloadPattern,
+    /// Options of generated TCP segments
+    #[serde(default)]
+    pub tcp_options: TcpOptions,
+    /// Which TCP segments carry payload data
+    #[serde(default)]
+    pub tcp_payload: TcpPayload,
+    /// Weights of ICMP messages, e.g. `timestamp: 0.2` or `"dest_unreachable:3": 0.1`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub icmp_types: BTreeMap<IcmpMessage, f64>,
+    /// Weights of ICMPv6 messages, e.g. `neighbor_solicitation: 0.5`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub icmpv6_types: BTreeMap<Icmpv6Kind, f64>,
+    /// Extension headers of IPv6 packets, e.g. `hop_by_hop, routing:0:2, fragment`
+    #[serde(default, skip_serializing_if = "Ipv6ExtensionChain::is_empty")]
+    pub ipv6_extension_headers: Ipv6ExtensionChain,
+    /// Splitting of IPv4 datagrams into fragments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fragmentation: Option<FragmentationConfig>,
+    /// Rules for header field values, e.g. `ipv4_ttl: {type: fixed, value: 1}`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub header_fields: BTreeMap<HeaderField, FieldRule>,
+    /// ARP variants and sender/target field overrides
+    #[serde(default, skip_serializing_if = "ArpConfig::is_default")]
+    pub arp: ArpConfig,
     pub duration: Option<u64>,
     pub burst_mode: bool,
     pub burst_pattern: Option<BurstPattern>,
+    #[serde(default)]
+    pub rate_profile: RateProfile,
+    #[serde(default)]
+    pub pacing: PacingConfig,
+    #[serde(default)]
+    pub timing: TimingModel,
+    /// Stop after exactly this many packets have been sent
+    #[serde(default)]
+    pub max_packets: Option<u64>,
+    /// Stop before the packet that would take the bytes sent past this limit
+    #[serde(default)]
+    pub max_bytes: Option<u64>,
 }
 
 /// Burst pattern configuration
@@ -77,13 +283,132 @@ pub struct BurstPattern {
     pub burst_multiplier: f64,
 }
 
+/// Per-thread rate profile over time
+///
+/// Rates are packets per second per thread, like `packet_rate`. Ramps and
+/// steps hold their final rate once their duration has elapsed.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RateProfile {
+    /// Constant `packet_rate`
+    #[default]
+    Constant,
+    /// Linear ramp from `start_rate` to `end_rate`
+    LinearRamp {
+        start_rate: f64,
+        end_rate: f64,
+        duration_secs: u64,
+    },
+    /// Exponential ramp from `start_rate` to `end_rate`
+    ExponentialRamp {
+        start_rate: f64,
+        end_rate: f64,
+        duration_secs: u64,
+    },
+    /// Staircase: add `step_rate` every `step_duration_secs`, up to `max_rate`
+    Step {
+        start_rate: f64,
+        step_rate: f64,
+        step_duration_secs: u64,
+        max_rate: f64,
+    },
+    /// Sinusoid oscillating between `min_rate` and `max_rate`, starting at the minimum
+    Sine {
+        min_rate: f64,
+        max_rate: f64,
+        period_secs: u64,
+    },
+    /// Replay of a recorded rate timeline (aggregate pps, see `load_trace`)
+    Trace {
+        points: Vec<TracePoint>,
+    },
+}
+
+impl RateProfile {
+    /// Length of a trace profile in whole seconds, `None` for other profiles
+    pub fn trace_duration_secs(&self) -> Option<u64> {
+        match self {
+            RateProfile::Trace { points } => points
+                .last()
+                .map(|point| point.offset_secs.ceil() as u64)
+                .filter(|secs| *secs > 0),
+            _ => None,
+        }
+    }
+}
+
+/// Inter-arrival time distribution of packets within a worker
+///
+/// Every model keeps the mean gap at `1 / rate`, so the average rate follows
+/// `packet_rate` and the rate profile; only the spacing of packets changes.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimingModel {
+    /// Evenly spaced packets
+    #[default]
+    Constant,
+    /// Exponentially distributed gaps (Poisson arrivals)
+    Poisson,
+    /// Gaps uniformly jittered by up to `jitter` (a fraction) around the mean
+    Uniform {
+        jitter: f64,
+    },
+    /// Heavy-tailed Pareto distributed gaps with tail index `shape`
+    Pareto {
+        shape: f64,
+    },
+    /// ON/OFF source with Pareto distributed period lengths; packets are
+    /// evenly spaced during ON periods and the source is silent while OFF
+    OnOff {
+        shape: f64,
+        mean_on_ms: f64,
+        mean_off_ms: f64,
+    },
+}
+
+/// Packet pacing configuration
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PacingConfig {
+    pub mode: PacingMode,
+    /// In hybrid mode, spin instead of sleeping for the last part of each wait
+    pub spin_threshold_us: u64,
+    /// Longest backlog a worker catches up after falling behind schedule
+    pub max_catch_up_ms: u64,
+}
+
+impl Default for PacingConfig {
+    fn default() -> Self {
+        Self {
+            mode: PacingMode::Sleep,
+            spin_threshold_us: defaults::DEFAULT_SPIN_THRESHOLD_US,
+            max_catch_up_ms: defaults::DEFAULT_MAX_CATCH_UP_MS,
+        }
+    }
+}
+
+/// How workers wait for the next send deadline
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PacingMode {
+    /// Sleep on the async timer (about 1 ms resolution, low CPU use)
+    #[default]
+    Sleep,
+    /// Sleep, then busy-wait the last `spin_threshold_us` (precise, uses a core per worker)
+    Hybrid,
+}
+
 /// Safety configuration
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Safety {
     pub dry_run: bool,
     pub perfect_simulation: bool,
+    /// Enforce the aggregate caps below across all workers
     pub rate_limit: bool,
     pub max_bandwidth_mbps: Option<f64>,
+    /// Aggregate packets per second across all workers
+    #[serde(default)]
+    pub max_packets_per_second: Option<f64>,
     pub allow_localhost: bool,
     pub require_confirmation: bool,
     #[serde(default)]
@@ -157,22 +482,41 @@ pub fn default_config() -> Config {
         target: Target {
             ip: "192.168.1.1".to_string(),
             ports: vec![80, 443],
+            port_selection: PortSelection::default(),
+            port_weights: BTreeMap::new(),
             protocol_mix: ProtocolMix::default(),
             interface: None,
         },
+        source: SourceConfig::default(),
         attack: LoadConfig {
             threads: defaults::DEFAULT_THREADS,
             packet_rate: defaults::DEFAULT_PACKET_RATE,
             payload_size: defaults::DEFAULT_PAYLOAD_SIZE,
+            payload_sizes: PayloadSizes::default(),
+            payload_pattern: PayloadPattern::default(),
+            tcp_options: TcpOptions::default(),
+            tcp_payload: TcpPayload::default(),
+            icmp_types: BTreeMap::new(),
+            icmpv6_types: BTreeMap::new(),
+            ipv6_extension_headers: Ipv6ExtensionChain::default(),
+            fragmentation: None,
+            header_fields: BTreeMap::new(),
+            arp: ArpConfig::default(),
             duration: Some(defaults::DEFAULT_DURATION_SECONDS),
             burst_mode: false,
             burst_pattern: None,
+            rate_profile: RateProfile::Constant,
+            pacing: PacingConfig::default(),
+            timing: TimingModel::Constant,
+            max_packets: None,
+            max_bytes: None,
         },
         safety: Safety {
             dry_run: false,
             perfect_simulation: false,
             rate_limit: true,
             max_bandwidth_mbps: Some(defaults::DEFAULT_MAX_BANDWIDTH_MBPS),
+            max_packets_per_second: None,
             allow_localhost: false,
             require_confirmation: true,
             allow_broadcast: false,  // Broadcasts blocked by default for safety
@@ -194,6 +538,8 @@ pub fn default_config() -> Config {
             enabled: true,
             log_file: "router_flood_audit.log".to_string(),
         },
+        scenario: None,
+        adaptive: None,
     }
 }
 
@@ -222,36 +568,174 @@ pub fn validate_config(config: &Config) -> Result<()> {
         warn!("    Ensure your system and network can handle this load!");
     }
     
+    // Validate burst pattern
+    if config.attack.burst_mode {
+        let pattern = config.attack.burst_pattern.as_ref().ok_or_else(|| {
+            ConfigError::new("Burst mode requires a burst_pattern")
+        })?;
+
+        if pattern.burst_duration_ms == 0 {
+            return Err(ConfigError::new("Burst duration must be greater than 0 ms").into());
+        }
+
+        if !pattern.burst_multiplier.is_finite() || pattern.burst_multiplier <= 0.0 {
+            return Err(ConfigError::new("Burst multiplier must be a positive number").into());
+        }
+
+        if config.attack.packet_rate * pattern.burst_multiplier > MAX_PACKET_RATE as f64 {
+            return Err(ConfigError::new(
+                format!("Burst rate (packet_rate * burst_multiplier) must not exceed {}", MAX_PACKET_RATE)
+            ).into());
+        }
+    }
+
+    validate_rate_profile(&config.attack.rate_profile, config.attack.threads)?;
+
+    if config.attack.pacing.spin_threshold_us > MAX_SPIN_THRESHOLD_US {
+        return Err(ConfigError::new(
+            format!("Pacing spin threshold must not exceed {} us", MAX_SPIN_THRESHOLD_US)
+        ).into());
+    }
+
+    validate_timing_model(&config.attack.timing)?;
+
+    if config.attack.max_packets == Some(0) || config.attack.max_bytes == Some(0) {
+        return Err(ConfigError::new("max_packets and max_bytes must be greater than 0").into());
+    }
+
+    // Validate aggregate rate caps
+    if let Some(mbps) = config.safety.max_bandwidth_mbps
+        && (!mbps.is_finite() || mbps <= 0.0) {
+            return Err(ConfigError::new("max_bandwidth_mbps must be a positive number").into());
+        }
+
+    if let Some(pps) = config.safety.max_packets_per_second
+        && (!pps.is_finite() || pps <= 0.0) {
+            return Err(ConfigError::new("max_packets_per_second must be a positive number").into());
+        }
+
     // Validate payload size
     if config.attack.payload_size < MIN_PAYLOAD_SIZE || config.attack.payload_size > MAX_PAYLOAD_SIZE {
         return Err(ConfigError::new(
             format!("Payload size must be between {} and {}", MIN_PAYLOAD_SIZE, MAX_PAYLOAD_SIZE)
         ).into());
     }
+
+    payload::validate_payload_sizes(&config.attack.payload_sizes)?;
+    payload::validate_payload_pattern(&config.attack.payload_pattern)?;
+    tcp::validate_tcp_options(&config.attack.tcp_options)?;
+    tcp::validate_tcp_flag_ratios(&config.target.protocol_mix)?;
+    icmp::validate_icmp_types(&config.attack.icmp_types, &config.attack.icmpv6_types)?;
+    ipv6::validate_ipv6_extensions(&config.attack.ipv6_extension_headers)?;
+    fields::validate_header_fields(&config.attack.header_fields)?;
+    arp::validate_arp(&config.attack.arp)?;
+    if let Some(ref fragmentation) = config.attack.fragmentation {
+        fragment::validate_fragmentation(fragmentation)?;
+    }
     
     // Validate protocol mix
-    let total_ratio = config.target.protocol_mix.udp_ratio
-        + config.target.protocol_mix.tcp_syn_ratio
-        + config.target.protocol_mix.tcp_ack_ratio
-        + config.target.protocol_mix.tcp_fin_ratio
-        + config.target.protocol_mix.tcp_rst_ratio
-        + config.target.protocol_mix.icmp_ratio
-        + config.target.protocol_mix.custom_ratio;
+    let total_ratio = config.target.protocol_mix.total();
     
     if (total_ratio - 1.0).abs() > 0.01 {
         return Err(ConfigError::new(
             format!("Protocol mix ratios must sum to 1.0, got {}", total_ratio)
         ).into());
     }
+    if let Some(ref ipv6) = config.target.protocol_mix.ipv6 {
+        let total_ratio = ipv6.total();
+        if (total_ratio - 1.0).abs() > 0.01 {
+            return Err(ConfigError::new(
+                format!("IPv6 protocol mix ratios must sum to 1.0, got {}", total_ratio)
+            ).into());
+        }
+    }
     
-    // Validate ports
-    if config.target.ports.is_empty() {
-        return Err(ConfigError::new("At least one target port must be specified").into());
+    ports::validate_ports(&config.target)?;
+
+    source::validate_source(&config.source)?;
+
+    if let Some(ref scenario) = config.scenario {
+        scenario::validate_scenario(scenario, config)?;
+    }
+
+    if let Some(ref search) = config.adaptive {
+        adaptive::validate_adaptive(search, config)?;
     }
     
     Ok(())
 }
 
+/// Validate rate profile parameters
+fn validate_rate_profile(profile: &RateProfile, threads: usize) -> Result<()> {
+    let (rates, duration_secs): (Vec<f64>, u64) = match profile {
+        RateProfile::Constant => return Ok(()),
+        RateProfile::Trace { points } => return trace::validate_trace(points, threads),
+        RateProfile::LinearRamp { start_rate, end_rate, duration_secs }
+        | RateProfile::ExponentialRamp { start_rate, end_rate, duration_secs } => {
+            (vec![*start_rate, *end_rate], *duration_secs)
+        }
+        RateProfile::Step { start_rate, step_rate, step_duration_secs, max_rate } => {
+            if !step_rate.is_finite() || *step_rate < 0.0 {
+                return Err(ConfigError::new("Step rate must not be negative").into());
+            }
+            if max_rate < start_rate {
+                return Err(ConfigError::new("Step max_rate must be at least start_rate").into());
+            }
+            (vec![*start_rate, *max_rate], *step_duration_secs)
+        }
+        RateProfile::Sine { min_rate, max_rate, period_secs } => {
+            if max_rate < min_rate {
+                return Err(ConfigError::new("Sine max_rate must be at least min_rate").into());
+            }
+            (vec![*min_rate, *max_rate], *period_secs)
+        }
+    };
+
+    if rates.iter().any(|rate| !rate.is_finite() || *rate <= 0.0 || *rate > MAX_PACKET_RATE as f64) {
+        return Err(ConfigError::new(
+            format!("Rate profile rates must be between 0 and {}", MAX_PACKET_RATE)
+        ).into());
+    }
+
+    if duration_secs == 0 {
+        return Err(ConfigError::new("Rate profile duration must be greater than 0 seconds").into());
+    }
+
+    Ok(())
+}
+
+/// Validate timing model parameters
+fn validate_timing_model(model: &TimingModel) -> Result<()> {
+    match model {
+        TimingModel::Constant | TimingModel::Poisson => {}
+        TimingModel::Uniform { jitter } => {
+            if !jitter.is_finite() || !(0.0..=1.0).contains(jitter) {
+                return Err(ConfigError::new("Uniform timing jitter must be between 0.0 and 1.0").into());
+            }
+        }
+        TimingModel::Pareto { shape } => validate_pareto_shape(*shape)?,
+        TimingModel::OnOff { shape, mean_on_ms, mean_off_ms } => {
+            validate_pareto_shape(*shape)?;
+            if !mean_on_ms.is_finite() || *mean_on_ms <= 0.0 {
+                return Err(ConfigError::new("ON/OFF mean_on_ms must be a positive number").into());
+            }
+            if !mean_off_ms.is_finite() || *mean_off_ms < 0.0 {
+                return Err(ConfigError::new("ON/OFF mean_off_ms must not be negative").into());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Pareto shapes at or below 1 have no finite mean, so the rate would be undefined
+fn validate_pareto_shape(shape: f64) -> Result<()> {
+    if !shape.is_finite() || shape <= 1.0 {
+        return Err(ConfigError::new("Pareto shape must be greater than 1.0").into());
+    }
+    Ok(())
+}
+
 /// Configuration builder for fluent API
 pub struct ConfigBuilder {
     config: Config,
@@ -0,0 +1,348 @@
+//! Payload size distributions and content patterns
+//!
+//! Without `payload_sizes`, UDP and TCP payloads have the fixed
+//! `payload_size` and ICMP keeps its ping-sized payloads. A distribution under `default`
+//! applies to every protocol without its own entry.
+//!
+//! IMIX distributions are given as Ethernet frame sizes including the FCS,
+//! the way router throughput is usually reported. The packet builder turns
+//! them into payload lengths from each protocol's header sizes.
+//!
+//! `payload_pattern` chooses what the payload bytes contain. Random bytes
+//! are the default.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::constants::{defaults, icmp, MAX_FRAME_SIZE, MAX_PAYLOAD_SIZE, MIN_FRAME_SIZE};
+use crate::error::{ConfigError, Result, RouterFloodError};
+
+/// How payload sizes are drawn
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SizeDistribution {
+    /// Every payload has the same size
+    Fixed { size: usize },
+    /// Sizes between `min` and `max`, skewed towards small and medium payloads
+    Range { min: usize, max: usize },
+    /// Sizes picked in proportion to their weights
+    Weighted { sizes: Vec<WeightedSize> },
+    /// Weighted Ethernet frame sizes: a named preset or a custom table
+    Imix {
+        #[serde(default)]
+        profile: Option<ImixProfile>,
+        #[serde(default)]
+        frames: Option<FrameTable>,
+    },
+}
+
+/// Standard IMIX frame size mixes
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImixProfile {
+    /// 64:7, 594:4, 1518:1
+    Simple,
+    /// 64:7, 570:4, 1518:1
+    Cisco,
+}
+
+impl ImixProfile {
+    /// Frame sizes and weights of the preset
+    pub fn frames(self) -> FrameTable {
+        let table: &[(usize, f64)] = match self {
+            ImixProfile::Simple => &[(64, 7.0), (594, 4.0), (1518, 1.0)],
+            ImixProfile::Cisco => &[(64, 7.0), (570, 4.0), (1518, 1.0)],
+        };
+        FrameTable(table.iter().map(|&(size, weight)| WeightedSize { size, weight }).collect())
+    }
+}
+
+impl FromStr for ImixProfile {
+    type Err = RouterFloodError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "simple" => Ok(ImixProfile::Simple),
+            "cisco" => Ok(ImixProfile::Cisco),
+            _ => Err(ConfigError::new(format!("Unknown IMIX profile '{}': must be 'simple' or 'cisco'", name)).into()),
+        }
+    }
+}
+
+/// Weighted frame sizes written as `size:weight` pairs, e.g. `64:7, 594:4, 1518:1`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct FrameTable(pub Vec<WeightedSize>);
+
+impl FromStr for FrameTable {
+    type Err = RouterFloodError;
+
+    fn from_str(table: &str) -> Result<Self> {
+        table
+            .split(',')
+            .map(|entry| {
+                let (size, weight) = entry.trim().split_once(':').ok_or_else(|| {
+                    ConfigError::new(format!("Invalid frame size entry '{}': expected size:weight", entry.trim()))
+                })?;
+                let size = size.trim().parse().map_err(|_| {
+                    ConfigError::new(format!("Invalid frame size '{}'", size.trim()))
+                })?;
+                let weight = weight.trim().parse().map_err(|_| {
+                    ConfigError::new(format!("Invalid frame weight '{}'", weight.trim()))
+                })?;
+                Ok(WeightedSize { size, weight })
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(FrameTable)
+    }
+}
+
+impl TryFrom<String> for FrameTable {
+    type Error = RouterFloodError;
+
+    fn try_from(table: String) -> Result<Self> {
+        table.parse()
+    }
+}
+
+impl From<FrameTable> for String {
+    fn from(table: FrameTable) -> Self {
+        table.to_string()
+    }
+}
+
+impl fmt::Display for FrameTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, entry) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}:{}", entry.size, entry.weight)?;
+        }
+        Ok(())
+    }
+}
+
+/// One entry of a weighted size table
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WeightedSize {
+    pub size: usize,
+    pub weight: f64,
+}
+
+impl SizeDistribution {
+    /// IMIX preset with Ethernet frame sizes
+    pub fn imix(profile: ImixProfile) -> Self {
+        SizeDistribution::Imix { profile: Some(profile), frames: None }
+    }
+
+    /// Frame sizes of an IMIX distribution; a custom table wins over the preset
+    pub fn frame_sizes(&self) -> Option<FrameTable> {
+        match *self {
+            SizeDistribution::Imix { ref frames, profile } => {
+                frames.clone().or_else(|| profile.map(ImixProfile::frames))
+            }
+            _ => None,
+        }
+    }
+
+    /// Smallest payload the distribution can produce; frame sizes for IMIX
+    pub fn min_size(&self) -> usize {
+        match *self {
+            SizeDistribution::Fixed { size } => size,
+            SizeDistribution::Range { min, .. } => min,
+            SizeDistribution::Weighted { ref sizes } => sizes.iter().map(|entry| entry.size).min().unwrap_or(0),
+            SizeDistribution::Imix { .. } => self.frame_sizes()
+                .and_then(|frames| frames.0.iter().map(|entry| entry.size).min())
+                .unwrap_or(0),
+        }
+    }
+
+    /// Largest payload the distribution can produce; frame sizes for IMIX,
+    /// which bound the payload from above
+    pub fn max_size(&self) -> usize {
+        match *self {
+            SizeDistribution::Fixed { size } => size,
+            SizeDistribution::Range { max, .. } => max,
+            SizeDistribution::Weighted { ref sizes } => sizes.iter().map(|entry| entry.size).max().unwrap_or(0),
+            SizeDistribution::Imix { .. } => self.frame_sizes()
+                .and_then(|frames| frames.0.iter().map(|entry| entry.size).max())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Payload size distributions per protocol
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct PayloadSizes {
+    /// Applies to every protocol without its own entry
+    #[serde(default)]
+    pub default: Option<SizeDistribution>,
+    /// UDP over IPv4 and IPv6
+    #[serde(default)]
+    pub udp: Option<SizeDistribution>,
+    /// ICMP echo over IPv4 and IPv6
+    #[serde(default)]
+    pub icmp: Option<SizeDistribution>,
+    /// Data-bearing TCP segments over IPv4 and IPv6
+    #[serde(default)]
+    pub tcp: Option<SizeDistribution>,
+}
+
+impl PayloadSizes {
+    /// UDP distribution, falling back to a fixed `payload_size`
+    pub fn udp_sizes(&self, payload_size: usize) -> SizeDistribution {
+        self.udp.clone()
+            .or_else(|| self.default.clone())
+            .unwrap_or(SizeDistribution::Fixed { size: payload_size })
+    }
+
+    /// TCP distribution, falling back to a fixed `payload_size`
+    pub fn tcp_sizes(&self, payload_size: usize) -> SizeDistribution {
+        self.tcp.clone()
+            .or_else(|| self.default.clone())
+            .unwrap_or(SizeDistribution::Fixed { size: payload_size })
+    }
+
+    /// ICMP distribution, falling back to ping-sized payloads
+    pub fn icmp_sizes(&self) -> SizeDistribution {
+        self.icmp.clone()
+            .or_else(|| self.default.clone())
+            .unwrap_or(SizeDistribution::Range { min: icmp::MIN_PING_SIZE, max: icmp::MAX_PING_SIZE })
+    }
+}
+
+/// What the payload bytes contain
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PayloadPattern {
+    /// Random bytes
+    #[default]
+    Random,
+    /// All bytes zero
+    Zero,
+    /// Bytes counting up from 0 and wrapping after 255
+    Incrementing,
+    /// Hex bytes repeated over the payload, e.g. `deadbeef`
+    Hex { pattern: String },
+    /// Contents of a file, repeated over the payload
+    File { path: String },
+    /// Header with magic cookie, stream ID, sequence number and TX timestamp,
+    /// followed by zero bytes
+    Instrumented {
+        #[serde(default = "default_magic")]
+        magic: u32,
+        /// First stream ID handed out; every packet strategy of every worker
+        /// sends its own stream
+        #[serde(default)]
+        stream_base: u32,
+    },
+}
+
+fn default_magic() -> u32 {
+    defaults::PAYLOAD_MAGIC
+}
+
+impl PayloadPattern {
+    /// Instrumented header with the default magic cookie
+    pub fn instrumented() -> Self {
+        PayloadPattern::Instrumented { magic: default_magic(), stream_base: 0 }
+    }
+
+    /// Decode a hex pattern; whitespace and a leading `0x` are ignored
+    pub fn decode_hex(pattern: &str) -> Result<Vec<u8>> {
+        let digits: String = pattern.trim().trim_start_matches("0x").split_whitespace().collect();
+        if digits.is_empty() || !digits.len().is_multiple_of(2) {
+            return Err(ConfigError::new(
+                format!("Invalid payload hex pattern '{}': needs an even, non-zero number of digits", pattern)
+            ).into());
+        }
+
+        (0..digits.len())
+            .step_by(2)
+            .map(|index| {
+                u8::from_str_radix(&digits[index..index + 2], 16).map_err(|_| {
+                    ConfigError::new(format!("Invalid payload hex pattern '{}': not a hex number", pattern)).into()
+                })
+            })
+            .collect()
+    }
+}
+
+/// Check a payload pattern without touching the file system
+pub(super) fn validate_payload_pattern(pattern: &PayloadPattern) -> Result<()> {
+    match pattern {
+        PayloadPattern::Hex { pattern } => PayloadPattern::decode_hex(pattern).map(|_| ()),
+        PayloadPattern::File { path } if path.trim().is_empty() => {
+            Err(ConfigError::new("Payload pattern file path must not be empty").into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Validate every configured distribution
+pub(super) fn validate_payload_sizes(sizes: &PayloadSizes) -> Result<()> {
+    let entries = [
+        ("default", &sizes.default),
+        ("udp", &sizes.udp),
+        ("icmp", &sizes.icmp),
+        ("tcp", &sizes.tcp),
+    ];
+    for (name, distribution) in entries {
+        if let Some(distribution) = distribution {
+            validate_distribution(name, distribution)?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_distribution(name: &str, distribution: &SizeDistribution) -> Result<()> {
+    match distribution {
+        SizeDistribution::Range { min, max } if min > max => {
+            return Err(ConfigError::new(format!("Payload sizes '{}': min must not exceed max", name)).into());
+        }
+        SizeDistribution::Weighted { sizes } => validate_weights(name, sizes)?,
+        SizeDistribution::Imix { profile, frames } => {
+            if profile.is_some() == frames.is_some() {
+                return Err(ConfigError::new(
+                    format!("Payload sizes '{}': IMIX needs either a profile or a frames table", name)
+                ).into());
+            }
+            let frames = distribution.frame_sizes().unwrap_or(FrameTable(Vec::new()));
+            validate_weights(name, &frames.0)?;
+            if frames.0.iter().any(|entry| !(MIN_FRAME_SIZE..=MAX_FRAME_SIZE).contains(&entry.size)) {
+                return Err(ConfigError::new(format!(
+                    "Payload sizes '{}': frame sizes must be between {} and {} bytes",
+                    name, MIN_FRAME_SIZE, MAX_FRAME_SIZE
+                )).into());
+            }
+            // Frame sizes are checked against Ethernet limits instead
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    if distribution.max_size() > MAX_PAYLOAD_SIZE {
+        return Err(ConfigError::new(
+            format!("Payload sizes '{}': sizes must not exceed {} bytes", name, MAX_PAYLOAD_SIZE)
+        ).into());
+    }
+
+    Ok(())
+}
+
+fn validate_weights(name: &str, sizes: &[WeightedSize]) -> Result<()> {
+    if sizes.is_empty() {
+        return Err(ConfigError::new(format!("Payload sizes '{}': weighted table is empty", name)).into());
+    }
+    if sizes.iter().any(|entry| !entry.weight.is_finite() || entry.weight < 0.0) {
+        return Err(ConfigError::new(
+            format!("Payload sizes '{}': weights must be non-negative numbers", name)
+        ).into());
+    }
+    if sizes.iter().map(|entry| entry.weight).sum::<f64>() <= 0.0 {
+        return Err(ConfigError::new(format!("Payload sizes '{}': weights must not all be 0", name)).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,248 @@
+//! Destination port lists and selection policies
+//!
+//! A port list is a comma-separated list of single ports (`80`), ranges
+//! (`1000-2000`), `all` (1-65535) and service names (`http`, `dns`). Each
+//! entry may carry a weight for weighted selection, as in `http:5,dns:1`.
+//! In YAML, `ports` takes either such a string or a list of ports and
+//! entries; weights go in `port_weights`.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::Target;
+use crate::error::{ConfigError, Result, RouterFloodError};
+
+/// Well-known service names accepted in port lists
+const SERVICES: &[(&str, u16)] = &[
+    ("ftp", 21),
+    ("ssh", 22),
+    ("telnet", 23),
+    ("smtp", 25),
+    ("dns", 53),
+    ("tftp", 69),
+    ("http", 80),
+    ("pop3", 110),
+    ("ntp", 123),
+    ("imap", 143),
+    ("snmp", 161),
+    ("bgp", 179),
+    ("ldap", 389),
+    ("https", 443),
+    ("smb", 445),
+    ("syslog", 514),
+    ("ldaps", 636),
+    ("imaps", 993),
+    ("pop3s", 995),
+    ("mssql", 1433),
+    ("mysql", 3306),
+    ("rdp", 3389),
+    ("postgres", 5432),
+    ("redis", 6379),
+    ("http-alt", 8080),
+    ("https-alt", 8443),
+    ("mongodb", 27017),
+];
+
+/// Order in which workers visit the target ports
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PortSelection {
+    /// Cycle through the ports in the order listed
+    #[default]
+    RoundRobin,
+    /// Uniformly random port for every packet
+    Random,
+    /// Random port in proportion to `port_weights` (1 when unset)
+    Weighted,
+    /// Sweep the distinct ports in ascending order, then start over
+    Sequential,
+}
+
+impl FromStr for PortSelection {
+    type Err = RouterFloodError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_lowercase().replace('-', "_").as_str() {
+            "round_robin" => Ok(PortSelection::RoundRobin),
+            "random" => Ok(PortSelection::Random),
+            "weighted" => Ok(PortSelection::Weighted),
+            "sequential" => Ok(PortSelection::Sequential),
+            _ => Err(ConfigError::new(format!(
+                "Unknown port selection '{}': must be 'round-robin', 'random', 'weighted' or 'sequential'",
+                name
+            )).into()),
+        }
+    }
+}
+
+/// Parsed port list with the weights given for its entries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PortSpec {
+    pub ports: Vec<u16>,
+    pub weights: BTreeMap<u16, u32>,
+}
+
+impl FromStr for PortSpec {
+    type Err = RouterFloodError;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let mut parsed = PortSpec::default();
+        for entry in spec.split(',') {
+            let (ports, weight) = match entry.split_once(':') {
+                Some((ports, weight)) => {
+                    let weight = weight.trim().parse::<u32>().ok().filter(|&weight| weight > 0).ok_or_else(|| {
+                        ConfigError::new(format!("Invalid port weight in '{}': must be a positive integer", entry.trim()))
+                    })?;
+                    (ports, Some(weight))
+                }
+                None => (entry, None),
+            };
+
+            let (first, last) = parse_entry(ports)?;
+            for port in first..=last {
+                parsed.ports.push(port);
+                if let Some(weight) = weight {
+                    parsed.weights.insert(port, weight);
+                }
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Port range of a single list entry
+fn parse_entry(entry: &str) -> Result<(u16, u16)> {
+    let entry = entry.trim();
+    let name = entry.to_lowercase();
+    if name == "all" {
+        return Ok((1, u16::MAX));
+    }
+    if let Some(port) = service_port(&name) {
+        return Ok((port, port));
+    }
+
+    let port = |value: &str| {
+        value.trim().parse::<u16>().ok().filter(|&port| port > 0).ok_or_else(|| -> RouterFloodError {
+            ConfigError::new(format!(
+                "Invalid port value '{}': must be a port number (1-65535), a range, 'all' or a service name",
+                entry
+            )).into()
+        })
+    };
+    match entry.split_once('-') {
+        Some((first, last)) => {
+            let (first, last) = (port(first)?, port(last)?);
+            if first > last {
+                return Err(ConfigError::new(format!("Invalid port range '{}': start is above end", entry)).into());
+            }
+            Ok((first, last))
+        }
+        None => port(entry).map(|port| (port, port)),
+    }
+}
+
+/// Port number of a well-known service name
+pub fn service_port(name: &str) -> Option<u16> {
+    SERVICES.iter()
+        .find(|(service, _)| service.eq_ignore_ascii_case(name))
+        .map(|&(_, port)| port)
+}
+
+/// Compact description of a port list for logs, e.g. `80, 443, 8000-8100`
+pub fn format_ports(ports: &[u16]) -> String {
+    let mut runs: Vec<(u16, u16)> = Vec::new();
+    for &port in ports {
+        match runs.last_mut() {
+            Some((_, last)) if port > 1 && *last == port - 1 => *last = port,
+            _ => runs.push((port, port)),
+        }
+    }
+
+    runs.iter()
+        .map(|&(first, last)| if first == last { first.to_string() } else { format!("{}-{}", first, last) })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A `ports` value in YAML: a port list string or a list of entries
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PortsValue {
+    Spec(String),
+    List(Vec<PortEntry>),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PortEntry {
+    Port(u16),
+    Spec(String),
+}
+
+impl fmt::Display for PortEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PortEntry::Port(port) => write!(f, "{}", port),
+            PortEntry::Spec(spec) => f.write_str(spec),
+        }
+    }
+}
+
+impl PortsValue {
+    fn into_ports(self) -> Result<Vec<u16>> {
+        let spec = match self {
+            PortsValue::Spec(spec) => spec,
+            PortsValue::List(entries) => entries.iter().map(ToString::to_string).collect::<Vec<_>>().join(","),
+        };
+        if spec.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let parsed = spec.parse::<PortSpec>()?;
+        if !parsed.weights.is_empty() {
+            return Err(ConfigError::new("Port weights in configuration files go in 'port_weights'").into());
+        }
+        Ok(parsed.ports)
+    }
+}
+
+pub(super) fn deserialize_ports<'de, D>(deserializer: D) -> std::result::Result<Vec<u16>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    PortsValue::deserialize(deserializer)?
+        .into_ports()
+        .map_err(serde::de::Error::custom)
+}
+
+pub(super) fn deserialize_optional_ports<'de, D>(deserializer: D) -> std::result::Result<Option<Vec<u16>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<PortsValue>::deserialize(deserializer)?
+        .map(PortsValue::into_ports)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+/// Check the port list and its weights
+pub(super) fn validate_ports(target: &Target) -> Result<()> {
+    if target.ports.is_empty() {
+        return Err(ConfigError::new("At least one target port must be specified").into());
+    }
+    if target.ports.contains(&0) {
+        return Err(ConfigError::new("Target port 0 is not a valid destination port").into());
+    }
+
+    if !target.port_weights.is_empty() && target.port_selection != PortSelection::Weighted {
+        return Err(ConfigError::new("'port_weights' require port_selection: weighted").into());
+    }
+    // Weights of ports outside the list are allowed, as scenario phases may
+    // replace the list
+    if let Some((port, _)) = target.port_weights.iter().find(|(_, weight)| **weight == 0) {
+        return Err(ConfigError::new(format!("Weight of port {} must be positive", port)).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+//! Multi-phase scenario configuration
+//!
+//! A scenario is an ordered list of phases (for example warmup, sustain,
+//! spike, cooldown) that the engine runs back to back on the same workers
+//! and transport channels. Settings a phase leaves out are taken from the
+//! base configuration.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::{validate_config, Config, PayloadSizes, ProtocolMix, RateProfile, SizeDistribution};
+use crate::error::{ConfigError, Result, RouterFloodError};
+
+/// Ordered list of load phases
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Scenario {
+    pub phases: Vec<ScenarioPhase>,
+}
+
+/// A single scenario phase
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScenarioPhase {
+    pub name: String,
+    pub duration_secs: u64,
+    /// Per-thread packet rate; defaults to `attack.packet_rate`
+    #[serde(default)]
+    pub packet_rate: Option<f64>,
+    /// Rate profile anchored at the start of the phase
+    #[serde(default)]
+    pub rate_profile: Option<RateProfile>,
+    #[serde(default)]
+    pub protocol_mix: Option<ProtocolMix>,
+    #[serde(default, deserialize_with = "super::ports::deserialize_optional_ports")]
+    pub ports: Option<Vec<u16>>,
+    /// Fixed UDP and TCP payload size for this phase
+    #[serde(default)]
+    pub payload_size: Option<usize>,
+    /// Replaces the base payload size distributions
+    #[serde(default)]
+    pub payload_sizes: Option<PayloadSizes>,
+}
+
+impl Scenario {
+    /// Total duration of all phases
+    pub fn total_duration_secs(&self) -> u64 {
+        self.phases.iter().map(|phase| phase.duration_secs).sum()
+    }
+}
+
+impl ScenarioPhase {
+    /// Build the effective configuration for this phase on top of `base`
+    ///
+    /// A phase that sets `packet_rate` without a `rate_profile` runs at a
+    /// constant rate; otherwise the base rate profile is kept.
+    pub fn apply_to(&self, base: &Config) -> Config {
+        let mut config = base.clone();
+        config.scenario = None;
+        config.attack.duration = Some(self.duration_secs);
+
+        if let Some(rate) = self.packet_rate {
+            config.attack.packet_rate = rate;
+            config.attack.rate_profile = RateProfile::Constant;
+        }
+        if let Some(ref profile) = self.rate_profile {
+            config.attack.rate_profile = profile.clone();
+        }
+        if let Some(ref mix) = self.protocol_mix {
+            config.target.protocol_mix = mix.clone();
+        }
+        if let Some(ref ports) = self.ports {
+            config.target.ports = ports.clone();
+        }
+        if let Some(ref sizes) = self.payload_sizes {
+            config.attack.payload_sizes = sizes.clone();
+        }
+        if let Some(size) = self.payload_size {
+            config.attack.payload_size = size;
+            config.attack.payload_sizes.udp = Some(SizeDistribution::Fixed { size });
+            config.attack.payload_sizes.tcp = Some(SizeDistribution::Fixed { size });
+        }
+
+        config
+    }
+}
+
+/// Load a scenario from a YAML file
+pub fn load_scenario(path: &str) -> Result<Scenario> {
+    if !Path::new(path).exists() {
+        return Err(ConfigError::new(format!("Scenario file not found: {}", path)).into());
+    }
+
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| ConfigError::new(format!("Failed to read scenario file: {}", e)))?;
+
+    serde_yaml::from_str(&contents)
+        .map_err(|e| ConfigError::new(format!("Failed to parse scenario: {}", e)).into())
+}
+
+/// Validate every phase against the base configuration
+pub(super) fn validate_scenario(scenario: &Scenario, base: &Config) -> Result<()> {
+    if scenario.phases.is_empty() {
+        return Err(ConfigError::new("Scenario must contain at least one phase").into());
+    }
+
+    for phase in &scenario.phases {
+        if phase.name.trim().is_empty() {
+            return Err(ConfigError::new("Scenario phase names must not be empty").into());
+        }
+
+        if phase.duration_secs == 0 {
+            return Err(ConfigError::new(
+                format!("Scenario phase '{}': duration must be greater than 0 seconds", phase.name)
+            ).into());
+        }
+
+        validate_config(&phase.apply_to(base)).map_err(|e| match e {
+            RouterFloodError::Config(msg) => {
+                ConfigError::new(format!("Scenario phase '{}': {}", phase.name, msg)).into()
+            }
+            other => other,
+        })?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+//! Source address configuration
+//!
+//! Without a `source` entry for an address family, every packet strategy
+//! picks one random host of 192.168.1.0/24 (IPv4) or fe80::/64 (IPv6), as
+//! earlier versions did. Source ports default to random ephemeral ports.
+
+use pnet::ipnetwork::IpNetwork;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use crate::constants::{EPHEMERAL_PORT_MAX, EPHEMERAL_PORT_MIN};
+use crate::error::{ConfigError, Result, RouterFloodError};
+use crate::security::validation::is_private_ip;
+
+/// Source addresses per address family
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SourceConfig {
+    #[serde(default)]
+    pub ipv4: Option<SourceAddress>,
+    #[serde(default)]
+    pub ipv6: Option<SourceAddress>,
+    /// Source ports of UDP and TCP packets
+    #[serde(default)]
+    pub port: SourcePort,
+}
+
+/// Where source addresses come from
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SourceAddress {
+    /// The selected interface's own address, so replies come back
+    Interface,
+    /// Addresses drawn from a private CIDR block, e.g. `10.20.0.0/16`
+    Pool {
+        cidr: String,
+        #[serde(default)]
+        selection: SourceSelection,
+    },
+    /// One fixed address
+    Fixed { address: IpAddr },
+}
+
+/// How often a pool address is drawn
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceSelection {
+    /// Once per flow: each worker keeps one address per protocol
+    #[default]
+    PerFlow,
+    /// A new address for every packet
+    PerPacket,
+}
+
+/// How UDP and TCP source ports are chosen
+///
+/// Each protocol of each worker is a stream with its own sequential counter.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SourcePort {
+    /// Random ephemeral port for every packet
+    #[default]
+    Random,
+    /// The same port for every packet
+    Fixed { port: u16 },
+    /// Random port within `min..=max` for every packet
+    Range { min: u16, max: u16 },
+    /// Counts up from `min` to `max`, then starts over
+    Sequential {
+        #[serde(default = "default_port_min")]
+        min: u16,
+        #[serde(default = "default_port_max")]
+        max: u16,
+    },
+}
+
+fn default_port_min() -> u16 {
+    EPHEMERAL_PORT_MIN
+}
+
+fn default_port_max() -> u16 {
+    EPHEMERAL_PORT_MAX
+}
+
+/// Parses `random`, `PORT`, `MIN-MAX`, `sequential` or `sequential:MIN-MAX`
+impl FromStr for SourcePort {
+    type Err = RouterFloodError;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let invalid = || -> RouterFloodError {
+            ConfigError::new(format!(
+                "Invalid source port '{}': expected random, PORT, MIN-MAX, sequential or sequential:MIN-MAX",
+                spec
+            )).into()
+        };
+        let port = |value: &str| value.trim().parse::<u16>().map_err(|_| invalid());
+        let range = |value: &str| {
+            let (min, max) = value.split_once('-').ok_or_else(invalid)?;
+            Ok::<_, RouterFloodError>((port(min)?, port(max)?))
+        };
+
+        let spec_lower = spec.trim().to_lowercase();
+        match spec_lower.as_str() {
+            "random" => Ok(SourcePort::Random),
+            "sequential" => Ok(SourcePort::Sequential { min: EPHEMERAL_PORT_MIN, max: EPHEMERAL_PORT_MAX }),
+            other => {
+                if let Some(bounds) = other.strip_prefix("sequential:") {
+                    let (min, max) = range(bounds)?;
+                    Ok(SourcePort::Sequential { min, max })
+                } else if other.contains('-') {
+                    let (min, max) = range(other)?;
+                    Ok(SourcePort::Range { min, max })
+                } else {
+                    Ok(SourcePort::Fixed { port: port(other)? })
+                }
+            }
+        }
+    }
+}
+
+impl SourceAddress {
+    /// Parse the CIDR block of a pool
+    pub fn parse_cidr(cidr: &str) -> Result<IpNetwork> {
+        cidr.trim().parse().map_err(|_| {
+            ConfigError::new(format!("Invalid source pool '{}': expected a CIDR block like 10.0.0.0/24", cidr)).into()
+        })
+    }
+}
+
+/// Check that source addresses match their family and stay private
+pub(super) fn validate_source(source: &SourceConfig) -> Result<()> {
+    for (family, address, ipv6) in [("ipv4", &source.ipv4, false), ("ipv6", &source.ipv6, true)] {
+        let (first, last) = match address {
+            None | Some(SourceAddress::Interface) => continue,
+            Some(SourceAddress::Fixed { address }) => (*address, *address),
+            Some(SourceAddress::Pool { cidr, .. }) => {
+                let network = SourceAddress::parse_cidr(cidr)?;
+                (network.network(), network.broadcast())
+            }
+        };
+
+        if first.is_ipv6() != ipv6 {
+            return Err(ConfigError::new(format!("Source '{}' must be an {} address", family, family)).into());
+        }
+        if !is_private_ip(&first) || !is_private_ip(&last) {
+            return Err(ConfigError::new(
+                format!("Source '{}' must stay within private address ranges", family)
+            ).into());
+        }
+    }
+    validate_source_port(source.port)
+}
+
+fn validate_source_port(port: SourcePort) -> Result<()> {
+    match port {
+        SourcePort::Random => Ok(()),
+        SourcePort::Fixed { port: 0 } => Err(ConfigError::new("Source port must be between 1 and 65535").into()),
+        SourcePort::Fixed { .. } => Ok(()),
+        SourcePort::Range { min, max } | SourcePort::Sequential { min, max } => {
+            if min == 0 || min > max {
+                return Err(ConfigError::new(format!(
+                    "Invalid source port range {}-{}: need 1 <= min <= max", min, max
+                )).into());
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! TCP header settings
+//!
+//! `tcp_options` adds options to generated TCP segments, either an OS-like
+//! preset or an explicit list such as `mss:1460, sack_permitted, timestamps,
+//! nop, window_scale:7`. Without it, segments carry a bare 20-byte header.
+//!
+//! `TcpFlagSet` names the control bits of a segment, either a preset such as
+//! `syn_ack` or `xmas`, or flags joined with `+` such as `syn+fin`.
+//!
+//! `tcp_payload` chooses which segments carry data. By default only segments
+//! with PSH set do, with payloads sized by `payload_sizes.tcp`.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use super::ProtocolMix;
+use crate::constants::{tcp_flag, tcp_option, TCP_MAX_OPTIONS_SIZE};
+use crate::error::{ConfigError, Result, RouterFloodError};
+
+/// TCP options of generated segments
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TcpOptions {
+    /// Bare 20-byte headers
+    #[default]
+    None,
+    /// The SYN options of a common operating system
+    Preset { os: TcpOsProfile },
+    /// Options in the order listed
+    List { options: TcpOptionList },
+}
+
+/// Operating systems with a TCP option preset
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TcpOsProfile {
+    /// MSS, SACK permitted, timestamps, NOP, window scale 7
+    Linux,
+    /// MSS, NOP, window scale 8, NOP, NOP, SACK permitted
+    Windows,
+    /// MSS, NOP, window scale 6, NOP, NOP, timestamps, SACK permitted, EOL
+    Macos,
+}
+
+impl TcpOsProfile {
+    /// Options of the preset
+    pub fn options(self) -> TcpOptionList {
+        use TcpOption::*;
+        TcpOptionList(match self {
+            TcpOsProfile::Linux => vec![Mss(1460), SackPermitted, Timestamps, Nop, WindowScale(7)],
+            TcpOsProfile::Windows => vec![Mss(1460), Nop, WindowScale(8), Nop, Nop, SackPermitted],
+            TcpOsProfile::Macos => {
+                vec![Mss(1460), Nop, WindowScale(6), Nop, Nop, Timestamps, SackPermitted, Eol]
+            }
+        })
+    }
+}
+
+impl FromStr for TcpOsProfile {
+    type Err = RouterFloodError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "linux" => Ok(TcpOsProfile::Linux),
+            "windows" => Ok(TcpOsProfile::Windows),
+            "macos" => Ok(TcpOsProfile::Macos),
+            _ => Err(ConfigError::new(format!(
+                "Unknown TCP option preset '{}': must be 'linux', 'windows' or 'macos'", name
+            )).into()),
+        }
+    }
+}
+
+/// A single TCP option
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpOption {
+    /// End of option list
+    Eol,
+    Nop,
+    /// Maximum segment size
+    Mss(u16),
+    /// Window scale shift count
+    WindowScale(u8),
+    SackPermitted,
+    /// Timestamp value and echo reply, filled in per segment
+    Timestamps,
+}
+
+impl TcpOption {
+    /// Encoded length in bytes
+    pub fn encoded_len(self) -> usize {
+        match self {
+            TcpOption::Eol | TcpOption::Nop => 1,
+            TcpOption::Mss(_) => tcp_option::MSS_LEN,
+            TcpOption::WindowScale(_) => tcp_option::WINDOW_SCALE_LEN,
+            TcpOption::SackPermitted => tcp_option::SACK_PERMITTED_LEN,
+            TcpOption::Timestamps => tcp_option::TIMESTAMPS_LEN,
+        }
+    }
+}
+
+impl FromStr for TcpOption {
+    type Err = RouterFloodError;
+
+    fn from_str(entry: &str) -> Result<Self> {
+        let entry = entry.trim();
+        let (name, value) = match entry.split_once(':') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (entry, None),
+        };
+        let invalid = || -> RouterFloodError {
+            ConfigError::new(format!(
+                "Invalid TCP option '{}': expected mss:N, window_scale:N, sack_permitted, timestamps, nop or eol",
+                entry
+            )).into()
+        };
+
+        match (name.to_lowercase().as_str(), value) {
+            ("eol", None) => Ok(TcpOption::Eol),
+            ("nop", None) => Ok(TcpOption::Nop),
+            ("sack_permitted" | "sack", None) => Ok(TcpOption::SackPermitted),
+            ("timestamps" | "ts", None) => Ok(TcpOption::Timestamps),
+            ("mss", Some(value)) => value.parse().map(TcpOption::Mss).map_err(|_| invalid()),
+            ("window_scale" | "ws", Some(value)) => value.parse().map(TcpOption::WindowScale).map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for TcpOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TcpOption::Eol => write!(f, "eol"),
+            TcpOption::Nop => write!(f, "nop"),
+            TcpOption::Mss(mss) => write!(f, "mss:{}", mss),
+            TcpOption::WindowScale(shift) => write!(f, "window_scale:{}", shift),
+            TcpOption::SackPermitted => write!(f, "sack_permitted"),
+            TcpOption::Timestamps => write!(f, "timestamps"),
+        }
+    }
+}
+
+/// TCP options written as a comma-separated list, e.g. `mss:1460, nop, window_scale:7`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub struct TcpOptionList(pub Vec<TcpOption>);
+
+impl TcpOptionList {
+    /// Encoded length without padding
+    pub fn encoded_len(&self) -> usize {
+        self.0.iter().map(|option| option.encoded_len()).sum()
+    }
+}
+
+impl FromStr for TcpOptionList {
+    type Err = RouterFloodError;
+
+    fn from_str(list: &str) -> Result<Self> {
+        if list.trim().is_empty() {
+            return Ok(TcpOptionList(Vec::new()));
+        }
+        list.split(',').map(str::parse).collect::<Result<Vec<_>>>().map(TcpOptionList)
+    }
+}
+
+impl TryFrom<String> for TcpOptionList {
+    type Error = RouterFloodError;
+
+    fn try_from(list: String) -> Result<Self> {
+        list.parse()
+    }
+}
+
+impl From<TcpOptionList> for String {
+    fn from(list: TcpOptionList) -> Self {
+        list.to_string()
+    }
+}
+
+impl fmt::Display for TcpOptionList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, option) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", option)?;
+        }
+        Ok(())
+    }
+}
+
+impl TcpOptions {
+    /// Options to put on SYN segments
+    pub fn list(&self) -> TcpOptionList {
+        match self {
+            TcpOptions::None => TcpOptionList(Vec::new()),
+            TcpOptions::Preset { os } => os.options(),
+            TcpOptions::List { options } => options.clone(),
+        }
+    }
+}
+
+/// Parses `none`, a preset name or an option list
+impl FromStr for TcpOptions {
+    type Err = RouterFloodError;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        if spec.trim().eq_ignore_ascii_case("none") {
+            return Ok(TcpOptions::None);
+        }
+        if let Ok(os) = spec.trim().parse::<TcpOsProfile>() {
+            return Ok(TcpOptions::Preset { os });
+        }
+        Ok(TcpOptions::List { options: spec.parse()? })
+    }
+}
+
+/// Control bits of a TCP segment
+///
+/// Any combination can be generated, including ones no conforming stack
+/// sends (`null`, `xmas`, `syn_fin`), to exercise stateless sanity filters.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(try_from = "String", into = "String")]
+pub struct TcpFlagSet(u8);
+
+impl TcpFlagSet {
+    pub const FIN: Self = Self(tcp_flag::FIN);
+    pub const SYN: Self = Self(tcp_flag::SYN);
+    pub const RST: Self = Self(tcp_flag::RST);
+    pub const PSH: Self = Self(tcp_flag::PSH);
+    pub const ACK: Self = Self(tcp_flag::ACK);
+    pub const URG: Self = Self(tcp_flag::URG);
+    pub const ECE: Self = Self(tcp_flag::ECE);
+    pub const CWR: Self = Self(tcp_flag::CWR);
+    pub const SYN_ACK: Self = Self(tcp_flag::SYN | tcp_flag::ACK);
+    pub const PSH_ACK: Self = Self(tcp_flag::PSH | tcp_flag::ACK);
+    pub const FIN_ACK: Self = Self(tcp_flag::FIN | tcp_flag::ACK);
+    pub const RST_ACK: Self = Self(tcp_flag::RST | tcp_flag::ACK);
+    pub const ECE_CWR: Self = Self(tcp_flag::ECE | tcp_flag::CWR);
+    /// No flags set
+    pub const NULL: Self = Self(0);
+    /// FIN, PSH and URG, "lit up like a Christmas tree"
+    pub const XMAS: Self = Self(tcp_flag::FIN | tcp_flag::PSH | tcp_flag::URG);
+    pub const SYN_FIN: Self = Self(tcp_flag::SYN | tcp_flag::FIN);
+
+    /// Named presets, in display order
+    pub const PRESETS: &'static [(&'static str, TcpFlagSet)] = &[
+        ("syn", Self::SYN),
+        ("ack", Self::ACK),
+        ("fin", Self::FIN),
+        ("rst", Self::RST),
+        ("syn_ack", Self::SYN_ACK),
+        ("psh_ack", Self::PSH_ACK),
+        ("fin_ack", Self::FIN_ACK),
+        ("rst_ack", Self::RST_ACK),
+        ("urg", Self::URG),
+        ("ece_cwr", Self::ECE_CWR),
+        ("null", Self::NULL),
+        ("xmas", Self::XMAS),
+        ("syn_fin", Self::SYN_FIN),
+    ];
+
+    /// Single flags, in the order they are written when joined with `+`
+    const FLAGS: &'static [(&'static str, u8)] = &[
+        ("syn", tcp_flag::SYN),
+        ("ack", tcp_flag::ACK),
+        ("fin", tcp_flag::FIN),
+        ("rst", tcp_flag::RST),
+        ("psh", tcp_flag::PSH),
+        ("urg", tcp_flag::URG),
+        ("ece", tcp_flag::ECE),
+        ("cwr", tcp_flag::CWR),
+    ];
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    /// Flag bits as written to the TCP header
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Whether all flags of `other` are set
+    pub const fn contains(self, other: TcpFlagSet) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Which TCP segments carry payload data
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TcpPayload {
+    /// Header-only segments
+    None,
+    /// Segments with PSH set, such as `psh_ack`
+    #[default]
+    Push,
+    /// Every segment, SYNs and invalid flag sets included
+    All,
+}
+
+impl TcpPayload {
+    /// Whether segments with `flags` carry a payload
+    pub fn carries_data(self, flags: TcpFlagSet) -> bool {
+        match self {
+            TcpPayload::None => false,
+            TcpPayload::Push => flags.contains(TcpFlagSet::PSH),
+            TcpPayload::All => true,
+        }
+    }
+}
+
+impl FromStr for TcpPayload {
+    type Err = RouterFloodError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "none" => Ok(TcpPayload::None),
+            "push" | "psh" => Ok(TcpPayload::Push),
+            "all" => Ok(TcpPayload::All),
+            _ => Err(ConfigError::new(format!(
+                "Unknown TCP payload mode '{}': must be 'none', 'push' or 'all'", name
+            )).into()),
+        }
+    }
+}
+
+/// Parses a preset name or flags joined with `+`, e.g. `syn_ack` or `syn+fin`
+impl FromStr for TcpFlagSet {
+    type Err = RouterFloodError;
+
+    fn from_str(spec: &str) -> Result<Self> {
+        let spec = spec.trim().to_lowercase().replace('-', "_");
+        if let Some(&(_, flags)) = Self::PRESETS.iter().find(|(name, _)| *name == spec) {
+            return Ok(flags);
+        }
+
+        spec.split('+')
+            .map(|flag| {
+                Self::FLAGS.iter()
+                    .find(|(name, _)| *name == flag.trim())
+                    .map(|&(_, bit)| bit)
+                    .ok_or_else(|| ConfigError::new(format!(
+                        "Unknown TCP flags '{}': expected a preset such as syn_ack, xmas or null, \
+                         or flags (syn, ack, fin, rst, psh, urg, ece, cwr) joined with '+'",
+                        spec
+                    )).into())
+            })
+            .try_fold(0, |bits, bit: Result<u8>| Ok(bits | bit?))
+            .map(Self)
+    }
+}
+
+impl TryFrom<String> for TcpFlagSet {
+    type Error = RouterFloodError;
+
+    fn try_from(spec: String) -> Result<Self> {
+        spec.parse()
+    }
+}
+
+impl From<TcpFlagSet> for String {
+    fn from(flags: TcpFlagSet) -> Self {
+        flags.to_string()
+    }
+}
+
+/// Writes the preset name if there is one, otherwise the flags joined with `+`
+impl fmt::Display for TcpFlagSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((name, _)) = Self::PRESETS.iter().find(|(_, flags)| flags == self) {
+            return write!(f, "{}", name);
+        }
+
+        let names: Vec<&str> = Self::FLAGS.iter()
+            .filter(|(_, bit)| self.0 & bit != 0)
+            .map(|&(name, _)| name)
+            .collect();
+        write!(f, "{}", names.join("+"))
+    }
+}
+
+/// Check that options fit in the header and hold valid values
+pub(super) fn validate_tcp_options(options: &TcpOptions) -> Result<()> {
+    let list = options.list();
+    if list.encoded_len() > TCP_MAX_OPTIONS_SIZE {
+        return Err(ConfigError::new(format!(
+            "TCP options take {} bytes, more than the {} bytes a TCP header can carry",
+            list.encoded_len(), TCP_MAX_OPTIONS_SIZE
+        )).into());
+    }
+
+    for option in &list.0 {
+        match *option {
+            TcpOption::Mss(0) => return Err(ConfigError::new("TCP MSS option must be greater than 0").into()),
+            TcpOption::WindowScale(shift) if shift > tcp_option::MAX_WINDOW_SCALE => {
+                return Err(ConfigError::new(format!(
+                    "TCP window scale {} exceeds the maximum of {}", shift, tcp_option::MAX_WINDOW_SCALE
+                )).into());
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Check that extra TCP flag set ratios are valid fractions
+pub(super) fn validate_tcp_flag_ratios(mix: &ProtocolMix) -> Result<()> {
+    let ipv6_flags = mix.ipv6.iter().flat_map(|ipv6| &ipv6.tcp_flags);
+    for (flags, &ratio) in mix.tcp_flags.iter().chain(ipv6_flags) {
+        if !(0.0..=1.0).contains(&ratio) {
+            return Err(ConfigError::new(format!(
+                "TCP flags '{}' ratio must be between 0.0 and 1.0, got {}", flags, ratio
+            )).into());
+        }
+    }
+    Ok(())
+}
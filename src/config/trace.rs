@@ -0,0 +1,165 @@
+//! Trace-driven rate replay
+//!
+//! A trace is a timeline of `(offset_seconds, pps)` points, typically taken
+//! from a production traffic graph. The rate is interpolated linearly
+//! between points and held after the last one. Points may also carry a
+//! protocol mix, which stays in effect until the next point that sets one.
+//!
+//! Trace rates are aggregate packets per second, like the graphs they come
+//! from, and are split evenly across worker threads.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::ProtocolMix;
+use crate::constants::MAX_PACKET_RATE;
+use crate::error::{ConfigError, Result};
+
+/// Column order of trace files without a header row
+const COLUMNS: [&str; 9] = [
+    "offset_seconds",
+    "pps",
+    "udp_ratio",
+    "tcp_syn_ratio",
+    "tcp_ack_ratio",
+    "tcp_fin_ratio",
+    "tcp_rst_ratio",
+    "icmp_ratio",
+    "custom_ratio",
+];
+
+/// A single point of a rate trace
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TracePoint {
+    pub offset_secs: f64,
+    /// Aggregate packets per second across all threads
+    pub pps: f64,
+    #[serde(default)]
+    pub protocol_mix: Option<ProtocolMix>,
+}
+
+/// Load a trace from a CSV file
+///
+/// Columns are `offset_seconds, pps` followed by optional protocol mix
+/// ratios named like the `protocol_mix` fields. The header row may be left
+/// out, in which case the columns are taken in that order. Ratio columns
+/// left empty on a row keep the previous mix; missing ratios count as 0.
+pub fn load_trace(path: &str) -> Result<Vec<TracePoint>> {
+    if !Path::new(path).exists() {
+        return Err(ConfigError::new(format!("Trace file not found: {}", path)).into());
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| ConfigError::new(format!("Failed to read trace file: {}", e)))?;
+
+    let mut records = reader.records().peekable();
+    let mut columns: Vec<String> = COLUMNS.iter().map(|name| name.to_string()).collect();
+
+    // A first row that does not start with a number is the header
+    if let Some(Ok(first)) = records.peek()
+        && first.get(0).is_some_and(|field| field.parse::<f64>().is_err()) {
+            columns = first.iter().map(|name| name.to_lowercase()).collect();
+            records.next();
+
+            for required in &COLUMNS[..2] {
+                if !columns.iter().any(|name| name == required) {
+                    return Err(ConfigError::new(format!("Trace file is missing the '{}' column", required)).into());
+                }
+            }
+        }
+
+    let mut points = Vec::new();
+    for (row, record) in records.enumerate() {
+        let record = record.map_err(|e| ConfigError::new(format!("Failed to read trace file: {}", e)))?;
+        let line = record.position().map_or(row as u64 + 1, |position| position.line());
+
+        let mut offset_secs = None;
+        let mut pps = None;
+        let mut mix: Option<ProtocolMix> = None;
+
+        for (name, field) in columns.iter().zip(record.iter()) {
+            if field.is_empty() {
+                continue;
+            }
+
+            let value: f64 = field.parse().map_err(|_| {
+                ConfigError::new(format!("Trace line {}: invalid {} value '{}'", line, name, field))
+            })?;
+
+            match name.as_str() {
+                "offset_seconds" => offset_secs = Some(value),
+                "pps" => pps = Some(value),
+                ratio => {
+                    let mix = mix.get_or_insert_with(ProtocolMix::empty);
+                    match ratio {
+                        "udp_ratio" => mix.udp_ratio = value,
+                        "tcp_syn_ratio" => mix.tcp_syn_ratio = value,
+                        "tcp_ack_ratio" => mix.tcp_ack_ratio = value,
+                        "tcp_fin_ratio" => mix.tcp_fin_ratio = value,
+                        "tcp_rst_ratio" => mix.tcp_rst_ratio = value,
+                        "icmp_ratio" => mix.icmp_ratio = value,
+                        "custom_ratio" => mix.custom_ratio = value,
+                        "arp_ratio" => mix.arp_ratio = value,
+                        other => {
+                            return Err(ConfigError::new(format!("Trace file has unknown column '{}'", other)).into());
+                        }
+                    }
+                }
+            }
+        }
+
+        let (Some(offset_secs), Some(pps)) = (offset_secs, pps) else {
+            return Err(ConfigError::new(format!("Trace line {}: offset_seconds and pps are required", line)).into());
+        };
+        points.push(TracePoint { offset_secs, pps, protocol_mix: mix });
+    }
+
+    Ok(points)
+}
+
+/// Validate trace points for a run with `threads` workers
+pub(super) fn validate_trace(points: &[TracePoint], threads: usize) -> Result<()> {
+    if points.is_empty() {
+        return Err(ConfigError::new("Rate trace must contain at least one point").into());
+    }
+
+    let mut previous = None;
+    for point in points {
+        if !point.offset_secs.is_finite() || point.offset_secs < 0.0 {
+            return Err(ConfigError::new("Rate trace offsets must not be negative").into());
+        }
+        if previous.is_some_and(|offset| point.offset_secs <= offset) {
+            return Err(ConfigError::new(
+                format!("Rate trace offsets must be increasing (at {} s)", point.offset_secs)
+            ).into());
+        }
+        previous = Some(point.offset_secs);
+
+        if !point.pps.is_finite() || point.pps < 0.0 {
+            return Err(ConfigError::new(
+                format!("Rate trace pps must not be negative (at {} s)", point.offset_secs)
+            ).into());
+        }
+        if point.pps / threads.max(1) as f64 > MAX_PACKET_RATE as f64 {
+            return Err(ConfigError::new(
+                format!("Rate trace pps at {} s exceeds {} per thread", point.offset_secs, MAX_PACKET_RATE)
+            ).into());
+        }
+
+        if let Some(ref mix) = point.protocol_mix {
+            let total = mix.total();
+            if (total - 1.0).abs() > 0.01 {
+                return Err(ConfigError::new(
+                    format!("Rate trace protocol mix at {} s must sum to 1.0, got {}", point.offset_secs, total)
+                ).into());
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -14,6 +14,7 @@ pub const MAX_THREADS: usize = 256;
 pub const MAX_PACKET_RATE: u64 = 1_000_000;  // 1M pps per thread for high-performance testing
 pub const RECOMMENDED_MAX_RATE: u64 = 100_000; // Warn above this rate
 pub const MIN_FILE_DESCRIPTORS: i64 = 1024;
+pub const MAX_SPIN_THRESHOLD_US: u64 = 10_000; // Longest busy-wait in hybrid pacing
 
 // Packet size constraints
 pub const MIN_PAYLOAD_SIZE: usize = 20;
@@ -26,6 +27,12 @@ pub const UDP_HEADER_SIZE: usize = 8;
 pub const ICMP_HEADER_SIZE: usize = 8;
 pub const ARP_PACKET_SIZE: usize = 28;
 pub const ARP_FRAME_SIZE: usize = ETHERNET_HEADER_SIZE + ARP_PACKET_SIZE;
+pub const ETHERNET_FCS_SIZE: usize = 4;
+pub const ETHERNET_FRAME_OVERHEAD: usize = ETHERNET_HEADER_SIZE + ETHERNET_FCS_SIZE;
+pub const MIN_FRAME_SIZE: usize = 64; // Smallest Ethernet frame including FCS
+pub const MAX_FRAME_SIZE: usize = 1518; // Largest standard Ethernet frame including FCS
+pub const INSTRUMENTED_HEADER_SIZE: usize = 24; // Magic, stream ID, sequence, TX timestamp
+pub const TCP_MAX_OPTIONS_SIZE: usize = 40; // Data offset tops out at 60-byte headers
 
 // Common frame size constants
 pub const IPV4_TCP_HEADER_SIZE: usize = IPV4_HEADER_SIZE + TCP_HEADER_SIZE;
@@ -94,7 +101,16 @@ pub mod defaults {
     pub const DEFAULT_DURATION_SECONDS: u64 = 60;
     pub const DEFAULT_MAX_BANDWIDTH_MBPS: f64 = 10_000.0;  // 10 Gbps capable
     pub const DEFAULT_STATS_INTERVAL_MS: u64 = 1000;
+    pub const DEFAULT_SPIN_THRESHOLD_US: u64 = 1000;  // Covers the async timer's ~1 ms resolution
+    pub const DEFAULT_MAX_CATCH_UP_MS: u64 = 50;
+    pub const ADAPTIVE_GROWTH_FACTOR: f64 = 2.0;
+    pub const ADAPTIVE_STEP_DURATION_MS: u64 = 5000;
+    pub const ADAPTIVE_PRECISION: f64 = 0.05;      // Bracket within 5% of the result
+    pub const ADAPTIVE_MAX_FAILURE_RATIO: f64 = 0.01;
+    pub const ADAPTIVE_PROBE_INTERVAL_MS: u64 = 100;
+    pub const ADAPTIVE_PROBE_TIMEOUT_MS: u64 = 1000;
     pub const STATS_INTERVAL: u64 = DEFAULT_STATS_INTERVAL;
+    pub const PAYLOAD_MAGIC: u32 = 0x5246_4C44; // "RFLD" in instrumented payloads
     
     // Protocol mix ratios (must sum to 1.0)
     pub const UDP_RATIO: f64 = 0.6;
@@ -146,6 +162,128 @@ pub mod icmp {
     pub const MIN_PING_SIZE: usize = 8;
     pub const MAX_PING_SIZE: usize = 56;
     pub const DEFAULT_PING_SIZE: usize = 32;
+
+    // Message types (RFC 792, RFC 950)
+    pub const TYPE_DEST_UNREACHABLE: u8 = 3;
+    pub const TYPE_ECHO_REQUEST: u8 = 8;
+    pub const TYPE_TIME_EXCEEDED: u8 = 11;
+    pub const TYPE_PARAMETER_PROBLEM: u8 = 12;
+    pub const TYPE_TIMESTAMP: u8 = 13;
+    pub const TYPE_ADDRESS_MASK: u8 = 17;
+
+    /// Highest destination unreachable code (RFC 1812)
+    pub const MAX_UNREACHABLE_CODE: u8 = 15;
+    /// Fragmentation needed and DF set; carries the next-hop MTU (RFC 1191)
+    pub const CODE_FRAG_NEEDED: u8 = 4;
+    /// Originate, receive and transmit timestamps
+    pub const TIMESTAMP_BODY_SIZE: usize = 12;
+    pub const ADDRESS_MASK_BODY_SIZE: usize = 4;
+    /// Bytes of the offending datagram quoted after its IP header
+    pub const QUOTED_DATA_SIZE: usize = 8;
+    /// Next-hop MTUs reported in fragmentation needed messages
+    pub const NEXT_HOP_MTUS: &[u16] = &[576, 1280, 1400, 1492];
+}
+
+// ICMPv6 constants (RFC 4443, RFC 4861, RFC 3810)
+pub mod icmpv6 {
+    use std::net::Ipv6Addr;
+
+    pub const TYPE_ECHO_REQUEST: u8 = 128;
+    pub const TYPE_ROUTER_SOLICITATION: u8 = 133;
+    pub const TYPE_NEIGHBOR_SOLICITATION: u8 = 135;
+    pub const TYPE_MLDV2_REPORT: u8 = 143;
+
+    /// Neighbor Discovery messages with another hop limit are dropped
+    pub const ND_HOP_LIMIT: u8 = 255;
+    /// MLD messages never leave the link
+    pub const MLD_HOP_LIMIT: u8 = 1;
+
+    pub const ALL_ROUTERS: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 2);
+    pub const ALL_MLDV2_ROUTERS: Ipv6Addr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0x16);
+    /// ff02::1:ff00:0/104, completed with the low 24 bits of the target
+    pub const SOLICITED_NODE_PREFIX: [u8; 13] = [0xff, 0x02, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0xff];
+
+    /// Source link-layer address option
+    pub const OPTION_SOURCE_LINK_ADDR: u8 = 1;
+    pub const SLLA_OPTION_SIZE: usize = 8;
+    /// Message lengths including the 8-byte ICMPv6 header
+    pub const NEIGHBOR_SOLICITATION_SIZE: usize = 24 + SLLA_OPTION_SIZE;
+    pub const ROUTER_SOLICITATION_SIZE: usize = 8 + SLLA_OPTION_SIZE;
+    /// Report header and one group record without sources
+    pub const MLDV2_REPORT_SIZE: usize = 8 + 20;
+    /// CHANGE_TO_EXCLUDE_MODE with no sources, i.e. a join
+    pub const MLD_RECORD_CHANGE_TO_EXCLUDE: u8 = 4;
+}
+
+// IPv4 fragmentation (RFC 791)
+pub mod fragment {
+    /// Fragment offsets count 8-byte units
+    pub const OFFSET_UNIT: usize = 8;
+    /// Fits a 576-byte datagram with a 20-byte header
+    pub const DEFAULT_FRAGMENT_SIZE: usize = 552;
+    /// Largest multiple of 8 that fits an IPv4 datagram after its header
+    pub const MAX_FRAGMENT_SIZE: usize = 65_512;
+    /// More fragments bit of the 3-bit flags field
+    pub const FLAG_MORE_FRAGMENTS: u8 = 1;
+    /// Payload bytes of the first fragment of the tiny-first pattern
+    pub const TINY_FRAGMENT_SIZE: usize = 8;
+    /// Bytes repeated at the start of each fragment of the overlapping pattern
+    pub const OVERLAP_SIZE: usize = 8;
+}
+
+// IPv6 extension headers (RFC 8200, 6275, 8754)
+pub mod ipv6_extension {
+    pub const HOP_BY_HOP: u8 = 0;
+    pub const ROUTING: u8 = 43;
+    pub const FRAGMENT: u8 = 44;
+    pub const DESTINATION_OPTIONS: u8 = 60;
+
+    /// Options headers and the fixed part of routing headers are multiples of 8 bytes
+    pub const UNIT: usize = 8;
+    pub const FRAGMENT_SIZE: usize = 8;
+    pub const MAX_OPTIONS_UNITS: u16 = 256;
+    /// Routing headers list at most 127 addresses in their 8-bit length
+    pub const MAX_ROUTING_SEGMENTS: u8 = 127;
+    /// Longest chain accepted, leaving room for the upper-layer header and payload
+    pub const MAX_CHAIN_SIZE: usize = 32 * 1024;
+
+    pub const OPTION_PAD1: u8 = 0;
+    pub const OPTION_PADN: u8 = 1;
+    pub const OPTION_ROUTER_ALERT: u8 = 5;
+    pub const ROUTER_ALERT_SIZE: usize = 4;
+    /// Data bytes of the longest PadN option
+    pub const MAX_PADN_DATA: usize = 255;
+
+    pub const ROUTING_TYPE_SOURCE_ROUTE: u8 = 0;
+    pub const ROUTING_TYPE_MOBILE_IPV6: u8 = 2;
+    pub const ROUTING_TYPE_SEGMENT_ROUTING: u8 = 4;
+}
+
+// TCP option kinds and lengths (RFC 793, 2018, 7323)
+pub mod tcp_option {
+    pub const KIND_EOL: u8 = 0;
+    pub const KIND_NOP: u8 = 1;
+    pub const KIND_MSS: u8 = 2;
+    pub const KIND_WINDOW_SCALE: u8 = 3;
+    pub const KIND_SACK_PERMITTED: u8 = 4;
+    pub const KIND_TIMESTAMPS: u8 = 8;
+    pub const MSS_LEN: usize = 4;
+    pub const WINDOW_SCALE_LEN: usize = 3;
+    pub const SACK_PERMITTED_LEN: usize = 2;
+    pub const TIMESTAMPS_LEN: usize = 10;
+    pub const MAX_WINDOW_SCALE: u8 = 14;
+}
+
+// TCP header control bits (RFC 9293, RFC 3168)
+pub mod tcp_flag {
+    pub const FIN: u8 = 0x01;
+    pub const SYN: u8 = 0x02;
+    pub const RST: u8 = 0x04;
+    pub const PSH: u8 = 0x08;
+    pub const ACK: u8 = 0x10;
+    pub const URG: u8 = 0x20;
+    pub const ECE: u8 = 0x40;
+    pub const CWR: u8 = 0x80;
 }
 
 // Error message constants
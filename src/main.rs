@@ -69,6 +69,28 @@ fn perform_validations(config: &router_flood::config::Config, target_ip: &IpAddr
         config.attack.packet_rate as u64,
     )?;
 
+    if let Some(ref scenario) = config.scenario {
+        for phase in &scenario.phases {
+            let phase_config = phase.apply_to(config);
+            validate_comprehensive_security(
+                target_ip,
+                &phase_config.target.ports,
+                phase_config.attack.threads,
+                phase_config.attack.packet_rate as u64,
+            )?;
+        }
+    }
+
+    // The search may take every worker up to its maximum rate
+    if let Some(ref search) = config.adaptive {
+        validate_comprehensive_security(
+            target_ip,
+            &config.target.ports,
+            config.attack.threads,
+            search.max_rate as u64,
+        )?;
+    }
+
     validate_system_requirements(config.safety.dry_run)?;
     Ok(())
 }
@@ -0,0 +1,166 @@
+//! Adaptive closed-loop rate search
+//!
+//! The search multiplies the rate by `growth_factor` while the target stays
+//! healthy. After the first unhealthy step it backs off and bisects between
+//! the highest healthy and the lowest unhealthy rate until the bracket is
+//! within `precision`. Each rate is published to the running workers through
+//! `PhaseControl` and held for one observation window.
+
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::time;
+use tracing::{info, warn};
+
+use crate::config::{AdaptiveSearch, Config, RateProfile};
+use crate::network::health::HealthMonitor;
+use crate::network::phase::{PhaseControl, PhaseSettings};
+use crate::stats::{SearchStep, Stats};
+
+/// Below this per-thread rate the search gives up backing off
+const MIN_SEARCH_RATE: f64 = 1.0;
+
+/// Search state: the rate to try next and the bracket found so far
+#[derive(Debug, Clone)]
+pub struct RateSearch {
+    rate: f64,
+    max_rate: f64,
+    growth_factor: f64,
+    precision: f64,
+    healthy: Option<f64>,
+    unhealthy: Option<f64>,
+    done: bool,
+}
+
+impl RateSearch {
+    pub fn new(search: &AdaptiveSearch) -> Self {
+        Self {
+            rate: search.start_rate,
+            max_rate: search.max_rate,
+            growth_factor: search.growth_factor,
+            precision: search.precision,
+            healthy: None,
+            unhealthy: None,
+            done: false,
+        }
+    }
+
+    /// Per-thread rate to try next
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Highest rate found healthy so far
+    pub fn sustainable_rate(&self) -> Option<f64> {
+        self.healthy
+    }
+
+    /// Record the outcome of the current rate and pick the next one
+    pub fn record(&mut self, healthy: bool) {
+        if healthy {
+            self.healthy = Some(self.healthy.map_or(self.rate, |rate| rate.max(self.rate)));
+        } else {
+            self.unhealthy = Some(self.unhealthy.map_or(self.rate, |rate| rate.min(self.rate)));
+        }
+
+        match (self.healthy, self.unhealthy) {
+            // Still ramping up
+            (Some(good), None) => {
+                if good >= self.max_rate {
+                    self.done = true;
+                } else {
+                    self.rate = (good * self.growth_factor).min(self.max_rate);
+                }
+            }
+            // Even the lowest rate tried degrades the target: back off further
+            (None, Some(bad)) => {
+                self.rate = bad / self.growth_factor;
+                if self.rate < MIN_SEARCH_RATE {
+                    self.done = true;
+                }
+            }
+            (Some(good), Some(bad)) => {
+                if (bad - good) / good <= self.precision {
+                    self.done = true;
+                } else {
+                    self.rate = (good + bad) / 2.0;
+                }
+            }
+            (None, None) => unreachable!("every step is recorded as healthy or unhealthy"),
+        }
+    }
+}
+
+/// Worker settings for a constant per-thread `rate` on top of `base`
+pub fn search_settings(base: &Config, rate: f64) -> PhaseSettings {
+    let mut config = base.clone();
+    config.attack.packet_rate = rate;
+    config.attack.rate_profile = RateProfile::Constant;
+    PhaseSettings::from_config(&config, None, Instant::now())
+}
+
+/// Run the search on the running workers and return the highest
+/// sustainable per-thread rate
+pub async fn run_rate_search(
+    base: &Config,
+    search: &AdaptiveSearch,
+    target_ip: IpAddr,
+    stats: Arc<Stats>,
+    phase_control: &PhaseControl,
+) -> Option<f64> {
+    let default_port = base.target.ports.first().copied().unwrap_or_default();
+    let mut monitor = HealthMonitor::new(search, Arc::clone(&stats), target_ip, default_port, base.safety.dry_run);
+    if base.safety.dry_run && search.health.uses_probes() {
+        warn!("Dry-run: health probes are simulated, every probe counts as answered");
+    }
+
+    let mut rate_search = RateSearch::new(search);
+    let step = Duration::from_millis(search.step_duration_ms);
+
+    while !rate_search.is_done() {
+        let rate = rate_search.rate();
+        phase_control.publish(search_settings(base, rate));
+        stats.set_target_rate(rate);
+        monitor.start_window();
+
+        time::sleep(step).await;
+
+        let sample = monitor.sample();
+        let healthy = monitor.is_healthy(&sample);
+        info!(
+            "🔎 Search step {:.1} pps/thread: {} (achieved {:.0} pps, failures {:.2}%{}{})",
+            rate,
+            if healthy { "healthy" } else { "degraded" },
+            sample.achieved_pps,
+            sample.failure_ratio * 100.0,
+            sample.probe_rtt_ms.map_or(String::new(), |rtt| format!(", rtt {:.2} ms", rtt)),
+            sample.response_ratio.map_or(String::new(), |ratio| format!(", answered {:.1}%", ratio * 100.0)),
+        );
+
+        stats.record_search_step(SearchStep {
+            rate,
+            achieved_pps: sample.achieved_pps,
+            failure_ratio: sample.failure_ratio,
+            probe_rtt_ms: sample.probe_rtt_ms,
+            response_ratio: sample.response_ratio,
+            healthy,
+        });
+        rate_search.record(healthy);
+    }
+
+    let result = rate_search.sustainable_rate();
+    stats.set_sustainable_rate(result, base.attack.threads);
+    match result {
+        Some(rate) => info!(
+            "🎯 Highest sustainable rate: {:.1} pps/thread ({:.1} pps total)",
+            rate, rate * base.attack.threads as f64
+        ),
+        None => warn!("No sustainable rate found: the target degraded even at the lowest rate tried"),
+    }
+
+    result
+}
@@ -0,0 +1,119 @@
+//! Burst (on/off) load shaping
+//!
+//! All workers share one schedule anchored at a common epoch, so burst and
+//! idle windows line up across threads and the target sees a single
+//! aggregate microburst instead of several out-of-phase ones.
+
+use std::time::{Duration, Instant};
+
+use crate::config::BurstPattern;
+
+/// Load phase reported in statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum LoadPhase {
+    /// Constant-rate sending (burst mode disabled)
+    Steady = 0,
+    /// Inside a burst window, sending at the multiplied rate
+    Burst = 1,
+    /// Inside an idle window, not sending
+    Idle = 2,
+}
+
+impl LoadPhase {
+    /// Convert from the raw value stored in stats
+    pub const fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Burst,
+            2 => Self::Idle,
+            _ => Self::Steady,
+        }
+    }
+
+    /// Get all phases
+    pub const fn all() -> [Self; 3] {
+        [Self::Steady, Self::Burst, Self::Idle]
+    }
+
+    /// Bit used when collecting the set of phases seen in an interval
+    pub const fn mask(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// Get the phase name for display and export
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Steady => "steady",
+            Self::Burst => "burst",
+            Self::Idle => "idle",
+        }
+    }
+}
+
+/// Position within the burst cycle at a given instant
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BurstState {
+    pub phase: LoadPhase,
+    /// Zero-based index of the current burst/idle cycle
+    pub cycle: u64,
+    /// Time left until the phase changes
+    pub remaining: Duration,
+}
+
+/// Shared burst schedule derived from a `BurstPattern`
+#[derive(Debug, Clone, Copy)]
+pub struct BurstSchedule {
+    epoch: Instant,
+    burst: Duration,
+    idle: Duration,
+    multiplier: f64,
+}
+
+impl BurstSchedule {
+    /// Create a schedule anchored at `epoch`
+    pub fn new(pattern: &BurstPattern, epoch: Instant) -> Self {
+        Self {
+            epoch,
+            burst: Duration::from_millis(pattern.burst_duration_ms),
+            idle: Duration::from_millis(pattern.idle_duration_ms),
+            multiplier: pattern.burst_multiplier,
+        }
+    }
+
+    /// Rate multiplier applied during burst windows
+    pub fn multiplier(&self) -> f64 {
+        self.multiplier
+    }
+
+    /// Length of one full burst + idle cycle
+    pub fn period(&self) -> Duration {
+        self.burst + self.idle
+    }
+
+    /// Determine the phase at the given instant
+    pub fn state_at(&self, now: Instant) -> BurstState {
+        let period = self.period().as_nanos();
+        if period == 0 {
+            return BurstState { phase: LoadPhase::Burst, cycle: 0, remaining: Duration::MAX };
+        }
+
+        let elapsed = now.saturating_duration_since(self.epoch).as_nanos();
+        let cycle = (elapsed / period) as u64;
+        let offset = elapsed % period;
+        let burst = self.burst.as_nanos();
+
+        if offset < burst {
+            BurstState {
+                phase: LoadPhase::Burst,
+                cycle,
+                remaining: Duration::from_nanos((burst - offset) as u64),
+            }
+        } else {
+            BurstState {
+                phase: LoadPhase::Idle,
+                cycle,
+                remaining: Duration::from_nanos((period - offset) as u64),
+            }
+        }
+    }
+}
@@ -3,18 +3,23 @@
 use std::net::IpAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::time;
 use tracing::{error, info, warn};
 
-use crate::config::Config;
+use crate::config::{
+    format_ports, Config, PacingMode, PayloadPattern, PayloadSizes, PortSelection, RateProfile, Scenario, SourceConfig,
+    TcpOptions, TimingModel,
+};
 use crate::constants::GRACEFUL_SHUTDOWN_TIMEOUT;
 use crate::error::{RouterFloodError, Result};
 use crate::system_monitor::SystemMonitor;
 use crate::network::{find_interface_by_name, default_interface};
 use crate::stats::Stats;
-use crate::network::target::PortTarget;
+use crate::network::adaptive::{run_rate_search, search_settings};
+use crate::network::phase::{PhaseControl, PhaseSettings};
 use crate::network::worker_manager::Workers;
+use crate::packet::PacketSizes;
 use crate::security::{AuditLogger, EventType};
 
 /// Network interface setup
@@ -114,9 +119,10 @@ impl Engine {
         target_ip: IpAddr,
         selected_interface: Option<pnet::datalink::NetworkInterface>,
     ) -> Self {
-        let stats = Arc::new(Stats::new(
-            config.export.enabled.then_some(config.export.clone()),
-        ));
+        let stats = Arc::new(
+            Stats::new(config.export.enabled.then_some(config.export.clone()))
+                .with_quota(config.attack.max_packets, config.attack.max_bytes),
+        );
         let running = Arc::new(AtomicBool::new(true));
         let audit_logger = AuditLogger::from_config(&config);
         
@@ -139,7 +145,7 @@ impl Engine {
             &self.config.target.ports,
             self.config.attack.threads,
             self.config.attack.packet_rate as u64,
-            self.config.attack.duration,
+            self.planned_duration(),
             self.config.target.interface.as_deref(),
             &self.stats.session_id,
         ) {
@@ -152,12 +158,17 @@ impl Engine {
         let monitoring = MonitoringTasks::new(Arc::clone(&self.stats), self.config.clone(), Arc::clone(&self.running));
         monitoring.spawn_all();
         
+        // Open the first scenario phase before any packet is counted
+        if let Some(first) = self.config.scenario.as_ref().and_then(|scenario| scenario.phases.first()) {
+            self.stats.begin_phase(&first.name);
+        }
+
         // Create and start workers
-        let multi_port_target = Arc::new(PortTarget::new(self.config.target.ports.clone()));
+        let phase_control = Arc::new(PhaseControl::new(self.initial_phase_settings()));
         let worker_manager = Workers::new(
             &self.config,
             Arc::clone(&self.stats),
-            multi_port_target,
+            Arc::clone(&phase_control),
             self.target_ip,
             self.selected_interface.as_ref(),
             self.config.safety.dry_run,
@@ -170,11 +181,16 @@ impl Engine {
                 self.running.store(false, Ordering::Relaxed);
                 worker_manager.stop();
             }
-            _ = self.wait_for_duration() => {
+            _ = self.wait_for_completion(&phase_control) => {
                 info!("⏰ Duration reached, stopping...");
                 self.running.store(false, Ordering::Relaxed);
                 worker_manager.stop();
             }
+            _ = self.stats.quota_reached() => {
+                info!("📦 Send quota reached, stopping...");
+                self.running.store(false, Ordering::Relaxed);
+                worker_manager.stop();
+            }
         }
         
         // Cleanup
@@ -186,13 +202,82 @@ impl Engine {
         Ok(())
     }
     
+    /// Total planned run time; a scenario overrides `attack.duration`, and
+    /// without a duration a rate trace runs to its last point. An adaptive
+    /// search runs until it converges.
+    fn planned_duration(&self) -> Option<u64> {
+        if self.config.adaptive.is_some() {
+            return None;
+        }
+
+        match self.config.scenario {
+            Some(ref scenario) => Some(scenario.total_duration_secs()),
+            None => self.config.attack.duration
+                .or_else(|| self.config.attack.rate_profile.trace_duration_secs()),
+        }
+    }
+
+    /// Settings for the first phase (or the whole run without a scenario)
+    fn initial_phase_settings(&self) -> PhaseSettings {
+        if let Some(ref search) = self.config.adaptive {
+            return search_settings(&self.config, search.start_rate);
+        }
+
+        match self.config.scenario.as_ref().and_then(|scenario| scenario.phases.first()) {
+            Some(phase) => PhaseSettings::from_config(
+                &phase.apply_to(&self.config),
+                Some(phase.name.clone()),
+                Instant::now(),
+            ),
+            None => PhaseSettings::from_config(&self.config, None, Instant::now()),
+        }
+    }
+
+    async fn wait_for_completion(&self, phase_control: &PhaseControl) {
+        if let Some(ref search) = self.config.adaptive {
+            run_rate_search(&self.config, search, self.target_ip, Arc::clone(&self.stats), phase_control).await;
+            return;
+        }
+
+        match self.config.scenario {
+            Some(ref scenario) => self.run_scenario(scenario, phase_control).await,
+            None => self.wait_for_duration().await,
+        }
+    }
+
     async fn wait_for_duration(&self) {
-        if let Some(duration_secs) = self.config.attack.duration {
+        if let Some(duration_secs) = self.planned_duration() {
             time::sleep(Duration::from_secs(duration_secs)).await;
         } else {
             std::future::pending().await
         }
     }
+
+    /// Run scenario phases back to back on the running workers
+    async fn run_scenario(&self, scenario: &Scenario, phase_control: &PhaseControl) {
+        let total = scenario.phases.len();
+
+        for (index, phase) in scenario.phases.iter().enumerate() {
+            // The first phase is already loaded and open when the workers start
+            if index > 0 {
+                let config = phase.apply_to(&self.config);
+                phase_control.publish(PhaseSettings::from_config(&config, Some(phase.name.clone()), Instant::now()));
+                self.stats.begin_phase(&phase.name);
+            }
+
+            info!("▶️  Phase {}/{}: {} ({} seconds)", index + 1, total, phase.name, phase.duration_secs);
+
+            time::sleep(Duration::from_secs(phase.duration_secs)).await;
+
+            self.stats.end_phase();
+
+            // The last phase is exported with the final statistics
+            if self.config.export.enabled && index + 1 < total
+                && let Err(e) = self.stats.export_stats().await {
+                    error!("Failed to export phase stats: {}", e);
+                }
+        }
+    }
     
     fn print_operation_info(&self) {
         let version = env!("CARGO_PKG_VERSION");
@@ -205,29 +290,120 @@ impl Engine {
         }
         
         info!("   Session ID: {}", self.stats.session_id);
-        info!("   Target: {} (Ports: {:?})", self.target_ip, self.config.target.ports);
+        info!("   Target: {} (Ports: {})", self.target_ip, format_ports(&self.config.target.ports));
+        if self.config.target.port_selection != PortSelection::RoundRobin {
+            info!("   Port selection: {:?}", self.config.target.port_selection);
+        }
         info!("   Threads: {}, Rate: {} pps/thread", 
             self.config.attack.threads, self.config.attack.packet_rate);
         
-        if let Some(d) = self.config.attack.duration {
+        if let Some(ref scenario) = self.config.scenario {
+            info!("   Scenario: {} phases, {} seconds total", scenario.phases.len(), scenario.total_duration_secs());
+            for phase in &scenario.phases {
+                let config = phase.apply_to(&self.config);
+                info!("     • {}: {}s at {} pps/thread, {} byte payload, ports {}",
+                    phase.name, phase.duration_secs, config.attack.packet_rate,
+                    config.attack.payload_size, format_ports(&config.target.ports));
+            }
+        } else if let Some(ref search) = self.config.adaptive {
+            info!("   Adaptive search: {} to {} pps/thread, x{} per step, {} ms steps, {:?}",
+                search.start_rate, search.max_rate, search.growth_factor,
+                search.step_duration_ms, search.health);
+        } else if let Some(d) = self.planned_duration() {
             info!("   Duration: {} seconds", d);
         }
+
+        if let Some(packets) = self.config.attack.max_packets {
+            info!("   Stop after: {} packets", packets);
+        }
+        if let Some(bytes) = self.config.attack.max_bytes {
+            info!("   Stop after: {} bytes", bytes);
+        }
+
+        match self.config.attack.rate_profile {
+            RateProfile::Constant => {}
+            RateProfile::Trace { ref points } => {
+                info!("   Rate trace: {} points over {} seconds", points.len(),
+                    self.config.attack.rate_profile.trace_duration_secs().unwrap_or(0));
+            }
+            ref profile => info!("   Rate profile: {:?}", profile),
+        }
+
+        if self.config.attack.payload_sizes != PayloadSizes::default() {
+            let sizes = PacketSizes::from_config(&self.config.attack);
+            info!("   Payload sizes: UDP {:?}, ICMP {:?}", sizes.udp, sizes.icmp);
+        }
+
+        if self.config.source != SourceConfig::default() {
+            info!(
+                "   Source addresses: IPv4 {:?}, IPv6 {:?}, ports {:?}",
+                self.config.source.ipv4, self.config.source.ipv6, self.config.source.port
+            );
+        }
+
+        if self.config.attack.payload_pattern != PayloadPattern::Random {
+            info!("   Payload pattern: {:?}", self.config.attack.payload_pattern);
+        }
+
+        if self.config.attack.tcp_options != TcpOptions::None {
+            info!("   TCP options: {}", self.config.attack.tcp_options.list());
+        }
+
+        if self.config.attack.timing != TimingModel::Constant {
+            info!("   Timing model: {:?}", self.config.attack.timing);
+        }
+
+        if let (true, Some(pattern)) = (self.config.attack.burst_mode, &self.config.attack.burst_pattern) {
+            info!("   Burst: {}ms at {}x rate, {}ms idle",
+                pattern.burst_duration_ms, pattern.burst_multiplier, pattern.idle_duration_ms);
+        }
         
+        if self.config.safety.rate_limit {
+            let pps = self.config.safety.max_packets_per_second
+                .map_or("unlimited".to_string(), |pps| format!("{} pps", pps));
+            let mbps = self.config.safety.max_bandwidth_mbps
+                .map_or("unlimited".to_string(), |mbps| format!("{} Mbps", mbps));
+            info!("   Global rate limit: {}, {}", pps, mbps);
+        }
+
+        let pacing = &self.config.attack.pacing;
+        if pacing.mode == PacingMode::Hybrid {
+            info!("   Pacing: hybrid, spinning the last {}us before each deadline", pacing.spin_threshold_us);
+        }
+
         if let Some(ref iface) = self.selected_interface {
             info!("   Interface: {}", iface.name);
         }
         
         let mix = &self.config.target.protocol_mix;
         info!(
-            "   Protocols: UDP({:.0}%), TCP-SYN({:.0}%), TCP-ACK({:.0}%), TCP-FIN({:.0}%), TCP-RST({:.0}%), ICMP({:.0}%), Custom({:.0}%)",
+            "   Protocols: UDP({:.0}%), TCP-SYN({:.0}%), TCP-ACK({:.0}%), TCP-FIN({:.0}%), TCP-RST({:.0}%), ICMP({:.0}%), ARP({:.0}%), Custom({:.0}%)",
             mix.udp_ratio * 100.0,
             mix.tcp_syn_ratio * 100.0,
             mix.tcp_ack_ratio * 100.0,
             mix.tcp_fin_ratio * 100.0,
             mix.tcp_rst_ratio * 100.0,
             mix.icmp_ratio * 100.0,
+            mix.arp_ratio * 100.0,
             mix.custom_ratio * 100.0
         );
+        if !mix.tcp_flags.is_empty() {
+            let ratios: Vec<String> = mix.tcp_flags.iter()
+                .map(|(flags, ratio)| format!("{}({:.0}%)", flags, ratio * 100.0))
+                .collect();
+            info!("   TCP flags: {}", ratios.join(", "));
+        }
+        if let Some(ref ipv6) = mix.ipv6 {
+            info!(
+                "   IPv6 protocols: UDP({:.0}%), TCP-SYN({:.0}%), TCP-ACK({:.0}%), TCP-FIN({:.0}%), TCP-RST({:.0}%), ICMPv6({:.0}%)",
+                ipv6.udp_ratio * 100.0,
+                ipv6.tcp_syn_ratio * 100.0,
+                ipv6.tcp_ack_ratio * 100.0,
+                ipv6.tcp_fin_ratio * 100.0,
+                ipv6.tcp_rst_ratio * 100.0,
+                ipv6.icmp_ratio * 100.0
+            );
+        }
         
         if self.config.safety.dry_run {
             info!("   📋 Mode: SIMULATION ONLY - Safe for testing configurations");
@@ -239,6 +415,9 @@ impl Engine {
     
     async fn finalize_operation(&self) -> Result<()> {
         time::sleep(GRACEFUL_SHUTDOWN_TIMEOUT).await;
+
+        // Close a scenario phase interrupted by Ctrl+C
+        self.stats.end_phase();
         
         // Log operation stop
         if let Err(e) = self.audit_logger.log_event(
@@ -247,7 +426,7 @@ impl Engine {
             &self.config.target.ports,
             self.config.attack.threads,
             self.config.attack.packet_rate as u64,
-            self.config.attack.duration,
+            self.planned_duration(),
             self.config.target.interface.as_deref(),
             &self.stats.session_id,
         ) {
@@ -0,0 +1,179 @@
+//! Live target health measurement for adaptive rate search
+//!
+//! Health is judged per observation window: either from the local send
+//! failure ratio, or from TCP connection probes sent to the target at a
+//! fixed interval. A probe counts as answered when the handshake completes
+//! or the target resets it; timeouts and other errors count as lost.
+
+use std::mem;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::time;
+
+use crate::config::{AdaptiveSearch, HealthSignal};
+use crate::stats::Stats;
+
+/// Probe outcomes completed during the current window
+#[derive(Debug, Default)]
+struct ProbeWindow {
+    sent: u64,
+    answered: u64,
+    rtts: Vec<Duration>,
+}
+
+/// Background task sending TCP connection probes
+struct HealthProbe {
+    window: Arc<Mutex<ProbeWindow>>,
+    running: Arc<AtomicBool>,
+}
+
+impl HealthProbe {
+    /// Start probing `target`; in dry-run mode probes are simulated as
+    /// answered immediately and nothing is sent
+    fn spawn(target: SocketAddr, interval: Duration, timeout: Duration, dry_run: bool) -> Self {
+        let window = Arc::new(Mutex::new(ProbeWindow::default()));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let task_window = Arc::clone(&window);
+        let task_running = Arc::clone(&running);
+        tokio::spawn(async move {
+            let mut ticker = time::interval(interval);
+            while task_running.load(Ordering::Relaxed) {
+                ticker.tick().await;
+
+                let window = Arc::clone(&task_window);
+                tokio::spawn(async move {
+                    let started = Instant::now();
+                    let answered = dry_run || match time::timeout(timeout, TcpStream::connect(target)).await {
+                        Ok(Ok(_)) => true,
+                        Ok(Err(e)) => e.kind() == std::io::ErrorKind::ConnectionRefused,
+                        Err(_) => false,
+                    };
+                    let rtt = if dry_run { Duration::ZERO } else { started.elapsed() };
+
+                    let mut window = window.lock().unwrap_or_else(|e| e.into_inner());
+                    window.sent += 1;
+                    if answered {
+                        window.answered += 1;
+                        window.rtts.push(rtt);
+                    }
+                });
+            }
+        });
+
+        Self { window, running }
+    }
+
+    /// Take the outcomes collected since the last call
+    fn take_window(&self) -> ProbeWindow {
+        mem::take(&mut *self.window.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Drop for HealthProbe {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Health measured over one observation window
+#[derive(Debug, Clone)]
+pub struct HealthSample {
+    /// Aggregate packets per second attempted during the window
+    pub achieved_pps: f64,
+    /// Failed sends as a fraction of all send attempts
+    pub failure_ratio: f64,
+    /// Median probe round-trip time, if any probe was answered
+    pub probe_rtt_ms: Option<f64>,
+    /// Fraction of probes answered, if any probe completed
+    pub response_ratio: Option<f64>,
+}
+
+/// Measures the configured health signal window by window
+pub struct HealthMonitor {
+    signal: HealthSignal,
+    stats: Arc<Stats>,
+    probe: Option<HealthProbe>,
+    window_start: Instant,
+    sent: u64,
+    failed: u64,
+}
+
+impl HealthMonitor {
+    /// Create a monitor; probes go to `search.probe_port` or `default_port`
+    pub fn new(
+        search: &AdaptiveSearch,
+        stats: Arc<Stats>,
+        target_ip: IpAddr,
+        default_port: u16,
+        dry_run: bool,
+    ) -> Self {
+        let probe = search.health.uses_probes().then(|| {
+            HealthProbe::spawn(
+                SocketAddr::new(target_ip, search.probe_port.unwrap_or(default_port)),
+                Duration::from_millis(search.probe_interval_ms),
+                Duration::from_millis(search.probe_timeout_ms),
+                dry_run,
+            )
+        });
+
+        let mut monitor = Self {
+            signal: search.health.clone(),
+            stats,
+            probe,
+            window_start: Instant::now(),
+            sent: 0,
+            failed: 0,
+        };
+        monitor.start_window();
+        monitor
+    }
+
+    /// Start a new observation window
+    pub fn start_window(&mut self) {
+        self.window_start = Instant::now();
+        self.sent = self.stats.packets_sent();
+        self.failed = self.stats.packets_failed();
+        if let Some(ref probe) = self.probe {
+            probe.take_window();
+        }
+    }
+
+    /// Measure the window started by the last `start_window`
+    pub fn sample(&mut self) -> HealthSample {
+        let elapsed = self.window_start.elapsed().as_secs_f64();
+        let sent = self.stats.packets_sent().saturating_sub(self.sent);
+        let failed = self.stats.packets_failed().saturating_sub(self.failed);
+        let attempts = sent + failed;
+
+        let (probe_rtt_ms, response_ratio) = match self.probe {
+            Some(ref probe) => {
+                let mut window = probe.take_window();
+                window.rtts.sort();
+                let rtt = window.rtts.get(window.rtts.len() / 2).map(|rtt| rtt.as_secs_f64() * 1000.0);
+                let ratio = (window.sent > 0).then(|| window.answered as f64 / window.sent as f64);
+                (rtt, ratio)
+            }
+            None => (None, None),
+        };
+
+        HealthSample {
+            achieved_pps: if elapsed > 0.0 { attempts as f64 / elapsed } else { 0.0 },
+            failure_ratio: if attempts > 0 { failed as f64 / attempts as f64 } else { 0.0 },
+            probe_rtt_ms,
+            response_ratio,
+        }
+    }
+
+    /// Whether a sample meets the configured health threshold
+    pub fn is_healthy(&self, sample: &HealthSample) -> bool {
+        match self.signal {
+            HealthSignal::FailureRate { max_ratio } => sample.failure_ratio <= max_ratio,
+            HealthSignal::ProbeRtt { max_rtt_ms } => sample.probe_rtt_ms.is_some_and(|rtt| rtt <= max_rtt_ms),
+            HealthSignal::ResponseRatio { min_ratio } => sample.response_ratio.is_some_and(|ratio| ratio >= min_ratio),
+        }
+    }
+}
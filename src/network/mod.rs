@@ -5,13 +5,29 @@
 use pnet::datalink::{self, NetworkInterface};
 
 pub mod worker;
+pub mod adaptive;
 pub mod engine;
+pub mod health;
+pub mod burst;
+pub mod pacer;
+pub mod phase;
+pub mod rate_profile;
+pub mod rate_limiter;
 pub mod target;
+pub mod timing;
 pub mod worker_manager;
 
 // Re-export commonly used types
+pub use adaptive::RateSearch;
 pub use engine::Engine;
+pub use health::HealthMonitor;
+pub use burst::{BurstSchedule, LoadPhase};
+pub use pacer::Pacer;
+pub use phase::{PhaseControl, PhaseSettings};
+pub use rate_profile::RateSchedule;
+pub use rate_limiter::RateLimiter;
 pub use target::PortTarget;
+pub use timing::ArrivalProcess;
 pub use worker_manager::Workers;
 
 // Network interface utilities
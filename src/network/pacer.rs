@@ -0,0 +1,95 @@
+//! Deadline-based packet pacing
+//!
+//! Every packet has a send deadline one interval after the previous one.
+//! Workers send whatever is due, then wait for the next deadline, so timer
+//! overshoot is caught up on the next wake instead of accumulating as rate
+//! drift. The backlog that may be caught up is bounded to avoid blasting
+//! out a long stall at once.
+
+use std::time::{Duration, Instant};
+use tokio::time;
+
+use crate::config::{PacingConfig, PacingMode};
+
+/// Waits shorter than this yield instead of sleeping in `sleep` mode
+const MIN_SLEEP: Duration = Duration::from_micros(50);
+
+/// Send schedule for a single worker
+pub struct Pacer {
+    mode: PacingMode,
+    spin_threshold: Duration,
+    max_backlog: Duration,
+    next_send: Instant,
+}
+
+impl Pacer {
+    pub fn new(config: &PacingConfig, now: Instant) -> Self {
+        Self {
+            mode: config.mode,
+            spin_threshold: Duration::from_micros(config.spin_threshold_us),
+            max_backlog: Duration::from_millis(config.max_catch_up_ms),
+            next_send: now,
+        }
+    }
+
+    /// Restart the schedule at `now`, discarding any backlog
+    pub fn reset(&mut self, now: Instant) {
+        self.next_send = now;
+    }
+
+    /// Deadline of the next packet
+    pub fn next_send(&self) -> Instant {
+        self.next_send
+    }
+
+    /// Schedule the packets due at `now`, at most `limit`, and return how
+    /// many there are; `next_gap` gives the spacing after each packet
+    pub fn due(&mut self, now: Instant, limit: usize, mut next_gap: impl FnMut() -> Duration) -> usize {
+        if now < self.next_send {
+            return 0;
+        }
+
+        // Give up on backlog older than the catch-up window
+        if let Some(oldest) = now.checked_sub(self.max_backlog)
+            && self.next_send < oldest {
+                self.next_send = oldest;
+            }
+
+        let mut due = 0;
+        while due < limit && self.next_send <= now {
+            self.next_send += next_gap();
+            due += 1;
+        }
+        due
+    }
+
+    /// Wait until the next deadline, or for at most `max_wait`
+    pub async fn wait(&self, max_wait: Duration) {
+        let now = Instant::now();
+        let deadline = self.next_send.min(now + max_wait);
+        let remaining = deadline.saturating_duration_since(now);
+
+        match self.mode {
+            PacingMode::Sleep => {
+                if remaining < MIN_SLEEP {
+                    tokio::task::yield_now().await;
+                } else {
+                    time::sleep(remaining).await;
+                }
+            }
+            PacingMode::Hybrid => {
+                // Timer wakeups are coarse; sleep for most of the wait and
+                // spin through the last part to hit the deadline precisely
+                if remaining > self.spin_threshold {
+                    time::sleep(remaining - self.spin_threshold).await;
+                } else {
+                    // Still give other tasks (and the timer driver) a turn
+                    tokio::task::yield_now().await;
+                }
+                while Instant::now() < deadline {
+                    std::hint::spin_loop();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Live phase settings shared with workers
+//!
+//! The engine publishes new settings when a scenario moves on to its next
+//! phase. Workers pick them up between batches, so worker tasks and their
+//! transport channels stay up for the whole run.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+use crate::config::{Config, ProtocolMix};
+use crate::network::rate_profile::RateSchedule;
+use crate::network::target::PortTarget;
+use crate::packet::PacketSizes;
+
+/// Settings that can change from one phase to the next
+pub struct PhaseSettings {
+    /// Scenario phase name, `None` for single-phase runs
+    pub name: Option<String>,
+    pub rate_schedule: RateSchedule,
+    pub packet_sizes: PacketSizes,
+    pub protocol_mix: ProtocolMix,
+    pub target_port: Arc<PortTarget>,
+}
+
+impl PhaseSettings {
+    /// Derive settings from an (effective) configuration, with the rate
+    /// profile anchored at `epoch`
+    pub fn from_config(config: &Config, name: Option<String>, epoch: Instant) -> Self {
+        Self {
+            name,
+            rate_schedule: RateSchedule::new(
+                config.attack.rate_profile.clone(),
+                config.attack.packet_rate,
+                epoch,
+            )
+            .with_threads(config.attack.threads),
+            packet_sizes: PacketSizes::from_config(&config.attack),
+            protocol_mix: config.target.protocol_mix.clone(),
+            target_port: Arc::new(PortTarget::from_target(&config.target)),
+        }
+    }
+}
+
+/// Current phase settings plus a generation counter workers poll cheaply
+pub struct PhaseControl {
+    generation: AtomicU64,
+    current: RwLock<Arc<PhaseSettings>>,
+}
+
+impl PhaseControl {
+    pub fn new(initial: PhaseSettings) -> Self {
+        Self {
+            generation: AtomicU64::new(0),
+            current: RwLock::new(Arc::new(initial)),
+        }
+    }
+
+    /// Number of phase changes published so far
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Get the current settings
+    pub fn current(&self) -> Arc<PhaseSettings> {
+        Arc::clone(&self.current.read().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Replace the current settings; workers switch over on their next batch
+    pub fn publish(&self, settings: PhaseSettings) {
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(settings);
+        self.generation.fetch_add(1, Ordering::Release);
+    }
+}
@@ -0,0 +1,100 @@
+//! Global aggregate rate limiter
+//!
+//! One limiter is shared by all workers and enforces `Safety` caps on the
+//! total packets per second and bits per second. Each dimension is a token
+//! bucket implemented as a lock-free GCRA: a single atomic "theoretical
+//! arrival time" that every charge pushes forward. Workers reserve a batch
+//! before sending it and wait until the reservation fits into the bucket,
+//! so over any window the aggregate stays within the cap plus the bucket
+//! depth.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::config::Safety;
+
+/// Burst tolerance of each bucket, expressed as time at the capped rate
+const BUCKET_DEPTH: Duration = Duration::from_millis(10);
+
+/// Token bucket for a single dimension (packets or bits)
+struct TokenBucket {
+    /// Time one unit is worth at the capped rate
+    nanos_per_unit: f64,
+    /// Theoretical arrival time, in nanoseconds since the limiter epoch
+    tat: AtomicU64,
+}
+
+impl TokenBucket {
+    fn new(units_per_second: f64) -> Self {
+        Self {
+            nanos_per_unit: 1e9 / units_per_second,
+            tat: AtomicU64::new(0),
+        }
+    }
+
+    /// Units that fit into the bucket depth
+    fn depth_units(&self) -> f64 {
+        BUCKET_DEPTH.as_nanos() as f64 / self.nanos_per_unit
+    }
+
+    /// Charge `units` at time `now` and return how long to wait
+    fn charge(&self, units: u64, now: u64) -> u64 {
+        let cost = (units as f64 * self.nanos_per_unit) as u64;
+        let previous = self.tat
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |tat| Some(tat.max(now) + cost))
+            .unwrap_or_else(|tat| tat);
+        let tat = previous.max(now) + cost;
+
+        tat.saturating_sub(now + BUCKET_DEPTH.as_nanos() as u64)
+    }
+}
+
+/// Shared packets-per-second and bandwidth limiter
+pub struct RateLimiter {
+    epoch: Instant,
+    packets: Option<TokenBucket>,
+    bits: Option<TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Create a limiter; `None` caps are not enforced
+    pub fn new(max_packets_per_second: Option<f64>, max_bandwidth_mbps: Option<f64>) -> Self {
+        Self {
+            epoch: Instant::now(),
+            packets: max_packets_per_second.map(TokenBucket::new),
+            bits: max_bandwidth_mbps.map(|mbps| TokenBucket::new(mbps * 1_000_000.0)),
+        }
+    }
+
+    /// Build the limiter from safety settings, if rate limiting applies
+    pub fn from_safety(safety: &Safety) -> Option<Self> {
+        if !safety.rate_limit
+            || (safety.max_packets_per_second.is_none() && safety.max_bandwidth_mbps.is_none())
+        {
+            return None;
+        }
+
+        Some(Self::new(safety.max_packets_per_second, safety.max_bandwidth_mbps))
+    }
+
+    /// Largest batch of `packet_bytes`-sized packets that fits into the
+    /// bucket depth, so a batch never has to be split across refills
+    pub fn max_batch(&self, packet_bytes: u64) -> usize {
+        let packets = self.packets.as_ref().map_or(f64::MAX, TokenBucket::depth_units);
+        let bits = self.bits.as_ref().map_or(f64::MAX, |bucket| {
+            bucket.depth_units() / (packet_bytes.max(1) * 8) as f64
+        });
+
+        (packets.min(bits) as usize).max(1)
+    }
+
+    /// Reserve capacity for traffic and return how long the caller must
+    /// wait before sending it
+    pub fn charge(&self, packets: u64, bytes: u64) -> Duration {
+        let now = self.epoch.elapsed().as_nanos() as u64;
+        let packet_wait = self.packets.as_ref().map_or(0, |bucket| bucket.charge(packets, now));
+        let bit_wait = self.bits.as_ref().map_or(0, |bucket| bucket.charge(bytes * 8, now));
+
+        Duration::from_nanos(packet_wait.max(bit_wait))
+    }
+}
@@ -0,0 +1,100 @@
+//! Time-varying rate evaluation
+//!
+//! Turns a configured `RateProfile` into the per-thread target rate at a
+//! given point in time. Workers re-evaluate it between batches. Trace
+//! profiles also provide the protocol mix in effect at that time.
+
+use std::f64::consts::PI;
+use std::time::{Duration, Instant};
+
+use crate::config::{ProtocolMix, RateProfile, TracePoint};
+
+/// Rate profile anchored at the start of the run
+#[derive(Debug, Clone)]
+pub struct RateSchedule {
+    profile: RateProfile,
+    base_rate: f64,
+    epoch: Instant,
+    /// Threads sharing aggregate (trace) rates
+    threads: usize,
+}
+
+impl RateSchedule {
+    /// Create a schedule; `base_rate` is used by the constant profile
+    pub fn new(profile: RateProfile, base_rate: f64, epoch: Instant) -> Self {
+        Self { profile, base_rate, epoch, threads: 1 }
+    }
+
+    /// Split aggregate trace rates across `threads` workers
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Whether the rate changes over time
+    pub fn is_constant(&self) -> bool {
+        self.profile == RateProfile::Constant
+    }
+
+    /// Target rate (packets per second per thread) at the given instant
+    pub fn rate_at(&self, now: Instant) -> f64 {
+        self.rate_after(now.saturating_duration_since(self.epoch))
+    }
+
+    /// Target rate after `elapsed` time since the start of the run
+    pub fn rate_after(&self, elapsed: Duration) -> f64 {
+        let t = elapsed.as_secs_f64();
+
+        match self.profile {
+            RateProfile::Constant => self.base_rate,
+            RateProfile::Trace { ref points } => Self::trace_rate(points, t) / self.threads as f64,
+            RateProfile::LinearRamp { start_rate, end_rate, duration_secs } => {
+                let progress = (t / duration_secs as f64).min(1.0);
+                start_rate + (end_rate - start_rate) * progress
+            }
+            RateProfile::ExponentialRamp { start_rate, end_rate, duration_secs } => {
+                let progress = (t / duration_secs as f64).min(1.0);
+                start_rate * (end_rate / start_rate).powf(progress)
+            }
+            RateProfile::Step { start_rate, step_rate, step_duration_secs, max_rate } => {
+                let steps = (t / step_duration_secs as f64).floor();
+                (start_rate + step_rate * steps).min(max_rate)
+            }
+            RateProfile::Sine { min_rate, max_rate, period_secs } => {
+                let mid = (min_rate + max_rate) / 2.0;
+                let amplitude = (max_rate - min_rate) / 2.0;
+                mid - amplitude * (2.0 * PI * t / period_secs as f64).cos()
+            }
+        }
+    }
+
+    /// Protocol mix set by the trace at the given instant, with the index of
+    /// the point it comes from so callers can detect changes cheaply
+    pub fn mix_at(&self, now: Instant) -> Option<(usize, &ProtocolMix)> {
+        let RateProfile::Trace { ref points } = self.profile else {
+            return None;
+        };
+
+        let t = now.saturating_duration_since(self.epoch).as_secs_f64();
+        let reached = points.partition_point(|point| point.offset_secs <= t);
+        points[..reached]
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(index, point)| point.protocol_mix.as_ref().map(|mix| (index, mix)))
+    }
+
+    /// Aggregate trace rate at `t` seconds, linearly interpolated
+    fn trace_rate(points: &[TracePoint], t: f64) -> f64 {
+        let next = points.partition_point(|point| point.offset_secs <= t);
+        match (next.checked_sub(1).map(|index| &points[index]), points.get(next)) {
+            (Some(before), Some(after)) => {
+                let progress = (t - before.offset_secs) / (after.offset_secs - before.offset_secs);
+                before.pps + (after.pps - before.pps) * progress
+            }
+            (Some(last), None) => last.pps,
+            (None, Some(first)) => first.pps,
+            (None, None) => 0.0,
+        }
+    }
+}
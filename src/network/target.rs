@@ -1,26 +1,75 @@
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
-/// Target port manager for round-robin port selection
+use crate::config::{PortSelection, Target};
+use crate::utils::rng::BatchedRng;
+
+/// Target port manager shared by all workers
 pub struct PortTarget {
     ports: Vec<u16>,
+    selection: PortSelection,
+    /// Running weight totals, for weighted selection
+    cumulative_weights: Vec<u64>,
     current_index: Arc<AtomicUsize>,
 }
 
 impl PortTarget {
+    /// Round-robin over `ports`
     pub fn new(ports: Vec<u16>) -> Self {
+        Self::with_selection(ports, PortSelection::RoundRobin, &BTreeMap::new())
+    }
+
+    /// Ports of a target configuration with its selection policy
+    pub fn from_target(target: &Target) -> Self {
+        Self::with_selection(target.ports.clone(), target.port_selection, &target.port_weights)
+    }
+
+    pub fn with_selection(mut ports: Vec<u16>, selection: PortSelection, weights: &BTreeMap<u16, u32>) -> Self {
+        if selection == PortSelection::Sequential {
+            ports.sort_unstable();
+            ports.dedup();
+        }
+
+        let cumulative_weights = if selection == PortSelection::Weighted {
+            ports.iter()
+                .scan(0u64, |total, port| {
+                    *total += weights.get(port).copied().unwrap_or(1) as u64;
+                    Some(*total)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
         Self {
             ports,
+            selection,
+            cumulative_weights,
             current_index: Arc::new(AtomicUsize::new(0)),
         }
     }
 
-    pub fn next_port(&self) -> u16 {
-        let index = self.current_index.fetch_add(1, Ordering::Relaxed) % self.ports.len();
+    pub fn next_port(&self, rng: &mut BatchedRng) -> u16 {
+        let index = match self.selection {
+            PortSelection::RoundRobin | PortSelection::Sequential => {
+                self.current_index.fetch_add(1, Ordering::Relaxed) % self.ports.len()
+            }
+            PortSelection::Random => rng.range(0, self.ports.len()),
+            PortSelection::Weighted => {
+                let total = self.cumulative_weights.last().copied().unwrap_or(1);
+                let draw = rng.range(0, total as usize) as u64;
+                self.cumulative_weights.partition_point(|&cumulative| cumulative <= draw)
+            }
+        };
         self.ports[index]
     }
 
     pub fn get_ports(&self) -> &[u16] {
         &self.ports
     }
+
+    pub fn selection(&self) -> PortSelection {
+        self.selection
+    }
 }
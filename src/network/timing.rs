@@ -0,0 +1,79 @@
+//! Packet inter-arrival time sampling
+//!
+//! Turns a `TimingModel` into the gap before each packet. Gaps are scaled to
+//! the current mean interval, so rate profiles and burst windows still set
+//! the average rate while the model shapes how packets cluster around it.
+
+use std::time::Duration;
+
+use crate::config::TimingModel;
+use crate::utils::rng::BatchedRng;
+
+/// Longest single sampled gap; heavy tails are cut off here
+const MAX_GAP: Duration = Duration::from_secs(3600);
+
+/// Per-worker gap sampler
+pub struct ArrivalProcess {
+    model: TimingModel,
+    rng: BatchedRng,
+    /// Time left in the current ON period (ON/OFF model only)
+    on_remaining: Duration,
+}
+
+impl ArrivalProcess {
+    pub fn new(model: TimingModel) -> Self {
+        let mut process = Self {
+            model,
+            rng: BatchedRng::new(),
+            on_remaining: Duration::ZERO,
+        };
+
+        if let TimingModel::OnOff { shape, mean_on_ms, .. } = process.model {
+            process.on_remaining = process.pareto(mean_on_ms / 1000.0, shape);
+        }
+        process
+    }
+
+    /// Gap until the next packet for a mean spacing of `interval`
+    pub fn next_gap(&mut self, interval: Duration) -> Duration {
+        match self.model {
+            TimingModel::Constant => interval,
+            TimingModel::Poisson => {
+                // Inverse transform of the exponential distribution
+                let uniform = 1.0 - self.rng.unit();
+                interval.mul_f64(-uniform.ln())
+            }
+            TimingModel::Uniform { jitter } => {
+                interval.mul_f64(1.0 + jitter * (2.0 * self.rng.unit() - 1.0))
+            }
+            TimingModel::Pareto { shape } => self.pareto(interval.as_secs_f64(), shape),
+            TimingModel::OnOff { shape, mean_on_ms, mean_off_ms } => {
+                // Send faster while ON so the long-run mean matches the rate
+                let duty_cycle = mean_on_ms / (mean_on_ms + mean_off_ms);
+                let on_interval = interval.mul_f64(duty_cycle);
+
+                if self.on_remaining >= on_interval {
+                    self.on_remaining -= on_interval;
+                    return on_interval;
+                }
+
+                // The ON period ends: stay silent, then start a new one
+                let gap = self.on_remaining + self.pareto(mean_off_ms / 1000.0, shape);
+                self.on_remaining = self.pareto(mean_on_ms / 1000.0, shape);
+                gap
+            }
+        }
+    }
+
+    /// Pareto sample with the given mean (seconds) and tail index
+    fn pareto(&mut self, mean_secs: f64, shape: f64) -> Duration {
+        if mean_secs <= 0.0 {
+            return Duration::ZERO;
+        }
+
+        let scale = mean_secs * (shape - 1.0) / shape;
+        let uniform = 1.0 - self.rng.unit();
+        let secs = scale / uniform.powf(1.0 / shape);
+        Duration::try_from_secs_f64(secs).map_or(MAX_GAP, |gap| gap.min(MAX_GAP))
+    }
+}
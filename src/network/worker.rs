@@ -1,53 +1,100 @@
 //! High-performance packet generation worker
 //!
-//! This worker uses buffer reuse, batched stats updates, and burst-mode sending
-//! for improved performance under high load.
+//! This worker uses buffer reuse, batched stats updates, and deadline-based
+//! pacing that sends every due packet per wakeup for accurate rates under
+//! high load.
 
+use std::mem;
 use std::net::IpAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::time;
 
-/// Number of packets to generate in each burst before sleeping
-/// This significantly reduces tokio::time::sleep overhead for high packet rates
+/// Most packets sent per wakeup, bounds catch-up bursts at high rates
 const BURST_SIZE: usize = 100;
 
-/// Minimum sleep duration in microseconds
-/// Below this threshold, we use yield_now() instead to avoid sleep overhead
-const MIN_SLEEP_MICROS: u64 = 50;
+/// Lowest rate used for pacing, guards against zero or negative profile values
+const MIN_PACKET_RATE: f64 = 0.1;
 
-use crate::stats::{Stats, BatchStats};
+/// Longest single wait, so shutdown and phase changes stay responsive
+const MAX_WAIT: Duration = Duration::from_millis(100);
+
+/// How often workers publish their pacing accuracy
+const PACING_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+use crate::stats::{Stats, BatchStats, PacingMeter, QuotaGrant};
+use crate::network::burst::{BurstSchedule, LoadPhase};
+use crate::network::pacer::Pacer;
+use crate::network::timing::ArrivalProcess;
+use crate::network::phase::{PhaseControl, PhaseSettings};
+use crate::network::rate_limiter::RateLimiter;
+use crate::network::rate_profile::RateSchedule;
 use crate::network::target::PortTarget;
-use crate::packet::{PacketBuilder, PacketType};
-use crate::config::ProtocolMix;
-use crate::packet::PacketSizeRange;
+use crate::packet::{Fragmenter, PacketBuilder, PacketOptions, PacketType};
+use crate::config::{PacingConfig, ProtocolMix, TimingModel};
+use crate::constants::{IPV6_HEADER_SIZE, TCP_HEADER_SIZE, TCP_MAX_OPTIONS_SIZE};
 use crate::error::Result;
 use crate::transport::{WorkerChannels, ChannelType};
+use crate::utils::rng::BatchedRng;
+
+/// Largest possible headers added on top of the payload: IPv6 (40) + TCP
+/// with options (60), before any IPv6 extension headers
+const MAX_HEADER_SIZE: usize = IPV6_HEADER_SIZE + TCP_HEADER_SIZE + TCP_MAX_OPTIONS_SIZE;
+
+/// Largest possible headers with the configured IPv6 extension headers
+fn max_header_size(options: &PacketOptions) -> usize {
+    MAX_HEADER_SIZE + options.ipv6_extensions.len()
+}
 
 /// Configuration for Worker
 pub struct WorkerConfig {
-    pub packet_rate: u64,
-    pub packet_size_range: PacketSizeRange,
-    pub protocol_mix: ProtocolMix,
-    pub randomize_timing: bool,
+    pub worker_id: usize,
+    pub phase_control: Arc<PhaseControl>,
+    pub timing: TimingModel,
     pub dry_run: bool,
     pub perfect_simulation: bool,
+    pub burst_schedule: Option<BurstSchedule>,
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    pub pacing: PacingConfig,
+    pub packet_options: PacketOptions,
 }
 
 /// Worker with performance optimizations
 pub struct Worker {
+    worker_id: usize,
+    stats: Arc<Stats>,
     local_stats: BatchStats,
     target_port: Arc<PortTarget>,
+    // Random source for random and weighted port selection
+    port_rng: BatchedRng,
     target_ip: IpAddr,
     packet_builder: PacketBuilder,
+    packet_options: PacketOptions,
     // Pre-allocated buffer for zero-copy
     buffer: Vec<u8>,
+    /// Splits IPv4 datagrams when fragmentation is configured
+    fragmenter: Option<Fragmenter>,
     // Pre-calculated packet types for efficiency
     packet_types: Vec<PacketType>,
     packet_type_index: usize,
-    base_delay: Duration,
-    randomize_timing: bool,
+    /// Trace point whose protocol mix is in use, if any
+    trace_mix: Option<usize>,
+    rate_schedule: RateSchedule,
+    burst_schedule: Option<BurstSchedule>,
+    phase_control: Arc<PhaseControl>,
+    phase_generation: u64,
+    pacer: Pacer,
+    meter: PacingMeter,
+    last_report: Instant,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    // Traffic sent in the current batch, settled with the rate limiter
+    batch_packets: u64,
+    batch_bytes: u64,
+    reserved_packets: u64,
+    reserved_bytes: u64,
+    estimated_packet_bytes: u64,
+    arrivals: ArrivalProcess,
     dry_run: bool,
     perfect_simulation: bool,
     // Transport channels for actual packet sending (None in dry-run mode)
@@ -58,69 +105,200 @@ impl Worker {
     pub fn new(
         stats: Arc<Stats>,
         target_ip: IpAddr,
-        target_port: Arc<PortTarget>,
         config: WorkerConfig,
         channels: Option<WorkerChannels>,
     ) -> Self {
-        let packet_rate = config.packet_rate;
-        let packet_size_range = config.packet_size_range;
-        let protocol_mix = config.protocol_mix;
-        let randomize_timing = config.randomize_timing;
-        let dry_run = config.dry_run;
-        let perfect_simulation = config.perfect_simulation;
+        let phase_generation = config.phase_control.generation();
+        let settings = config.phase_control.current();
         // Create local stats with batching (flush every 50 packets)
         let local_stats = BatchStats::new(stats.clone(), 50);
-        let packet_builder = PacketBuilder::new(packet_size_range, protocol_mix.clone());
-        let base_delay = Duration::from_nanos(1_000_000_000 / packet_rate.max(1));
+        let packet_builder = PacketBuilder::with_options(
+            &settings.packet_sizes,
+            &config.packet_options,
+            settings.protocol_mix.clone(),
+        );
         
         // Pre-calculate packet type distribution based on protocol mix
-        let packet_types = Self::generate_packet_types(&protocol_mix);
+        let packet_types = Self::generate_packet_types(&settings.protocol_mix, target_ip);
 
         // Pre-allocate buffer for zero-copy operations
-        // Size = max payload + largest possible headers (IPv6 + UDP = 48 bytes)
-        const MAX_HEADER_SIZE: usize = 48;  // IPv6 (40) + UDP (8)
-        let buffer = vec![0u8; packet_size_range.max + MAX_HEADER_SIZE];
+        let header_size = max_header_size(&config.packet_options);
+        let buffer = vec![0u8; settings.packet_sizes.max_payload() + header_size];
         
+        let now = Instant::now();
+
         Self {
+            worker_id: config.worker_id,
+            stats,
             local_stats,
-            target_port,
+            target_port: Arc::clone(&settings.target_port),
+            port_rng: BatchedRng::new(),
             target_ip,
             packet_builder,
+            fragmenter: config.packet_options.fragmentation.as_ref().map(Fragmenter::new),
+            packet_options: config.packet_options,
             buffer,
             packet_types,
             packet_type_index: 0,
-            base_delay,
-            randomize_timing,
-            dry_run,
-            perfect_simulation,
+            trace_mix: None,
+            rate_schedule: settings.rate_schedule.clone(),
+            burst_schedule: config.burst_schedule,
+            phase_control: config.phase_control,
+            phase_generation,
+            pacer: Pacer::new(&config.pacing, now),
+            meter: PacingMeter::new(now),
+            last_report: now,
+            rate_limiter: config.rate_limiter,
+            batch_packets: 0,
+            batch_bytes: 0,
+            reserved_packets: 0,
+            reserved_bytes: 0,
+            estimated_packet_bytes: (settings.packet_sizes.max_payload() + header_size) as u64,
+            arrivals: ArrivalProcess::new(config.timing),
+            dry_run: config.dry_run,
+            perfect_simulation: config.perfect_simulation,
             channels,
         }
     }
+
+    /// Switch to new phase settings, keeping the channels and stats batch
+    fn apply_phase(&mut self, settings: &PhaseSettings) {
+        // Counts so far belong to the previous phase
+        self.local_stats.flush();
+
+        self.packet_builder = PacketBuilder::with_options(
+            &settings.packet_sizes,
+            &self.packet_options,
+            settings.protocol_mix.clone(),
+        );
+        self.packet_types = Self::generate_packet_types(&settings.protocol_mix, self.target_ip);
+        self.packet_type_index = 0;
+        self.trace_mix = None;
+
+        let buffer_size = settings.packet_sizes.max_payload() + max_header_size(&self.packet_options);
+        if self.buffer.len() < buffer_size {
+            self.buffer.resize(buffer_size, 0);
+        }
+
+        self.rate_schedule = settings.rate_schedule.clone();
+        self.target_port = Arc::clone(&settings.target_port);
+
+        // The new phase starts on its own schedule
+        self.pacer.reset(Instant::now());
+        self.meter.break_gap();
+    }
     
     pub async fn run(&mut self, running: Arc<AtomicBool>) {
-        while running.load(Ordering::Relaxed) {
-            // Process packets in bursts to reduce sleep overhead
-            for _ in 0..BURST_SIZE {
-                if !running.load(Ordering::Relaxed) {
+        let start = Instant::now();
+        self.pacer.reset(start);
+        self.meter = PacingMeter::new(start);
+        self.last_report = start;
+
+        while running.load(Ordering::Relaxed) && !self.stats.quota_exhausted() {
+            let generation = self.phase_control.generation();
+            if generation != self.phase_generation {
+                self.phase_generation = generation;
+                let settings = self.phase_control.current();
+                self.apply_phase(&settings);
+            }
+
+            let now = Instant::now();
+            self.publish_pacing(now, false);
+            self.follow_trace_mix(now);
+
+            let mut rate = self.rate_schedule.rate_at(now);
+            if !self.rate_schedule.is_constant() {
+                self.stats.set_target_rate(rate);
+            }
+            if rate <= 0.0 {
+                // Zero sections of a trace send nothing; check the schedule
+                // again shortly instead of trickling packets out
+                self.local_stats.flush();
+                self.meter.set_rate(now, 0.0);
+                self.meter.break_gap();
+                time::sleep(MAX_WAIT).await;
+                self.pacer.reset(Instant::now());
+                continue;
+            }
+            rate = rate.max(MIN_PACKET_RATE);
+            let mut limit = BURST_SIZE;
+
+            if let Some(schedule) = self.burst_schedule {
+                let state = schedule.state_at(now);
+                self.stats.record_load_phase(state);
+
+                if state.phase == LoadPhase::Idle {
+                    // Nothing is sent during idle windows; publish pending
+                    // counts and wake up again when the next burst starts
+                    self.local_stats.flush();
+                    self.meter.set_rate(now, 0.0);
+                    self.meter.break_gap();
+                    time::sleep(state.remaining.min(MAX_WAIT)).await;
+                    // Start the burst on time instead of catching up the idle window
+                    self.pacer.reset(Instant::now());
+                    continue;
+                }
+
+                rate *= schedule.multiplier();
+                // Keep the batch inside the remaining burst window
+                limit = Self::batch_len(state.remaining, Duration::from_secs_f64(1.0 / rate));
+            }
+
+            let interval = Duration::from_secs_f64(1.0 / rate);
+            self.meter.set_rate(now, rate);
+
+            let limit = limit.min(self.global_batch_limit());
+            let arrivals = &mut self.arrivals;
+            let due = self.pacer.due(now, limit, || arrivals.next_gap(interval));
+            if due == 0 {
+                self.pacer.wait(MAX_WAIT).await;
+                continue;
+            }
+
+            self.acquire_global_limit(due).await;
+
+            // Send everything that is due in one go, catching up timer overshoot
+            for _ in 0..due {
+                if !running.load(Ordering::Relaxed) || self.stats.quota_exhausted() {
                     break;
                 }
 
-                // Process single packet
+                self.meter.record_send(Instant::now());
                 if self.process_packet().await.is_err() {
                     self.local_stats.increment_failed();
                 }
             }
 
-            // Apply rate limiting once per burst instead of per packet
-            self.apply_burst_rate_limiting().await;
+            self.settle_global_limit();
         }
 
         // Ensure final flush of batched stats
         self.local_stats.flush();
+        self.publish_pacing(Instant::now(), true);
+    }
+
+    /// Switch to the protocol mix of the current trace point, if it changed
+    fn follow_trace_mix(&mut self, now: Instant) {
+        if let Some((index, mix)) = self.rate_schedule.mix_at(now)
+            && self.trace_mix != Some(index) {
+                self.packet_types = Self::generate_packet_types(mix, self.target_ip);
+                self.packet_type_index = 0;
+                self.trace_mix = Some(index);
+            }
+    }
+
+    /// Publish the pacing report periodically, or now if `force` is set
+    fn publish_pacing(&mut self, now: Instant, force: bool) {
+        if force || now.duration_since(self.last_report) >= PACING_REPORT_INTERVAL {
+            // Keep shared counters current at low rates, where batches fill slowly
+            self.local_stats.flush();
+            self.stats.update_pacing(self.meter.report(self.worker_id, now));
+            self.last_report = now;
+        }
     }
     
     async fn process_packet(&mut self) -> Result<()> {
-        let port = self.target_port.next_port();
+        let port = self.target_port.next_port(&mut self.port_rng);
         let packet_type = self.next_packet_type();
         
         // Try zero-copy build first
@@ -131,17 +309,18 @@ impl Worker {
             port
         ) {
             Ok((size, protocol)) => {
-                self.simulate_or_send(size, protocol);
+                let buffer = mem::take(&mut self.buffer);
+                self.send_datagram(&buffer[..size], protocol, packet_type);
+                self.buffer = buffer;
             }
             Err(_) => {
                 // Fallback to regular build
                 match self.packet_builder.build_packet(packet_type, self.target_ip, port) {
                     Ok((packet_data, protocol)) => {
-                        let size = packet_data.len();
-                        self.simulate_or_send(size, protocol);
+                        self.send_datagram(&packet_data, protocol, packet_type);
                     }
                     Err(_) => {
-                        self.local_stats.increment_failed();
+                        self.record_failed(packet_type);
                     }
                 }
             }
@@ -150,81 +329,108 @@ impl Worker {
         Ok(())
     }
     
-    fn simulate_or_send(&mut self, size: usize, protocol: &str) {
-        if self.dry_run {
+    /// Send a datagram, or its fragments if it is an IPv4 datagram picked
+    /// for fragmentation; every fragment counts as a packet
+    fn send_datagram(&mut self, datagram: &[u8], protocol: &str, packet_type: PacketType) {
+        let ipv4 = packet_type != PacketType::Arp && self.target_ip.is_ipv4();
+        let Some(mut fragmenter) = self.fragmenter.take_if(|_| ipv4) else {
+            self.simulate_or_send(datagram, protocol, packet_type);
+            return;
+        };
+
+        if fragmenter.fragment(datagram) {
+            for fragment in fragmenter.fragments() {
+                self.simulate_or_send(fragment, protocol, packet_type);
+            }
+        } else {
+            self.simulate_or_send(datagram, protocol, packet_type);
+        }
+        self.fragmenter = Some(fragmenter);
+    }
+
+    fn simulate_or_send(&mut self, packet: &[u8], protocol: &str, packet_type: PacketType) {
+        let size = packet.len();
+        // Packets beyond a packet or byte quota are never sent; while
+        // another worker's packet is in flight this deadline is skipped
+        if self.stats.reserve_quota(size as u64) != QuotaGrant::Granted {
+            return;
+        }
+
+        let success = if self.dry_run {
             // Dry-run simulation mode
-            let success = if self.perfect_simulation {
-                true
-            } else {
-                self.packet_builder.rng_gen_bool(0.98)
+            self.perfect_simulation || self.packet_builder.rng_gen_bool(0.98)
+        } else if let Some(ref mut channels) = self.channels {
+            // Real packet sending mode: ARP frames go out through the
+            // layer-2 channel, IP packets through the target's family
+            let channel_type = match (packet_type, self.target_ip) {
+                (PacketType::Arp, _) => ChannelType::Layer2,
+                (_, IpAddr::V4(_)) => ChannelType::IPv4,
+                (_, IpAddr::V6(_)) => ChannelType::IPv6,
             };
 
-            if success {
-                self.local_stats.increment_sent(size as u64, protocol);
-            } else {
-                self.local_stats.increment_failed();
-            }
+            channels.send_packet(packet, self.target_ip, channel_type).is_ok()
         } else {
-            // Real packet sending mode
-            if let Some(ref mut channels) = self.channels {
-                // Determine channel type based on target IP
-                let channel_type = match self.target_ip {
-                    IpAddr::V4(_) => ChannelType::IPv4,
-                    IpAddr::V6(_) => ChannelType::IPv6,
-                };
-
-                // Send the packet using the buffer (already contains packet data)
-                match channels.send_packet(&self.buffer[..size], self.target_ip, channel_type) {
-                    Ok(()) => {
-                        self.local_stats.increment_sent(size as u64, protocol);
-                    }
-                    Err(_) => {
-                        self.local_stats.increment_failed();
-                    }
-                }
-            } else {
-                // No channels available - this shouldn't happen in non-dry-run mode
-                self.local_stats.increment_failed();
-            }
+            // No channels available - this shouldn't happen in non-dry-run mode
+            false
+        };
+
+        self.stats.settle_quota(size as u64, success);
+        if success {
+            self.record_sent(size, protocol, packet_type);
+        } else {
+            self.record_failed(packet_type);
+        }
+    }
+
+    /// Count a failed packet, ARP failures also on their own
+    fn record_failed(&mut self, packet_type: PacketType) {
+        if packet_type == PacketType::Arp {
+            self.local_stats.increment_arp_failed();
+        } else {
+            self.local_stats.increment_failed();
         }
     }
     
+    fn record_sent(&mut self, size: usize, protocol: &str, packet_type: PacketType) {
+        self.local_stats.increment_sent(size as u64, protocol);
+        if let Some(flags) = packet_type.tcp_flags() {
+            self.local_stats.record_tcp_flags(flags);
+        }
+        self.batch_packets += 1;
+        self.batch_bytes += size as u64;
+    }
+
     fn next_packet_type(&mut self) -> PacketType {
         let packet_type = self.packet_types[self.packet_type_index];
         self.packet_type_index = (self.packet_type_index + 1) % self.packet_types.len();
         packet_type
     }
     
-    fn generate_packet_types(mix: &ProtocolMix) -> Vec<PacketType> {
+    fn generate_packet_types(mix: &ProtocolMix, target_ip: IpAddr) -> Vec<PacketType> {
+        if target_ip.is_ipv6() {
+            return Self::generate_ipv6_packet_types(mix);
+        }
+
         let mut types = Vec::with_capacity(100);
         
         // Generate 100 packet types based on ratios
         let udp_count = (mix.udp_ratio * 100.0) as usize;
-        let tcp_syn_count = (mix.tcp_syn_ratio * 100.0) as usize;
-        let tcp_ack_count = (mix.tcp_ack_ratio * 100.0) as usize;
-        let tcp_fin_count = (mix.tcp_fin_ratio * 100.0) as usize;
-        let tcp_rst_count = (mix.tcp_rst_ratio * 100.0) as usize;
         let icmp_count = (mix.icmp_ratio * 100.0) as usize;
-        // Removed ipv6 and arp for simplification
         
         for _ in 0..udp_count {
             types.push(PacketType::Udp);
         }
-        for _ in 0..tcp_syn_count {
-            types.push(PacketType::TcpSyn);
-        }
-        for _ in 0..tcp_ack_count {
-            types.push(PacketType::TcpAck);
-        }
-        for _ in 0..tcp_fin_count {
-            types.push(PacketType::TcpFin);
-        }
-        for _ in 0..tcp_rst_count {
-            types.push(PacketType::TcpRst);
+        for (flags, ratio) in mix.tcp_ratios() {
+            for _ in 0..(ratio * 100.0) as usize {
+                types.push(PacketType::Tcp(flags));
+            }
         }
         for _ in 0..icmp_count {
             types.push(PacketType::Icmp);
         }
+        for _ in 0..(mix.arp_ratio * 100.0) as usize {
+            types.push(PacketType::Arp);
+        }
         // Use UDP for custom ratio since we simplified packet types
         let custom_count = (mix.custom_ratio * 100.0) as usize;
         for _ in 0..custom_count {
@@ -238,26 +444,78 @@ impl Worker {
         
         types
     }
+
+    /// Packet types for an IPv6 target, from the IPv6 ratios of the mix
+    fn generate_ipv6_packet_types(mix: &ProtocolMix) -> Vec<PacketType> {
+        let mix = mix.ipv6_mix();
+        let mut types = Vec::with_capacity(100);
+
+        for _ in 0..(mix.udp_ratio * 100.0) as usize {
+            types.push(PacketType::Ipv6Udp);
+        }
+        for (flags, ratio) in mix.tcp_ratios() {
+            for _ in 0..(ratio * 100.0) as usize {
+                types.push(PacketType::Ipv6Tcp(flags));
+            }
+        }
+        for _ in 0..(mix.icmp_ratio * 100.0) as usize {
+            types.push(PacketType::Ipv6Icmp);
+        }
+
+        // Fill remainder with UDP if needed
+        while types.len() < 100 {
+            types.push(PacketType::Ipv6Udp);
+        }
+
+        types
+    }
     
-    /// Apply rate limiting for an entire burst of packets
-    /// This replaces per-packet sleep with per-burst sleep for much better performance
-    async fn apply_burst_rate_limiting(&mut self) {
-        // Calculate delay for entire burst
-        let burst_delay = self.base_delay.saturating_mul(BURST_SIZE as u32);
-
-        // Apply jitter if randomization is enabled
-        let delay = if self.randomize_timing {
-            let jitter = self.packet_builder.rng_gen_range(0.8..1.2);
-            Duration::from_nanos((burst_delay.as_nanos() as f64 * jitter) as u64)
-        } else {
-            burst_delay
+    /// Largest batch the shared rate limiter accepts in one reservation
+    fn global_batch_limit(&self) -> usize {
+        self.rate_limiter
+            .as_ref()
+            .map_or(usize::MAX, |limiter| limiter.max_batch(self.estimated_packet_bytes))
+    }
+
+    /// Reserve a batch with the shared rate limiter, waiting if the
+    /// aggregate caps are reached
+    async fn acquire_global_limit(&mut self, burst_len: usize) {
+        let Some(limiter) = self.rate_limiter.clone() else {
+            return;
         };
 
-        // For very high rates, use yield instead of sleep to avoid overhead
-        if delay.as_micros() < MIN_SLEEP_MICROS as u128 {
-            tokio::task::yield_now().await;
-        } else {
-            time::sleep(delay).await;
+        self.reserved_packets = burst_len as u64;
+        self.reserved_bytes = burst_len as u64 * self.estimated_packet_bytes;
+
+        let wait = limiter.charge(burst_len as u64, self.reserved_bytes);
+        if !wait.is_zero() {
+            self.stats.record_throttle(wait);
+            time::sleep(wait).await;
         }
     }
+
+    /// Charge packets and bytes sent beyond the reservation, such as extra
+    /// fragments, and update the size estimate
+    fn settle_global_limit(&mut self) {
+        let packets = mem::take(&mut self.batch_packets);
+        let bytes = mem::take(&mut self.batch_bytes);
+        let reserved_packets = mem::take(&mut self.reserved_packets);
+        let reserved = mem::take(&mut self.reserved_bytes);
+
+        if let Some(ref limiter) = self.rate_limiter {
+            if packets > reserved_packets || bytes > reserved {
+                // The debt is paid by the next reservation's wait
+                limiter.charge(packets.saturating_sub(reserved_packets), bytes.saturating_sub(reserved));
+            }
+            if let Some(average) = bytes.checked_div(packets) {
+                self.estimated_packet_bytes = average;
+            }
+        }
+    }
+
+    /// Number of packets (1..=BURST_SIZE) that fit into `window` at `delay` spacing
+    fn batch_len(window: Duration, delay: Duration) -> usize {
+        let fits = window.as_nanos() / delay.as_nanos().max(1);
+        (fits as usize).clamp(1, BURST_SIZE)
+    }
 }
\ No newline at end of file
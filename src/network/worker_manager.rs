@@ -6,15 +6,18 @@
 use std::net::IpAddr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::task::JoinHandle;
 use tracing::{info, warn};
 
 use crate::config::Config;
 use crate::error::{RouterFloodError, Result};
 use crate::stats::Stats;
-use crate::network::target::PortTarget;
+use crate::network::burst::BurstSchedule;
+use crate::network::phase::PhaseControl;
+use crate::network::rate_limiter::RateLimiter;
 use crate::network::worker::{Worker, WorkerConfig};
-use crate::packet::PacketSizeRange;
+use crate::packet::PacketOptions;
 use crate::performance::cpu_affinity::CpuAffinity;
 use crate::transport::ChannelFactory;
 
@@ -27,10 +30,13 @@ pub struct Workers {
 
 impl Workers {
     /// Create a new worker manager and spawn worker threads
+    ///
+    /// Rate, ports, payload size and protocol mix come from `phase_control`
+    /// and may change while the workers run.
     pub fn new(
         config: &Config,
         stats: Arc<Stats>,
-        multi_port_target: Arc<PortTarget>,
+        phase_control: Arc<PhaseControl>,
         target_ip: IpAddr,
         interface: Option<&pnet::datalink::NetworkInterface>,
         dry_run: bool,
@@ -58,7 +64,7 @@ impl Workers {
             config,
             stats,
             running.clone(),
-            multi_port_target,
+            phase_control,
             target_ip,
             cpu_affinity.clone(),
             interface,
@@ -73,7 +79,7 @@ impl Workers {
         config: &Config,
         stats: Arc<Stats>,
         running: Arc<AtomicBool>,
-        multi_port_target: Arc<PortTarget>,
+        phase_control: Arc<PhaseControl>,
         target_ip: IpAddr,
         cpu_affinity: Option<Arc<CpuAffinity>>,
         interface: Option<&pnet::datalink::NetworkInterface>,
@@ -81,6 +87,9 @@ impl Workers {
     ) -> Result<Vec<JoinHandle<()>>> {
         let mut handles = Vec::with_capacity(config.attack.threads);
 
+        // Shared so payload files are read once and stream IDs stay unique
+        let packet_options = PacketOptions::from_config(config, interface)?;
+
         // Create transport channels for all workers (one channel per worker for lock-free operation)
         let mut all_channels = if !dry_run {
             info!("Creating {} transport channels for workers...", config.attack.threads);
@@ -89,24 +98,29 @@ impl Workers {
             Vec::new()
         };
 
-        // Note: packet_rate is already specified as "per thread" in the CLI
-        let per_worker_rate = config.attack.packet_rate as u64;
+        // Shared epoch so burst windows stay aligned across workers
+        let epoch = Instant::now();
+        let burst_schedule = config.attack.burst_pattern.as_ref()
+            .filter(|_| config.attack.burst_mode)
+            .map(|pattern| BurstSchedule::new(pattern, epoch));
+
+        // One limiter for all workers so the caps apply to the aggregate
+        let rate_limiter = RateLimiter::from_safety(&config.safety).map(Arc::new);
 
         for task_id in 0..config.attack.threads {
             let running = running.clone();
             let stats = stats.clone();
-            let target_port = multi_port_target.clone();
-            let packet_size_range = PacketSizeRange::new(config.attack.payload_size, config.attack.payload_size);
-            let protocol_mix = config.target.protocol_mix.clone();
-            let randomize_timing = false;  // Simplified for now
 
             let worker_config = WorkerConfig {
-                packet_rate: per_worker_rate,
-                packet_size_range,
-                protocol_mix,
-                randomize_timing,
+                worker_id: task_id,
+                phase_control: Arc::clone(&phase_control),
+                timing: config.attack.timing.clone(),
                 dry_run,
                 perfect_simulation: config.safety.perfect_simulation,
+                burst_schedule,
+                rate_limiter: rate_limiter.clone(),
+                pacing: config.attack.pacing.clone(),
+                packet_options: packet_options.clone(),
             };
 
             // Take ownership of channel for this worker (pop from end for efficiency)
@@ -119,7 +133,6 @@ impl Workers {
             let mut worker = Worker::new(
                 stats,
                 target_ip,
-                target_port,
                 worker_config,
                 worker_channels,
             );
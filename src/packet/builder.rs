@@ -1,8 +1,10 @@
 //! Main packet builder implementation using strategy pattern
 
 use super::{PacketStrategy, PacketType, PacketTarget};
-use crate::packet::PacketSizeRange;
-use crate::config::ProtocolMix;
+use super::protocols::{Ipv6TcpStrategy, TcpStrategy};
+use crate::packet::{HeaderFieldRules, Ipv6ExtensionLayout, PacketOptions, PacketSizeRange, PacketSizes, PayloadFill, PayloadSizer, PayloadWriter, PortPicker, SourcePool, TcpOptionLayout};
+use crate::config::{Ipv6ProtocolMix, ProtocolMix, SizeDistribution, SourcePort, TcpFlagSet, TcpPayload};
+use crate::constants::{IPV4_ICMP_HEADER_SIZE, IPV4_TCP_HEADER_SIZE, IPV4_UDP_HEADER_SIZE, IPV6_ICMP_HEADER_SIZE, IPV6_TCP_HEADER_SIZE, IPV6_UDP_HEADER_SIZE};
 use crate::error::{PacketError, Result};
 use crate::utils::rng::BatchedRng;
 use std::collections::HashMap;
@@ -11,10 +13,53 @@ use std::net::IpAddr;
 /// Main packet builder that coordinates different packet strategies
 pub struct PacketBuilder {
     strategies: HashMap<PacketType, Box<dyn PacketStrategy>>,
+    tcp: TcpTemplate,
     protocol_selector: ProtocolSelector,
     rng: BatchedRng,
 }
 
+/// Settings shared by the TCP strategies, to set up flag sets on first use
+struct TcpTemplate {
+    source: SourcePool,
+    source6: SourcePool,
+    port: SourcePort,
+    options: TcpOptionLayout,
+    extensions: Ipv6ExtensionLayout,
+    fields: HeaderFieldRules,
+    payload: TcpPayload,
+    sizes: SizeDistribution,
+    fill: PayloadFill,
+}
+
+impl TcpTemplate {
+    fn strategy(&self, flags: TcpFlagSet, rng: &mut BatchedRng) -> TcpStrategy {
+        let data = self.data(flags, IPV4_TCP_HEADER_SIZE);
+        TcpStrategy::new(flags, data, &self.source, PortPicker::new(self.port), self.fields.picker(), &self.options, rng)
+    }
+
+    fn ipv6_strategy(&self, flags: TcpFlagSet, rng: &mut BatchedRng) -> Ipv6TcpStrategy {
+        let data = self.data(flags, IPV6_TCP_HEADER_SIZE);
+        Ipv6TcpStrategy::new(flags, data, &self.source6, PortPicker::new(self.port), self.fields.picker(), &self.options, &self.extensions, rng)
+    }
+
+    /// Strategy for a TCP packet type of either address family
+    fn strategy_for(&self, packet_type: PacketType, rng: &mut BatchedRng) -> Option<Box<dyn PacketStrategy>> {
+        match packet_type {
+            PacketType::Tcp(flags) => Some(Box::new(self.strategy(flags, rng))),
+            PacketType::Ipv6Tcp(flags) => Some(Box::new(self.ipv6_strategy(flags, rng))),
+            _ => None,
+        }
+    }
+
+    /// Payload sizer and writer for segments with `flags`, if they carry data
+    fn data(&self, flags: TcpFlagSet, header_len: usize) -> Option<(PayloadSizer, PayloadWriter)> {
+        self.payload.carries_data(flags).then(|| {
+            let header_len = header_len + self.options.len(flags.contains(TcpFlagSet::SYN));
+            (PayloadSizer::new(&self.sizes, header_len), PayloadWriter::new(&self.fill))
+        })
+    }
+}
+
 impl PacketBuilder {
     /// Create a new packet builder with the given configuration
     pub fn new(packet_size_range: PacketSizeRange, protocol_mix: ProtocolMix) -> Self {
@@ -27,54 +72,72 @@ impl PacketBuilder {
         let clamped_min = packet_size_range.min.min(MAX_PAYLOAD_SIZE);
         let clamped_max = packet_size_range.max.min(MAX_PAYLOAD_SIZE);
         let clamped_range = PacketSizeRange::new(clamped_min, clamped_max);
-        
+
+        Self::with_sizes(&PacketSizes::from_range(clamped_range), protocol_mix)
+    }
+
+    /// Create a packet builder with per-protocol payload size distributions
+    pub fn with_sizes(sizes: &PacketSizes, protocol_mix: ProtocolMix) -> Self {
+        Self::with_options(sizes, &PacketOptions::default(), protocol_mix)
+    }
+
+    /// Create a packet builder with payload sizes and run-wide packet options
+    pub fn with_options(sizes: &PacketSizes, options: &PacketOptions, protocol_mix: ProtocolMix) -> Self {
+        let payload = || PayloadWriter::new(&options.payload);
+        let ports = || PortPicker::new(options.source.port);
+        let fields = || options.header_fields.picker();
         let mut strategies: HashMap<PacketType, Box<dyn PacketStrategy>> = HashMap::new();
         let mut rng = BatchedRng::new();
         
         // Initialize strategies for each packet type
         strategies.insert(
             PacketType::Udp,
-            Box::new(super::protocols::UdpStrategy::new(clamped_range, &mut rng)),
-        );
-        strategies.insert(
-            PacketType::TcpSyn,
-            Box::new(super::protocols::TcpStrategy::new_syn(&mut rng)),
-        );
-        strategies.insert(
-            PacketType::TcpAck,
-            Box::new(super::protocols::TcpStrategy::new_ack(&mut rng)),
-        );
-        strategies.insert(
-            PacketType::TcpFin,
-            Box::new(super::protocols::TcpStrategy::new_fin(&mut rng)),
-        );
-        strategies.insert(
-            PacketType::TcpRst,
-            Box::new(super::protocols::TcpStrategy::new_rst(&mut rng)),
+            Box::new(super::protocols::UdpStrategy::new(PayloadSizer::new(&sizes.udp, IPV4_UDP_HEADER_SIZE), payload(), &options.source.ipv4, ports(), fields(), &mut rng)),
         );
+        let tcp = TcpTemplate {
+            source: options.source.ipv4,
+            source6: options.source.ipv6,
+            port: options.source.port,
+            options: options.tcp_options.clone(),
+            extensions: options.ipv6_extensions.clone(),
+            fields: options.header_fields.clone(),
+            payload: options.tcp_payload,
+            sizes: sizes.tcp.clone(),
+            fill: options.payload.clone(),
+        };
+        let basic_flags = [TcpFlagSet::SYN, TcpFlagSet::ACK, TcpFlagSet::FIN, TcpFlagSet::RST];
+        let tcp_types = basic_flags.into_iter()
+            .chain(protocol_mix.tcp_ratios().into_iter().map(|(flags, _)| flags))
+            .map(PacketType::Tcp)
+            .chain(basic_flags.into_iter()
+                .chain(protocol_mix.ipv6_mix().tcp_ratios().into_iter().map(|(flags, _)| flags))
+                .map(PacketType::Ipv6Tcp));
+        for packet_type in tcp_types {
+            if !strategies.contains_key(&packet_type)
+                && let Some(strategy) = tcp.strategy_for(packet_type, &mut rng) {
+                    strategies.insert(packet_type, strategy);
+                }
+        }
         strategies.insert(
             PacketType::Icmp,
-            Box::new(super::protocols::IcmpStrategy::new(&mut rng)),
+            Box::new(super::protocols::IcmpStrategy::new(options.icmp_messages.clone(), PayloadSizer::new(&sizes.icmp, IPV4_ICMP_HEADER_SIZE), payload(), &options.source.ipv4, fields(), &mut rng)),
         );
         strategies.insert(
             PacketType::Ipv6Udp,
-            Box::new(super::protocols::Ipv6UdpStrategy::new(clamped_range, &mut rng)),
-        );
-        strategies.insert(
-            PacketType::Ipv6Tcp,
-            Box::new(super::protocols::Ipv6TcpStrategy::new(&mut rng)),
+            Box::new(super::protocols::Ipv6UdpStrategy::new(PayloadSizer::new(&sizes.udp, IPV6_UDP_HEADER_SIZE), payload(), &options.source.ipv6, ports(), fields(), &options.ipv6_extensions, &mut rng)),
         );
         strategies.insert(
             PacketType::Ipv6Icmp,
-            Box::new(super::protocols::Ipv6IcmpStrategy::new(&mut rng)),
+            Box::new(super::protocols::Ipv6IcmpStrategy::new(options.icmpv6_messages.clone(), PayloadSizer::new(&sizes.icmp, IPV6_ICMP_HEADER_SIZE), payload(), &options.source.ipv6, options.source.mac, fields(), &options.ipv6_extensions, &mut rng)),
         );
         strategies.insert(
             PacketType::Arp,
-            Box::new(super::protocols::ArpStrategy::new(&mut rng)),
+            Box::new(super::protocols::ArpStrategy::new(&options.arp, &options.source.ipv4, options.source.mac, &mut rng)),
         );
 
         Self {
             strategies,
+            tcp,
             protocol_selector: ProtocolSelector::new(protocol_mix),
             rng,
         }
@@ -90,11 +153,7 @@ impl PacketBuilder {
         target_port: u16,
     ) -> Result<(usize, &'static str)> {
         let target = PacketTarget::new(target_ip, target_port);
-        
-        let strategy = self.strategies.get_mut(&packet_type)
-            .ok_or_else(|| PacketError::build_failed("Packet", 
-                format!("No strategy available for packet type: {}", packet_type)
-            ))?;
+        let strategy = self.strategy(packet_type)?;
 
         if !strategy.is_compatible_with(target_ip) {
             return Err(PacketError::build_failed("Packet", 
@@ -116,12 +175,7 @@ impl PacketBuilder {
         target_ip: IpAddr,
         target_port: u16,
     ) -> Result<(Vec<u8>, &'static str)> {
-        let strategy = self.strategies.get(&packet_type)
-            .ok_or_else(|| PacketError::build_failed("Packet", 
-                format!("No strategy available for packet type: {}", packet_type)
-            ))?;
-
-        let max_size = strategy.max_packet_size();
+        let max_size = self.strategy(packet_type)?.max_packet_size();
         let mut buffer = vec![0u8; max_size];
         
         let (actual_size, protocol_name) = self.build_packet_into_buffer(
@@ -132,6 +186,19 @@ impl PacketBuilder {
         Ok((buffer, protocol_name))
     }
 
+    /// Strategy for a packet type; TCP flag sets outside the mix are set up on first use
+    fn strategy(&mut self, packet_type: PacketType) -> Result<&mut Box<dyn PacketStrategy>> {
+        if !self.strategies.contains_key(&packet_type)
+            && let Some(strategy) = self.tcp.strategy_for(packet_type, &mut self.rng) {
+                self.strategies.insert(packet_type, strategy);
+            }
+
+        self.strategies.get_mut(&packet_type)
+            .ok_or_else(|| PacketError::build_failed("Packet",
+                format!("No strategy available for packet type: {}", packet_type)
+            ).into())
+    }
+
     /// Select the next packet type based on protocol mix and target IP compatibility
     #[inline]
     pub fn next_packet_type_for_ip(&mut self, target_ip: IpAddr) -> PacketType {
@@ -154,11 +221,17 @@ impl PacketBuilder {
 /// Protocol selector that chooses packet types based on configured ratios
 struct ProtocolSelector {
     protocol_mix: ProtocolMix,
+    tcp_ratios: Vec<(TcpFlagSet, f64)>,
+    ipv6_mix: Ipv6ProtocolMix,
+    ipv6_tcp_ratios: Vec<(TcpFlagSet, f64)>,
 }
 
 impl ProtocolSelector {
     fn new(protocol_mix: ProtocolMix) -> Self {
-        Self { protocol_mix }
+        let tcp_ratios = protocol_mix.tcp_ratios();
+        let ipv6_mix = protocol_mix.ipv6_mix();
+        let ipv6_tcp_ratios = ipv6_mix.tcp_ratios();
+        Self { protocol_mix, tcp_ratios, ipv6_mix, ipv6_tcp_ratios }
     }
 
     fn select_packet_type(&self, target_ip: IpAddr, rng: &mut BatchedRng) -> PacketType {
@@ -173,24 +246,11 @@ impl ProtocolSelector {
                     return PacketType::Udp;
                 }
 
-                cumulative += self.protocol_mix.tcp_syn_ratio;
-                if rand_val < cumulative {
-                    return PacketType::TcpSyn;
-                }
-
-                cumulative += self.protocol_mix.tcp_ack_ratio;
-                if rand_val < cumulative {
-                    return PacketType::TcpAck;
-                }
-
-                cumulative += self.protocol_mix.tcp_fin_ratio;
-                if rand_val < cumulative {
-                    return PacketType::TcpFin;
-                }
-
-                cumulative += self.protocol_mix.tcp_rst_ratio;
-                if rand_val < cumulative {
-                    return PacketType::TcpRst;
+                for &(flags, ratio) in &self.tcp_ratios {
+                    cumulative += ratio;
+                    if rand_val < cumulative {
+                        return PacketType::Tcp(flags);
+                    }
                 }
 
                 cumulative += self.protocol_mix.icmp_ratio;
@@ -202,16 +262,21 @@ impl ProtocolSelector {
                 PacketType::Arp
             }
             IpAddr::V6(_) => {
-                // IPv6 protocols only - evenly distribute across IPv6 types
-                let norm_val = rand_val * 3.0; // 3 IPv6 packet types
-                
-                if norm_val < 1.0 {
-                    PacketType::Ipv6Udp
-                } else if norm_val < 2.0 {
-                    PacketType::Ipv6Tcp
-                } else {
-                    PacketType::Ipv6Icmp
+                // IPv6 protocols only
+                cumulative += self.ipv6_mix.udp_ratio;
+                if rand_val < cumulative {
+                    return PacketType::Ipv6Udp;
                 }
+
+                for &(flags, ratio) in &self.ipv6_tcp_ratios {
+                    cumulative += ratio;
+                    if rand_val < cumulative {
+                        return PacketType::Ipv6Tcp(flags);
+                    }
+                }
+
+                // ICMPv6 takes the remainder
+                PacketType::Ipv6Icmp
             }
         }
     }
@@ -0,0 +1,99 @@
+//! Header field values under override rules
+//!
+//! Strategies ask a `FieldPicker` for every field a rule can set, passing
+//! the generator they used before rules existed; fields without a rule
+//! still come from that generator.
+
+use std::collections::BTreeMap;
+
+use crate::config::{FieldRule, HeaderField};
+use crate::utils::rng::BatchedRng;
+
+/// Header field rules of a run
+#[derive(Debug, Clone, Default)]
+pub struct HeaderFieldRules {
+    rules: BTreeMap<HeaderField, FieldRule>,
+}
+
+impl HeaderFieldRules {
+    pub fn from_config(rules: &BTreeMap<HeaderField, FieldRule>) -> Self {
+        Self { rules: rules.clone() }
+    }
+
+    /// Picker for one stream, with its own counters and list positions
+    pub fn picker(&self) -> FieldPicker {
+        let mut streams: [Option<FieldStream>; HeaderField::ALL.len()] = Default::default();
+        for (&field, rule) in &self.rules {
+            let next = match *rule {
+                FieldRule::Sequential { start, .. } => start,
+                _ => 0,
+            };
+            streams[field as usize] = Some(FieldStream { rule: rule.clone(), max: field.max_value(), next });
+        }
+        FieldPicker { streams }
+    }
+}
+
+/// A field's rule and position
+#[derive(Debug, Clone)]
+struct FieldStream {
+    rule: FieldRule,
+    max: u32,
+    /// Next sequential value, or index into the list
+    next: u32,
+}
+
+impl FieldStream {
+    fn next(&mut self, rng: &mut BatchedRng) -> u32 {
+        match self.rule {
+            FieldRule::Random => rng.range(0, self.max as usize + 1) as u32,
+            FieldRule::Fixed { value } => value,
+            FieldRule::Range { min, max } => rng.range(min as usize, max as usize + 1) as u32,
+            FieldRule::Sequential { step, .. } => {
+                let value = self.next;
+                self.next = ((value as u64 + step as u64) % (self.max as u64 + 1)) as u32;
+                value
+            }
+            FieldRule::List { ref values } => {
+                let value = values[self.next as usize % values.len()];
+                self.next = (self.next + 1) % values.len() as u32;
+                value
+            }
+        }
+    }
+}
+
+/// Header field values of one stream
+#[derive(Debug, Clone, Default)]
+pub struct FieldPicker {
+    streams: [Option<FieldStream>; HeaderField::ALL.len()],
+}
+
+impl FieldPicker {
+    /// Value of an 8-bit field, from `default` when it has no rule
+    #[inline]
+    pub fn u8(&mut self, field: HeaderField, rng: &mut BatchedRng, default: impl FnOnce(&mut BatchedRng) -> u8) -> u8 {
+        match self.streams[field as usize] {
+            Some(ref mut stream) => stream.next(rng) as u8,
+            None => default(rng),
+        }
+    }
+
+    /// Value of a 16-bit field, from `default` when it has no rule
+    #[inline]
+    pub fn u16(&mut self, field: HeaderField, rng: &mut BatchedRng, default: impl FnOnce(&mut BatchedRng) -> u16) -> u16 {
+        match self.streams[field as usize] {
+            Some(ref mut stream) => stream.next(rng) as u16,
+            None => default(rng),
+        }
+    }
+
+    /// Value of a field of up to 32 bits, from `default` when it has no rule
+    #[inline]
+    pub fn u32(&mut self, field: HeaderField, rng: &mut BatchedRng, default: impl FnOnce(&mut BatchedRng) -> u32) -> u32 {
+        match self.streams[field as usize] {
+            Some(ref mut stream) => stream.next(rng),
+            None => default(rng),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! IPv4 fragmentation
+//!
+//! Fragments copy the IPv4 header of the datagram, including its
+//! identification, and get their own total length, offset, more-fragments
+//! bit and checksum. Don't-fragment is cleared. Datagrams whose payload
+//! fits in a single fragment are sent whole.
+
+use pnet::packet::ipv4::{checksum, Ipv4Packet, MutableIpv4Packet};
+use std::ops::Range;
+
+use crate::config::{FragmentPattern, FragmentationConfig};
+use crate::constants::{fragment, IPV4_HEADER_SIZE};
+use crate::utils::rng::BatchedRng;
+
+/// Part of the datagram payload carried by one fragment
+#[derive(Debug, Clone, Copy)]
+struct Piece {
+    offset: usize,
+    len: usize,
+    more: bool,
+    /// Leading bytes repeated from the previous fragment, inverted
+    overlap: usize,
+}
+
+/// Splits IPv4 datagrams into fragments, reusing its buffers between datagrams
+pub struct Fragmenter {
+    pattern: FragmentPattern,
+    fragment_size: usize,
+    ratio: f64,
+    pieces: Vec<Piece>,
+    /// Fragments back to back, in sending order
+    bytes: Vec<u8>,
+    ranges: Vec<Range<usize>>,
+    rng: BatchedRng,
+}
+
+impl Fragmenter {
+    pub fn new(config: &FragmentationConfig) -> Self {
+        Self {
+            pattern: config.pattern,
+            fragment_size: config.fragment_size,
+            ratio: config.ratio,
+            pieces: Vec::new(),
+            bytes: Vec::new(),
+            ranges: Vec::new(),
+            rng: BatchedRng::new(),
+        }
+    }
+
+    /// Split an IPv4 datagram, unless the ratio passes it over or its payload
+    /// fits in one fragment; returns whether `fragments` holds its fragments
+    pub fn fragment(&mut self, datagram: &[u8]) -> bool {
+        self.bytes.clear();
+        self.ranges.clear();
+
+        if self.ratio < 1.0 && !self.rng.bool_with_probability(self.ratio) {
+            return false;
+        }
+        let Some(ip) = Ipv4Packet::new(datagram) else {
+            return false;
+        };
+        let header_len = ip.get_header_length() as usize * 4;
+        let total_len = ip.get_total_length() as usize;
+        if header_len < IPV4_HEADER_SIZE || total_len <= header_len || total_len > datagram.len() {
+            return false;
+        }
+
+        self.split(total_len - header_len);
+        if self.pieces.len() < 2 {
+            return false;
+        }
+        self.order();
+
+        let (header, payload) = datagram[..total_len].split_at(header_len);
+        for piece in &self.pieces {
+            let start = self.bytes.len();
+            self.bytes.extend_from_slice(header);
+            self.bytes.extend_from_slice(&payload[piece.offset..piece.offset + piece.len]);
+            let data_at = start + header_len;
+            for byte in &mut self.bytes[data_at..data_at + piece.overlap] {
+                *byte = !*byte;
+            }
+
+            // Holds at least the 20 bytes of a header, checked above
+            if let Some(mut fragment_packet) = MutableIpv4Packet::new(&mut self.bytes[start..]) {
+                fragment_packet.set_total_length((header_len + piece.len) as u16);
+                fragment_packet.set_flags(if piece.more { fragment::FLAG_MORE_FRAGMENTS } else { 0 });
+                fragment_packet.set_fragment_offset((piece.offset / fragment::OFFSET_UNIT) as u16);
+                fragment_packet.set_checksum(0);
+                let sum = checksum(&fragment_packet.to_immutable());
+                fragment_packet.set_checksum(sum);
+            }
+
+            self.ranges.push(start..self.bytes.len());
+        }
+        true
+    }
+
+    /// Fragments of the last datagram split, in sending order
+    pub fn fragments(&self) -> impl Iterator<Item = &[u8]> {
+        self.ranges.iter().map(|range| &self.bytes[range.clone()])
+    }
+
+    /// Cut a payload of `len` bytes into pieces in offset order
+    fn split(&mut self, len: usize) {
+        self.pieces.clear();
+        let first = match self.pattern {
+            FragmentPattern::TinyFirst => fragment::TINY_FRAGMENT_SIZE,
+            _ => self.fragment_size,
+        };
+        if len <= first {
+            return;
+        }
+
+        let mut offset = 0;
+        let mut piece_len = first;
+        while offset < len {
+            let piece_len_now = piece_len.min(len - offset);
+            self.pieces.push(Piece {
+                offset,
+                len: piece_len_now,
+                more: offset + piece_len_now < len,
+                overlap: 0,
+            });
+            offset += piece_len_now;
+            piece_len = self.fragment_size;
+        }
+
+        if self.pattern == FragmentPattern::Overlapping {
+            // Offsets stay on 8-byte boundaries
+            for piece in &mut self.pieces[1..] {
+                piece.offset -= fragment::OVERLAP_SIZE;
+                piece.len += fragment::OVERLAP_SIZE;
+                piece.overlap = fragment::OVERLAP_SIZE;
+            }
+        }
+    }
+
+    /// Put the pieces in sending order
+    fn order(&mut self) {
+        match self.pattern {
+            FragmentPattern::Reversed => self.pieces.reverse(),
+            FragmentPattern::Random => {
+                for index in (1..self.pieces.len()).rev() {
+                    let other = self.rng.range(0, index + 1);
+                    self.pieces.swap(index, other);
+                }
+            }
+            FragmentPattern::MissingLast => {
+                self.pieces.pop();
+            }
+            FragmentPattern::InOrder | FragmentPattern::Overlapping | FragmentPattern::TinyFirst => {}
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! IPv6 extension header encoding
+//!
+//! The chain is encoded once per run. Per packet, only the next header
+//! field of the last header, the routing header addresses and fragment
+//! identifications change. Routing headers list the target as every
+//! segment, so the packet goes nowhere else even where a router still
+//! processes type 0 headers, and upper-layer checksums stay valid.
+
+use std::net::Ipv6Addr;
+
+use crate::config::{Ipv6Extension, Ipv6ExtensionChain};
+use crate::constants::ipv6_extension;
+use crate::utils::rng::BatchedRng;
+
+/// Resolved extension header chain, shared by all IPv6 strategies of a run
+#[derive(Debug, Clone, Default)]
+pub struct Ipv6ExtensionLayout {
+    headers: Vec<Ipv6Extension>,
+    bytes: Vec<u8>,
+    /// Protocol of the first header, for the IPv6 next header field
+    first: Option<u8>,
+    /// Offset of the next header field of the last header
+    last_next_header_at: usize,
+    /// Offsets of routing header addresses
+    addresses_at: Vec<usize>,
+    /// Offsets of fragment identifications
+    identifications_at: Vec<usize>,
+}
+
+impl Ipv6ExtensionLayout {
+    pub fn from_config(chain: &Ipv6ExtensionChain) -> Self {
+        Self::encode_chain(chain.0.clone(), false)
+    }
+
+    /// The same chain with a Router Alert option (RFC 2711) for MLD first in
+    /// its Hop-by-Hop header. A packet may carry only one Hop-by-Hop header,
+    /// right after the IPv6 header (RFC 8200), so the option joins the
+    /// chain's own when it starts with one and a new header leads otherwise.
+    pub fn with_router_alert(&self) -> Self {
+        let mut headers = self.headers.clone();
+        if !matches!(headers.first(), Some(Ipv6Extension::HopByHop(_))) {
+            headers.insert(0, Ipv6Extension::HopByHop(1));
+        }
+        Self::encode_chain(headers, true)
+    }
+
+    fn encode_chain(headers: Vec<Ipv6Extension>, router_alert: bool) -> Self {
+        let mut layout = Self::default();
+        for (index, &header) in headers.iter().enumerate() {
+            let start = layout.bytes.len();
+            match layout.first {
+                None => layout.first = Some(header.protocol()),
+                Some(_) => layout.bytes[layout.last_next_header_at] = header.protocol(),
+            }
+            layout.last_next_header_at = start;
+            layout.encode(header, router_alert && index == 0);
+        }
+        layout.headers = headers;
+        layout
+    }
+
+    fn encode(&mut self, header: Ipv6Extension, router_alert: bool) {
+        let start = self.bytes.len();
+        match header {
+            Ipv6Extension::HopByHop(units) | Ipv6Extension::DestinationOptions(units) => {
+                // Header extension length counts 8-byte units after the first
+                self.bytes.extend([0, (units - 1) as u8]);
+                let mut options = header.encoded_len() - 2;
+                if router_alert {
+                    // Value 0: the packet carries an MLD message
+                    self.bytes.extend([ipv6_extension::OPTION_ROUTER_ALERT, 2, 0, 0]);
+                    options -= ipv6_extension::ROUTER_ALERT_SIZE;
+                }
+                pad(&mut self.bytes, options);
+            }
+            Ipv6Extension::Routing { routing_type, segments } => {
+                let segments_left = match routing_type {
+                    ipv6_extension::ROUTING_TYPE_SEGMENT_ROUTING => segments - 1,
+                    _ => segments,
+                };
+                self.bytes.extend([0, segments * 2, routing_type, segments_left]);
+                if routing_type == ipv6_extension::ROUTING_TYPE_SEGMENT_ROUTING {
+                    // Last entry, flags and tag
+                    self.bytes.extend([segments - 1, 0, 0, 0]);
+                } else {
+                    self.bytes.extend([0; 4]);
+                }
+                for _ in 0..segments {
+                    self.addresses_at.push(self.bytes.len());
+                    self.bytes.extend([0; 16]);
+                }
+            }
+            Ipv6Extension::Fragment => {
+                // Offset 0 without the more fragments bit: an atomic fragment
+                self.bytes.extend([0; 4]);
+                self.identifications_at.push(self.bytes.len());
+                self.bytes.extend([0; 4]);
+            }
+        }
+        debug_assert_eq!(self.bytes.len() - start, header.encoded_len());
+    }
+
+    /// Length of the whole chain
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Write the chain into `area`, which must be `len()` bytes long, ending
+    /// in `upper`; returns the protocol for the IPv6 next header field
+    #[inline]
+    pub fn write(&self, area: &mut [u8], upper: u8, target: Ipv6Addr, rng: &mut BatchedRng) -> u8 {
+        let Some(first) = self.first else {
+            return upper;
+        };
+
+        area.copy_from_slice(&self.bytes);
+        area[self.last_next_header_at] = upper;
+        for &at in &self.addresses_at {
+            area[at..at + 16].copy_from_slice(&target.octets());
+        }
+        for &at in &self.identifications_at {
+            area[at..at + 4].copy_from_slice(&rng.sequence().to_be_bytes());
+        }
+        first
+    }
+}
+
+/// Fill an option area with PadN options, and Pad1 for a last odd byte
+fn pad(bytes: &mut Vec<u8>, mut remaining: usize) {
+    while remaining > 0 {
+        if remaining == 1 {
+            bytes.push(ipv6_extension::OPTION_PAD1);
+            break;
+        }
+        let data = (remaining - 2).min(ipv6_extension::MAX_PADN_DATA);
+        bytes.extend([ipv6_extension::OPTION_PADN, data as u8]);
+        bytes.resize(bytes.len() + data, 0);
+        remaining -= data + 2;
+    }
+}
@@ -0,0 +1,106 @@
+//! Weighted message selection
+//!
+//! ICMP, ICMPv6 and ARP packets pick their message type per packet from
+//! configured weights, falling back to a single default message (echo
+//! requests, ARP requests) when no weights are configured.
+
+use std::collections::BTreeMap;
+
+use crate::config::{ArpKind, IcmpKind, IcmpMessage, Icmpv6Kind};
+use crate::utils::rng::BatchedRng;
+
+/// A message type a mix can pick
+pub trait MessageKind: Copy + Ord {
+    /// Sent when no weights are configured
+    const DEFAULT: Self;
+
+    /// Whether the message carries a sized payload
+    fn carries_payload(&self) -> bool {
+        false
+    }
+}
+
+impl MessageKind for IcmpMessage {
+    const DEFAULT: Self = IcmpMessage::new(IcmpKind::EchoRequest);
+
+    fn carries_payload(&self) -> bool {
+        self.kind == IcmpKind::EchoRequest
+    }
+}
+
+impl MessageKind for Icmpv6Kind {
+    const DEFAULT: Self = Icmpv6Kind::EchoRequest;
+
+    fn carries_payload(&self) -> bool {
+        *self == Icmpv6Kind::EchoRequest
+    }
+}
+
+impl MessageKind for ArpKind {
+    const DEFAULT: Self = ArpKind::Request;
+}
+
+/// Resolved message weights, shared by all strategies of a run
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageMix<T> {
+    messages: Vec<T>,
+    /// Running weight totals, ending at the total weight
+    cumulative: Vec<f64>,
+}
+
+/// Resolved ICMP message weights
+pub type IcmpMessageMix = MessageMix<IcmpMessage>;
+
+/// Resolved ICMPv6 message weights
+pub type Icmpv6MessageMix = MessageMix<Icmpv6Kind>;
+
+/// Resolved ARP variant weights
+pub type ArpMessageMix = MessageMix<ArpKind>;
+
+impl<T: MessageKind> Default for MessageMix<T> {
+    /// The default message only
+    fn default() -> Self {
+        Self {
+            messages: vec![T::DEFAULT],
+            cumulative: vec![1.0],
+        }
+    }
+}
+
+impl<T: MessageKind> MessageMix<T> {
+    /// Mix of the configured weights; the default message only without any
+    pub fn from_config(types: &BTreeMap<T, f64>) -> Self {
+        let mut total = 0.0;
+        let (messages, cumulative): (Vec<_>, Vec<_>) = types
+            .iter()
+            .filter(|&(_, &weight)| weight > 0.0)
+            .map(|(&message, &weight)| {
+                total += weight;
+                (message, total)
+            })
+            .unzip();
+
+        if messages.is_empty() {
+            return Self::default();
+        }
+        Self { messages, cumulative }
+    }
+
+    /// Pick the next message in proportion to the weights
+    #[inline]
+    pub fn pick(&self, rng: &mut BatchedRng) -> T {
+        if self.messages.len() == 1 {
+            return self.messages[0];
+        }
+        let total = self.cumulative.last().copied().unwrap_or(0.0);
+        let pick = rng.unit() * total;
+        let index = self.cumulative.partition_point(|&bound| bound <= pick);
+        self.messages.get(index).or(self.messages.last()).copied()
+            .unwrap_or(T::DEFAULT)
+    }
+
+    /// Whether any message carries a sized payload
+    pub fn has_payload(&self) -> bool {
+        self.messages.iter().any(T::carries_payload)
+    }
+}
@@ -3,11 +3,29 @@
 //! This module provides packet construction for multiple protocols.
 
 pub mod builder;
+pub mod fields;
+pub mod fragment;
+pub mod ipv6_extensions;
+pub mod messages;
+pub mod options;
+pub mod payload;
 pub mod protocols;
+pub mod size;
+pub mod source;
+pub mod tcp_options;
 pub mod types;
 
 pub use builder::PacketBuilder;
+pub use fields::{FieldPicker, HeaderFieldRules};
+pub use fragment::Fragmenter;
+pub use ipv6_extensions::Ipv6ExtensionLayout;
+pub use messages::{ArpMessageMix, IcmpMessageMix, Icmpv6MessageMix, MessageKind, MessageMix};
 pub use types::PacketType;
+pub use options::{ArpOptions, PacketOptions};
+pub use payload::{InstrumentedHeader, PayloadFill, PayloadWriter};
+pub use size::{PacketSizes, PayloadSizer};
+pub use source::{PortPicker, SourceAddresses, SourcePicker, SourcePool};
+pub use tcp_options::{TcpOptionLayout, TcpOptionWriter};
 
 
 use crate::error::Result;
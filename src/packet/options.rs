@@ -0,0 +1,68 @@
+//! Packet settings that hold for a whole run
+
+use pnet::datalink::NetworkInterface;
+use pnet::util::MacAddr;
+use std::net::Ipv4Addr;
+
+use crate::config::arp::parse_mac;
+use crate::config::{ArpConfig, Config, FragmentationConfig, TcpPayload};
+use crate::error::Result;
+use crate::packet::{
+    ArpMessageMix, HeaderFieldRules, IcmpMessageMix, Icmpv6MessageMix, Ipv6ExtensionLayout, PayloadFill, SourceAddresses, TcpOptionLayout,
+};
+
+/// Run-wide packet settings, resolved once and shared by all workers
+#[derive(Debug, Clone, Default)]
+pub struct PacketOptions {
+    pub payload: PayloadFill,
+    pub source: SourceAddresses,
+    pub tcp_options: TcpOptionLayout,
+    pub tcp_payload: TcpPayload,
+    pub icmp_messages: IcmpMessageMix,
+    pub icmpv6_messages: Icmpv6MessageMix,
+    pub ipv6_extensions: Ipv6ExtensionLayout,
+    pub fragmentation: Option<FragmentationConfig>,
+    pub header_fields: HeaderFieldRules,
+    pub arp: ArpOptions,
+}
+
+/// ARP variants and parsed field overrides
+#[derive(Debug, Clone, Default)]
+pub struct ArpOptions {
+    pub messages: ArpMessageMix,
+    pub sender_mac: Option<MacAddr>,
+    pub sender_ip: Option<Ipv4Addr>,
+    pub target_mac: Option<MacAddr>,
+    pub target_ip: Option<Ipv4Addr>,
+}
+
+impl ArpOptions {
+    pub fn from_config(arp: &ArpConfig) -> Result<Self> {
+        Ok(Self {
+            messages: ArpMessageMix::from_config(&arp.types),
+            sender_mac: arp.sender_mac.as_deref().map(|mac| parse_mac("sender_mac", mac)).transpose()?,
+            sender_ip: arp.sender_ip,
+            target_mac: arp.target_mac.as_deref().map(|mac| parse_mac("target_mac", mac)).transpose()?,
+            target_ip: arp.target_ip,
+        })
+    }
+}
+
+impl PacketOptions {
+    /// Resolve the options of a configuration, reading any files it refers
+    /// to and the addresses of the selected interface
+    pub fn from_config(config: &Config, interface: Option<&NetworkInterface>) -> Result<Self> {
+        Ok(Self {
+            payload: PayloadFill::from_pattern(&config.attack.payload_pattern)?,
+            source: SourceAddresses::resolve(&config.source, interface)?,
+            tcp_options: TcpOptionLayout::from_config(&config.attack.tcp_options),
+            tcp_payload: config.attack.tcp_payload,
+            icmp_messages: IcmpMessageMix::from_config(&config.attack.icmp_types),
+            icmpv6_messages: Icmpv6MessageMix::from_config(&config.attack.icmpv6_types),
+            ipv6_extensions: Ipv6ExtensionLayout::from_config(&config.attack.ipv6_extension_headers),
+            fragmentation: config.attack.fragmentation.clone(),
+            header_fields: HeaderFieldRules::from_config(&config.attack.header_fields),
+            arp: ArpOptions::from_config(&config.attack.arp)?,
+        })
+    }
+}
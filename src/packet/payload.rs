@@ -0,0 +1,170 @@
+//! Payload content generation
+//!
+//! Instrumented payloads start with a fixed header in network byte order:
+//!
+//! | Offset | Size | Field                                 |
+//! |--------|------|---------------------------------------|
+//! | 0      | 4    | Magic cookie                          |
+//! | 4      | 4    | Stream ID                             |
+//! | 8      | 8    | Sequence number within the stream     |
+//! | 16     | 8    | TX timestamp, ns since the Unix epoch |
+//!
+//! Each packet strategy writes its own stream with sequence numbers counting
+//! from 0, so a receiver can detect loss, reordering and duplication per
+//! stream. Payloads too short for the header are zero-filled and do not use
+//! up a sequence number.
+
+use std::fs;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::PayloadPattern;
+use crate::constants::INSTRUMENTED_HEADER_SIZE;
+use crate::error::{ConfigError, Result};
+use crate::utils::rng::BatchedRng;
+
+/// Resolved payload pattern, shared by all strategies of a run
+#[derive(Debug, Clone, Default)]
+pub enum PayloadFill {
+    #[default]
+    Random,
+    Zero,
+    Incrementing,
+    /// Bytes repeated over the payload
+    Repeat(Arc<[u8]>),
+    Instrumented {
+        magic: u32,
+        stream_base: u32,
+        /// Streams handed out so far
+        next_stream: Arc<AtomicU32>,
+    },
+}
+
+impl PayloadFill {
+    /// Resolve a configured pattern, reading pattern files
+    pub fn from_pattern(pattern: &PayloadPattern) -> Result<Self> {
+        Ok(match *pattern {
+            PayloadPattern::Random => PayloadFill::Random,
+            PayloadPattern::Zero => PayloadFill::Zero,
+            PayloadPattern::Incrementing => PayloadFill::Incrementing,
+            PayloadPattern::Hex { ref pattern } => PayloadFill::Repeat(PayloadPattern::decode_hex(pattern)?.into()),
+            PayloadPattern::File { ref path } => {
+                let bytes = fs::read(path).map_err(|e| {
+                    ConfigError::new(format!("Cannot read payload file '{}': {}", path, e))
+                })?;
+                if bytes.is_empty() {
+                    return Err(ConfigError::new(format!("Payload file '{}' is empty", path)).into());
+                }
+                PayloadFill::Repeat(bytes.into())
+            }
+            PayloadPattern::Instrumented { magic, stream_base } => PayloadFill::Instrumented {
+                magic,
+                stream_base,
+                next_stream: Arc::new(AtomicU32::new(0)),
+            },
+        })
+    }
+}
+
+/// Writes payloads for one packet strategy
+#[derive(Debug)]
+pub struct PayloadWriter {
+    fill: PayloadFill,
+    stream_id: u32,
+    sequence: u64,
+}
+
+impl PayloadWriter {
+    /// Writer for `fill`; instrumented fills assign it the next stream ID
+    pub fn new(fill: &PayloadFill) -> Self {
+        let stream_id = match *fill {
+            PayloadFill::Instrumented { stream_base, ref next_stream, .. } => {
+                stream_base.wrapping_add(next_stream.fetch_add(1, Ordering::Relaxed))
+            }
+            _ => 0,
+        };
+
+        Self {
+            fill: fill.clone(),
+            stream_id,
+            sequence: 0,
+        }
+    }
+
+    /// Stream ID written into instrumented payloads
+    pub fn stream_id(&self) -> Option<u32> {
+        matches!(self.fill, PayloadFill::Instrumented { .. }).then_some(self.stream_id)
+    }
+
+    /// Fill the whole of `payload`
+    #[inline]
+    pub fn write(&mut self, payload: &mut [u8], rng: &mut BatchedRng) {
+        match self.fill {
+            PayloadFill::Random => payload.iter_mut().for_each(|byte| *byte = rng.byte()),
+            PayloadFill::Zero => payload.fill(0),
+            PayloadFill::Incrementing => {
+                for (index, byte) in payload.iter_mut().enumerate() {
+                    *byte = index as u8;
+                }
+            }
+            PayloadFill::Repeat(ref pattern) => {
+                for (byte, value) in payload.iter_mut().zip(pattern.iter().cycle()) {
+                    *byte = *value;
+                }
+            }
+            PayloadFill::Instrumented { magic, .. } => {
+                payload.fill(0);
+                if payload.len() >= INSTRUMENTED_HEADER_SIZE {
+                    let header = InstrumentedHeader {
+                        magic,
+                        stream_id: self.stream_id,
+                        sequence: self.sequence,
+                        tx_timestamp_ns: now_ns(),
+                    };
+                    header.write_to(payload);
+                    self.sequence += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Header at the start of instrumented payloads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstrumentedHeader {
+    pub magic: u32,
+    pub stream_id: u32,
+    pub sequence: u64,
+    pub tx_timestamp_ns: u64,
+}
+
+impl InstrumentedHeader {
+    /// Read the header of a received payload, `None` unless it starts with `magic`
+    pub fn parse(payload: &[u8], magic: u32) -> Option<Self> {
+        let header = payload.get(..INSTRUMENTED_HEADER_SIZE)?;
+        let word = |range: std::ops::Range<usize>| header[range].iter().fold(0u64, |acc, &b| acc << 8 | b as u64);
+
+        let parsed = Self {
+            magic: word(0..4) as u32,
+            stream_id: word(4..8) as u32,
+            sequence: word(8..16),
+            tx_timestamp_ns: word(16..24),
+        };
+        (parsed.magic == magic).then_some(parsed)
+    }
+
+    fn write_to(&self, payload: &mut [u8]) {
+        payload[0..4].copy_from_slice(&self.magic.to_be_bytes());
+        payload[4..8].copy_from_slice(&self.stream_id.to_be_bytes());
+        payload[8..16].copy_from_slice(&self.sequence.to_be_bytes());
+        payload[16..24].copy_from_slice(&self.tx_timestamp_ns.to_be_bytes());
+    }
+}
+
+fn now_ns() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as u64)
+        .unwrap_or(0)
+}
@@ -1,9 +1,14 @@
 //! ARP packet building strategy
+//!
+//! Builds complete Ethernet frames, which the worker sends through the
+//! layer-2 channel. Requests, gratuitous ARPs and probes are broadcast;
+//! replies go to the configured target MAC, or broadcast without one.
 
 use super::PacketStrategy;
+use crate::config::ArpKind;
 use crate::constants::ARP_FRAME_SIZE;
 use crate::error::{PacketError, Result};
-use crate::packet::PacketTarget;
+use crate::packet::{ArpMessageMix, ArpOptions, PacketTarget, SourcePicker, SourcePool};
 use crate::utils::rng::BatchedRng;
 use pnet::packet::arp::{ArpHardwareTypes, ArpOperations, MutableArpPacket};
 use pnet::packet::ethernet::{EtherTypes, MutableEthernetPacket};
@@ -12,27 +17,36 @@ use pnet::util::MacAddr;
 use std::net::{IpAddr, Ipv4Addr};
 
 pub struct ArpStrategy {
-    source_ip: Ipv4Addr,
+    source: SourcePicker,
     source_mac: MacAddr,
-    _rng: BatchedRng,
+    messages: ArpMessageMix,
+    sender_ip: Option<Ipv4Addr>,
+    target_mac: Option<MacAddr>,
+    target_ip: Option<Ipv4Addr>,
+    rng: BatchedRng,
 }
 
 impl ArpStrategy {
-    pub fn new(rng: &mut BatchedRng) -> Self {
-        let source_ip = Ipv4Addr::new(192, 168, 1, rng.range(2, 254) as u8);
-        let source_mac = MacAddr::new(
+    /// Sender with the configured MAC, `mac`, or a random locally
+    /// administered MAC
+    pub fn new(options: &ArpOptions, source: &SourcePool, mac: Option<MacAddr>, rng: &mut BatchedRng) -> Self {
+        let source_mac = options.sender_mac.or(mac).unwrap_or_else(|| MacAddr::new(
             0x02,
             rng.byte(),
             rng.byte(),
             rng.byte(),
             rng.byte(),
             rng.byte(),
-        );
-        
+        ));
+
         Self {
-            source_ip,
+            source: source.picker(rng),
             source_mac,
-            _rng: BatchedRng::new(),
+            messages: options.messages.clone(),
+            sender_ip: options.sender_ip,
+            target_mac: options.target_mac,
+            target_ip: options.target_ip,
+            rng: BatchedRng::new(),
         }
     }
 }
@@ -40,7 +54,7 @@ impl ArpStrategy {
 impl PacketStrategy for ArpStrategy {
     fn build_packet(&mut self, target: &PacketTarget, buffer: &mut [u8]) -> Result<usize> {
         let target_ip = match target.ip {
-            IpAddr::V4(ip) => ip,
+            IpAddr::V4(ip) => self.target_ip.unwrap_or(ip),
             IpAddr::V6(_) => {
                 return Err(PacketError::build_failed("Packet", "ARP strategy requires IPv4 target").into());
             }
@@ -51,26 +65,43 @@ impl PacketStrategy for ArpStrategy {
         }
         buffer[..ARP_FRAME_SIZE].fill(0);
 
+        let kind = self.messages.pick(&mut self.rng);
+        let sender_ip = match self.sender_ip {
+            Some(ip) => ip,
+            None => self.source.next_ipv4(&mut self.rng),
+        };
+
+        // Ethernet destination, operation, sender IP, target MAC and target IP
+        let (destination, operation, sender_ip, target_hw, target_ip) = match kind {
+            ArpKind::Request => (MacAddr::broadcast(), ArpOperations::Request, sender_ip, MacAddr::zero(), target_ip),
+            ArpKind::Reply => {
+                let target_mac = self.target_mac.unwrap_or(MacAddr::broadcast());
+                (target_mac, ArpOperations::Reply, sender_ip, target_mac, target_ip)
+            }
+            ArpKind::Gratuitous => (MacAddr::broadcast(), ArpOperations::Request, sender_ip, MacAddr::zero(), sender_ip),
+            ArpKind::Probe => (MacAddr::broadcast(), ArpOperations::Request, Ipv4Addr::UNSPECIFIED, MacAddr::zero(), target_ip),
+        };
+
         // Build Ethernet header
         let mut ethernet_packet = MutableEthernetPacket::new(&mut buffer[..ARP_FRAME_SIZE])
             .ok_or_else(|| PacketError::build_failed("ARP", "Failed to create Ethernet packet"))?;
-        
-        ethernet_packet.set_destination(MacAddr::broadcast());
+
+        ethernet_packet.set_destination(destination);
         ethernet_packet.set_source(self.source_mac);
         ethernet_packet.set_ethertype(EtherTypes::Arp);
 
         // Build ARP packet
         let mut arp_packet = MutableArpPacket::new(ethernet_packet.payload_mut())
             .ok_or_else(|| PacketError::build_failed("ARP", "Failed to create ARP packet"))?;
-        
+
         arp_packet.set_hardware_type(ArpHardwareTypes::Ethernet);
         arp_packet.set_protocol_type(EtherTypes::Ipv4);
         arp_packet.set_hw_addr_len(6);
         arp_packet.set_proto_addr_len(4);
-        arp_packet.set_operation(ArpOperations::Request);
+        arp_packet.set_operation(operation);
         arp_packet.set_sender_hw_addr(self.source_mac);
-        arp_packet.set_sender_proto_addr(self.source_ip);
-        arp_packet.set_target_hw_addr(MacAddr::zero());
+        arp_packet.set_sender_proto_addr(sender_ip);
+        arp_packet.set_target_hw_addr(target_hw);
         arp_packet.set_target_proto_addr(target_ip);
 
         Ok(ARP_FRAME_SIZE)
@@ -87,4 +118,4 @@ impl PacketStrategy for ArpStrategy {
     fn is_compatible_with(&self, target_ip: IpAddr) -> bool {
         matches!(target_ip, IpAddr::V4(_))
     }
-}
\ No newline at end of file
+}
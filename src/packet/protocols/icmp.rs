@@ -1,50 +1,180 @@
 //! ICMP packet building strategy
+//!
+//! Error messages (destination unreachable, time exceeded, parameter
+//! problem) quote the IP header and first 8 bytes of a UDP datagram the
+//! target supposedly sent to the source address, as RFC 792 requires, so
+//! stateful firewalls can match them against a flow.
 
 use super::PacketStrategy;
-use crate::constants::{IPV4_HEADER_SIZE, ICMP_HEADER_SIZE, IPV4_ICMP_HEADER_SIZE, icmp};
+use crate::config::{HeaderField, IcmpKind, IcmpMessage};
+use crate::constants::{icmp, IPV4_HEADER_SIZE, ICMP_HEADER_SIZE, IPV4_ICMP_HEADER_SIZE, UDP_HEADER_SIZE};
 use crate::error::{PacketError, Result};
-use crate::packet::PacketTarget;
+use crate::packet::{FieldPicker, IcmpMessageMix, PacketTarget, PayloadSizer, PayloadWriter, SourcePicker, SourcePool};
 use crate::utils::rng::BatchedRng;
-use pnet::packet::icmp::{IcmpTypes, MutableIcmpPacket};
+use pnet::packet::icmp::{IcmpCode, IcmpType, MutableIcmpPacket};
 use pnet::packet::ip::IpNextHeaderProtocols;
 use pnet::packet::ipv4::MutableIpv4Packet;
+use pnet::packet::udp::MutableUdpPacket;
 use pnet::packet::MutablePacket;
 use std::net::{IpAddr, Ipv4Addr};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Milliseconds in a day, the range of ICMP timestamps
+const MS_PER_DAY: u128 = 86_400_000;
 
 pub struct IcmpStrategy {
-    source_ip: Ipv4Addr,
+    source: SourcePicker,
+    messages: IcmpMessageMix,
+    fields: FieldPicker,
+    sizer: PayloadSizer,
+    payload: PayloadWriter,
     rng: BatchedRng,
 }
 
 impl IcmpStrategy {
-    pub fn new(rng: &mut BatchedRng) -> Self {
-        let source_ip = Ipv4Addr::new(192, 168, 1, rng.range(2, 254) as u8);
-        
+    pub fn new(
+        messages: IcmpMessageMix,
+        sizer: PayloadSizer,
+        payload: PayloadWriter,
+        source: &SourcePool,
+        fields: FieldPicker,
+        rng: &mut BatchedRng,
+    ) -> Self {
         Self {
-            source_ip,
+            source: source.picker(rng),
+            messages,
+            fields,
+            sizer,
+            payload,
             rng: BatchedRng::new(),
         }
     }
 
+    /// Length of the message after the 8-byte ICMP header
+    fn body_len(&mut self, kind: IcmpKind) -> usize {
+        match kind {
+            IcmpKind::EchoRequest => self.sizer.sample(&mut self.rng),
+            IcmpKind::Timestamp => icmp::TIMESTAMP_BODY_SIZE,
+            IcmpKind::AddressMask => icmp::ADDRESS_MASK_BODY_SIZE,
+            IcmpKind::DestUnreachable | IcmpKind::TimeExceeded | IcmpKind::ParameterProblem => {
+                IPV4_HEADER_SIZE + icmp::QUOTED_DATA_SIZE
+            }
+        }
+    }
+
+    /// Write the second header word and the body; `rest` starts after the checksum
+    fn write_message(
+        &mut self,
+        rest: &mut [u8],
+        message: IcmpMessage,
+        code: u8,
+        target: &PacketTarget,
+        source_ip: Ipv4Addr,
+        target_ip: Ipv4Addr,
+    ) {
+        match message.kind {
+            // The echo identifier and sequence precede the payload
+            IcmpKind::EchoRequest => {
+                self.write_query_id(rest, true);
+                self.payload.write(&mut rest[4..], &mut self.rng);
+            }
+            IcmpKind::Timestamp => {
+                self.write_query_id(rest, false);
+                let now_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+                // Originate timestamp; receive and transmit stay 0
+                rest[4..8].copy_from_slice(&((now_ms % MS_PER_DAY) as u32).to_be_bytes());
+            }
+            IcmpKind::AddressMask => self.write_query_id(rest, false),
+            IcmpKind::DestUnreachable => {
+                if code == icmp::CODE_FRAG_NEEDED {
+                    let mtu = icmp::NEXT_HOP_MTUS[self.rng.range(0, icmp::NEXT_HOP_MTUS.len())];
+                    rest[2..4].copy_from_slice(&mtu.to_be_bytes());
+                }
+                self.write_quote(&mut rest[4..], code == icmp::CODE_FRAG_NEEDED, None, target, source_ip, target_ip);
+            }
+            IcmpKind::TimeExceeded => {
+                // TTL expiry in transit quotes a datagram that arrived with TTL 1
+                let ttl = (code == 0).then_some(1);
+                self.write_quote(&mut rest[4..], false, ttl, target, source_ip, target_ip);
+            }
+            IcmpKind::ParameterProblem => {
+                // The pointer names the offending octet of the quoted header
+                if code == 0 {
+                    rest[0] = self.rng.range(0, IPV4_HEADER_SIZE) as u8;
+                }
+                self.write_quote(&mut rest[4..], false, None, target, source_ip, target_ip);
+            }
+        }
+    }
+
+    /// Identifier and sequence number of a query, zero for echo requests
+    /// and random otherwise unless a rule sets them
+    fn write_query_id(&mut self, rest: &mut [u8], echo: bool) {
+        let default = move |rng: &mut BatchedRng| if echo { 0 } else { rng.identification() };
+        rest[0..2].copy_from_slice(&self.fields.u16(HeaderField::IcmpId, &mut self.rng, default).to_be_bytes());
+        rest[2..4].copy_from_slice(&self.fields.u16(HeaderField::IcmpSequence, &mut self.rng, default).to_be_bytes());
+    }
+
+    /// Quote the IP header and UDP header of a datagram from the target's
+    /// `target.port` to the source address
+    fn write_quote(
+        &mut self,
+        quote: &mut [u8],
+        dont_fragment: bool,
+        ttl: Option<u8>,
+        target: &PacketTarget,
+        source_ip: Ipv4Addr,
+        target_ip: Ipv4Addr,
+    ) {
+        let quoted_len = IPV4_HEADER_SIZE + UDP_HEADER_SIZE;
+        let Some(mut inner) = MutableIpv4Packet::new(&mut quote[..quoted_len]) else {
+            return;
+        };
+        inner.set_version(4);
+        inner.set_header_length(5);
+        inner.set_total_length(quoted_len as u16);
+        inner.set_identification(self.rng.identification());
+        if dont_fragment {
+            inner.set_flags(2);
+        }
+        inner.set_ttl(ttl.unwrap_or_else(|| self.rng.ttl()));
+        inner.set_next_level_protocol(IpNextHeaderProtocols::Udp);
+        inner.set_source(target_ip);
+        inner.set_destination(source_ip);
+        inner.set_checksum(pnet::packet::ipv4::checksum(&inner.to_immutable()));
+
+        if let Some(mut udp) = MutableUdpPacket::new(inner.payload_mut()) {
+            udp.set_source(target.port);
+            udp.set_destination(self.rng.port());
+            udp.set_length(UDP_HEADER_SIZE as u16);
+            // A zero UDP checksum means none was computed
+            udp.set_checksum(0);
+        }
+    }
+
     fn setup_ip_header(
         &mut self,
         ip_packet: &mut MutableIpv4Packet,
         total_len: usize,
+        source_ip: Ipv4Addr,
         target_ip: Ipv4Addr,
     ) {
         ip_packet.set_version(4);
         ip_packet.set_header_length(5);
         ip_packet.set_total_length(total_len as u16);
-        ip_packet.set_ttl(self.rng.ttl());
+        let tos = self.fields.u8(HeaderField::Ipv4Tos, &mut self.rng, |_| 0);
+        ip_packet.set_dscp(tos >> 2);
+        ip_packet.set_ecn(tos & 0x3);
+        ip_packet.set_ttl(self.fields.u8(HeaderField::Ipv4Ttl, &mut self.rng, BatchedRng::ttl));
         ip_packet.set_next_level_protocol(IpNextHeaderProtocols::Icmp);
-        ip_packet.set_source(self.source_ip);
+        ip_packet.set_source(source_ip);
         ip_packet.set_destination(target_ip);
-        ip_packet.set_identification(self.rng.identification());
+        ip_packet.set_identification(self.fields.u16(HeaderField::Ipv4Id, &mut self.rng, BatchedRng::identification));
 
-        // Occasionally set fragmentation flags
-        if self.rng.bool_with_probability(0.1) {
-            ip_packet.set_flags(2); // Don't fragment
-        }
+        // Occasionally set don't fragment
+        ip_packet.set_flags(self.fields.u8(HeaderField::Ipv4Flags, &mut self.rng, |rng| {
+            if rng.bool_with_probability(0.1) { 2 } else { 0 }
+        }));
     }
 }
 
@@ -57,8 +187,11 @@ impl PacketStrategy for IcmpStrategy {
             }
         };
 
-        let payload_size = self.rng.range(icmp::MIN_PING_SIZE, icmp::MAX_PING_SIZE + 1);
-        let total_len = IPV4_HEADER_SIZE + ICMP_HEADER_SIZE + payload_size;
+        let message = self.messages.pick(&mut self.rng);
+        let code = message.code
+            .unwrap_or_else(|| self.rng.range(0, message.kind.max_code() as usize + 1) as u8);
+        let body_len = self.body_len(message.kind);
+        let total_len = IPV4_HEADER_SIZE + ICMP_HEADER_SIZE + body_len;
         
         if buffer.len() < total_len {
             return Err(PacketError::build_failed("Packet", "Buffer too small").into());
@@ -71,19 +204,17 @@ impl PacketStrategy for IcmpStrategy {
         let mut ip_packet = MutableIpv4Packet::new(&mut buffer[..total_len])
             .ok_or_else(|| PacketError::build_failed("ICMP", "Failed to create IPv4 packet"))?;
         
-        self.setup_ip_header(&mut ip_packet, total_len, target_ip);
+        let source_ip = self.source.next_ipv4(&mut self.rng);
+        self.setup_ip_header(&mut ip_packet, total_len, source_ip, target_ip);
 
         // Build ICMP packet
         let mut icmp_packet = MutableIcmpPacket::new(ip_packet.payload_mut())
             .ok_or_else(|| PacketError::build_failed("ICMP", "Failed to create ICMP packet"))?;
         
-        icmp_packet.set_icmp_type(IcmpTypes::EchoRequest);
-        icmp_packet.set_icmp_code(pnet::packet::icmp::IcmpCode(0));
+        icmp_packet.set_icmp_type(IcmpType(message.kind.type_number()));
+        icmp_packet.set_icmp_code(IcmpCode(code));
         icmp_packet.set_checksum(0);
-
-        // Add payload
-        let payload = self.rng.payload(payload_size);
-        icmp_packet.set_payload(&payload);
+        self.write_message(icmp_packet.payload_mut(), message, code, target, source_ip, target_ip);
 
         // Calculate and set ICMP checksum
         let checksum = pnet::packet::icmp::checksum(&icmp_packet.to_immutable());
@@ -100,7 +231,8 @@ impl PacketStrategy for IcmpStrategy {
     }
 
     fn max_packet_size(&self) -> usize {
-        IPV4_ICMP_HEADER_SIZE + icmp::MAX_PING_SIZE
+        let echo = if self.messages.has_payload() { self.sizer.max() } else { 0 };
+        IPV4_ICMP_HEADER_SIZE + echo.max(IPV4_HEADER_SIZE + icmp::QUOTED_DATA_SIZE)
     }
 
     fn is_compatible_with(&self, target_ip: IpAddr) -> bool {
@@ -1,39 +1,132 @@
 //! IPv6 ICMP packet building strategy
+//!
+//! Neighbor and Router Solicitations go to the target's solicited-node
+//! group and to all-routers with hop limit 255, since receivers drop
+//! Neighbor Discovery messages that may have crossed a router (RFC 4861).
+//! MLDv2 reports follow RFC 3810 instead: hop limit 1 and a Hop-by-Hop
+//! Router Alert, without which routers ignore them.
 
 use super::PacketStrategy;
-use crate::constants::{IPV6_HEADER_SIZE, ICMP_HEADER_SIZE, IPV6_ICMP_HEADER_SIZE, icmp};
+use crate::config::{HeaderField, Icmpv6Kind};
+use crate::constants::{icmpv6, IPV6_HEADER_SIZE, ICMP_HEADER_SIZE, IPV6_ICMP_HEADER_SIZE};
 use crate::error::{PacketError, Result};
-use crate::packet::PacketTarget;
+use crate::packet::{FieldPicker, Icmpv6MessageMix, Ipv6ExtensionLayout, PacketTarget, PayloadSizer, PayloadWriter, SourcePicker, SourcePool};
 use crate::utils::rng::BatchedRng;
-use pnet::packet::icmp::{IcmpTypes, MutableIcmpPacket};
-use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::icmpv6::{checksum, Icmpv6Code, Icmpv6Type, MutableIcmpv6Packet};
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
 use pnet::packet::ipv6::MutableIpv6Packet;
 use pnet::packet::MutablePacket;
+use pnet::util::MacAddr;
 use std::net::{IpAddr, Ipv6Addr};
 
 pub struct Ipv6IcmpStrategy {
-    source_ipv6: Ipv6Addr,
+    source: SourcePicker,
+    source_mac: MacAddr,
+    messages: Icmpv6MessageMix,
+    fields: FieldPicker,
+    sizer: PayloadSizer,
+    payload: PayloadWriter,
+    extensions: Ipv6ExtensionLayout,
+    /// The chain with the Router Alert that MLD reports need
+    mld_extensions: Ipv6ExtensionLayout,
     rng: BatchedRng,
 }
 
 impl Ipv6IcmpStrategy {
-    pub fn new(rng: &mut BatchedRng) -> Self {
-        let source_ipv6 = Ipv6Addr::new(
-            0xfe80,
-            0,
-            0,
-            0,
-            rng.identification(),
-            rng.identification(),
-            rng.identification(),
-            rng.identification(),
-        );
-        
+    /// Solicitations advertise `mac`, or a random locally administered MAC
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        messages: Icmpv6MessageMix,
+        sizer: PayloadSizer,
+        payload: PayloadWriter,
+        source: &SourcePool,
+        mac: Option<MacAddr>,
+        fields: FieldPicker,
+        extensions: &Ipv6ExtensionLayout,
+        rng: &mut BatchedRng,
+    ) -> Self {
+        let source_mac = mac.unwrap_or_else(|| MacAddr::new(
+            0x02,
+            rng.byte(),
+            rng.byte(),
+            rng.byte(),
+            rng.byte(),
+            rng.byte(),
+        ));
+
         Self {
-            source_ipv6,
+            source: source.picker(rng),
+            source_mac,
+            messages,
+            fields,
+            sizer,
+            payload,
+            extensions: extensions.clone(),
+            mld_extensions: extensions.with_router_alert(),
             rng: BatchedRng::new(),
         }
     }
+
+    /// Length of the ICMPv6 message, header included
+    fn message_len(&mut self, kind: Icmpv6Kind) -> usize {
+        match kind {
+            Icmpv6Kind::EchoRequest => ICMP_HEADER_SIZE + self.sizer.sample(&mut self.rng),
+            Icmpv6Kind::NeighborSolicitation => icmpv6::NEIGHBOR_SOLICITATION_SIZE,
+            Icmpv6Kind::RouterSolicitation => icmpv6::ROUTER_SOLICITATION_SIZE,
+            Icmpv6Kind::MldReport => icmpv6::MLDV2_REPORT_SIZE,
+        }
+    }
+
+    /// Destination address and hop limit of the message
+    fn addressing(&mut self, kind: Icmpv6Kind, target_ip: Ipv6Addr) -> (Ipv6Addr, u8) {
+        match kind {
+            Icmpv6Kind::EchoRequest => (target_ip, self.rng.ttl()),
+            Icmpv6Kind::NeighborSolicitation => (solicited_node(target_ip), icmpv6::ND_HOP_LIMIT),
+            Icmpv6Kind::RouterSolicitation => (icmpv6::ALL_ROUTERS, icmpv6::ND_HOP_LIMIT),
+            Icmpv6Kind::MldReport => (icmpv6::ALL_MLDV2_ROUTERS, icmpv6::MLD_HOP_LIMIT),
+        }
+    }
+
+    /// Write the message body; `rest` starts after the checksum
+    fn write_message(&mut self, rest: &mut [u8], kind: Icmpv6Kind, target_ip: Ipv6Addr) {
+        match kind {
+            // The echo identifier and sequence precede the payload
+            Icmpv6Kind::EchoRequest => {
+                let id = self.fields.u16(HeaderField::IcmpId, &mut self.rng, |_| 0);
+                let sequence = self.fields.u16(HeaderField::IcmpSequence, &mut self.rng, |_| 0);
+                rest[0..2].copy_from_slice(&id.to_be_bytes());
+                rest[2..4].copy_from_slice(&sequence.to_be_bytes());
+                self.payload.write(&mut rest[4..], &mut self.rng);
+            }
+            Icmpv6Kind::NeighborSolicitation => {
+                rest[4..20].copy_from_slice(&target_ip.octets());
+                self.write_source_link_addr(&mut rest[20..]);
+            }
+            Icmpv6Kind::RouterSolicitation => self.write_source_link_addr(&mut rest[4..]),
+            Icmpv6Kind::MldReport => {
+                // One record joining a random site-local group, no sources
+                rest[2..4].copy_from_slice(&1u16.to_be_bytes());
+                rest[4] = icmpv6::MLD_RECORD_CHANGE_TO_EXCLUDE;
+                let group = Ipv6Addr::new(0xff05, 0, 0, 0, 0, 0, self.rng.port(), self.rng.port());
+                rest[8..24].copy_from_slice(&group.octets());
+            }
+        }
+    }
+
+    fn write_source_link_addr(&self, option: &mut [u8]) {
+        option[0] = icmpv6::OPTION_SOURCE_LINK_ADDR;
+        // Length in units of 8 bytes
+        option[1] = (icmpv6::SLLA_OPTION_SIZE / 8) as u8;
+        option[2..8].copy_from_slice(&self.source_mac.octets());
+    }
+}
+
+/// Solicited-node multicast group of an address, ff02::1:ffXX:XXXX
+fn solicited_node(ip: Ipv6Addr) -> Ipv6Addr {
+    let mut octets = [0; 16];
+    octets[..13].copy_from_slice(&icmpv6::SOLICITED_NODE_PREFIX);
+    octets[13..].copy_from_slice(&ip.octets()[13..]);
+    Ipv6Addr::from(octets)
 }
 
 impl PacketStrategy for Ipv6IcmpStrategy {
@@ -45,9 +138,16 @@ impl PacketStrategy for Ipv6IcmpStrategy {
             }
         };
 
-        let payload_size = self.rng.range(icmp::MIN_PING_SIZE, icmp::MAX_PING_SIZE + 1);
-        let total_len = IPV6_HEADER_SIZE + ICMP_HEADER_SIZE + payload_size;
-        
+        let kind = self.messages.pick(&mut self.rng);
+        let message_len = self.message_len(kind);
+        let (destination, hop_limit) = self.addressing(kind, target_ip);
+        let extensions = match kind {
+            Icmpv6Kind::MldReport => &self.mld_extensions,
+            _ => &self.extensions,
+        };
+        let extension_len = extensions.len();
+        let total_len = IPV6_HEADER_SIZE + extension_len + message_len;
+
         if buffer.len() < total_len {
             return Err(PacketError::build_failed("Packet", "Buffer too small").into());
         }
@@ -55,32 +155,37 @@ impl PacketStrategy for Ipv6IcmpStrategy {
         // Zero out the buffer area we'll use
         buffer[..total_len].fill(0);
 
+        // Routing headers name the packet's destination, which the checksum covers
+        let next_header = extensions.write(
+            &mut buffer[IPV6_HEADER_SIZE..IPV6_HEADER_SIZE + extension_len],
+            IpNextHeaderProtocols::Icmpv6.0,
+            destination,
+            &mut self.rng,
+        );
+
         // Build IPv6 header
         let mut ip_packet = MutableIpv6Packet::new(&mut buffer[..total_len])
             .ok_or_else(|| PacketError::build_failed("IPv6-ICMP", "Failed to create IPv6 packet"))?;
-        
+
         ip_packet.set_version(6);
-        ip_packet.set_traffic_class(0);
-        ip_packet.set_flow_label(self.rng.flow_label());
-        ip_packet.set_payload_length((ICMP_HEADER_SIZE + payload_size) as u16);
-        ip_packet.set_next_header(IpNextHeaderProtocols::Icmpv6);
-        ip_packet.set_hop_limit(self.rng.ttl());
-        ip_packet.set_source(self.source_ipv6);
-        ip_packet.set_destination(target_ip);
-
-        // Build ICMPv6 packet (simplified - using ICMP structure)
-        let mut icmp_packet = MutableIcmpPacket::new(ip_packet.payload_mut())
-            .ok_or_else(|| PacketError::build_failed("IPv6-ICMP", "Failed to create ICMP packet"))?;
-        
-        icmp_packet.set_icmp_type(IcmpTypes::EchoRequest);
-        icmp_packet.set_icmp_code(pnet::packet::icmp::IcmpCode(0));
-        icmp_packet.set_checksum(0);
-
-        let payload = self.rng.payload(payload_size);
-        icmp_packet.set_payload(&payload);
-
-        // ICMPv6 checksum calculation would be more complex in real implementation
-        let checksum = pnet::packet::icmp::checksum(&icmp_packet.to_immutable());
+        ip_packet.set_traffic_class(self.fields.u8(HeaderField::Ipv6TrafficClass, &mut self.rng, |_| 0));
+        ip_packet.set_flow_label(self.fields.u32(HeaderField::Ipv6FlowLabel, &mut self.rng, BatchedRng::flow_label));
+        ip_packet.set_payload_length((extension_len + message_len) as u16);
+        ip_packet.set_hop_limit(self.fields.u8(HeaderField::Ipv6HopLimit, &mut self.rng, |_| hop_limit));
+        let source_ip = self.source.next_ipv6(&mut self.rng);
+        ip_packet.set_source(source_ip);
+        ip_packet.set_destination(destination);
+        ip_packet.set_next_header(IpNextHeaderProtocol(next_header));
+
+        let mut icmp_packet = MutableIcmpv6Packet::new(&mut ip_packet.payload_mut()[extension_len..])
+            .ok_or_else(|| PacketError::build_failed("IPv6-ICMP", "Failed to create ICMPv6 packet"))?;
+
+        icmp_packet.set_icmpv6_type(Icmpv6Type(kind.type_number()));
+        icmp_packet.set_icmpv6_code(Icmpv6Code(0));
+        self.write_message(icmp_packet.payload_mut(), kind, target_ip);
+
+        // The checksum covers a pseudo-header with both addresses
+        let checksum = checksum(&icmp_packet.to_immutable(), &source_ip, &destination);
         icmp_packet.set_checksum(checksum);
 
         Ok(total_len)
@@ -91,10 +196,13 @@ impl PacketStrategy for Ipv6IcmpStrategy {
     }
 
     fn max_packet_size(&self) -> usize {
-        IPV6_ICMP_HEADER_SIZE + icmp::MAX_PING_SIZE
+        let solicitation = IPV6_HEADER_SIZE + icmpv6::NEIGHBOR_SOLICITATION_SIZE;
+        let echo = if self.messages.has_payload() { IPV6_ICMP_HEADER_SIZE + self.sizer.max() } else { 0 };
+        let mld = IPV6_HEADER_SIZE + icmpv6::MLDV2_REPORT_SIZE + self.mld_extensions.len();
+        (echo.max(solicitation) + self.extensions.len()).max(mld)
     }
 
     fn is_compatible_with(&self, target_ip: IpAddr) -> bool {
         matches!(target_ip, IpAddr::V6(_))
     }
-}
\ No newline at end of file
+}
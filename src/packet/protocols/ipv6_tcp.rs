@@ -1,36 +1,50 @@
 //! IPv6 TCP packet building strategy
 
 use super::PacketStrategy;
-use crate::constants::{IPV6_TCP_HEADER_SIZE, TCP_HEADER_SIZE};
+use crate::config::{HeaderField, TcpFlagSet};
+use crate::constants::{IPV6_HEADER_SIZE, IPV6_TCP_HEADER_SIZE, TCP_HEADER_SIZE};
 use crate::error::{PacketError, Result};
-use crate::packet::PacketTarget;
+use crate::packet::{FieldPicker, Ipv6ExtensionLayout, PacketTarget, PayloadSizer, PayloadWriter, PortPicker, SourcePicker, SourcePool, TcpOptionLayout, TcpOptionWriter};
 use crate::utils::rng::BatchedRng;
-use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
 use pnet::packet::ipv6::MutableIpv6Packet;
-use pnet::packet::tcp::{MutableTcpPacket, TcpFlags};
+use pnet::packet::tcp::MutableTcpPacket;
 use pnet::packet::MutablePacket;
-use std::net::{IpAddr, Ipv6Addr};
+use std::net::IpAddr;
 
 pub struct Ipv6TcpStrategy {
-    source_ipv6: Ipv6Addr,
+    source: SourcePicker,
+    ports: PortPicker,
+    fields: FieldPicker,
+    options: TcpOptionWriter,
+    tcp_flags: TcpFlagSet,
+    extensions: Ipv6ExtensionLayout,
+    /// Payload sizes and contents of data-bearing segments
+    data: Option<(PayloadSizer, PayloadWriter)>,
     rng: BatchedRng,
 }
 
 impl Ipv6TcpStrategy {
-    pub fn new(rng: &mut BatchedRng) -> Self {
-        let source_ipv6 = Ipv6Addr::new(
-            0xfe80,
-            0,
-            0,
-            0,
-            rng.identification(),
-            rng.identification(),
-            rng.identification(),
-            rng.identification(),
-        );
-        
+    /// Segments with the given control bits, header-only without `data`
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        tcp_flags: TcpFlagSet,
+        data: Option<(PayloadSizer, PayloadWriter)>,
+        source: &SourcePool,
+        ports: PortPicker,
+        fields: FieldPicker,
+        options: &TcpOptionLayout,
+        extensions: &Ipv6ExtensionLayout,
+        rng: &mut BatchedRng,
+    ) -> Self {
         Self {
-            source_ipv6,
+            source: source.picker(rng),
+            ports,
+            fields,
+            options: TcpOptionWriter::new(options, rng),
+            tcp_flags,
+            extensions: extensions.clone(),
+            data,
             rng: BatchedRng::new(),
         }
     }
@@ -45,45 +59,73 @@ impl PacketStrategy for Ipv6TcpStrategy {
             }
         };
 
-        if buffer.len() < IPV6_TCP_HEADER_SIZE {
+        let syn = self.tcp_flags.contains(TcpFlagSet::SYN);
+        let options_len = self.options.len(syn);
+        let extension_len = self.extensions.len();
+        let header_len = IPV6_TCP_HEADER_SIZE + extension_len + options_len;
+        let payload_size = match self.data {
+            Some((ref sizer, _)) => sizer.sample(&mut self.rng),
+            None => 0,
+        };
+        let total_len = header_len + payload_size;
+
+        if buffer.len() < total_len {
             return Err(PacketError::build_failed("Packet", "Buffer too small").into());
         }
 
-        // Zero out the buffer area we'll use
-        buffer[..IPV6_TCP_HEADER_SIZE].fill(0);
+        // Zero out the header area; the payload is written in full
+        buffer[..header_len].fill(0);
+        let next_header = self.extensions.write(
+            &mut buffer[IPV6_HEADER_SIZE..IPV6_HEADER_SIZE + extension_len],
+            IpNextHeaderProtocols::Tcp.0,
+            target_ip,
+            &mut self.rng,
+        );
 
         // Build IPv6 header
-        let mut ip_packet = MutableIpv6Packet::new(&mut buffer[..IPV6_TCP_HEADER_SIZE])
+        let mut ip_packet = MutableIpv6Packet::new(&mut buffer[..total_len])
             .ok_or_else(|| PacketError::build_failed("IPv6-TCP", "Failed to create IPv6 packet"))?;
         
         ip_packet.set_version(6);
-        ip_packet.set_traffic_class(0);
-        ip_packet.set_flow_label(self.rng.flow_label());
-        ip_packet.set_payload_length(TCP_HEADER_SIZE as u16);
-        ip_packet.set_next_header(IpNextHeaderProtocols::Tcp);
-        ip_packet.set_hop_limit(self.rng.ttl());
-        ip_packet.set_source(self.source_ipv6);
+        ip_packet.set_traffic_class(self.fields.u8(HeaderField::Ipv6TrafficClass, &mut self.rng, |_| 0));
+        ip_packet.set_flow_label(self.fields.u32(HeaderField::Ipv6FlowLabel, &mut self.rng, BatchedRng::flow_label));
+        ip_packet.set_payload_length((extension_len + TCP_HEADER_SIZE + options_len + payload_size) as u16);
+        ip_packet.set_next_header(IpNextHeaderProtocol(next_header));
+        ip_packet.set_hop_limit(self.fields.u8(HeaderField::Ipv6HopLimit, &mut self.rng, BatchedRng::ttl));
+        let source_ip = self.source.next_ipv6(&mut self.rng);
+        ip_packet.set_source(source_ip);
         ip_packet.set_destination(target_ip);
 
         // Build TCP packet
-        let mut tcp_packet = MutableTcpPacket::new(ip_packet.payload_mut())
+        let mut tcp_packet = MutableTcpPacket::new(&mut ip_packet.payload_mut()[extension_len..])
             .ok_or_else(|| PacketError::build_failed("IPv6-TCP", "Failed to create TCP packet"))?;
         
-        tcp_packet.set_source(self.rng.port());
+        let ack = self.tcp_flags.contains(TcpFlagSet::ACK);
+        let urg = self.tcp_flags.contains(TcpFlagSet::URG);
+        tcp_packet.set_source(self.fields.u16(HeaderField::TcpSourcePort, &mut self.rng, |rng| self.ports.next(rng)));
         tcp_packet.set_destination(target.port);
-        tcp_packet.set_sequence(self.rng.sequence());
-        tcp_packet.set_acknowledgement(self.rng.sequence());
-        tcp_packet.set_data_offset(5);
-        tcp_packet.set_flags(TcpFlags::SYN); // Default to SYN for IPv6
-        tcp_packet.set_window(self.rng.window_size());
-        tcp_packet.set_urgent_ptr(0);
+        tcp_packet.set_sequence(self.fields.u32(HeaderField::TcpSequence, &mut self.rng, BatchedRng::sequence));
+        tcp_packet.set_acknowledgement(self.fields.u32(HeaderField::TcpAck, &mut self.rng, |rng| {
+            if ack { rng.sequence() } else { 0 }
+        }));
+        tcp_packet.set_data_offset(((TCP_HEADER_SIZE + options_len) / 4) as u8);
+        self.options.write(&mut tcp_packet.get_options_raw_mut()[..options_len], syn, &mut self.rng);
+        tcp_packet.set_flags(self.tcp_flags.bits());
+        tcp_packet.set_window(self.fields.u16(HeaderField::TcpWindow, &mut self.rng, BatchedRng::window_size));
+        // The urgent pointer is only meaningful with URG set
+        tcp_packet.set_urgent_ptr(self.fields.u16(HeaderField::TcpUrgent, &mut self.rng, |rng| {
+            if urg { rng.port() } else { 0 }
+        }));
+        if let Some((_, ref mut payload)) = self.data {
+            payload.write(&mut tcp_packet.payload_mut()[..payload_size], &mut self.rng);
+        }
         tcp_packet.set_checksum(pnet::packet::tcp::ipv6_checksum(
             &tcp_packet.to_immutable(),
-            &self.source_ipv6,
+            &source_ip,
             &target_ip,
         ));
 
-        Ok(IPV6_TCP_HEADER_SIZE)
+        Ok(total_len)
     }
 
     fn protocol_name(&self) -> &'static str {
@@ -91,7 +133,8 @@ impl PacketStrategy for Ipv6TcpStrategy {
     }
 
     fn max_packet_size(&self) -> usize {
-        IPV6_TCP_HEADER_SIZE
+        let payload = self.data.as_ref().map_or(0, |(sizer, _)| sizer.max());
+        IPV6_TCP_HEADER_SIZE + self.extensions.len() + self.options.max_len() + payload
     }
 
     fn is_compatible_with(&self, target_ip: IpAddr) -> bool {
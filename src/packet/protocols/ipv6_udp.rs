@@ -1,82 +1,49 @@
 //! IPv6 UDP packet building strategy
 
 use super::PacketStrategy;
-use crate::packet::PacketSizeRange;
+use crate::config::HeaderField;
+use crate::packet::{FieldPicker, Ipv6ExtensionLayout, PayloadSizer, PayloadWriter, PortPicker, SourcePicker, SourcePool};
 use crate::constants::{IPV6_HEADER_SIZE, UDP_HEADER_SIZE, IPV6_UDP_HEADER_SIZE};
 use crate::error::{PacketError, Result};
 use crate::packet::PacketTarget;
 use crate::utils::rng::BatchedRng;
-use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ip::{IpNextHeaderProtocol, IpNextHeaderProtocols};
 use pnet::packet::ipv6::MutableIpv6Packet;
 use pnet::packet::udp::MutableUdpPacket;
 use pnet::packet::MutablePacket;
-use std::net::{IpAddr, Ipv6Addr};
+use std::net::IpAddr;
 
 pub struct Ipv6UdpStrategy {
-    source_ipv6: Ipv6Addr,
-    packet_size_range: PacketSizeRange,
+    source: SourcePicker,
+    ports: PortPicker,
+    fields: FieldPicker,
+    sizer: PayloadSizer,
+    payload: PayloadWriter,
+    extensions: Ipv6ExtensionLayout,
     rng: BatchedRng,
 }
 
 impl Ipv6UdpStrategy {
-    pub fn new(packet_size_range: PacketSizeRange, rng: &mut BatchedRng) -> Self {
-        let source_ipv6 = Ipv6Addr::new(
-            0xfe80,
-            0,
-            0,
-            0,
-            rng.identification(),
-            rng.identification(),
-            rng.identification(),
-            rng.identification(),
-        );
-        
+    pub fn new(
+        sizer: PayloadSizer,
+        payload: PayloadWriter,
+        source: &SourcePool,
+        ports: PortPicker,
+        fields: FieldPicker,
+        extensions: &Ipv6ExtensionLayout,
+        rng: &mut BatchedRng,
+    ) -> Self {
         Self {
-            source_ipv6,
-            packet_size_range,
+            source: source.picker(rng),
+            ports,
+            fields,
+            sizer,
+            payload,
+            extensions: extensions.clone(),
             rng: BatchedRng::new(),
         }
     }
 
-    fn random_payload_size(&mut self) -> usize {
-        // More realistic payload size distribution
-        let min_size = self.packet_size_range.min;
-        let max_size = self.packet_size_range.max;
-        
-        // Ensure we don't create empty ranges
-        match self.rng.range(0, 100) {
-            0..=40 => {
-                // Small packets: use min_size to min(200, max_size)
-                let upper = std::cmp::min(200, max_size);
-                if min_size <= upper {
-                    self.rng.range(min_size, upper + 1)
-                } else {
-                    min_size
-                }
-            },
-            41..=80 => {
-                // Medium packets: use 200 to min(800, max_size)
-                let lower = std::cmp::max(200, min_size);
-                let upper = std::cmp::min(800, max_size);
-                if lower <= upper {
-                    self.rng.range(lower, upper + 1)
-                } else {
-                    // Fallback to valid range
-                    self.rng.range(min_size, max_size + 1)
-                }
-            },
-            _ => {
-                // Large packets: use max(800, min_size) to max_size
-                let lower = std::cmp::max(800, min_size);
-                if lower <= max_size {
-                    self.rng.range(lower, max_size + 1)
-                } else {
-                    // Fallback to valid range
-                    self.rng.range(min_size, max_size + 1)
-                }
-            }
-        }
-    }
 }
 
 impl PacketStrategy for Ipv6UdpStrategy {
@@ -88,8 +55,9 @@ impl PacketStrategy for Ipv6UdpStrategy {
             }
         };
 
-        let payload_size = self.random_payload_size();
-        let total_len = IPV6_HEADER_SIZE + UDP_HEADER_SIZE + payload_size;
+        let payload_size = self.sizer.sample(&mut self.rng);
+        let extension_len = self.extensions.len();
+        let total_len = IPV6_HEADER_SIZE + extension_len + UDP_HEADER_SIZE + payload_size;
         
         if buffer.len() < total_len {
             return Err(PacketError::build_failed("Packet", "Buffer too small").into());
@@ -97,33 +65,39 @@ impl PacketStrategy for Ipv6UdpStrategy {
 
         // Zero out the buffer area we'll use
         buffer[..total_len].fill(0);
+        let next_header = self.extensions.write(
+            &mut buffer[IPV6_HEADER_SIZE..IPV6_HEADER_SIZE + extension_len],
+            IpNextHeaderProtocols::Udp.0,
+            target_ip,
+            &mut self.rng,
+        );
 
         // Build IPv6 header
         let mut ip_packet = MutableIpv6Packet::new(&mut buffer[..total_len])
             .ok_or_else(|| PacketError::build_failed("IPv6-UDP", "Failed to create IPv6 packet"))?;
         
         ip_packet.set_version(6);
-        ip_packet.set_traffic_class(0);
-        ip_packet.set_flow_label(self.rng.flow_label());
-        ip_packet.set_payload_length((UDP_HEADER_SIZE + payload_size) as u16);
-        ip_packet.set_next_header(IpNextHeaderProtocols::Udp);
-        ip_packet.set_hop_limit(self.rng.ttl());
-        ip_packet.set_source(self.source_ipv6);
+        ip_packet.set_traffic_class(self.fields.u8(HeaderField::Ipv6TrafficClass, &mut self.rng, |_| 0));
+        ip_packet.set_flow_label(self.fields.u32(HeaderField::Ipv6FlowLabel, &mut self.rng, BatchedRng::flow_label));
+        ip_packet.set_payload_length((extension_len + UDP_HEADER_SIZE + payload_size) as u16);
+        ip_packet.set_next_header(IpNextHeaderProtocol(next_header));
+        ip_packet.set_hop_limit(self.fields.u8(HeaderField::Ipv6HopLimit, &mut self.rng, BatchedRng::ttl));
+        let source_ip = self.source.next_ipv6(&mut self.rng);
+        ip_packet.set_source(source_ip);
         ip_packet.set_destination(target_ip);
 
         // Build UDP header + payload
-        let mut udp_packet = MutableUdpPacket::new(ip_packet.payload_mut())
+        let mut udp_packet = MutableUdpPacket::new(&mut ip_packet.payload_mut()[extension_len..])
             .ok_or_else(|| PacketError::build_failed("IPv6-UDP", "Failed to create UDP packet"))?;
         
-        udp_packet.set_source(self.rng.port());
+        udp_packet.set_source(self.fields.u16(HeaderField::UdpSourcePort, &mut self.rng, |rng| self.ports.next(rng)));
         udp_packet.set_destination(target.port);
         udp_packet.set_length((UDP_HEADER_SIZE + payload_size) as u16);
 
-        let payload = self.rng.payload(payload_size);
-        udp_packet.set_payload(&payload);
+        self.payload.write(&mut udp_packet.payload_mut()[..payload_size], &mut self.rng);
         udp_packet.set_checksum(pnet::packet::udp::ipv6_checksum(
             &udp_packet.to_immutable(),
-            &self.source_ipv6,
+            &source_ip,
             &target_ip,
         ));
 
@@ -135,7 +109,7 @@ impl PacketStrategy for Ipv6UdpStrategy {
     }
 
     fn max_packet_size(&self) -> usize {
-        IPV6_UDP_HEADER_SIZE + self.packet_size_range.max
+        IPV6_UDP_HEADER_SIZE + self.extensions.len() + self.sizer.max()
     }
 
     fn is_compatible_with(&self, target_ip: IpAddr) -> bool {
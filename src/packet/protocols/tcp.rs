@@ -1,59 +1,46 @@
 //! TCP packet building strategy
 
 use super::PacketStrategy;
-use crate::constants::IPV4_TCP_HEADER_SIZE;
+use crate::config::{HeaderField, TcpFlagSet};
+use crate::constants::{IPV4_TCP_HEADER_SIZE, TCP_HEADER_SIZE};
 use crate::error::{PacketError, Result};
-use crate::packet::PacketTarget;
+use crate::packet::{FieldPicker, PacketTarget, PayloadSizer, PayloadWriter, PortPicker, SourcePicker, SourcePool, TcpOptionLayout, TcpOptionWriter};
 use crate::utils::rng::BatchedRng;
 use pnet::packet::ip::IpNextHeaderProtocols;
 use pnet::packet::ipv4::MutableIpv4Packet;
-use pnet::packet::tcp::{MutableTcpPacket, TcpFlags};
+use pnet::packet::tcp::MutableTcpPacket;
 use pnet::packet::MutablePacket;
 use std::net::{IpAddr, Ipv4Addr};
 
 pub struct TcpStrategy {
-    source_ip: Ipv4Addr,
-    tcp_flags: u8,
+    source: SourcePicker,
+    ports: PortPicker,
+    fields: FieldPicker,
+    options: TcpOptionWriter,
+    tcp_flags: TcpFlagSet,
+    /// Payload sizes and contents of data-bearing segments
+    data: Option<(PayloadSizer, PayloadWriter)>,
     rng: BatchedRng,
 }
 
 impl TcpStrategy {
-    pub fn new_syn(rng: &mut BatchedRng) -> Self {
-        let source_ip = Ipv4Addr::new(192, 168, 1, rng.range(2, 254) as u8);
-        
-        Self {
-            source_ip,
-            tcp_flags: TcpFlags::SYN,
-            rng: BatchedRng::new(),
-        }
-    }
-
-    pub fn new_ack(rng: &mut BatchedRng) -> Self {
-        let source_ip = Ipv4Addr::new(192, 168, 1, rng.range(2, 254) as u8);
-        
+    /// Segments with the given control bits, header-only without `data`
+    pub fn new(
+        tcp_flags: TcpFlagSet,
+        data: Option<(PayloadSizer, PayloadWriter)>,
+        source: &SourcePool,
+        ports: PortPicker,
+        fields: FieldPicker,
+        options: &TcpOptionLayout,
+        rng: &mut BatchedRng,
+    ) -> Self {
         Self {
-            source_ip,
-            tcp_flags: TcpFlags::ACK,
-            rng: BatchedRng::new(),
-        }
-    }
-
-    pub fn new_fin(rng: &mut BatchedRng) -> Self {
-        let source_ip = Ipv4Addr::new(192, 168, 1, rng.range(2, 254) as u8);
-        
-        Self {
-            source_ip,
-            tcp_flags: TcpFlags::FIN,
-            rng: BatchedRng::new(),
-        }
-    }
-
-    pub fn new_rst(rng: &mut BatchedRng) -> Self {
-        let source_ip = Ipv4Addr::new(192, 168, 1, rng.range(2, 254) as u8);
-        
-        Self {
-            source_ip,
-            tcp_flags: TcpFlags::RST,
+            source: source.picker(rng),
+            ports,
+            fields,
+            options: TcpOptionWriter::new(options, rng),
+            tcp_flags,
+            data,
             rng: BatchedRng::new(),
         }
     }
@@ -62,21 +49,25 @@ impl TcpStrategy {
         &mut self,
         ip_packet: &mut MutableIpv4Packet,
         total_len: usize,
+        source_ip: Ipv4Addr,
         target_ip: Ipv4Addr,
     ) {
         ip_packet.set_version(4);
         ip_packet.set_header_length(5);
         ip_packet.set_total_length(total_len as u16);
-        ip_packet.set_ttl(self.rng.ttl());
+        let tos = self.fields.u8(HeaderField::Ipv4Tos, &mut self.rng, |_| 0);
+        ip_packet.set_dscp(tos >> 2);
+        ip_packet.set_ecn(tos & 0x3);
+        ip_packet.set_ttl(self.fields.u8(HeaderField::Ipv4Ttl, &mut self.rng, BatchedRng::ttl));
         ip_packet.set_next_level_protocol(IpNextHeaderProtocols::Tcp);
-        ip_packet.set_source(self.source_ip);
+        ip_packet.set_source(source_ip);
         ip_packet.set_destination(target_ip);
-        ip_packet.set_identification(self.rng.identification());
+        ip_packet.set_identification(self.fields.u16(HeaderField::Ipv4Id, &mut self.rng, BatchedRng::identification));
 
-        // Occasionally set fragmentation flags
-        if self.rng.bool_with_probability(0.1) {
-            ip_packet.set_flags(2); // Don't fragment
-        }
+        // Occasionally set don't fragment
+        ip_packet.set_flags(self.fields.u8(HeaderField::Ipv4Flags, &mut self.rng, |rng| {
+            if rng.bool_with_probability(0.1) { 2 } else { 0 }
+        }));
     }
 }
 
@@ -89,45 +80,62 @@ impl PacketStrategy for TcpStrategy {
             }
         };
 
-        if buffer.len() < IPV4_TCP_HEADER_SIZE {
+        let syn = self.tcp_flags.contains(TcpFlagSet::SYN);
+        let options_len = self.options.len(syn);
+        let header_len = IPV4_TCP_HEADER_SIZE + options_len;
+        let payload_size = match self.data {
+            Some((ref sizer, _)) => sizer.sample(&mut self.rng),
+            None => 0,
+        };
+        let total_len = header_len + payload_size;
+
+        if buffer.len() < total_len {
             return Err(PacketError::build_failed("Packet", "Buffer too small").into());
         }
 
-        // Zero out the buffer area we'll use
-        buffer[..IPV4_TCP_HEADER_SIZE].fill(0);
+        // Zero out the header area; the payload is written in full
+        buffer[..header_len].fill(0);
 
         // Build IP header
-        let mut ip_packet = MutableIpv4Packet::new(&mut buffer[..IPV4_TCP_HEADER_SIZE])
+        let mut ip_packet = MutableIpv4Packet::new(&mut buffer[..total_len])
             .ok_or_else(|| PacketError::build_failed("TCP", "Failed to create IPv4 packet"))?;
         
-        self.setup_ip_header(&mut ip_packet, IPV4_TCP_HEADER_SIZE, target_ip);
+        let source_ip = self.source.next_ipv4(&mut self.rng);
+        self.setup_ip_header(&mut ip_packet, total_len, source_ip, target_ip);
 
         // Build TCP packet
         let mut tcp_packet = MutableTcpPacket::new(ip_packet.payload_mut())
             .ok_or_else(|| PacketError::build_failed("TCP", "Failed to create TCP packet"))?;
         
-        tcp_packet.set_source(self.rng.port());
+        let ack = self.tcp_flags.contains(TcpFlagSet::ACK);
+        let urg = self.tcp_flags.contains(TcpFlagSet::URG);
+        tcp_packet.set_source(self.fields.u16(HeaderField::TcpSourcePort, &mut self.rng, |rng| self.ports.next(rng)));
         tcp_packet.set_destination(target.port);
-        tcp_packet.set_sequence(self.rng.sequence());
-        tcp_packet.set_acknowledgement(if self.tcp_flags == TcpFlags::ACK {
-            self.rng.sequence()
-        } else {
-            0
-        });
-        tcp_packet.set_data_offset(5);
-        tcp_packet.set_flags(self.tcp_flags);
-        tcp_packet.set_window(self.rng.window_size());
-        tcp_packet.set_urgent_ptr(0);
+        tcp_packet.set_sequence(self.fields.u32(HeaderField::TcpSequence, &mut self.rng, BatchedRng::sequence));
+        tcp_packet.set_acknowledgement(self.fields.u32(HeaderField::TcpAck, &mut self.rng, |rng| {
+            if ack { rng.sequence() } else { 0 }
+        }));
+        tcp_packet.set_data_offset(((TCP_HEADER_SIZE + options_len) / 4) as u8);
+        self.options.write(&mut tcp_packet.get_options_raw_mut()[..options_len], syn, &mut self.rng);
+        tcp_packet.set_flags(self.tcp_flags.bits());
+        tcp_packet.set_window(self.fields.u16(HeaderField::TcpWindow, &mut self.rng, BatchedRng::window_size));
+        // The urgent pointer is only meaningful with URG set
+        tcp_packet.set_urgent_ptr(self.fields.u16(HeaderField::TcpUrgent, &mut self.rng, |rng| {
+            if urg { rng.port() } else { 0 }
+        }));
+        if let Some((_, ref mut payload)) = self.data {
+            payload.write(&mut tcp_packet.payload_mut()[..payload_size], &mut self.rng);
+        }
         tcp_packet.set_checksum(pnet::packet::tcp::ipv4_checksum(
             &tcp_packet.to_immutable(),
-            &self.source_ip,
+            &source_ip,
             &target_ip,
         ));
 
         // Set IP checksum last
         ip_packet.set_checksum(pnet::packet::ipv4::checksum(&ip_packet.to_immutable()));
         
-        Ok(IPV4_TCP_HEADER_SIZE)
+        Ok(total_len)
     }
 
     fn protocol_name(&self) -> &'static str {
@@ -135,7 +143,8 @@ impl PacketStrategy for TcpStrategy {
     }
 
     fn max_packet_size(&self) -> usize {
-        IPV4_TCP_HEADER_SIZE
+        let payload = self.data.as_ref().map_or(0, |(sizer, _)| sizer.max());
+        IPV4_TCP_HEADER_SIZE + self.options.max_len() + payload
     }
 
     fn is_compatible_with(&self, target_ip: IpAddr) -> bool {
@@ -1,7 +1,8 @@
 //! UDP packet building strategy
 
 use super::PacketStrategy;
-use crate::packet::PacketSizeRange;
+use crate::config::HeaderField;
+use crate::packet::{FieldPicker, PayloadSizer, PayloadWriter, PortPicker, SourcePicker, SourcePool};
 use crate::constants::{IPV4_HEADER_SIZE, UDP_HEADER_SIZE, IPV4_UDP_HEADER_SIZE};
 use crate::error::{PacketError, Result};
 use crate::packet::PacketTarget;
@@ -13,83 +14,50 @@ use pnet::packet::MutablePacket;
 use std::net::{IpAddr, Ipv4Addr};
 
 pub struct UdpStrategy {
-    source_ip: Ipv4Addr,
-    packet_size_range: PacketSizeRange,
+    source: SourcePicker,
+    ports: PortPicker,
+    fields: FieldPicker,
+    sizer: PayloadSizer,
+    payload: PayloadWriter,
     rng: BatchedRng,
 }
 
 impl UdpStrategy {
-    pub fn new(packet_size_range: PacketSizeRange, rng: &mut BatchedRng) -> Self {
-        let source_ip = Ipv4Addr::new(192, 168, 1, rng.range(2, 254) as u8);
-        
+    pub fn new(sizer: PayloadSizer, payload: PayloadWriter, source: &SourcePool, ports: PortPicker, fields: FieldPicker, rng: &mut BatchedRng) -> Self {
         Self {
-            source_ip,
-            packet_size_range,
+            source: source.picker(rng),
+            ports,
+            fields,
+            sizer,
+            payload,
             rng: BatchedRng::new(),
         }
     }
 
-    #[inline]
-    fn random_payload_size(&mut self) -> usize {
-        // More realistic payload size distribution
-        let min_size = self.packet_size_range.min;
-        let max_size = self.packet_size_range.max;
-        
-        // Ensure we don't create empty ranges
-        match self.rng.range(0, 100) {
-            0..=40 => {
-                // Small packets: use min_size to min(200, max_size)
-                let upper = std::cmp::min(200, max_size);
-                if min_size <= upper {
-                    self.rng.range(min_size, upper + 1)
-                } else {
-                    min_size
-                }
-            },
-            41..=80 => {
-                // Medium packets: use 200 to min(800, max_size)
-                let lower = std::cmp::max(200, min_size);
-                let upper = std::cmp::min(800, max_size);
-                if lower <= upper {
-                    self.rng.range(lower, upper + 1)
-                } else {
-                    // Fallback to valid range
-                    self.rng.range(min_size, max_size + 1)
-                }
-            },
-            _ => {
-                // Large packets: use max(800, min_size) to max_size
-                let lower = std::cmp::max(800, min_size);
-                if lower <= max_size {
-                    self.rng.range(lower, max_size + 1)
-                } else {
-                    // Fallback to valid range
-                    self.rng.range(min_size, max_size + 1)
-                }
-            }
-        }
-    }
-
     #[inline]
     fn setup_ip_header(
         &mut self,
         ip_packet: &mut MutableIpv4Packet,
         total_len: usize,
+        source_ip: Ipv4Addr,
         target_ip: Ipv4Addr,
     ) {
         ip_packet.set_version(4);
         ip_packet.set_header_length(5);
         ip_packet.set_total_length(total_len as u16);
-        ip_packet.set_ttl(self.rng.ttl());
+        let tos = self.fields.u8(HeaderField::Ipv4Tos, &mut self.rng, |_| 0);
+        ip_packet.set_dscp(tos >> 2);
+        ip_packet.set_ecn(tos & 0x3);
+        ip_packet.set_ttl(self.fields.u8(HeaderField::Ipv4Ttl, &mut self.rng, BatchedRng::ttl));
         ip_packet.set_next_level_protocol(IpNextHeaderProtocols::Udp);
-        ip_packet.set_source(self.source_ip);
+        ip_packet.set_source(source_ip);
         ip_packet.set_destination(target_ip);
-        ip_packet.set_identification(self.rng.identification());
+        ip_packet.set_identification(self.fields.u16(HeaderField::Ipv4Id, &mut self.rng, BatchedRng::identification));
 
-        // Occasionally set fragmentation flags
-        if self.rng.bool_with_probability(0.1) {
-            ip_packet.set_flags(2); // Don't fragment
-        }
+        // Occasionally set don't fragment
+        ip_packet.set_flags(self.fields.u8(HeaderField::Ipv4Flags, &mut self.rng, |rng| {
+            if rng.bool_with_probability(0.1) { 2 } else { 0 }
+        }));
     }
 }
 
@@ -106,7 +74,7 @@ impl PacketStrategy for UdpStrategy {
             }
         };
 
-        let payload_size = self.random_payload_size();
+        let payload_size = self.sizer.sample(&mut self.rng);
         let total_len = IPV4_HEADER_SIZE + UDP_HEADER_SIZE + payload_size;
         
         if buffer.len() < total_len {
@@ -126,7 +94,8 @@ impl PacketStrategy for UdpStrategy {
                 "Failed to create IPv4 packet"
             ))?;
         
-        self.setup_ip_header(&mut ip_packet, total_len, target_ip);
+        let source_ip = self.source.next_ipv4(&mut self.rng);
+        self.setup_ip_header(&mut ip_packet, total_len, source_ip, target_ip);
 
         // Build UDP header + payload
         let mut udp_packet = MutableUdpPacket::new(ip_packet.payload_mut())
@@ -135,21 +104,15 @@ impl PacketStrategy for UdpStrategy {
                 "Failed to create UDP packet"
             ))?;
         
-        udp_packet.set_source(self.rng.port());
+        udp_packet.set_source(self.fields.u16(HeaderField::UdpSourcePort, &mut self.rng, |rng| self.ports.next(rng)));
         udp_packet.set_destination(target.port);
         udp_packet.set_length((UDP_HEADER_SIZE + payload_size) as u16);
 
         // Write payload directly to buffer for true zero-copy
-        let payload_slice = udp_packet.payload_mut();
-        if payload_slice.len() >= payload_size {
-            // Fill payload directly in the buffer
-            for byte in payload_slice.iter_mut().take(payload_size) {
-                *byte = self.rng.byte();
-            }
-        }
+        self.payload.write(&mut udp_packet.payload_mut()[..payload_size], &mut self.rng);
         udp_packet.set_checksum(pnet::packet::udp::ipv4_checksum(
             &udp_packet.to_immutable(),
-            &self.source_ip,
+            &source_ip,
             &target_ip,
         ));
 
@@ -166,7 +129,7 @@ impl PacketStrategy for UdpStrategy {
 
     #[inline(always)]
     fn max_packet_size(&self) -> usize {
-        IPV4_UDP_HEADER_SIZE + self.packet_size_range.max
+        IPV4_UDP_HEADER_SIZE + self.sizer.max()
     }
 
     #[inline(always)]
@@ -0,0 +1,148 @@
+//! Payload size sampling
+
+use crate::config::{LoadConfig, SizeDistribution, WeightedSize};
+use crate::constants::{icmp, ETHERNET_FRAME_OVERHEAD};
+use crate::packet::PacketSizeRange;
+use crate::utils::rng::BatchedRng;
+
+/// Draws payload sizes from a configured distribution
+#[derive(Debug, Clone)]
+pub enum PayloadSizer {
+    Fixed(usize),
+    Range(PacketSizeRange),
+    Weighted {
+        sizes: Vec<usize>,
+        /// Running weight totals, ending at the total weight
+        cumulative: Vec<f64>,
+    },
+}
+
+impl PayloadSizer {
+    /// Sampler for a protocol whose IP and transport headers take
+    /// `header_len` bytes; only frame-sized (IMIX) distributions depend on it
+    pub fn new(distribution: &SizeDistribution, header_len: usize) -> Self {
+        match *distribution {
+            SizeDistribution::Fixed { size } => PayloadSizer::Fixed(size),
+            SizeDistribution::Range { min, max } => PayloadSizer::Range(PacketSizeRange::new(min, max.max(min))),
+            SizeDistribution::Weighted { ref sizes } => Self::weighted(sizes, 0),
+            SizeDistribution::Imix { .. } => {
+                let frames = distribution.frame_sizes().map(|frames| frames.0).unwrap_or_default();
+                // Frames too small for the headers carry an empty payload
+                Self::weighted(&frames, ETHERNET_FRAME_OVERHEAD + header_len)
+            }
+        }
+    }
+
+    fn weighted(sizes: &[WeightedSize], overhead: usize) -> Self {
+        let mut total = 0.0;
+        let cumulative = sizes
+            .iter()
+            .map(|entry| {
+                total += entry.weight.max(0.0);
+                total
+            })
+            .collect();
+        PayloadSizer::Weighted {
+            sizes: sizes.iter().map(|entry| entry.size.saturating_sub(overhead)).collect(),
+            cumulative,
+        }
+    }
+
+    /// Draw the next payload size
+    #[inline]
+    pub fn sample(&self, rng: &mut BatchedRng) -> usize {
+        match *self {
+            PayloadSizer::Fixed(size) => size,
+            PayloadSizer::Range(range) => Self::sample_range(range, rng),
+            PayloadSizer::Weighted { ref sizes, ref cumulative } => {
+                let total = cumulative.last().copied().unwrap_or(0.0);
+                let pick = rng.unit() * total;
+                let index = cumulative.partition_point(|&bound| bound <= pick);
+                sizes.get(index).or(sizes.last()).copied().unwrap_or(0)
+            }
+        }
+    }
+
+    /// Largest size `sample` can return
+    pub fn max(&self) -> usize {
+        match *self {
+            PayloadSizer::Fixed(size) => size,
+            PayloadSizer::Range(range) => range.max,
+            PayloadSizer::Weighted { ref sizes, .. } => sizes.iter().copied().max().unwrap_or(0),
+        }
+    }
+
+    /// Realistic mix of small, medium and large payloads within the range
+    fn sample_range(range: PacketSizeRange, rng: &mut BatchedRng) -> usize {
+        let min_size = range.min;
+        let max_size = range.max;
+
+        // Ensure we don't create empty ranges
+        match rng.range(0, 100) {
+            0..=40 => {
+                // Small packets: use min_size to min(200, max_size)
+                let upper = std::cmp::min(200, max_size);
+                if min_size <= upper {
+                    rng.range(min_size, upper + 1)
+                } else {
+                    min_size
+                }
+            },
+            41..=80 => {
+                // Medium packets: use 200 to min(800, max_size)
+                let lower = std::cmp::max(200, min_size);
+                let upper = std::cmp::min(800, max_size);
+                if lower <= upper {
+                    rng.range(lower, upper + 1)
+                } else {
+                    // Fallback to valid range
+                    rng.range(min_size, max_size + 1)
+                }
+            },
+            _ => {
+                // Large packets: use max(800, min_size) to max_size
+                let lower = std::cmp::max(800, min_size);
+                if lower <= max_size {
+                    rng.range(lower, max_size + 1)
+                } else {
+                    // Fallback to valid range
+                    rng.range(min_size, max_size + 1)
+                }
+            }
+        }
+    }
+}
+
+/// Payload size distributions resolved for each protocol
+#[derive(Debug, Clone, PartialEq)]
+pub struct PacketSizes {
+    pub udp: SizeDistribution,
+    pub icmp: SizeDistribution,
+    /// Payloads of data-bearing TCP segments
+    pub tcp: SizeDistribution,
+}
+
+impl PacketSizes {
+    /// Resolve the distributions of a load configuration
+    pub fn from_config(attack: &LoadConfig) -> Self {
+        Self {
+            udp: attack.payload_sizes.udp_sizes(attack.payload_size),
+            icmp: attack.payload_sizes.icmp_sizes(),
+            tcp: attack.payload_sizes.tcp_sizes(attack.payload_size),
+        }
+    }
+
+    /// UDP and TCP sizes from `range`, ICMP with ping-sized payloads
+    pub fn from_range(range: PacketSizeRange) -> Self {
+        Self {
+            udp: SizeDistribution::Range { min: range.min, max: range.max },
+            icmp: SizeDistribution::Range { min: icmp::MIN_PING_SIZE, max: icmp::MAX_PING_SIZE },
+            tcp: SizeDistribution::Range { min: range.min, max: range.max },
+        }
+    }
+
+    /// Upper bound on the payload of any protocol
+    pub fn max_payload(&self) -> usize {
+        self.udp.max_size().max(self.icmp.max_size()).max(self.tcp.max_size())
+    }
+}
@@ -0,0 +1,207 @@
+//! Source address and port selection
+
+use pnet::datalink::NetworkInterface;
+use pnet::ipnetwork::IpNetwork;
+use pnet::util::MacAddr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::config::{SourceAddress, SourceConfig, SourcePort, SourceSelection};
+use crate::constants::{IPV6_LINK_LOCAL_MASK, IPV6_LINK_LOCAL_PREFIX};
+use crate::error::{ConfigError, Result};
+use crate::utils::rng::BatchedRng;
+
+/// Contiguous block of source addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourcePool {
+    first: u128,
+    count: u128,
+    per_packet: bool,
+}
+
+impl SourcePool {
+    /// A single address
+    pub fn fixed(address: IpAddr) -> Self {
+        Self { first: to_bits(address), count: 1, per_packet: false }
+    }
+
+    /// Host addresses of a CIDR block, without the IPv4 network and broadcast
+    /// addresses or the IPv6 subnet-router anycast address
+    pub fn from_network(network: IpNetwork, selection: SourceSelection) -> Self {
+        let first = to_bits(network.network());
+        let size = to_bits(network.broadcast()) - first + 1;
+        let (first, count) = match network {
+            IpNetwork::V4(_) if size > 2 => (first + 1, size - 2),
+            IpNetwork::V6(_) if size > 1 => (first + 1, size - 1),
+            _ => (first, size),
+        };
+        Self { first, count, per_packet: selection == SourceSelection::PerPacket }
+    }
+
+    /// One random host of 192.168.1.0/24 per flow, as before `source` existed
+    pub fn legacy_ipv4() -> Self {
+        Self { first: to_bits(Ipv4Addr::new(192, 168, 1, 2).into()), count: 252, per_packet: false }
+    }
+
+    /// One random link-local fe80::/64 address per flow
+    pub fn legacy_ipv6() -> Self {
+        Self { first: to_bits(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1).into()), count: u64::MAX as u128, per_packet: false }
+    }
+
+    /// Whether `address` can be drawn from the pool
+    pub fn contains(&self, address: IpAddr) -> bool {
+        let bits = to_bits(address);
+        bits >= self.first && bits - self.first < self.count
+    }
+
+    /// Picker for one flow
+    pub fn picker(&self, rng: &mut BatchedRng) -> SourcePicker {
+        SourcePicker { pool: *self, flow: self.draw(rng) }
+    }
+
+    fn draw(&self, rng: &mut BatchedRng) -> u128 {
+        if self.count <= 1 {
+            return self.first;
+        }
+        let random = (0..4).fold(0u128, |acc, _| acc << 32 | rng.sequence() as u128);
+        self.first + random % self.count
+    }
+}
+
+/// Source addresses of one flow
+#[derive(Debug, Clone)]
+pub struct SourcePicker {
+    pool: SourcePool,
+    flow: u128,
+}
+
+impl SourcePicker {
+    #[inline]
+    fn next(&mut self, rng: &mut BatchedRng) -> u128 {
+        if self.pool.per_packet {
+            self.pool.draw(rng)
+        } else {
+            self.flow
+        }
+    }
+
+    /// Source for the next IPv4 packet
+    #[inline]
+    pub fn next_ipv4(&mut self, rng: &mut BatchedRng) -> Ipv4Addr {
+        Ipv4Addr::from(self.next(rng) as u32)
+    }
+
+    /// Source for the next IPv6 packet
+    #[inline]
+    pub fn next_ipv6(&mut self, rng: &mut BatchedRng) -> Ipv6Addr {
+        Ipv6Addr::from(self.next(rng))
+    }
+}
+
+/// Source ports of one stream
+#[derive(Debug, Clone)]
+pub struct PortPicker {
+    policy: SourcePort,
+    next: u16,
+}
+
+impl PortPicker {
+    pub fn new(policy: SourcePort) -> Self {
+        let next = match policy {
+            SourcePort::Sequential { min, .. } => min,
+            _ => 0,
+        };
+        Self { policy, next }
+    }
+
+    /// Source port for the next packet
+    #[inline]
+    pub fn next(&mut self, rng: &mut BatchedRng) -> u16 {
+        match self.policy {
+            SourcePort::Random => rng.port(),
+            SourcePort::Fixed { port } => port,
+            SourcePort::Range { min, max } => rng.range(min as usize, max as usize + 1) as u16,
+            SourcePort::Sequential { min, max } => {
+                let port = self.next;
+                self.next = if port >= max { min } else { port + 1 };
+                port
+            }
+        }
+    }
+}
+
+/// Resolved source settings for both address families
+#[derive(Debug, Clone)]
+pub struct SourceAddresses {
+    pub ipv4: SourcePool,
+    pub ipv6: SourcePool,
+    /// Interface MAC for ARP, when the interface address is used
+    pub mac: Option<MacAddr>,
+    /// UDP and TCP source port policy
+    pub port: SourcePort,
+}
+
+impl Default for SourceAddresses {
+    fn default() -> Self {
+        Self {
+            ipv4: SourcePool::legacy_ipv4(),
+            ipv6: SourcePool::legacy_ipv6(),
+            mac: None,
+            port: SourcePort::default(),
+        }
+    }
+}
+
+impl SourceAddresses {
+    /// Resolve the configured sources, looking up interface addresses
+    pub fn resolve(config: &SourceConfig, interface: Option<&NetworkInterface>) -> Result<Self> {
+        let mut resolved = Self { port: config.port, ..Self::default() };
+        if let Some(ref address) = config.ipv4 {
+            resolved.ipv4 = Self::resolve_pool(address, interface, false)?;
+        }
+        if let Some(ref address) = config.ipv6 {
+            resolved.ipv6 = Self::resolve_pool(address, interface, true)?;
+        }
+
+        let uses_interface = [&config.ipv4, &config.ipv6]
+            .into_iter()
+            .any(|address| *address == Some(SourceAddress::Interface));
+        if uses_interface {
+            resolved.mac = interface.and_then(|interface| interface.mac);
+        }
+        Ok(resolved)
+    }
+
+    fn resolve_pool(address: &SourceAddress, interface: Option<&NetworkInterface>, ipv6: bool) -> Result<SourcePool> {
+        let family = if ipv6 { "IPv6" } else { "IPv4" };
+        match *address {
+            SourceAddress::Fixed { address } => Ok(SourcePool::fixed(address)),
+            SourceAddress::Pool { ref cidr, selection } => {
+                Ok(SourcePool::from_network(SourceAddress::parse_cidr(cidr)?, selection))
+            }
+            SourceAddress::Interface => {
+                let interface = interface.ok_or_else(|| {
+                    ConfigError::new(format!("{} source uses the interface address, but no interface is selected", family))
+                })?;
+                let addresses: Vec<IpAddr> = interface.ips.iter()
+                    .map(|network| network.ip())
+                    .filter(|ip| ip.is_ipv6() == ipv6)
+                    .collect();
+                // Prefer routable addresses over IPv6 link-local ones
+                addresses.iter()
+                    .find(|ip| !matches!(ip, IpAddr::V6(v6) if v6.segments()[0] & IPV6_LINK_LOCAL_MASK == IPV6_LINK_LOCAL_PREFIX))
+                    .or(addresses.first())
+                    .map(|&ip| SourcePool::fixed(ip))
+                    .ok_or_else(|| {
+                        ConfigError::new(format!("Interface {} has no {} address", interface.name, family)).into()
+                    })
+            }
+        }
+    }
+}
+
+fn to_bits(address: IpAddr) -> u128 {
+    match address {
+        IpAddr::V4(ip) => u32::from(ip) as u128,
+        IpAddr::V6(ip) => u128::from(ip),
+    }
+}
@@ -0,0 +1,138 @@
+//! TCP option encoding
+//!
+//! SYN segments carry the whole configured option list. MSS, window scale
+//! and SACK-permitted are only valid on SYNs, so other segments carry just
+//! the timestamps option (after two NOPs) when the list includes it, the
+//! way a stack does once timestamps are negotiated (RFC 7323).
+
+use std::time::Instant;
+
+use crate::config::{TcpOption, TcpOptions};
+use crate::constants::tcp_option;
+use crate::utils::rng::BatchedRng;
+
+/// Encoded options of one segment kind
+#[derive(Debug, Clone, Default)]
+struct EncodedOptions {
+    /// Option bytes padded to a multiple of four
+    bytes: Vec<u8>,
+    /// Offset of the timestamp value, if present
+    timestamp_at: Option<usize>,
+}
+
+impl EncodedOptions {
+    fn encode(options: &[TcpOption]) -> Self {
+        let mut encoded = Self::default();
+        for option in options {
+            let bytes = &mut encoded.bytes;
+            match *option {
+                TcpOption::Eol => bytes.push(tcp_option::KIND_EOL),
+                TcpOption::Nop => bytes.push(tcp_option::KIND_NOP),
+                TcpOption::Mss(mss) => {
+                    bytes.extend([tcp_option::KIND_MSS, tcp_option::MSS_LEN as u8]);
+                    bytes.extend(mss.to_be_bytes());
+                }
+                TcpOption::WindowScale(shift) => {
+                    bytes.extend([tcp_option::KIND_WINDOW_SCALE, tcp_option::WINDOW_SCALE_LEN as u8, shift]);
+                }
+                TcpOption::SackPermitted => {
+                    bytes.extend([tcp_option::KIND_SACK_PERMITTED, tcp_option::SACK_PERMITTED_LEN as u8]);
+                }
+                TcpOption::Timestamps => {
+                    bytes.extend([tcp_option::KIND_TIMESTAMPS, tcp_option::TIMESTAMPS_LEN as u8]);
+                    encoded.timestamp_at = Some(bytes.len());
+                    bytes.extend([0; 8]);
+                }
+            }
+        }
+
+        // Pad with end-of-list bytes to a whole number of 32-bit words
+        let padded = encoded.bytes.len().next_multiple_of(4);
+        encoded.bytes.resize(padded, tcp_option::KIND_EOL);
+        encoded
+    }
+}
+
+/// Resolved TCP options, shared by all strategies of a run
+#[derive(Debug, Clone, Default)]
+pub struct TcpOptionLayout {
+    syn: EncodedOptions,
+    other: EncodedOptions,
+}
+
+impl TcpOptionLayout {
+    pub fn from_config(options: &TcpOptions) -> Self {
+        let list = options.list();
+        let other = if list.0.contains(&TcpOption::Timestamps) {
+            EncodedOptions::encode(&[TcpOption::Nop, TcpOption::Nop, TcpOption::Timestamps])
+        } else {
+            EncodedOptions::default()
+        };
+
+        Self { syn: EncodedOptions::encode(&list.0), other }
+    }
+
+    /// Option bytes of SYN or other segments, including padding
+    pub fn len(&self, syn: bool) -> usize {
+        self.options(syn).bytes.len()
+    }
+
+    /// Longest option area of any segment
+    pub fn max_len(&self) -> usize {
+        self.len(true).max(self.len(false))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.max_len() == 0
+    }
+
+    fn options(&self, syn: bool) -> &EncodedOptions {
+        if syn { &self.syn } else { &self.other }
+    }
+}
+
+/// Writes TCP options for one packet strategy, with its own timestamp clock
+#[derive(Debug)]
+pub struct TcpOptionWriter {
+    layout: TcpOptionLayout,
+    timestamp_base: u32,
+    started: Instant,
+}
+
+impl TcpOptionWriter {
+    pub fn new(layout: &TcpOptionLayout, rng: &mut BatchedRng) -> Self {
+        Self {
+            layout: layout.clone(),
+            timestamp_base: rng.sequence(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Option bytes of SYN or other segments, including padding
+    #[inline]
+    pub fn len(&self, syn: bool) -> usize {
+        self.layout.len(syn)
+    }
+
+    /// Longest option area of any segment
+    #[inline]
+    pub fn max_len(&self) -> usize {
+        self.layout.max_len()
+    }
+
+    /// Write the options into `area`, which must be `len(syn)` bytes long
+    ///
+    /// The timestamp value counts milliseconds; SYNs echo no timestamp.
+    #[inline]
+    pub fn write(&self, area: &mut [u8], syn: bool, rng: &mut BatchedRng) {
+        let options = self.layout.options(syn);
+        area.copy_from_slice(&options.bytes);
+
+        if let Some(at) = options.timestamp_at {
+            let value = self.timestamp_base.wrapping_add(self.started.elapsed().as_millis() as u32);
+            let echo = if syn { 0 } else { rng.sequence() };
+            area[at..at + 4].copy_from_slice(&value.to_be_bytes());
+            area[at + 4..at + 8].copy_from_slice(&echo.to_be_bytes());
+        }
+    }
+}
@@ -2,33 +2,42 @@
 
 use std::fmt;
 
+use crate::config::TcpFlagSet;
+
 /// Supported packet types for network simulation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PacketType {
     Udp,
-    TcpSyn,
-    TcpAck,
-    TcpFin,
-    TcpRst,
+    /// IPv4 TCP segment with the given control bits
+    Tcp(TcpFlagSet),
     Icmp,
     Ipv6Udp,
-    Ipv6Tcp,
+    /// IPv6 TCP segment with the given control bits
+    Ipv6Tcp(TcpFlagSet),
     Ipv6Icmp,
     Arp,
 }
 
 impl PacketType {
-    /// Get all available packet types
+    pub const TCP_SYN: PacketType = PacketType::Tcp(TcpFlagSet::SYN);
+    pub const TCP_ACK: PacketType = PacketType::Tcp(TcpFlagSet::ACK);
+    pub const TCP_FIN: PacketType = PacketType::Tcp(TcpFlagSet::FIN);
+    pub const TCP_RST: PacketType = PacketType::Tcp(TcpFlagSet::RST);
+    pub const IPV6_TCP_SYN: PacketType = PacketType::Ipv6Tcp(TcpFlagSet::SYN);
+    pub const IPV6_TCP_ACK: PacketType = PacketType::Ipv6Tcp(TcpFlagSet::ACK);
+
+    /// Get all available packet types, with TCP as SYN, ACK, FIN and RST and
+    /// IPv6 TCP as SYN
     pub const fn all() -> &'static [PacketType] {
         &[
             PacketType::Udp,
-            PacketType::TcpSyn,
-            PacketType::TcpAck,
-            PacketType::TcpFin,
-            PacketType::TcpRst,
+            PacketType::TCP_SYN,
+            PacketType::TCP_ACK,
+            PacketType::TCP_FIN,
+            PacketType::TCP_RST,
             PacketType::Icmp,
             PacketType::Ipv6Udp,
-            PacketType::Ipv6Tcp,
+            PacketType::IPV6_TCP_SYN,
             PacketType::Ipv6Icmp,
             PacketType::Arp,
         ]
@@ -36,40 +45,45 @@ impl PacketType {
     
     /// Check if this packet type is IPv6-based
     pub const fn is_ipv6(&self) -> bool {
-        matches!(self, PacketType::Ipv6Udp | PacketType::Ipv6Tcp | PacketType::Ipv6Icmp)
+        matches!(self, PacketType::Ipv6Udp | PacketType::Ipv6Tcp(_) | PacketType::Ipv6Icmp)
     }
     
     /// Check if this packet type is IPv4-based
     pub const fn is_ipv4(&self) -> bool {
-        matches!(self, PacketType::Udp | PacketType::TcpSyn | PacketType::TcpAck | PacketType::TcpFin | PacketType::TcpRst | PacketType::Icmp | PacketType::Arp)
+        matches!(self, PacketType::Udp | PacketType::Tcp(_) | PacketType::Icmp | PacketType::Arp)
     }
     
     /// Get the protocol name for statistics
     pub const fn protocol_name(&self) -> &'static str {
         match self {
             PacketType::Udp => "UDP",
-            PacketType::TcpSyn | PacketType::TcpAck | PacketType::TcpFin | PacketType::TcpRst => "TCP",
+            PacketType::Tcp(_) => "TCP",
             PacketType::Icmp => "ICMP",
-            PacketType::Ipv6Udp | PacketType::Ipv6Tcp | PacketType::Ipv6Icmp => "IPv6",
+            PacketType::Ipv6Udp | PacketType::Ipv6Tcp(_) | PacketType::Ipv6Icmp => "IPv6",
             PacketType::Arp => "ARP",
         }
     }
+
+    /// TCP control bits, for IPv4 and IPv6 TCP packet types
+    pub const fn tcp_flags(&self) -> Option<TcpFlagSet> {
+        match self {
+            PacketType::Tcp(flags) | PacketType::Ipv6Tcp(flags) => Some(*flags),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for PacketType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let name = match self {
             PacketType::Udp => "UDP",
-            PacketType::TcpSyn => "TCP-SYN",
-            PacketType::TcpAck => "TCP-ACK",
-            PacketType::TcpFin => "TCP-FIN",
-            PacketType::TcpRst => "TCP-RST",
+            PacketType::Tcp(flags) => return write!(f, "TCP-{}", flags.to_string().to_uppercase()),
             PacketType::Icmp => "ICMP",
             PacketType::Ipv6Udp => "IPv6-UDP",
-            PacketType::Ipv6Tcp => "IPv6-TCP",
+            PacketType::Ipv6Tcp(flags) => return write!(f, "IPv6-TCP-{}", flags.to_string().to_uppercase()),
             PacketType::Ipv6Icmp => "IPv6-ICMP",
             PacketType::Arp => "ARP",
         };
         write!(f, "{}", name)
     }
-}
\ No newline at end of file
+}
@@ -12,39 +12,33 @@ use crate::error::{ValidationError, Result};
 
 /// Enhanced safety validation functions
 pub fn validate_target_ip(ip: &IpAddr) -> Result<()> {
+    if !is_private_ip(ip) {
+        return Err(ValidationError::new("ip", "Invalid IP range").into());
+    }
+
+    match ip {
+        IpAddr::V4(_) => info!("Target IP {} validated as private range", ip),
+        IpAddr::V6(_) => info!("Target IPv6 {} validated as private range", ip),
+    }
+    Ok(())
+}
+
+/// Private IPv4 ranges, IPv6 link-local (fe80::/10) or unique local (fc00::/7)
+pub fn is_private_ip(ip: &IpAddr) -> bool {
     match ip {
         IpAddr::V4(ipv4) => {
             let ip_u32 = u32::from(*ipv4);
 
             // Check against defined private ranges using bitwise operations
-            let is_private = PRIVATE_IPV4_RANGES.iter().any(|(network, mask)| {
+            PRIVATE_IPV4_RANGES.iter().any(|(network, mask)| {
                 (ip_u32 & mask) == *network
-            });
-
-            if is_private {
-                info!("Target IP {} validated as private range", ip);
-                Ok(())
-            } else {
-                Err(ValidationError::new("ip", "Invalid IP range").into())
-            }
+            })
         }
         IpAddr::V6(ipv6) => {
-            // Check for IPv6 private ranges (link-local, unique local)
-            if ipv6.is_loopback() {
-                return Err(ValidationError::new("ip", "Invalid IP range").into());
-            }
-
-            // Link-local (fe80::/10) or unique local (fc00::/7)
             let segments = ipv6.segments();
-            let is_private = (segments[0] & IPV6_LINK_LOCAL_MASK) == IPV6_LINK_LOCAL_PREFIX
-                || (segments[0] & IPV6_UNIQUE_LOCAL_MASK) == IPV6_UNIQUE_LOCAL_PREFIX;
-
-            if is_private {
-                info!("Target IPv6 {} validated as private range", ip);
-                Ok(())
-            } else {
-                Err(ValidationError::new("ip", "Invalid IP range").into())
-            }
+            !ipv6.is_loopback()
+                && ((segments[0] & IPV6_LINK_LOCAL_MASK) == IPV6_LINK_LOCAL_PREFIX
+                    || (segments[0] & IPV6_UNIQUE_LOCAL_MASK) == IPV6_UNIQUE_LOCAL_PREFIX)
         }
     }
 }
@@ -2,7 +2,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use crate::error::Result;
 
 /// Core trait for statistics collection
@@ -36,14 +36,83 @@ pub struct SessionStats {
     pub timestamp: DateTime<Utc>,
     pub packets_sent: u64,
     pub packets_failed: u64,
+    /// Failed ARP sends through the layer-2 channel, included in `packets_failed`
+    pub arp_failed: u64,
     pub bytes_sent: u64,
     pub duration_secs: f64,
     pub packets_per_second: f64,
     pub megabits_per_second: f64,
     pub protocol_breakdown: HashMap<String, u64>,
+    /// Sent TCP packets per flag set, e.g. `syn_ack` or `syn+fin`
+    pub tcp_flags: BTreeMap<String, u64>,
+    pub load_phase: String,
+    pub burst_cycles: u64,
+    pub target_rate: Option<f64>,
+    /// Pauses imposed by the global rate limiter
+    pub throttle_events: u64,
+    pub throttled_secs: f64,
+    /// Scenario phase the snapshot was taken in
+    pub phase: Option<String>,
+    /// Completed scenario phases
+    pub phases: Vec<PhaseStats>,
+    /// Per-worker pacing accuracy
+    pub pacing: Vec<PacingReport>,
+    /// Adaptive rate search steps and result
+    pub search: Option<SearchReport>,
     pub system_stats: Option<SystemStats>,
 }
 
+/// Pacing accuracy of one worker
+#[derive(Debug, Serialize, Clone)]
+pub struct PacingReport {
+    pub worker_id: usize,
+    pub duration_secs: f64,
+    /// Send attempts, including failed sends
+    pub packets: u64,
+    pub requested_pps: f64,
+    pub achieved_pps: f64,
+    pub gap_mean_us: f64,
+    pub gap_p50_us: f64,
+    pub gap_p90_us: f64,
+    pub gap_p99_us: f64,
+    pub gap_max_us: f64,
+}
+
+/// One rate tried by the adaptive rate search
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchStep {
+    /// Requested per-thread rate
+    pub rate: f64,
+    /// Aggregate send attempts per second during the step
+    pub achieved_pps: f64,
+    pub failure_ratio: f64,
+    pub probe_rtt_ms: Option<f64>,
+    pub response_ratio: Option<f64>,
+    pub healthy: bool,
+}
+
+/// Outcome of an adaptive rate search
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SearchReport {
+    pub steps: Vec<SearchStep>,
+    /// Highest healthy per-thread rate, `None` until found
+    pub sustainable_rate: Option<f64>,
+    /// `sustainable_rate` across all threads
+    pub sustainable_total_pps: Option<f64>,
+}
+
+/// Statistics for a single completed scenario phase
+#[derive(Debug, Serialize, Clone)]
+pub struct PhaseStats {
+    pub name: String,
+    pub duration_secs: f64,
+    pub packets_sent: u64,
+    pub packets_failed: u64,
+    pub bytes_sent: u64,
+    pub packets_per_second: f64,
+    pub megabits_per_second: f64,
+}
+
 /// System resource statistics
 #[derive(Debug, Serialize, Clone)]
 pub struct SystemStats {
@@ -61,13 +61,24 @@ impl StatsExporter for DefaultStatsExporter {
     }
 }
 
+/// Build the export file path, tagged with the scenario phase if any
+fn export_filename(stats: &SessionStats, config: &Export, extension: &str) -> String {
+    let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+    match stats.phase {
+        Some(ref phase) => {
+            let tag: String = phase
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+                .collect();
+            format!("{}/router_flood_stats_{}_{}.{}", config.path, tag, timestamp, extension)
+        }
+        None => format!("{}/router_flood_stats_{}.{}", config.path, timestamp, extension),
+    }
+}
+
 impl DefaultStatsExporter {
     async fn export_json(&self, stats: &SessionStats, config: &Export) -> Result<()> {
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-        let filename = format!(
-            "{}/router_flood_stats_{}.json",
-            config.path, timestamp
-        );
+        let filename = export_filename(stats, config, "json");
 
         let json = serde_json::to_string_pretty(stats)
             .map_err(|e| StatsError::new(format!("Failed to serialize stats: {}", e)))?;
@@ -81,11 +92,7 @@ impl DefaultStatsExporter {
     }
 
     async fn export_csv(&self, stats: &SessionStats, config: &Export) -> Result<()> {
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-        let filename = format!(
-            "{}/router_flood_stats_{}.csv",
-            config.path, timestamp
-        );
+        let filename = export_filename(stats, config, "csv");
 
         let file = std::fs::File::create(&filename)
             .map_err(|e| StatsError::new(format!("Failed to create CSV file: {}", e)))?;
@@ -108,6 +115,25 @@ impl DefaultStatsExporter {
                 "icmp_packets",
                 "ipv6_packets",
                 "arp_packets",
+                "load_phase",
+                "burst_cycles",
+                "target_rate",
+                "scope",
+                "phase",
+                "throttle_events",
+                "throttled_secs",
+                "worker",
+                "requested_pps",
+                "gap_mean_us",
+                "gap_p50_us",
+                "gap_p90_us",
+                "gap_p99_us",
+                "gap_max_us",
+                "failure_ratio",
+                "probe_rtt_ms",
+                "response_ratio",
+                "healthy",
+                "sustainable_rate",
             ])
             .map_err(|e| StatsError::new(format!("Failed to write CSV header: {}", e)))?;
 
@@ -127,9 +153,96 @@ impl DefaultStatsExporter {
                 &stats.protocol_breakdown.get(crate::constants::protocols::ICMP).unwrap_or(&0).to_string(),
                 &stats.protocol_breakdown.get(crate::constants::protocols::IPV6).unwrap_or(&0).to_string(),
                 &stats.protocol_breakdown.get(crate::constants::protocols::ARP).unwrap_or(&0).to_string(),
+                &stats.load_phase,
+                &stats.burst_cycles.to_string(),
+                &stats.target_rate.map(|rate| rate.to_string()).unwrap_or_default(),
+                "session",
+                stats.phase.as_deref().unwrap_or_default(),
+                &stats.throttle_events.to_string(),
+                &stats.throttled_secs.to_string(),
+                "", "", "", "", "", "", "",
+                "", "", "", "",
+                &stats.search.as_ref()
+                    .and_then(|search| search.sustainable_rate)
+                    .map(|rate| rate.to_string())
+                    .unwrap_or_default(),
             ])
             .map_err(|e| StatsError::new(format!("Failed to write CSV data: {}", e)))?;
 
+        // One row per completed scenario phase; session-only columns stay empty
+        for phase in &stats.phases {
+            writer
+                .write_record([
+                    &stats.session_id,
+                    &stats.timestamp.to_rfc3339(),
+                    &phase.packets_sent.to_string(),
+                    &phase.packets_failed.to_string(),
+                    &phase.bytes_sent.to_string(),
+                    &phase.duration_secs.to_string(),
+                    &phase.packets_per_second.to_string(),
+                    &phase.megabits_per_second.to_string(),
+                    "", "", "", "", "", "", "", "",
+                    "phase",
+                    &phase.name,
+                    "", "",
+                    "", "", "", "", "", "", "",
+                    "", "", "", "", "",
+                ])
+                .map_err(|e| StatsError::new(format!("Failed to write CSV data: {}", e)))?;
+        }
+
+        // One row per worker with its pacing accuracy
+        for report in &stats.pacing {
+            writer
+                .write_record([
+                    &stats.session_id,
+                    &stats.timestamp.to_rfc3339(),
+                    &report.packets.to_string(),
+                    "",
+                    "",
+                    &report.duration_secs.to_string(),
+                    &report.achieved_pps.to_string(),
+                    "",
+                    "", "", "", "", "", "", "", "",
+                    "worker",
+                    "",
+                    "", "",
+                    &report.worker_id.to_string(),
+                    &report.requested_pps.to_string(),
+                    &report.gap_mean_us.to_string(),
+                    &report.gap_p50_us.to_string(),
+                    &report.gap_p90_us.to_string(),
+                    &report.gap_p99_us.to_string(),
+                    &report.gap_max_us.to_string(),
+                    "", "", "", "", "",
+                ])
+                .map_err(|e| StatsError::new(format!("Failed to write CSV data: {}", e)))?;
+        }
+
+        // One row per adaptive search step, with the tried rate as target_rate
+        for step in stats.search.iter().flat_map(|search| &search.steps) {
+            writer
+                .write_record([
+                    &stats.session_id,
+                    &stats.timestamp.to_rfc3339(),
+                    "", "", "", "",
+                    &step.achieved_pps.to_string(),
+                    "",
+                    "", "", "", "", "", "", "",
+                    &step.rate.to_string(),
+                    "search",
+                    "",
+                    "", "",
+                    "", "", "", "", "", "", "",
+                    &step.failure_ratio.to_string(),
+                    &step.probe_rtt_ms.map(|rtt| rtt.to_string()).unwrap_or_default(),
+                    &step.response_ratio.map(|ratio| ratio.to_string()).unwrap_or_default(),
+                    &step.healthy.to_string(),
+                    "",
+                ])
+                .map_err(|e| StatsError::new(format!("Failed to write CSV data: {}", e)))?;
+        }
+
         writer
             .flush()
             .map_err(|e| StatsError::new(format!("Failed to flush CSV: {}", e)))?;
@@ -139,11 +252,7 @@ impl DefaultStatsExporter {
     }
 
     async fn export_yaml(&self, stats: &SessionStats, config: &Export) -> Result<()> {
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-        let filename = format!(
-            "{}/router_flood_stats_{}.yaml",
-            config.path, timestamp
-        );
+        let filename = export_filename(stats, config, "yaml");
 
         let yaml = serde_yaml::to_string(stats)
             .map_err(|e| StatsError::new(format!("Failed to serialize stats to YAML: {}", e)))?;
@@ -157,11 +266,7 @@ impl DefaultStatsExporter {
     }
 
     async fn export_text(&self, stats: &SessionStats, config: &Export) -> Result<()> {
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-        let filename = format!(
-            "{}/router_flood_stats_{}.txt",
-            config.path, timestamp
-        );
+        let filename = export_filename(stats, config, "txt");
 
         // Create human-readable text format
         let mut text = String::new();
@@ -169,16 +274,72 @@ impl DefaultStatsExporter {
         writeln!(&mut text, "Session ID:          {}", stats.session_id).unwrap();
         writeln!(&mut text, "Timestamp:           {}", stats.timestamp.to_rfc3339()).unwrap();
         writeln!(&mut text, "Duration:            {:.2} seconds", stats.duration_secs).unwrap();
+        if let Some(ref phase) = stats.phase {
+            writeln!(&mut text, "Scenario Phase:      {}", phase).unwrap();
+        }
         writeln!(&mut text).unwrap();
         
         writeln!(&mut text, "=== Performance Metrics ===").unwrap();
         writeln!(&mut text, "Packets Sent:        {:>12}", stats.packets_sent).unwrap();
         writeln!(&mut text, "Packets Failed:      {:>12}", stats.packets_failed).unwrap();
+        if stats.arp_failed > 0 {
+            writeln!(&mut text, "  of which ARP:      {:>12}", stats.arp_failed).unwrap();
+        }
         writeln!(&mut text, "Bytes Sent:          {:>12}", stats.bytes_sent).unwrap();
         writeln!(&mut text, "Packets/Second:      {:>12.2}", stats.packets_per_second).unwrap();
         writeln!(&mut text, "Megabits/Second:     {:>12.2}", stats.megabits_per_second).unwrap();
+        writeln!(&mut text, "Load Phase:          {:>12}", stats.load_phase).unwrap();
+        writeln!(&mut text, "Burst Cycles:        {:>12}", stats.burst_cycles).unwrap();
+        if let Some(target_rate) = stats.target_rate {
+            writeln!(&mut text, "Target Rate:         {:>12.1} pps/thread", target_rate).unwrap();
+        }
+        writeln!(&mut text, "Throttle Events:     {:>12}", stats.throttle_events).unwrap();
+        writeln!(&mut text, "Throttled Time:      {:>12.2} seconds", stats.throttled_secs).unwrap();
         writeln!(&mut text).unwrap();
         
+        if !stats.phases.is_empty() {
+            writeln!(&mut text, "=== Scenario Phases ===").unwrap();
+            writeln!(&mut text, "{:<16} {:>10} {:>12} {:>10} {:>12} {:>10}",
+                "Phase", "Seconds", "Sent", "Failed", "PPS", "Mbps").unwrap();
+            for phase in &stats.phases {
+                writeln!(&mut text, "{:<16} {:>10.1} {:>12} {:>10} {:>12.1} {:>10.2}",
+                    phase.name, phase.duration_secs, phase.packets_sent, phase.packets_failed,
+                    phase.packets_per_second, phase.megabits_per_second).unwrap();
+            }
+            writeln!(&mut text).unwrap();
+        }
+
+        if !stats.pacing.is_empty() {
+            writeln!(&mut text, "=== Pacing (per worker) ===").unwrap();
+            writeln!(&mut text, "{:<8} {:>12} {:>12} {:>10} {:>10} {:>10} {:>10}",
+                "Worker", "Requested", "Achieved", "Gap p50", "Gap p90", "Gap p99", "Gap max").unwrap();
+            for report in &stats.pacing {
+                writeln!(&mut text, "{:<8} {:>12.1} {:>12.1} {:>8.1}us {:>8.1}us {:>8.1}us {:>8.1}us",
+                    report.worker_id, report.requested_pps, report.achieved_pps,
+                    report.gap_p50_us, report.gap_p90_us, report.gap_p99_us, report.gap_max_us).unwrap();
+            }
+            writeln!(&mut text).unwrap();
+        }
+
+        if let Some(ref search) = stats.search {
+            writeln!(&mut text, "=== Adaptive Rate Search ===").unwrap();
+            writeln!(&mut text, "{:>12} {:>12} {:>10} {:>10} {:>10} {:>9}",
+                "Rate/Thread", "Achieved", "Failures", "RTT ms", "Answered", "Healthy").unwrap();
+            for step in &search.steps {
+                let optional = |value: Option<f64>| value.map_or("-".to_string(), |value| format!("{:.2}", value));
+                writeln!(&mut text, "{:>12.1} {:>12.1} {:>9.2}% {:>10} {:>10} {:>9}",
+                    step.rate, step.achieved_pps, step.failure_ratio * 100.0,
+                    optional(step.probe_rtt_ms), optional(step.response_ratio),
+                    if step.healthy { "yes" } else { "no" }).unwrap();
+            }
+            match (search.sustainable_rate, search.sustainable_total_pps) {
+                (Some(rate), Some(total)) => writeln!(&mut text,
+                    "Sustainable Rate:    {:>12.1} pps/thread ({:.1} pps total)", rate, total).unwrap(),
+                _ => writeln!(&mut text, "Sustainable Rate:    {:>12}", "not found").unwrap(),
+            }
+            writeln!(&mut text).unwrap();
+        }
+
         if !stats.protocol_breakdown.is_empty() {
             writeln!(&mut text, "=== Protocol Breakdown ===").unwrap();
             for (protocol, count) in &stats.protocol_breakdown {
@@ -186,6 +347,14 @@ impl DefaultStatsExporter {
             }
             writeln!(&mut text).unwrap();
         }
+
+        if !stats.tcp_flags.is_empty() {
+            writeln!(&mut text, "=== TCP Flags ===").unwrap();
+            for (flags, count) in &stats.tcp_flags {
+                writeln!(&mut text, "{:<20} {:>12}", flags, count).unwrap();
+            }
+            writeln!(&mut text).unwrap();
+        }
         
         if let Some(ref system_stats) = stats.system_stats {
             writeln!(&mut text, "=== System Resources ===").unwrap();
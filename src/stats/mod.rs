@@ -7,12 +7,16 @@ pub mod export;
 pub mod display;
 pub mod stats_aggregator;
 pub mod protocol_breakdown;
+pub mod pacing;
+pub mod quota;
 
 // Main stats implementation
 pub use stats_aggregator::{Stats, BatchStats};
 
 // Core types
-pub use collector::{SessionStats, SystemStats};
+pub use collector::{PacingReport, PhaseStats, SearchReport, SearchStep, SessionStats, SystemStats};
+pub use pacing::{GapHistogram, PacingMeter};
+pub use quota::{QuotaGrant, SendQuota};
 pub use export::StatsExporter;
 pub use display::{init_display, display};
 pub use protocol_breakdown::ProtocolBreakdown;
\ No newline at end of file
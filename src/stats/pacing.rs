@@ -0,0 +1,177 @@
+//! Per-worker pacing accuracy measurement
+//!
+//! Workers compare the packets they were asked to send (the integral of the
+//! target rate over time) with the packets they actually attempted, and
+//! record the gap between consecutive packets in a log-linear histogram.
+
+use std::time::{Duration, Instant};
+
+use super::collector::PacingReport;
+
+/// Linear sub-buckets per power of two (about 12% resolution)
+const SUB_BUCKETS: usize = 8;
+const SUB_BITS: u32 = SUB_BUCKETS.trailing_zeros();
+
+/// Gaps are recorded up to 2^41 ns (about 36 minutes)
+const MAX_EXPONENT: u32 = 41;
+const BUCKETS: usize = (MAX_EXPONENT - SUB_BITS + 1) as usize * SUB_BUCKETS;
+
+/// Log-linear histogram of inter-packet gaps in nanoseconds
+#[derive(Debug, Clone)]
+pub struct GapHistogram {
+    buckets: Vec<u64>,
+    count: u64,
+    sum: u128,
+    max: u64,
+}
+
+impl Default for GapHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: vec![0; BUCKETS],
+            count: 0,
+            sum: 0,
+            max: 0,
+        }
+    }
+}
+
+impl GapHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one gap
+    pub fn record(&mut self, gap: Duration) {
+        let nanos = (gap.as_nanos() as u64).min((1 << MAX_EXPONENT) - 1);
+        self.buckets[Self::index(nanos)] += 1;
+        self.count += 1;
+        self.sum += nanos as u128;
+        self.max = self.max.max(nanos);
+    }
+
+    /// Number of recorded gaps
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Mean gap
+    pub fn mean(&self) -> Duration {
+        match self.count {
+            0 => Duration::ZERO,
+            count => Duration::from_nanos((self.sum / count as u128) as u64),
+        }
+    }
+
+    /// Largest gap
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max)
+    }
+
+    /// Approximate gap at quantile `q` (0.0 to 1.0)
+    pub fn percentile(&self, q: f64) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+
+        let target = ((q.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, &bucket) in self.buckets.iter().enumerate() {
+            seen += bucket;
+            if seen >= target {
+                return Duration::from_nanos(Self::midpoint(index).min(self.max));
+            }
+        }
+
+        self.max()
+    }
+
+    fn index(nanos: u64) -> usize {
+        if nanos < SUB_BUCKETS as u64 {
+            return nanos as usize;
+        }
+
+        let exponent = 63 - nanos.leading_zeros();
+        let sub = (nanos >> (exponent - SUB_BITS)) as usize & (SUB_BUCKETS - 1);
+        (exponent - SUB_BITS + 1) as usize * SUB_BUCKETS + sub
+    }
+
+    fn midpoint(index: usize) -> u64 {
+        if index < SUB_BUCKETS {
+            return index as u64;
+        }
+
+        let exponent = (index / SUB_BUCKETS) as u32 + SUB_BITS - 1;
+        let sub = (index % SUB_BUCKETS) as u64;
+        let width = 1u64 << (exponent - SUB_BITS);
+        (SUB_BUCKETS as u64 + sub) * width + width / 2
+    }
+}
+
+/// Requested versus achieved rate and gap distribution of one worker
+pub struct PacingMeter {
+    started: Instant,
+    last_tick: Instant,
+    current_rate: f64,
+    requested: f64,
+    packets: u64,
+    last_send: Option<Instant>,
+    gaps: GapHistogram,
+}
+
+impl PacingMeter {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            started: now,
+            last_tick: now,
+            current_rate: 0.0,
+            requested: 0.0,
+            packets: 0,
+            last_send: None,
+            gaps: GapHistogram::new(),
+        }
+    }
+
+    /// Set the requested rate from `now` on (0 while idle)
+    pub fn set_rate(&mut self, now: Instant, rate: f64) {
+        self.requested += self.current_rate * now.saturating_duration_since(self.last_tick).as_secs_f64();
+        self.current_rate = rate;
+        self.last_tick = now;
+    }
+
+    /// Record a send attempt
+    pub fn record_send(&mut self, now: Instant) {
+        if let Some(last) = self.last_send {
+            self.gaps.record(now.saturating_duration_since(last));
+        }
+        self.last_send = Some(now);
+        self.packets += 1;
+    }
+
+    /// Do not count the time until the next send as a gap (idle windows,
+    /// phase changes)
+    pub fn break_gap(&mut self) {
+        self.last_send = None;
+    }
+
+    /// Snapshot for stats and exports
+    pub fn report(&self, worker_id: usize, now: Instant) -> PacingReport {
+        let elapsed = now.saturating_duration_since(self.started).as_secs_f64();
+        let requested = self.requested
+            + self.current_rate * now.saturating_duration_since(self.last_tick).as_secs_f64();
+        let micros = |gap: Duration| gap.as_secs_f64() * 1_000_000.0;
+
+        PacingReport {
+            worker_id,
+            duration_secs: elapsed,
+            packets: self.packets,
+            requested_pps: if elapsed > 0.0 { requested / elapsed } else { 0.0 },
+            achieved_pps: if elapsed > 0.0 { self.packets as f64 / elapsed } else { 0.0 },
+            gap_mean_us: micros(self.gaps.mean()),
+            gap_p50_us: micros(self.gaps.percentile(0.50)),
+            gap_p90_us: micros(self.gaps.percentile(0.90)),
+            gap_p99_us: micros(self.gaps.percentile(0.99)),
+            gap_max_us: micros(self.gaps.max()),
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Exact packet and byte quotas shared by all workers
+//!
+//! Workers reserve each packet before sending it and then either commit the
+//! reservation (sent) or release it (failed). Failed sends therefore do not
+//! use up the quota, and no worker can send past it. The quota is exhausted
+//! once a reservation fails while no other packet is in flight.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tokio::sync::Notify;
+
+/// Outcome of a quota reservation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaGrant {
+    /// The packet may be sent
+    Granted,
+    /// No room right now, but packets in flight may still be released
+    Busy,
+    /// The quota is used up
+    Exhausted,
+}
+
+/// Packet and byte limits for a whole run
+#[derive(Debug)]
+pub struct SendQuota {
+    max_packets: Option<u64>,
+    max_bytes: Option<u64>,
+    reserved_packets: AtomicU64,
+    reserved_bytes: AtomicU64,
+    committed_packets: AtomicU64,
+    committed_bytes: AtomicU64,
+    exhausted: AtomicBool,
+    notify: Notify,
+}
+
+impl SendQuota {
+    pub fn new(max_packets: Option<u64>, max_bytes: Option<u64>) -> Self {
+        Self {
+            max_packets,
+            max_bytes,
+            reserved_packets: AtomicU64::new(0),
+            reserved_bytes: AtomicU64::new(0),
+            committed_packets: AtomicU64::new(0),
+            committed_bytes: AtomicU64::new(0),
+            exhausted: AtomicBool::new(false),
+            notify: Notify::new(),
+        }
+    }
+
+    /// Reserve one packet of `bytes` bytes
+    pub fn reserve(&self, bytes: u64) -> QuotaGrant {
+        if self.is_exhausted() {
+            return QuotaGrant::Exhausted;
+        }
+
+        if Self::try_add(&self.reserved_packets, 1, self.max_packets) {
+            if Self::try_add(&self.reserved_bytes, bytes, self.max_bytes) {
+                return QuotaGrant::Granted;
+            }
+            self.reserved_packets.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        // Reservations only shrink when an in-flight packet fails
+        if self.reserved_packets.load(Ordering::SeqCst) > self.committed_packets.load(Ordering::SeqCst) {
+            QuotaGrant::Busy
+        } else {
+            self.exhausted.store(true, Ordering::SeqCst);
+            self.notify.notify_waiters();
+            QuotaGrant::Exhausted
+        }
+    }
+
+    /// Count a reserved packet as sent
+    pub fn commit(&self, bytes: u64) {
+        self.committed_bytes.fetch_add(bytes, Ordering::SeqCst);
+        let committed = self.committed_packets.fetch_add(1, Ordering::SeqCst) + 1;
+
+        if self.max_packets.is_some_and(|max| committed >= max)
+            || self.max_bytes.is_some_and(|max| self.committed_bytes.load(Ordering::SeqCst) >= max) {
+            self.exhausted.store(true, Ordering::SeqCst);
+            self.notify.notify_waiters();
+        }
+    }
+
+    /// Return the reservation of a packet that was not sent
+    pub fn release(&self, bytes: u64) {
+        self.reserved_bytes.fetch_sub(bytes, Ordering::SeqCst);
+        self.reserved_packets.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the quota is used up
+    pub async fn exhausted(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_exhausted() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Packets and bytes sent against the quota
+    pub fn committed(&self) -> (u64, u64) {
+        (
+            self.committed_packets.load(Ordering::SeqCst),
+            self.committed_bytes.load(Ordering::SeqCst),
+        )
+    }
+
+    fn try_add(counter: &AtomicU64, amount: u64, max: Option<u64>) -> bool {
+        counter
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |current| {
+                let next = current.checked_add(amount)?;
+                max.is_none_or(|max| next <= max).then_some(next)
+            })
+            .is_ok()
+    }
+}
@@ -1,24 +1,58 @@
 //! Simple statistics tracking using atomic operations
 
-use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::Instant;
-use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use std::collections::{BTreeMap, HashMap};
 use chrono::Utc;
 
-use crate::config::Export;
+use crate::config::{Export, TcpFlagSet};
 use crate::error::Result;
-use super::collector::{SessionStats, SystemStats};
+use crate::network::burst::{BurstState, LoadPhase};
+use super::quota::{QuotaGrant, SendQuota};
+use super::collector::{PacingReport, PhaseStats, SearchReport, SearchStep, SessionStats, SystemStats};
+
+/// Scenario phase bookkeeping
+#[derive(Default)]
+struct PhaseTracker {
+    /// Name of the latest phase, kept after it ends so final exports stay tagged
+    current: Option<String>,
+    open: Option<PhaseStart>,
+    completed: Vec<PhaseStats>,
+}
+
+/// Counter values at the start of a phase
+struct PhaseStart {
+    name: String,
+    started: Instant,
+    packets_sent: u64,
+    packets_failed: u64,
+    bytes_sent: u64,
+}
 
 /// Simple statistics tracker using atomic operations
 pub struct Stats {
     packets_sent: Arc<AtomicU64>,
     bytes_sent: Arc<AtomicU64>,
     packets_failed: Arc<AtomicU64>,
+    /// Failed ARP sends, also counted in `packets_failed`
+    arp_failed: AtomicU64,
     udp_packets: Arc<AtomicU64>,
     tcp_packets: Arc<AtomicU64>,
     icmp_packets: Arc<AtomicU64>,
     other_packets: Arc<AtomicU64>,
+    /// TCP packets per flag set, indexed by the flag bits
+    tcp_flag_packets: Box<[AtomicU64]>,
+    load_phase: AtomicU8,
+    interval_phases: AtomicU8,
+    burst_cycles: AtomicU64,
+    target_rate_bits: AtomicU64,
+    throttle_events: AtomicU64,
+    throttle_nanos: AtomicU64,
+    phases: Mutex<PhaseTracker>,
+    pacing: Mutex<BTreeMap<usize, PacingReport>>,
+    search: Mutex<Option<SearchReport>>,
+    quota: Option<SendQuota>,
     pub start_time: Instant,
     pub session_id: String,
     pub export_config: Option<Export>,
@@ -30,10 +64,22 @@ impl Default for Stats {
             packets_sent: Arc::new(AtomicU64::new(0)),
             bytes_sent: Arc::new(AtomicU64::new(0)),
             packets_failed: Arc::new(AtomicU64::new(0)),
+            arp_failed: AtomicU64::new(0),
             udp_packets: Arc::new(AtomicU64::new(0)),
             tcp_packets: Arc::new(AtomicU64::new(0)),
             icmp_packets: Arc::new(AtomicU64::new(0)),
             other_packets: Arc::new(AtomicU64::new(0)),
+            tcp_flag_packets: (0..=u8::MAX).map(|_| AtomicU64::new(0)).collect(),
+            load_phase: AtomicU8::new(LoadPhase::Steady as u8),
+            interval_phases: AtomicU8::new(0),
+            burst_cycles: AtomicU64::new(0),
+            target_rate_bits: AtomicU64::new(0),
+            throttle_events: AtomicU64::new(0),
+            throttle_nanos: AtomicU64::new(0),
+            phases: Mutex::new(PhaseTracker::default()),
+            pacing: Mutex::new(BTreeMap::new()),
+            search: Mutex::new(None),
+            quota: None,
             start_time: Instant::now(),
             session_id: format!("session_{}", Utc::now().timestamp()),
             export_config: None,
@@ -50,6 +96,42 @@ impl Stats {
         }
     }
 
+    /// Limit the run to `max_packets` sent packets and/or `max_bytes` sent bytes
+    pub fn with_quota(mut self, max_packets: Option<u64>, max_bytes: Option<u64>) -> Self {
+        self.quota = (max_packets.is_some() || max_bytes.is_some())
+            .then(|| SendQuota::new(max_packets, max_bytes));
+        self
+    }
+
+    /// Reserve quota for a packet of `bytes` bytes before sending it
+    pub fn reserve_quota(&self, bytes: u64) -> QuotaGrant {
+        self.quota.as_ref().map_or(QuotaGrant::Granted, |quota| quota.reserve(bytes))
+    }
+
+    /// Settle a reservation: sent packets use up the quota, failed ones return it
+    pub fn settle_quota(&self, bytes: u64, sent: bool) {
+        if let Some(ref quota) = self.quota {
+            if sent {
+                quota.commit(bytes);
+            } else {
+                quota.release(bytes);
+            }
+        }
+    }
+
+    /// Whether a packet or byte quota is configured and used up
+    pub fn quota_exhausted(&self) -> bool {
+        self.quota.as_ref().is_some_and(SendQuota::is_exhausted)
+    }
+
+    /// Wait until the quota is used up; never completes without a quota
+    pub async fn quota_reached(&self) {
+        match self.quota {
+            Some(ref quota) => quota.exhausted().await,
+            None => std::future::pending().await,
+        }
+    }
+
     /// Record a sent packet
     pub fn increment_sent(&self, bytes: u64, protocol: &str) {
         self.packets_sent.fetch_add(1, Ordering::Relaxed);
@@ -65,11 +147,30 @@ impl Stats {
         };
     }
 
+    /// Record a sent TCP packet with the given flags
+    pub fn increment_tcp_flags(&self, flags: TcpFlagSet) {
+        self.tcp_flag_packets[flags.bits() as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Sent TCP packets per flag set, for flag sets that were sent
+    pub fn tcp_flag_counts(&self) -> BTreeMap<TcpFlagSet, u64> {
+        self.tcp_flag_packets.iter()
+            .enumerate()
+            .map(|(bits, count)| (TcpFlagSet::from_bits(bits as u8), count.load(Ordering::Relaxed)))
+            .filter(|&(_, count)| count > 0)
+            .collect()
+    }
+
     /// Record a failed packet
     pub fn increment_failed(&self) {
         self.packets_failed.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Get failed ARP sends, a subset of `packets_failed`
+    pub fn arp_failed(&self) -> u64 {
+        self.arp_failed.load(Ordering::Relaxed)
+    }
+
     /// Get packets sent count
     pub fn packets_sent(&self) -> u64 {
         self.packets_sent.load(Ordering::Relaxed)
@@ -85,11 +186,150 @@ impl Stats {
         self.bytes_sent.load(Ordering::Relaxed)
     }
 
+    /// Record the burst phase observed by a worker
+    ///
+    /// Workers share an aligned schedule, so concurrent updates agree on the
+    /// phase; the cycle count only ever moves forward.
+    pub fn record_load_phase(&self, state: BurstState) {
+        self.load_phase.store(state.phase as u8, Ordering::Relaxed);
+        self.interval_phases.fetch_or(state.phase.mask(), Ordering::Relaxed);
+        self.burst_cycles.fetch_max(state.cycle + 1, Ordering::Relaxed);
+    }
+
+    /// Get the current load phase
+    pub fn load_phase(&self) -> LoadPhase {
+        LoadPhase::from_u8(self.load_phase.load(Ordering::Relaxed))
+    }
+
+    /// Take the set of phases observed since the previous call
+    ///
+    /// Used by the periodic reporter so each interval shows every phase it
+    /// overlapped, not just the phase at the sampling instant.
+    pub fn take_interval_phases(&self) -> Vec<LoadPhase> {
+        let mask = self.interval_phases.swap(0, Ordering::Relaxed);
+        LoadPhase::all()
+            .into_iter()
+            .filter(|phase| mask & phase.mask() != 0)
+            .collect()
+    }
+
+    /// Get the number of burst cycles started so far
+    pub fn burst_cycles(&self) -> u64 {
+        self.burst_cycles.load(Ordering::Relaxed)
+    }
+
+    /// Record the current per-thread target rate of a time-varying profile
+    pub fn set_target_rate(&self, rate: f64) {
+        self.target_rate_bits.store(rate.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Get the current per-thread target rate, if a rate profile is active
+    pub fn target_rate(&self) -> Option<f64> {
+        let rate = f64::from_bits(self.target_rate_bits.load(Ordering::Relaxed));
+        (rate > 0.0).then_some(rate)
+    }
+
+    /// Record a pause imposed by the global rate limiter
+    pub fn record_throttle(&self, extra_delay: Duration) {
+        self.throttle_events.fetch_add(1, Ordering::Relaxed);
+        self.throttle_nanos.fetch_add(extra_delay.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Get how many times workers were throttled by the global rate limiter
+    pub fn throttle_events(&self) -> u64 {
+        self.throttle_events.load(Ordering::Relaxed)
+    }
+
+    /// Get the total extra time workers spent waiting on the global rate limiter
+    pub fn throttled_time(&self) -> Duration {
+        Duration::from_nanos(self.throttle_nanos.load(Ordering::Relaxed))
+    }
+
+    /// Publish the latest pacing report of a worker
+    pub fn update_pacing(&self, report: PacingReport) {
+        self.pacing.lock().unwrap_or_else(|e| e.into_inner()).insert(report.worker_id, report);
+    }
+
+    /// Get the latest pacing report of every worker, ordered by worker ID
+    pub fn pacing_reports(&self) -> Vec<PacingReport> {
+        self.pacing.lock().unwrap_or_else(|e| e.into_inner()).values().cloned().collect()
+    }
+
+    /// Record a step of the adaptive rate search
+    pub fn record_search_step(&self, step: SearchStep) {
+        self.search.lock().unwrap_or_else(|e| e.into_inner())
+            .get_or_insert_with(SearchReport::default)
+            .steps.push(step);
+    }
+
+    /// Record the highest sustainable per-thread rate found by the search
+    pub fn set_sustainable_rate(&self, rate: Option<f64>, threads: usize) {
+        let mut search = self.search.lock().unwrap_or_else(|e| e.into_inner());
+        let report = search.get_or_insert_with(SearchReport::default);
+        report.sustainable_rate = rate;
+        report.sustainable_total_pps = rate.map(|rate| rate * threads as f64);
+    }
+
+    /// Get the adaptive rate search report, if a search ran
+    pub fn search_report(&self) -> Option<SearchReport> {
+        self.search.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Start a scenario phase, closing the previous one if still open
+    pub fn begin_phase(&self, name: &str) {
+        self.end_phase();
+
+        let mut phases = self.phases.lock().unwrap_or_else(|e| e.into_inner());
+        phases.current = Some(name.to_string());
+        phases.open = Some(PhaseStart {
+            name: name.to_string(),
+            started: Instant::now(),
+            packets_sent: self.packets_sent(),
+            packets_failed: self.packets_failed(),
+            bytes_sent: self.bytes_sent(),
+        });
+    }
+
+    /// Close the current scenario phase and record its statistics
+    ///
+    /// Returns `None` if no phase is open.
+    pub fn end_phase(&self) -> Option<PhaseStats> {
+        let mut phases = self.phases.lock().unwrap_or_else(|e| e.into_inner());
+        let start = phases.open.take()?;
+
+        let elapsed = start.started.elapsed().as_secs_f64();
+        let packets_sent = self.packets_sent().saturating_sub(start.packets_sent);
+        let bytes_sent = self.bytes_sent().saturating_sub(start.bytes_sent);
+        let phase = PhaseStats {
+            name: start.name,
+            duration_secs: elapsed,
+            packets_sent,
+            packets_failed: self.packets_failed().saturating_sub(start.packets_failed),
+            bytes_sent,
+            packets_per_second: if elapsed > 0.0 { packets_sent as f64 / elapsed } else { 0.0 },
+            megabits_per_second: if elapsed > 0.0 { (bytes_sent as f64 * 8.0) / (elapsed * 1_000_000.0) } else { 0.0 },
+        };
+
+        phases.completed.push(phase.clone());
+        Some(phase)
+    }
+
+    /// Get the name of the current (or last) scenario phase
+    pub fn current_phase(&self) -> Option<String> {
+        self.phases.lock().unwrap_or_else(|e| e.into_inner()).current.clone()
+    }
+
+    /// Get statistics of all completed scenario phases
+    pub fn completed_phases(&self) -> Vec<PhaseStats> {
+        self.phases.lock().unwrap_or_else(|e| e.into_inner()).completed.clone()
+    }
+
     /// Reset all statistics
     pub fn reset(&self) {
         self.packets_sent.store(0, Ordering::Relaxed);
         self.bytes_sent.store(0, Ordering::Relaxed);
         self.packets_failed.store(0, Ordering::Relaxed);
+        self.arp_failed.store(0, Ordering::Relaxed);
     }
 
     /// Print statistics to console
@@ -107,6 +347,54 @@ impl Stats {
             packets_sent, packets_failed, pps, mbps
         );
 
+        if self.load_phase() != LoadPhase::Steady {
+            println!("⚡ Burst cycles: {}", self.burst_cycles());
+        }
+
+        let tcp_flags = self.tcp_flag_counts();
+        if !tcp_flags.is_empty() {
+            let counts: Vec<String> = tcp_flags.iter()
+                .map(|(flags, count)| format!("{}: {}", flags, count))
+                .collect();
+            println!("🚩 TCP flags - {}", counts.join(", "));
+        }
+
+        if self.throttle_events() > 0 {
+            println!(
+                "🚦 Throttled: {} times, {:.2}s total (global rate limit)",
+                self.throttle_events(), self.throttled_time().as_secs_f64()
+            );
+        }
+
+        for report in self.pacing_reports() {
+            let accuracy = if report.requested_pps > 0.0 {
+                report.achieved_pps / report.requested_pps * 100.0
+            } else {
+                0.0
+            };
+            println!(
+                "⏱️  Worker {} - Requested: {:.1} pps, Achieved: {:.1} pps ({:.1}%), Gap p50/p99/max: {:.0}/{:.0}/{:.0} µs",
+                report.worker_id, report.requested_pps, report.achieved_pps, accuracy,
+                report.gap_p50_us, report.gap_p99_us, report.gap_max_us
+            );
+        }
+
+        for phase in self.completed_phases() {
+            println!(
+                "📍 Phase {} - Sent: {}, Failed: {}, Rate: {:.1} pps, {:.2} Mbps ({:.1}s)",
+                phase.name, phase.packets_sent, phase.packets_failed,
+                phase.packets_per_second, phase.megabits_per_second, phase.duration_secs
+            );
+        }
+
+        if let Some(search) = self.search_report()
+            && let Some(rate) = search.sustainable_rate {
+                println!(
+                    "🎯 Sustainable rate: {:.1} pps/thread ({:.1} pps total) after {} steps",
+                    rate, search.sustainable_total_pps.unwrap_or(rate), search.steps.len()
+                );
+            }
+
         if let Some(sys) = system_stats {
             println!(
                 "💻 System - CPU: {:.1}%, Memory: {:.1}%",
@@ -131,6 +419,25 @@ impl Stats {
         print!("\r\x1b[K📊 Stats - Sent: {}, Failed: {}, Rate: {:.1} pps, {:.2} Mbps",
             packets_sent, packets_failed, pps, mbps
         );
+
+        if let Some(phase) = self.current_phase() {
+            print!(" | 📍 Phase: {}", phase);
+        }
+
+        if let Some(target) = self.target_rate() {
+            print!(" | 🎯 Target: {:.0} pps/thread", target);
+        }
+
+        let phases = self.take_interval_phases();
+        if phases.iter().any(|phase| *phase != LoadPhase::Steady) {
+            let names: Vec<&str> = phases.iter().map(|phase| phase.name()).collect();
+            print!(" | ⚡ Phase: {} (cycle {})", names.join("/"), self.burst_cycles());
+        }
+        
+        let throttle_events = self.throttle_events();
+        if throttle_events > 0 {
+            print!(" | 🚦 Throttled: {}", throttle_events);
+        }
         
         if let Some(sys) = system_stats {
             print!(" | 💻 CPU: {:.1}%, Memory: {:.1}%",
@@ -159,11 +466,25 @@ impl Stats {
                 timestamp: Utc::now(),
                 packets_sent: self.packets_sent(),
                 packets_failed: self.packets_failed(),
+                arp_failed: self.arp_failed(),
                 bytes_sent: self.bytes_sent(),
                 duration_secs: elapsed,
                 packets_per_second: if elapsed > 0.0 { self.packets_sent() as f64 / elapsed } else { 0.0 },
                 megabits_per_second: if elapsed > 0.0 { (self.bytes_sent() as f64 * 8.0) / (elapsed * 1_000_000.0) } else { 0.0 },
                 protocol_breakdown,
+                tcp_flags: self.tcp_flag_counts()
+                    .into_iter()
+                    .map(|(flags, count)| (flags.to_string(), count))
+                    .collect(),
+                load_phase: self.load_phase().name().to_string(),
+                burst_cycles: self.burst_cycles(),
+                target_rate: self.target_rate(),
+                throttle_events: self.throttle_events(),
+                throttled_secs: self.throttled_time().as_secs_f64(),
+                phase: self.current_phase(),
+                phases: self.completed_phases(),
+                pacing: self.pacing_reports(),
+                search: self.search_report(),
                 system_stats: None,
             };
             
@@ -189,6 +510,13 @@ impl Stats {
         if icmp > 0 { self.icmp_packets.fetch_add(icmp, Ordering::Relaxed); }
         if other > 0 { self.other_packets.fetch_add(other, Ordering::Relaxed); }
     }
+
+    /// Submit batch TCP flag set counts from a worker
+    pub fn submit_tcp_flags_batch(&self, counts: &[(TcpFlagSet, u64)]) {
+        for &(flags, count) in counts {
+            self.tcp_flag_packets[flags.bits() as usize].fetch_add(count, Ordering::Relaxed);
+        }
+    }
 }
 
 /// Batch statistics for worker threads with auto-flush
@@ -197,10 +525,12 @@ pub struct BatchStats {
     packets_sent: u64,
     bytes_sent: u64,
     packets_failed: u64,
+    arp_failed: u64,
     udp_packets: u64,
     tcp_packets: u64,
     icmp_packets: u64,
     other_packets: u64,
+    tcp_flags: Vec<(TcpFlagSet, u64)>,
     batch_size: u64,
     count: u64,
 }
@@ -212,10 +542,12 @@ impl BatchStats {
             packets_sent: 0,
             bytes_sent: 0,
             packets_failed: 0,
+            arp_failed: 0,
             udp_packets: 0,
             tcp_packets: 0,
             icmp_packets: 0,
             other_packets: 0,
+            tcp_flags: Vec::new(),
             batch_size,
             count: 0,
         }
@@ -243,6 +575,12 @@ impl BatchStats {
     pub fn increment_failed(&mut self) {
         self.record_failure();
     }
+
+    /// Count a failed ARP send, in the failures and on its own
+    pub fn increment_arp_failed(&mut self) {
+        self.arp_failed += 1;
+        self.record_failure();
+    }
     
     pub fn increment_sent(&mut self, bytes: u64, protocol: &str) {
         self.record_success(bytes);
@@ -256,11 +594,20 @@ impl BatchStats {
         }
     }
     
+    /// Count a sent TCP packet under its flag set; call after `increment_sent`
+    pub fn record_tcp_flags(&mut self, flags: TcpFlagSet) {
+        match self.tcp_flags.iter_mut().find(|(existing, _)| *existing == flags) {
+            Some((_, count)) => *count += 1,
+            None => self.tcp_flags.push((flags, 1)),
+        }
+    }
+
     pub fn flush(&mut self) {
-        if self.count > 0 {
+        if self.count > 0 || !self.tcp_flags.is_empty() {
             self.stats.packets_sent.fetch_add(self.packets_sent, Ordering::Relaxed);
             self.stats.bytes_sent.fetch_add(self.bytes_sent, Ordering::Relaxed);
             self.stats.packets_failed.fetch_add(self.packets_failed, Ordering::Relaxed);
+            self.stats.arp_failed.fetch_add(self.arp_failed, Ordering::Relaxed);
             
             // Submit protocol-specific counts
             self.stats.submit_protocol_batch(
@@ -269,14 +616,17 @@ impl BatchStats {
                 self.icmp_packets,
                 self.other_packets,
             );
+            self.stats.submit_tcp_flags_batch(&self.tcp_flags);
             
             self.packets_sent = 0;
             self.bytes_sent = 0;
             self.packets_failed = 0;
+            self.arp_failed = 0;
             self.udp_packets = 0;
             self.tcp_packets = 0;
             self.icmp_packets = 0;
             self.other_packets = 0;
+            self.tcp_flags.clear();
             self.count = 0;
         }
     }
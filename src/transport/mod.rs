@@ -122,15 +122,16 @@ impl WorkerChannels {
     }
 
     /// Send Layer 2 packet
+    ///
+    /// Fails without a layer-2 channel, which needs a selected interface
     fn send_l2_packet(&mut self, packet_data: &[u8]) -> Result<()> {
-        if let Some(ref mut tx) = self.l2_sender {
-            match tx.send_to(packet_data, None) {
-                Some(Ok(())) => {}
-                Some(Err(e)) => return Err(RouterFloodError::Network(format!("Failed to send L2 packet: {}", e))),
-                None => return Err(RouterFloodError::Network("L2 send returned None".to_string())),
-            }
+        let tx = self.l2_sender.as_mut()
+            .ok_or_else(|| RouterFloodError::Network("No L2 channel: ARP needs an interface".to_string()))?;
+        match tx.send_to(packet_data, None) {
+            Some(Ok(())) => Ok(()),
+            Some(Err(e)) => Err(RouterFloodError::Network(format!("Failed to send L2 packet: {}", e))),
+            None => Err(RouterFloodError::Network("L2 send returned None".to_string())),
         }
-        Ok(())
     }
 }
 
@@ -16,9 +16,9 @@ impl ProtocolUtils {
     pub const fn packet_type_to_protocol_name(packet_type: PacketType) -> &'static str {
         match packet_type {
             PacketType::Udp => protocols::UDP,
-            PacketType::TcpSyn | PacketType::TcpAck | PacketType::TcpFin | PacketType::TcpRst => protocols::TCP,
+            PacketType::Tcp(_) => protocols::TCP,
             PacketType::Icmp => protocols::ICMP,
-            PacketType::Ipv6Udp | PacketType::Ipv6Tcp | PacketType::Ipv6Icmp => protocols::IPV6,
+            PacketType::Ipv6Udp | PacketType::Ipv6Tcp(_) | PacketType::Ipv6Icmp => protocols::IPV6,
             PacketType::Arp => protocols::ARP,
         }
     }
@@ -28,9 +28,9 @@ impl ProtocolUtils {
     pub const fn packet_type_to_protocol_index(packet_type: PacketType) -> ProtocolIndex {
         match packet_type {
             PacketType::Udp => ProtocolIndex::Udp,
-            PacketType::TcpSyn | PacketType::TcpAck | PacketType::TcpFin | PacketType::TcpRst => ProtocolIndex::Tcp,
+            PacketType::Tcp(_) => ProtocolIndex::Tcp,
             PacketType::Icmp => ProtocolIndex::Icmp,
-            PacketType::Ipv6Udp | PacketType::Ipv6Tcp | PacketType::Ipv6Icmp => ProtocolIndex::Ipv6,
+            PacketType::Ipv6Udp | PacketType::Ipv6Tcp(_) | PacketType::Ipv6Icmp => ProtocolIndex::Ipv6,
             PacketType::Arp => ProtocolIndex::Arp,
         }
     }
@@ -86,15 +86,14 @@ impl PacketTypeExt for PacketType {
     #[inline(always)]
     fn is_ipv4(&self) -> bool {
         matches!(self, 
-            PacketType::Udp | PacketType::TcpSyn | PacketType::TcpAck | 
-            PacketType::TcpFin | PacketType::TcpRst | PacketType::Icmp | PacketType::Arp
+            PacketType::Udp | PacketType::Tcp(_) | PacketType::Icmp | PacketType::Arp
         )
     }
     
     #[inline(always)]
     fn is_ipv6(&self) -> bool {
         matches!(self, 
-            PacketType::Ipv6Udp | PacketType::Ipv6Tcp | PacketType::Ipv6Icmp
+            PacketType::Ipv6Udp | PacketType::Ipv6Tcp(_) | PacketType::Ipv6Icmp
         )
     }
 }
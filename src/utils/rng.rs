@@ -20,6 +20,7 @@ pub enum RandomValueType {
     Window,      // 16-bit TCP window size
     FlowLabel,   // 20-bit IPv6 flow label
     Byte,        // 8-bit random byte for payload
+    Unit,        // Float in [0, 1) for sampling distributions
 }
 
 /// Optimized RNG with batched random value generation
@@ -32,6 +33,7 @@ pub struct BatchedRng {
     window_batch: VecDeque<u16>,
     flow_batch: VecDeque<u32>,
     byte_batch: VecDeque<u8>,
+    unit_batch: VecDeque<f64>,
     batch_size: usize,
 }
 
@@ -53,6 +55,7 @@ impl BatchedRng {
             window_batch: VecDeque::with_capacity(batch_size),
             flow_batch: VecDeque::with_capacity(batch_size),
             byte_batch: VecDeque::with_capacity(batch_size),
+            unit_batch: VecDeque::with_capacity(batch_size),
             batch_size,
         };
         
@@ -121,6 +124,15 @@ impl BatchedRng {
         self.byte_batch.pop_front().unwrap_or(0)
     }
 
+    /// Get a random float in [0, 1)
+    #[inline]
+    pub fn unit(&mut self) -> f64 {
+        if self.unit_batch.is_empty() {
+            self.replenish_unit_batch();
+        }
+        self.unit_batch.pop_front().unwrap_or(0.5)
+    }
+
     /// Generate random payload of specified size
     pub fn payload(&mut self, size: usize) -> Vec<u8> {
         // For large payloads, generate directly instead of using byte batches
@@ -171,6 +183,7 @@ impl BatchedRng {
         self.replenish_window_batch();
         self.replenish_flow_batch();
         self.replenish_byte_batch();
+        self.replenish_unit_batch();
     }
 
     /// Replenish port number batch
@@ -222,6 +235,13 @@ impl BatchedRng {
         }
     }
 
+    /// Replenish unit float batch
+    fn replenish_unit_batch(&mut self) {
+        for _ in 0..self.batch_size {
+            self.unit_batch.push_back(self.rng.r#gen::<f64>());
+        }
+    }
+
     /// Get remaining count for a specific batch (for testing/monitoring)
     pub fn batch_remaining(&self, value_type: RandomValueType) -> usize {
         match value_type {
@@ -232,6 +252,7 @@ impl BatchedRng {
             RandomValueType::Window => self.window_batch.len(),
             RandomValueType::FlowLabel => self.flow_batch.len(),
             RandomValueType::Byte => self.byte_batch.len(),
+            RandomValueType::Unit => self.unit_batch.len(),
         }
     }
 
@@ -277,7 +298,8 @@ impl BatchedRng {
         self.ttl_batch.len() < threshold ||
         self.window_batch.len() < threshold ||
         self.flow_batch.len() < threshold ||
-        self.byte_batch.len() < threshold
+        self.byte_batch.len() < threshold ||
+        self.unit_batch.len() < threshold
     }
 
     /// Proactively replenish batches that are running low
@@ -291,5 +313,6 @@ impl BatchedRng {
         if self.window_batch.len() < threshold { self.replenish_window_batch(); }
         if self.flow_batch.len() < threshold { self.replenish_flow_batch(); }
         if self.byte_batch.len() < threshold { self.replenish_byte_batch(); }
+        if self.unit_batch.len() < threshold { self.replenish_unit_batch(); }
     }
 }
@@ -0,0 +1,183 @@
+//! Adaptive rate search tests
+
+mod common;
+
+use common::{perfect_dry_run_config, start_workers, stop_workers};
+use router_flood::config::{validate_config, AdaptiveSearch, Config, HealthSignal, Scenario, ScenarioPhase};
+use router_flood::network::adaptive::{run_rate_search, search_settings, RateSearch};
+use router_flood::network::health::HealthMonitor;
+use router_flood::network::phase::PhaseControl;
+use router_flood::stats::Stats;
+use std::sync::Arc;
+
+fn search(start_rate: f64, max_rate: f64) -> AdaptiveSearch {
+    serde_yaml::from_str(&format!("start_rate: {}\nmax_rate: {}\n", start_rate, max_rate)).unwrap()
+}
+
+/// Drive a search against a target that copes with anything up to `capacity`
+fn simulate(search: &AdaptiveSearch, capacity: f64) -> (Option<f64>, Vec<f64>) {
+    let mut rate_search = RateSearch::new(search);
+    let mut tried = Vec::new();
+    while !rate_search.is_done() {
+        tried.push(rate_search.rate());
+        rate_search.record(rate_search.rate() <= capacity);
+        assert!(tried.len() < 100, "search did not converge: {:?}", tried);
+    }
+    (rate_search.sustainable_rate(), tried)
+}
+
+#[test]
+fn test_search_converges_on_capacity() {
+    let search = search(100.0, 100_000.0);
+    let (result, tried) = simulate(&search, 3000.0);
+
+    // Ramp 100 → 3200, then bisect
+    assert_eq!(&tried[..6], &[100.0, 200.0, 400.0, 800.0, 1600.0, 3200.0]);
+    let rate = result.unwrap();
+    assert!(rate <= 3000.0);
+    assert!((3000.0 - rate) / rate <= search.precision, "{} not within precision", rate);
+}
+
+#[test]
+fn test_search_stops_at_max_rate() {
+    let (result, tried) = simulate(&search(100.0, 1000.0), f64::INFINITY);
+    assert_eq!(result, Some(1000.0));
+    assert_eq!(tried, vec![100.0, 200.0, 400.0, 800.0, 1000.0]);
+}
+
+#[test]
+fn test_search_backs_off_below_start_rate() {
+    let (result, tried) = simulate(&search(100.0, 1000.0), 30.0);
+    assert_eq!(&tried[..3], &[100.0, 50.0, 25.0]);
+    assert!(result.unwrap() <= 30.0);
+
+    let (result, _) = simulate(&search(100.0, 1000.0), 0.5);
+    assert_eq!(result, None);
+}
+
+#[test]
+fn test_adaptive_config_parsing() {
+    let config: AdaptiveSearch = serde_yaml::from_str(
+        "start_rate: 50\n\
+         max_rate: 5000\n\
+         growth_factor: 1.5\n\
+         step_duration_ms: 2000\n\
+         health:\n  type: probe_rtt\n  max_rtt_ms: 20\n\
+         probe_port: 22\n",
+    )
+    .unwrap();
+
+    assert_eq!(config.growth_factor, 1.5);
+    assert_eq!(config.health, HealthSignal::ProbeRtt { max_rtt_ms: 20.0 });
+    assert!(config.health.uses_probes());
+    assert_eq!(config.probe_port, Some(22));
+
+    let defaults = search(1.0, 2.0);
+    assert_eq!(defaults.growth_factor, 2.0);
+    assert_eq!(defaults.step_duration_ms, 5000);
+    assert!(!defaults.health.uses_probes());
+}
+
+#[test]
+fn test_adaptive_validation() {
+    let mut config = Config { adaptive: Some(search(100.0, 1000.0)), ..Default::default() };
+    assert!(validate_config(&config).is_ok());
+
+    let invalid: Vec<fn(&mut AdaptiveSearch)> = vec![
+        |s| s.start_rate = 0.0,
+        |s| s.max_rate = 50.0,
+        |s| s.max_rate = 10_000_000.0,
+        |s| s.growth_factor = 1.0,
+        |s| s.step_duration_ms = 0,
+        |s| s.precision = 1.0,
+        |s| s.health = HealthSignal::FailureRate { max_ratio: 1.5 },
+        |s| s.health = HealthSignal::ResponseRatio { min_ratio: 0.0 },
+        |s| {
+            s.health = HealthSignal::ProbeRtt { max_rtt_ms: 10.0 };
+            s.probe_interval_ms = s.step_duration_ms;
+        },
+        |s| {
+            s.health = HealthSignal::ProbeRtt { max_rtt_ms: 10.0 };
+            s.probe_port = Some(0);
+        },
+    ];
+    for (index, mutate) in invalid.iter().enumerate() {
+        let mut adaptive = search(100.0, 1000.0);
+        mutate(&mut adaptive);
+        config.adaptive = Some(adaptive);
+        assert!(validate_config(&config).is_err(), "case {} accepted", index);
+    }
+
+    config.adaptive = Some(search(100.0, 1000.0));
+    config.scenario = Some(Scenario {
+        phases: vec![ScenarioPhase {
+            name: "warmup".to_string(),
+            duration_secs: 1,
+            packet_rate: None,
+            rate_profile: None,
+            protocol_mix: None,
+            ports: None,
+            payload_size: None,
+            payload_sizes: None,
+        }],
+    });
+    assert!(validate_config(&config).is_err());
+}
+
+async fn dry_run_search(start_rate: f64) -> Arc<Stats> {
+    let mut config = perfect_dry_run_config(1);
+    let mut adaptive = search(start_rate, start_rate * 4.0);
+    adaptive.step_duration_ms = 150;
+    config.adaptive = Some(adaptive.clone());
+
+    let stats = Arc::new(Stats::new(None));
+    let control = Arc::new(PhaseControl::new(search_settings(&config, adaptive.start_rate)));
+    let workers = start_workers(&config, &stats, &control);
+
+    run_rate_search(&config, &adaptive, config.target.ip.parse().unwrap(), Arc::clone(&stats), &control).await;
+
+    stop_workers(workers).await;
+    stats
+}
+
+#[tokio::test]
+async fn test_dry_run_search_reaches_max_rate() {
+    let stats = dry_run_search(2000.0).await;
+    let report = stats.search_report().unwrap();
+
+    let rates: Vec<f64> = report.steps.iter().map(|step| step.rate).collect();
+    assert_eq!(rates, vec![2000.0, 4000.0, 8000.0]);
+    assert!(report.steps.iter().all(|step| step.healthy && step.achieved_pps > 0.0), "{:?}", report.steps);
+    assert_eq!(report.sustainable_rate, Some(8000.0));
+    assert_eq!(report.sustainable_total_pps, Some(8000.0));
+}
+
+#[test]
+fn test_failures_mark_target_unhealthy() {
+    let stats = Arc::new(Stats::new(None));
+    let mut monitor = HealthMonitor::new(&search(100.0, 1000.0), Arc::clone(&stats), "192.168.1.1".parse().unwrap(), 80, true);
+    let record = |sent: usize, failed: usize| {
+        (0..sent).for_each(|_| stats.increment_sent(64, "udp"));
+        (0..failed).for_each(|_| stats.increment_failed());
+    };
+
+    // Failures before the window started do not count
+    record(0, 50);
+    monitor.start_window();
+    record(98, 2);
+    let sample = monitor.sample();
+    assert_eq!(sample.failure_ratio, 0.02);
+    assert!(!monitor.is_healthy(&sample), "{:?}", sample);
+
+    // The default threshold is 1%
+    monitor.start_window();
+    record(99, 1);
+    let sample = monitor.sample();
+    assert_eq!(sample.failure_ratio, 0.01);
+    assert!(monitor.is_healthy(&sample), "{:?}", sample);
+
+    monitor.start_window();
+    let sample = monitor.sample();
+    assert_eq!(sample.failure_ratio, 0.0);
+    assert!(monitor.is_healthy(&sample));
+}
@@ -0,0 +1,171 @@
+//! ARP variant and layer-2 accounting tests
+
+use pnet::packet::arp::{ArpOperations, ArpPacket};
+use pnet::packet::ethernet::{EtherTypes, EthernetPacket};
+use pnet::packet::Packet;
+use pnet::util::MacAddr;
+use router_flood::config::{validate_config, ArpConfig, ArpKind, Config};
+use router_flood::packet::{ArpOptions, PacketBuilder, PacketOptions, PacketSizes, PacketType, SourceAddresses};
+use router_flood::stats::BatchStats;
+use router_flood::Stats;
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+
+const INTERFACE_MAC: MacAddr = MacAddr(0x02, 0, 0, 0, 0, 0x01);
+
+fn builder(arp: ArpConfig) -> PacketBuilder {
+    let options = PacketOptions {
+        arp: ArpOptions::from_config(&arp).unwrap(),
+        source: SourceAddresses { mac: Some(INTERFACE_MAC), ..Default::default() },
+        ..Default::default()
+    };
+    PacketBuilder::with_options(&PacketSizes::from_config(&Config::default().attack), &options, Default::default())
+}
+
+fn only(kind: ArpKind) -> ArpConfig {
+    ArpConfig { types: BTreeMap::from([(kind, 1.0)]), ..Default::default() }
+}
+
+/// Build an ARP frame, returning its Ethernet destination and the ARP packet bytes
+fn build(builder: &mut PacketBuilder) -> (MacAddr, Vec<u8>) {
+    let target: IpAddr = "192.168.1.1".parse().unwrap();
+    let (frame, protocol) = builder.build_packet(PacketType::Arp, target, 0).unwrap();
+    assert_eq!(protocol, "ARP");
+    assert_eq!(frame.len(), 42);
+    let ethernet = EthernetPacket::new(&frame).unwrap();
+    assert_eq!(ethernet.get_ethertype(), EtherTypes::Arp);
+    (ethernet.get_destination(), ethernet.payload().to_vec())
+}
+
+#[test]
+fn test_request_by_default() {
+    let mut builder = builder(ArpConfig::default());
+    let (destination, arp) = build(&mut builder);
+    let arp = ArpPacket::new(&arp).unwrap();
+    assert_eq!(destination, MacAddr::broadcast());
+    assert_eq!(arp.get_operation(), ArpOperations::Request);
+    assert_eq!(arp.get_sender_hw_addr(), INTERFACE_MAC);
+    assert_eq!(arp.get_target_hw_addr(), MacAddr::zero());
+    assert_eq!(arp.get_target_proto_addr(), Ipv4Addr::new(192, 168, 1, 1));
+}
+
+#[test]
+fn test_reply_to_target_mac() {
+    let mut config = only(ArpKind::Reply);
+    config.target_mac = Some("02:aa:bb:cc:dd:ee".to_string());
+    config.sender_ip = Some(Ipv4Addr::new(192, 168, 1, 254));
+    let mut reply = builder(config);
+
+    let (destination, arp) = build(&mut reply);
+    let arp = ArpPacket::new(&arp).unwrap();
+    let target_mac = MacAddr::new(0x02, 0xaa, 0xbb, 0xcc, 0xdd, 0xee);
+    assert_eq!(destination, target_mac);
+    assert_eq!(arp.get_operation(), ArpOperations::Reply);
+    assert_eq!(arp.get_sender_proto_addr(), Ipv4Addr::new(192, 168, 1, 254));
+    assert_eq!(arp.get_target_hw_addr(), target_mac);
+    assert_eq!(arp.get_target_proto_addr(), Ipv4Addr::new(192, 168, 1, 1));
+
+    // Without a target MAC, replies are broadcast
+    let mut broadcast = builder(only(ArpKind::Reply));
+    assert_eq!(build(&mut broadcast).0, MacAddr::broadcast());
+}
+
+#[test]
+fn test_gratuitous_and_probe() {
+    let mut gratuitous = builder(only(ArpKind::Gratuitous));
+    let (destination, arp) = build(&mut gratuitous);
+    let arp = ArpPacket::new(&arp).unwrap();
+    assert_eq!(destination, MacAddr::broadcast());
+    assert_eq!(arp.get_operation(), ArpOperations::Request);
+    assert_eq!(arp.get_sender_proto_addr(), arp.get_target_proto_addr());
+
+    let mut probe = builder(only(ArpKind::Probe));
+    let (_, arp) = build(&mut probe);
+    let arp = ArpPacket::new(&arp).unwrap();
+    assert_eq!(arp.get_operation(), ArpOperations::Request);
+    assert_eq!(arp.get_sender_proto_addr(), Ipv4Addr::UNSPECIFIED);
+    assert_eq!(arp.get_target_proto_addr(), Ipv4Addr::new(192, 168, 1, 1));
+}
+
+#[test]
+fn test_field_overrides() {
+    let config = ArpConfig {
+        sender_mac: Some("02:11:22:33:44:55".to_string()),
+        target_ip: Some(Ipv4Addr::new(10, 0, 0, 9)),
+        ..Default::default()
+    };
+    let mut builder = builder(config);
+    let frame = builder.build_packet(PacketType::Arp, "192.168.1.1".parse().unwrap(), 0).unwrap().0;
+    let ethernet = EthernetPacket::new(&frame).unwrap();
+    let arp = ArpPacket::new(ethernet.payload()).unwrap();
+    let sender_mac = MacAddr::new(0x02, 0x11, 0x22, 0x33, 0x44, 0x55);
+    assert_eq!(ethernet.get_source(), sender_mac);
+    assert_eq!(arp.get_sender_hw_addr(), sender_mac);
+    assert_eq!(arp.get_target_proto_addr(), Ipv4Addr::new(10, 0, 0, 9));
+}
+
+#[test]
+fn test_weighted_variants() {
+    let mut builder = builder(ArpConfig {
+        types: BTreeMap::from([(ArpKind::Request, 1.0), (ArpKind::Reply, 1.0), (ArpKind::Probe, 0.0)]),
+        ..Default::default()
+    });
+    let mut counts: HashMap<u16, usize> = HashMap::new();
+    for _ in 0..2000 {
+        let (_, arp) = build(&mut builder);
+        *counts.entry(ArpPacket::new(&arp).unwrap().get_operation().0).or_insert(0) += 1;
+    }
+    assert_eq!(counts.len(), 2);
+    assert!((counts[&2] as f64 / 2000.0 - 0.5).abs() < 0.05);
+}
+
+#[test]
+fn test_arp_yaml_and_validation() {
+    let yaml = serde_yaml::to_string(&Config::default()).unwrap();
+    let yaml = yaml.replace(
+        "attack:\n",
+        "attack:\n  arp:\n    types:\n      gratuitous: 0.5\n      probe: 0.5\n    sender_ip: 192.168.1.50\n",
+    );
+    let mut config: Config = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(config.attack.arp.types[&ArpKind::Gratuitous], 0.5);
+    assert_eq!(config.attack.arp.sender_ip, Some(Ipv4Addr::new(192, 168, 1, 50)));
+    assert!(validate_config(&config).is_ok());
+
+    config.attack.arp.sender_mac = Some("02:00:00:00:00".to_string());
+    assert!(validate_config(&config).is_err());
+
+    config.attack.arp.sender_mac = None;
+    config.attack.arp.target_ip = Some(Ipv4Addr::new(8, 8, 8, 8));
+    assert!(validate_config(&config).is_err());
+
+    config.attack.arp.target_ip = None;
+    config.attack.arp.types.insert(ArpKind::Reply, -1.0);
+    assert!(validate_config(&config).is_err());
+}
+
+#[test]
+fn test_arp_ratio_in_protocol_mix() {
+    let mut config = Config::default();
+    config.target.protocol_mix.udp_ratio -= 0.05;
+    config.target.protocol_mix.arp_ratio = 0.05;
+    assert!(validate_config(&config).is_ok());
+
+    // Mixes written before ARP ratios existed still load
+    let yaml = serde_yaml::to_string(&Config::default()).unwrap().replace("    arp_ratio: 0.0\n", "");
+    let config: Config = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(config.target.protocol_mix.arp_ratio, 0.0);
+}
+
+#[test]
+fn test_arp_failures_counted_separately() {
+    let stats = Arc::new(Stats::new(None));
+    let mut batch = BatchStats::new(stats.clone(), 100);
+    batch.increment_failed();
+    batch.increment_arp_failed();
+    batch.increment_arp_failed();
+    batch.flush();
+
+    assert_eq!(stats.packets_failed(), 3);
+    assert_eq!(stats.arp_failed(), 2);
+}
@@ -1,6 +1,6 @@
 //! Custom test assertions and helpers
 
-use router_flood::error::{RouterFloodError, ValidationError};
+use router_flood::error::RouterFloodError;
 use std::fmt::Debug;
 
 /// Assert that a Result contains a validation error with specific field
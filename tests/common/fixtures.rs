@@ -1,5 +1,8 @@
 //! Test data generators and fixtures
 
+use router_flood::config::{FrameTable, WeightedSize};
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
 /// Generate a list of valid private IPv4 addresses for testing
@@ -142,4 +145,18 @@ pub fn packet_rates() -> Vec<u32> {
 /// Test payload sizes
 pub fn payload_sizes() -> Vec<usize> {
     vec![20, 64, 128, 256, 512, 1024, 1400]
-}
\ No newline at end of file
+}
+
+/// Frame table from `(size, weight)` pairs
+pub fn frame_table(table: &[(usize, f64)]) -> FrameTable {
+    FrameTable(table.iter().map(|&(size, weight)| WeightedSize { size, weight }).collect())
+}
+
+/// Count how often each value comes up in `draws` calls of `draw`
+pub fn tally<T: Hash + Eq>(draws: usize, mut draw: impl FnMut() -> T) -> HashMap<T, usize> {
+    let mut counts = HashMap::new();
+    for _ in 0..draws {
+        *counts.entry(draw()).or_insert(0) += 1;
+    }
+    counts
+}
@@ -1,10 +1,17 @@
 //! Common test utilities and helpers
 
+// Each test crate uses only some of the helpers
+#![allow(dead_code, unused_imports)]
+
 pub mod assertions;
 pub mod fixtures;
+pub mod packets;
 pub mod test_config;
+pub mod workers;
 
 // Re-export commonly used items
 pub use assertions::*;
 pub use fixtures::*;
-pub use test_config::*;
\ No newline at end of file
+pub use packets::*;
+pub use test_config::*;
+pub use workers::*;
\ No newline at end of file
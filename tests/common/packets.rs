@@ -0,0 +1,57 @@
+//! Packet building helpers for protocol tests
+
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::TcpPacket;
+use pnet::packet::udp::UdpPacket;
+use pnet::packet::Packet;
+use router_flood::config::{Config, SizeDistribution};
+use router_flood::packet::{PacketBuilder, PacketOptions, PacketSizes, PacketType};
+use std::net::IpAddr;
+
+/// Private target of the packet type's IP version
+pub fn target_for(packet_type: PacketType) -> IpAddr {
+    match packet_type {
+        PacketType::Ipv6Udp | PacketType::Ipv6Tcp(_) | PacketType::Ipv6Icmp => "fd00::1".parse().unwrap(),
+        _ => "192.168.1.1".parse().unwrap(),
+    }
+}
+
+/// Packet builder with the default packet sizes and the given options
+pub fn packet_builder(options: &PacketOptions) -> PacketBuilder {
+    PacketBuilder::with_options(&PacketSizes::from_config(&Config::default().attack), options, Default::default())
+}
+
+/// Packet sizes drawing every protocol from the same distribution
+pub fn uniform_sizes(distribution: SizeDistribution) -> PacketSizes {
+    PacketSizes { udp: distribution.clone(), icmp: distribution.clone(), tcp: distribution }
+}
+
+/// Build a packet to `port` of the private target of its IP version
+pub fn build_packet(builder: &mut PacketBuilder, packet_type: PacketType, port: u16) -> Vec<u8> {
+    builder.build_packet(packet_type, target_for(packet_type), port).unwrap().0
+}
+
+/// Payload of an IPv4 or IPv6 packet, extension headers included
+pub fn ip_payload(packet: &[u8]) -> Vec<u8> {
+    match packet[0] >> 4 {
+        4 => Ipv4Packet::new(packet).unwrap().payload().to_vec(),
+        _ => Ipv6Packet::new(packet).unwrap().payload().to_vec(),
+    }
+}
+
+/// Source port of a UDP or TCP packet of `packet_type`
+pub fn source_port(packet_type: PacketType, packet: &[u8]) -> u16 {
+    let segment = ip_payload(packet);
+    match packet_type {
+        PacketType::Udp | PacketType::Ipv6Udp => UdpPacket::new(&segment).unwrap().get_source(),
+        _ => TcpPacket::new(&segment).unwrap().get_source(),
+    }
+}
+
+/// Option kinds of a TCP packet over IPv4 or IPv6, in order
+pub fn tcp_option_kinds(packet: &[u8]) -> Vec<u8> {
+    let segment = ip_payload(packet);
+    let tcp = TcpPacket::new(&segment).unwrap();
+    tcp.get_options_iter().map(|option| option.get_number().0).collect()
+}
@@ -13,6 +13,16 @@ pub fn minimal_config() -> Config {
     config
 }
 
+/// Create a dry-run configuration without simulated failures, so that
+/// every packet a worker sends is counted as sent
+pub fn perfect_dry_run_config(threads: usize) -> Config {
+    let mut config = Config::default();
+    config.attack.threads = threads;
+    config.safety.dry_run = true;
+    config.safety.perfect_simulation = true;
+    config
+}
+
 /// Create a configuration with specified target IP
 pub fn config_with_ip(ip: &str) -> Config {
     let mut config = minimal_config();
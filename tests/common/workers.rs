@@ -0,0 +1,19 @@
+//! Helpers for tests that run workers in dry-run mode
+
+use router_flood::config::Config;
+use router_flood::network::phase::PhaseControl;
+use router_flood::network::worker_manager::Workers;
+use router_flood::stats::Stats;
+use std::sync::Arc;
+
+/// Start dry-run workers on `config`, sending to the configured target
+pub fn start_workers(config: &Config, stats: &Arc<Stats>, control: &Arc<PhaseControl>) -> Workers {
+    let target = config.target.ip.parse().unwrap();
+    Workers::new(config, Arc::clone(stats), Arc::clone(control), target, None, true).unwrap()
+}
+
+/// Stop workers and wait until they have flushed their statistics
+pub async fn stop_workers(workers: Workers) {
+    workers.stop();
+    workers.join_all().await.unwrap();
+}
@@ -0,0 +1,147 @@
+//! IPv4 fragmentation tests
+
+use pnet::packet::ipv4::{self, Ipv4Packet};
+use pnet::packet::Packet;
+use router_flood::config::{validate_config, Config, FragmentPattern, FragmentationConfig, SizeDistribution};
+use router_flood::packet::{Fragmenter, PacketBuilder, PacketSizes, PacketType};
+use std::collections::BTreeSet;
+use std::net::IpAddr;
+
+/// A UDP datagram with 1000 bytes of UDP header and payload
+fn datagram() -> Vec<u8> {
+    let mut sizes = PacketSizes::from_config(&Config::default().attack);
+    sizes.udp = SizeDistribution::Fixed { size: 992 };
+    let mut builder = PacketBuilder::with_sizes(&sizes, Default::default());
+    let target: IpAddr = "192.168.1.1".parse().unwrap();
+    builder.build_packet(PacketType::Udp, target, 53).unwrap().0
+}
+
+fn fragmenter(pattern: FragmentPattern) -> Fragmenter {
+    Fragmenter::new(&FragmentationConfig { pattern, fragment_size: 256, ratio: 1.0 })
+}
+
+/// Offset in bytes, more-fragments bit and payload of each fragment, after
+/// checking the fields copied from the datagram
+fn fragments(fragmenter: &mut Fragmenter, datagram: &[u8]) -> Vec<(usize, bool, Vec<u8>)> {
+    assert!(fragmenter.fragment(datagram));
+    let original = Ipv4Packet::new(datagram).unwrap();
+    fragmenter
+        .fragments()
+        .map(|fragment| {
+            let ip = Ipv4Packet::new(fragment).unwrap();
+            assert_eq!(ip.get_total_length() as usize, fragment.len());
+            assert_eq!(ip.get_identification(), original.get_identification());
+            assert_eq!(ip.get_source(), original.get_source());
+            assert_eq!(ip.get_checksum(), ipv4::checksum(&ip));
+            assert_eq!(ip.get_flags() & 0b010, 0, "don't fragment is cleared");
+            (ip.get_fragment_offset() as usize * 8, ip.get_flags() & 1 == 1, ip.payload().to_vec())
+        })
+        .collect()
+}
+
+/// Reassemble fragments, with earlier fragments winning overlaps
+fn reassemble(fragments: &[(usize, bool, Vec<u8>)]) -> Vec<u8> {
+    let len = fragments.iter().map(|(offset, _, data)| offset + data.len()).max().unwrap();
+    let mut payload = vec![None; len];
+    for (offset, _, data) in fragments {
+        for (index, &byte) in data.iter().enumerate() {
+            payload[offset + index].get_or_insert(byte);
+        }
+    }
+    payload.into_iter().map(Option::unwrap).collect()
+}
+
+#[test]
+fn test_in_order() {
+    let datagram = datagram();
+    let fragments = fragments(&mut fragmenter(FragmentPattern::InOrder), &datagram);
+
+    let layout: Vec<(usize, bool, usize)> = fragments.iter().map(|(offset, more, data)| (*offset, *more, data.len())).collect();
+    assert_eq!(layout, vec![(0, true, 256), (256, true, 256), (512, true, 256), (768, false, 232)]);
+    assert_eq!(reassemble(&fragments), &datagram[20..]);
+}
+
+#[test]
+fn test_reversed_and_random() {
+    let datagram = datagram();
+    let offsets = |fragments: Vec<(usize, bool, Vec<u8>)>| -> Vec<usize> {
+        fragments.into_iter().map(|(offset, _, _)| offset).collect()
+    };
+
+    let reversed = fragments(&mut fragmenter(FragmentPattern::Reversed), &datagram);
+    assert_eq!(reassemble(&reversed), &datagram[20..]);
+    assert_eq!(offsets(reversed), vec![768, 512, 256, 0]);
+
+    let mut random = fragmenter(FragmentPattern::Random);
+    let orders: BTreeSet<Vec<usize>> = (0..50).map(|_| offsets(fragments(&mut random, &datagram))).collect();
+    assert!(orders.len() > 1);
+    for order in orders {
+        assert_eq!(order.iter().copied().collect::<BTreeSet<_>>(), BTreeSet::from([0, 256, 512, 768]));
+    }
+}
+
+#[test]
+fn test_overlapping() {
+    let datagram = datagram();
+    let fragments = fragments(&mut fragmenter(FragmentPattern::Overlapping), &datagram);
+
+    let layout: Vec<(usize, usize)> = fragments.iter().map(|(offset, _, data)| (*offset, data.len())).collect();
+    assert_eq!(layout, vec![(0, 256), (248, 264), (504, 264), (760, 240)]);
+    // Repeated bytes are inverted, so reassembly policies disagree
+    let (offset, _, data) = &fragments[1];
+    for index in 0..8 {
+        assert_eq!(data[index], !datagram[20 + offset + index]);
+    }
+    assert_eq!(data[8..], datagram[20 + offset + 8..20 + offset + 264]);
+    assert_eq!(reassemble(&fragments), &datagram[20..]);
+}
+
+#[test]
+fn test_tiny_first() {
+    let datagram = datagram();
+    let fragments = fragments(&mut fragmenter(FragmentPattern::TinyFirst), &datagram);
+
+    assert_eq!(fragments[0].2.len(), 8);
+    assert_eq!(fragments[1].0, 8);
+    assert_eq!(fragments.len(), 5);
+    assert_eq!(reassemble(&fragments), &datagram[20..]);
+}
+
+#[test]
+fn test_missing_last() {
+    let datagram = datagram();
+    let fragments = fragments(&mut fragmenter(FragmentPattern::MissingLast), &datagram);
+
+    assert_eq!(fragments.len(), 3);
+    assert!(fragments.iter().all(|(_, more, _)| *more));
+}
+
+#[test]
+fn test_small_or_skipped_datagrams_sent_whole() {
+    let datagram = datagram();
+    let mut large = Fragmenter::new(&FragmentationConfig { fragment_size: 1000, ..Default::default() });
+    assert!(!large.fragment(&datagram));
+    assert_eq!(large.fragments().count(), 0);
+
+    let mut none = Fragmenter::new(&FragmentationConfig { ratio: 0.0, ..Default::default() });
+    assert!(!none.fragment(&datagram));
+}
+
+#[test]
+fn test_fragmentation_yaml_and_validation() {
+    let yaml = serde_yaml::to_string(&Config::default()).unwrap();
+    assert!(!yaml.contains("fragmentation"));
+    let yaml = yaml.replace("attack:\n", "attack:\n  fragmentation:\n    pattern: tiny_first\n    ratio: 0.5\n");
+    let mut config: Config = serde_yaml::from_str(&yaml).unwrap();
+    let fragmentation = config.attack.fragmentation.clone().unwrap();
+    assert_eq!(fragmentation.pattern, FragmentPattern::TinyFirst);
+    assert_eq!(fragmentation.fragment_size, 552);
+    assert!(validate_config(&config).is_ok());
+
+    config.attack.fragmentation.as_mut().unwrap().fragment_size = 100;
+    assert!(validate_config(&config).is_err());
+
+    config.attack.fragmentation.as_mut().unwrap().fragment_size = 512;
+    config.attack.fragmentation.as_mut().unwrap().ratio = 1.5;
+    assert!(validate_config(&config).is_err());
+}
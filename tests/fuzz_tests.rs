@@ -32,6 +32,9 @@ proptest! {
             tcp_rst_ratio,
             icmp_ratio,
             custom_ratio,
+            arp_ratio: 0.0,
+            ipv6: None,
+            tcp_flags: Default::default(),
         };
         
         let size_range = if min_size <= max_size {
@@ -45,11 +48,11 @@ proptest! {
         
         let packet_type = match packet_type_num % 8 {
             0 => PacketType::Udp,
-            1 => PacketType::TcpSyn,
-            2 => PacketType::TcpAck,
+            1 => PacketType::TCP_SYN,
+            2 => PacketType::TCP_ACK,
             3 => PacketType::Icmp,
             4 => PacketType::Ipv6Udp,
-            5 => PacketType::Ipv6Tcp,
+            5 => PacketType::IPV6_TCP_SYN,
             6 => PacketType::Ipv6Icmp,
             _ => PacketType::Arp,
         };
@@ -88,24 +91,44 @@ proptest! {
             target: router_flood::config::Target {
                 ip: "192.168.1.1".to_string(),
                 ports: vec![80, 443],
+                port_selection: Default::default(),
+                port_weights: Default::default(),
                 protocol_mix: ProtocolMix::default(),
                 interface: None,
             },
+            source: Default::default(),
             attack: LoadConfig {
                 threads,
                 packet_rate,
                 payload_size,
+                payload_sizes: Default::default(),
+                payload_pattern: Default::default(),
+                tcp_options: Default::default(),
+                tcp_payload: Default::default(),
+                icmp_types: Default::default(),
+                icmpv6_types: Default::default(),
+                ipv6_extension_headers: Default::default(),
+                fragmentation: None,
+                header_fields: Default::default(),
+                arp: Default::default(),
                 duration,
                 burst_mode,
                 burst_pattern: None,
+                rate_profile: Default::default(),
+                pacing: Default::default(),
+                timing: Default::default(),
+                max_packets: None,
+                max_bytes: None,
             },
             safety: Safety {
                 dry_run,
                 perfect_simulation: false,
                 rate_limit,
+                max_packets_per_second: None,
                 max_bandwidth_mbps: max_bandwidth,
                 allow_localhost,
                 require_confirmation,
+                allow_broadcast: false,
             },
             monitoring: router_flood::config::Monitoring {
                 enabled: true,
@@ -124,6 +147,8 @@ proptest! {
                 enabled: false,
                 log_file: "/tmp/audit.log".to_string(),
             },
+            scenario: None,
+            adaptive: None,
         };
         
         // Validation should handle any input gracefully
@@ -212,6 +237,9 @@ proptest! {
             tcp_rst_ratio: ratios[4],
             icmp_ratio: ratios[5],
             custom_ratio: ratios[6],
+            arp_ratio: 0.0,
+            ipv6: None,
+            tcp_flags: Default::default(),
         };
         
         // Create builder - normalization should handle any input
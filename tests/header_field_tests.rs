@@ -0,0 +1,206 @@
+//! Header field override rule tests
+
+mod common;
+
+use common::{build_packet, packet_builder};
+use pnet::packet::icmp::IcmpPacket;
+use pnet::packet::icmpv6::Icmpv6Packet;
+use pnet::packet::ipv4::{self, Ipv4Packet};
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::{self, TcpPacket};
+use pnet::packet::udp::UdpPacket;
+use pnet::packet::Packet;
+use router_flood::config::{parse_field_override, validate_config, Config, FieldRule, HeaderField, TcpFlagSet};
+use router_flood::packet::{HeaderFieldRules, PacketOptions, PacketType};
+use router_flood::utils::rng::BatchedRng;
+use std::collections::{BTreeMap, HashSet};
+
+#[test]
+fn test_parse_rules() {
+    let parse = |spec: &str| spec.parse::<FieldRule>().unwrap();
+
+    assert_eq!(parse("random"), FieldRule::Random);
+    assert_eq!(parse("1"), FieldRule::Fixed { value: 1 });
+    assert_eq!(parse("32-64"), FieldRule::Range { min: 32, max: 64 });
+    assert_eq!(parse("Sequential"), FieldRule::Sequential { start: 0, step: 1 });
+    assert_eq!(parse("sequential:100"), FieldRule::Sequential { start: 100, step: 1 });
+    assert_eq!(parse("sequential:100:4"), FieldRule::Sequential { start: 100, step: 4 });
+    assert_eq!(parse("1, 64,255"), FieldRule::List { values: vec![1, 64, 255] });
+
+    assert_eq!(
+        parse_field_override("ipv4-ttl=1").unwrap(),
+        (HeaderField::Ipv4Ttl, FieldRule::Fixed { value: 1 })
+    );
+    for invalid in ["", "ttl", "-1", "1-", "1,,2", "sequential:", "sequential:1:2:3"] {
+        assert!(invalid.parse::<FieldRule>().is_err(), "accepted {:?}", invalid);
+    }
+    for invalid in ["ipv4_ttl", "ttl=1", "ipv4_ttl=x"] {
+        assert!(parse_field_override(invalid).is_err(), "accepted {:?}", invalid);
+    }
+}
+
+#[test]
+fn test_yaml_and_validation() {
+    let yaml = serde_yaml::to_string(&Config::default()).unwrap();
+    assert!(!yaml.contains("header_fields"));
+    let yaml = yaml.replace(
+        "attack:\n",
+        "attack:\n  header_fields:\n    ipv4_ttl:\n      type: fixed\n      value: 1\n    ipv6_flow_label:\n      type: sequential\n",
+    );
+    let config: Config = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(config.attack.header_fields.get(&HeaderField::Ipv4Ttl), Some(&FieldRule::Fixed { value: 1 }));
+    assert_eq!(
+        config.attack.header_fields.get(&HeaderField::Ipv6FlowLabel),
+        Some(&FieldRule::Sequential { start: 0, step: 1 })
+    );
+    assert!(validate_config(&config).is_ok());
+
+    for (field, rule) in [
+        (HeaderField::Ipv4Ttl, FieldRule::Fixed { value: 256 }),
+        (HeaderField::Ipv4Flags, FieldRule::Range { min: 0, max: 8 }),
+        (HeaderField::TcpWindow, FieldRule::Range { min: 10, max: 5 }),
+        (HeaderField::Ipv6FlowLabel, FieldRule::Sequential { start: 0x10_0000, step: 1 }),
+        (HeaderField::Ipv4Id, FieldRule::Sequential { start: 0, step: 0 }),
+        (HeaderField::IcmpId, FieldRule::List { values: vec![] }),
+        (HeaderField::UdpSourcePort, FieldRule::List { values: vec![53, 70000] }),
+    ] {
+        let mut config = config.clone();
+        config.attack.header_fields.insert(field, rule.clone());
+        assert!(validate_config(&config).is_err(), "accepted {} {:?}", field, rule);
+    }
+}
+
+#[test]
+fn test_picker_modes() {
+    let mut rng = BatchedRng::new();
+    let mut picker = HeaderFieldRules::from_config(&BTreeMap::from([
+        (HeaderField::Ipv4Flags, FieldRule::Sequential { start: 5, step: 1 }),
+        (HeaderField::Ipv4Id, FieldRule::Sequential { start: 65530, step: 3 }),
+        (HeaderField::Ipv4Ttl, FieldRule::List { values: vec![1, 2, 3] }),
+        (HeaderField::TcpWindow, FieldRule::Range { min: 100, max: 103 }),
+        (HeaderField::Ipv6TrafficClass, FieldRule::Random),
+    ]))
+    .picker();
+
+    let flags: Vec<u8> = (0..5).map(|_| picker.u8(HeaderField::Ipv4Flags, &mut rng, |_| 0)).collect();
+    assert_eq!(flags, vec![5, 6, 7, 0, 1]);
+    let ids: Vec<u16> = (0..4).map(|_| picker.u16(HeaderField::Ipv4Id, &mut rng, |_| 0)).collect();
+    assert_eq!(ids, vec![65530, 65533, 0, 3]);
+    let ttls: Vec<u8> = (0..5).map(|_| picker.u8(HeaderField::Ipv4Ttl, &mut rng, |_| 0)).collect();
+    assert_eq!(ttls, vec![1, 2, 3, 1, 2]);
+
+    let windows: HashSet<u16> = (0..200).map(|_| picker.u16(HeaderField::TcpWindow, &mut rng, |_| 0)).collect();
+    assert_eq!(windows, (100..=103).collect());
+    let classes: HashSet<u8> = (0..2000).map(|_| picker.u8(HeaderField::Ipv6TrafficClass, &mut rng, |_| 0)).collect();
+    assert!(classes.len() > 200);
+
+    // Fields without a rule come from the default generator
+    assert_eq!(picker.u16(HeaderField::TcpUrgent, &mut rng, |_| 7), 7);
+}
+
+#[test]
+fn test_ipv4_fields() {
+    let header_fields = HeaderFieldRules::from_config(&BTreeMap::from([
+        (HeaderField::Ipv4Ttl, FieldRule::Fixed { value: 1 }),
+        (HeaderField::Ipv4Id, FieldRule::Sequential { start: 1000, step: 1 }),
+        (HeaderField::Ipv4Tos, FieldRule::Fixed { value: 0xb9 }),
+        (HeaderField::Ipv4Flags, FieldRule::Fixed { value: 2 }),
+        (HeaderField::UdpSourcePort, FieldRule::Fixed { value: 5353 }),
+    ]));
+    let mut builder = packet_builder(&PacketOptions { header_fields, ..Default::default() });
+
+    for packet_type in [PacketType::Udp, PacketType::TCP_SYN, PacketType::Icmp] {
+        let packets: Vec<Vec<u8>> = (0..3).map(|_| build_packet(&mut builder, packet_type, 80)).collect();
+        for (index, packet) in packets.iter().enumerate() {
+            let ip = Ipv4Packet::new(packet).unwrap();
+            assert_eq!(ip.get_ttl(), 1);
+            assert_eq!(ip.get_identification(), 1000 + index as u16, "{:?}", packet_type);
+            assert_eq!((ip.get_dscp() << 2) | ip.get_ecn(), 0xb9);
+            assert_eq!(ip.get_flags(), 2);
+            assert_eq!(ip.get_checksum(), ipv4::checksum(&ip));
+        }
+    }
+
+    let udp = build_packet(&mut builder, PacketType::Udp, 80);
+    assert_eq!(UdpPacket::new(Ipv4Packet::new(&udp).unwrap().payload()).unwrap().get_source(), 5353);
+}
+
+#[test]
+fn test_tcp_fields() {
+    let header_fields = HeaderFieldRules::from_config(&BTreeMap::from([
+        (HeaderField::TcpWindow, FieldRule::Fixed { value: 0 }),
+        (HeaderField::TcpAck, FieldRule::Fixed { value: 12345 }),
+        (HeaderField::TcpSequence, FieldRule::Sequential { start: u32::MAX - 1, step: 1 }),
+        (HeaderField::TcpSourcePort, FieldRule::List { values: vec![1000, 2000] }),
+    ]));
+    let mut builder = packet_builder(&PacketOptions { header_fields, ..Default::default() });
+
+    // The rule writes the acknowledgment number even without ACK
+    let mut expected_sequence = u32::MAX - 1;
+    for (index, source_port) in [1000, 2000, 1000].into_iter().enumerate() {
+        let packet = build_packet(&mut builder, PacketType::TCP_SYN, 80);
+        let ip = Ipv4Packet::new(&packet).unwrap();
+        let segment = TcpPacket::new(ip.payload()).unwrap();
+        assert_eq!(segment.get_window(), 0);
+        assert_eq!(segment.get_acknowledgement(), 12345);
+        assert_eq!(segment.get_sequence(), expected_sequence, "segment {}", index);
+        assert_eq!(segment.get_source(), source_port);
+        assert_eq!(segment.get_urgent_ptr(), 0);
+        assert_eq!(segment.get_checksum(), tcp::ipv4_checksum(&segment, &ip.get_source(), &ip.get_destination()));
+        expected_sequence = expected_sequence.wrapping_add(1);
+    }
+
+    let packet = build_packet(&mut builder, PacketType::Ipv6Tcp(TcpFlagSet::SYN), 80);
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    let segment = TcpPacket::new(ip.payload()).unwrap();
+    assert_eq!(segment.get_window(), 0);
+    assert_eq!(segment.get_acknowledgement(), 12345);
+    assert_eq!(segment.get_checksum(), tcp::ipv6_checksum(&segment, &ip.get_source(), &ip.get_destination()));
+}
+
+#[test]
+fn test_ipv6_fields() {
+    let header_fields = HeaderFieldRules::from_config(&BTreeMap::from([
+        (HeaderField::Ipv6FlowLabel, FieldRule::Sequential { start: 0xf_fffe, step: 1 }),
+        (HeaderField::Ipv6HopLimit, FieldRule::Fixed { value: 1 }),
+        (HeaderField::Ipv6TrafficClass, FieldRule::Fixed { value: 0x2e }),
+    ]));
+    let mut builder = packet_builder(&PacketOptions { header_fields, ..Default::default() });
+
+    for packet_type in [PacketType::Ipv6Udp, PacketType::IPV6_TCP_SYN, PacketType::Ipv6Icmp] {
+        let labels: Vec<u32> = (0..3)
+            .map(|_| {
+                let packet = build_packet(&mut builder, packet_type, 80);
+                let ip = Ipv6Packet::new(&packet).unwrap();
+                assert_eq!(ip.get_hop_limit(), 1);
+                assert_eq!(ip.get_traffic_class(), 0x2e);
+                ip.get_flow_label()
+            })
+            .collect();
+        assert_eq!(labels, vec![0xf_fffe, 0xf_ffff, 0], "{:?}", packet_type);
+    }
+}
+
+#[test]
+fn test_icmp_query_fields() {
+    let header_fields = HeaderFieldRules::from_config(&BTreeMap::from([
+        (HeaderField::IcmpId, FieldRule::Fixed { value: 0x1234 }),
+        (HeaderField::IcmpSequence, FieldRule::Sequential { start: 1, step: 1 }),
+    ]));
+    let mut builder = packet_builder(&PacketOptions { header_fields, ..Default::default() });
+
+    for sequence in 1..=3u16 {
+        let packet = build_packet(&mut builder, PacketType::Icmp, 80);
+        let icmp = IcmpPacket::new(Ipv4Packet::new(&packet).unwrap().payload()).unwrap().payload().to_vec();
+        assert_eq!(&icmp[0..4], &[0x12, 0x34, 0, sequence as u8]);
+
+        let packet = build_packet(&mut builder, PacketType::Ipv6Icmp, 80);
+        let icmp = Icmpv6Packet::new(Ipv6Packet::new(&packet).unwrap().payload()).unwrap().payload().to_vec();
+        assert_eq!(&icmp[0..4], &[0x12, 0x34, 0, sequence as u8]);
+    }
+
+    // Without rules the echo identifier and sequence stay zero
+    let packet = build_packet(&mut packet_builder(&PacketOptions::default()), PacketType::Icmp, 80);
+    let icmp = IcmpPacket::new(Ipv4Packet::new(&packet).unwrap().payload()).unwrap().payload().to_vec();
+    assert_eq!(&icmp[0..4], &[0, 0, 0, 0]);
+}
@@ -0,0 +1,172 @@
+//! ICMP message type tests
+
+use pnet::packet::icmp::{self, IcmpPacket};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::{self as ipv4, Ipv4Packet};
+use pnet::packet::udp::UdpPacket;
+use pnet::packet::Packet;
+use router_flood::config::{validate_config, Config, IcmpKind, IcmpMessage};
+use router_flood::packet::{IcmpMessageMix, PacketBuilder, PacketOptions, PacketSizes, PacketType};
+use std::collections::{BTreeMap, HashMap};
+
+fn message(spec: &str) -> IcmpMessage {
+    spec.parse().unwrap()
+}
+
+fn builder(types: &[(&str, f64)]) -> PacketBuilder {
+    let types: BTreeMap<IcmpMessage, f64> = types.iter().map(|&(spec, weight)| (message(spec), weight)).collect();
+    let options = PacketOptions { icmp_messages: IcmpMessageMix::from_config(&types), ..Default::default() };
+    PacketBuilder::with_options(&PacketSizes::from_config(&Config::default().attack), &options, Default::default())
+}
+
+/// Build an ICMP packet, checking both checksums
+fn build(builder: &mut PacketBuilder) -> Vec<u8> {
+    let (packet, protocol) = builder.build_packet(PacketType::Icmp, "192.168.1.1".parse().unwrap(), 53).unwrap();
+    assert_eq!(protocol, "ICMP");
+    let ip = Ipv4Packet::new(&packet).unwrap();
+    assert_eq!(ip.get_total_length() as usize, packet.len());
+    assert_eq!(ip.get_checksum(), ipv4::checksum(&ip));
+    let message = IcmpPacket::new(ip.payload()).unwrap();
+    assert_eq!(message.get_checksum(), icmp::checksum(&message));
+    packet
+}
+
+#[test]
+fn test_message_parsing() {
+    assert_eq!(message("timestamp"), IcmpMessage::new(IcmpKind::Timestamp));
+    assert_eq!(message("Dest-Unreachable:3"), IcmpMessage::with_code(IcmpKind::DestUnreachable, 3));
+    assert_eq!(message("port_unreachable"), message("dest_unreachable:3"));
+    assert_eq!(message("frag_needed"), message("dest_unreachable:4"));
+    assert_eq!(message("ttl_exceeded"), message("time_exceeded:0"));
+    assert_eq!(message("parameter_problem:2").to_string(), "parameter_problem:2");
+    assert_eq!(message("port_unreachable").to_string(), "dest_unreachable:3");
+
+    for invalid in ["", "echo_reply", "dest_unreachable:16", "time_exceeded:2", "timestamp:1", "parameter_problem:x"] {
+        assert!(invalid.parse::<IcmpMessage>().is_err(), "accepted {:?}", invalid);
+    }
+}
+
+#[test]
+fn test_echo_request_by_default() {
+    let mut builder = builder(&[]);
+    for _ in 0..20 {
+        let packet = build(&mut builder);
+        let ip = Ipv4Packet::new(&packet).unwrap();
+        let message = IcmpPacket::new(ip.payload()).unwrap();
+        assert_eq!(message.get_icmp_type().0, 8);
+        assert_eq!(message.get_icmp_code().0, 0);
+        assert!((36..=84).contains(&packet.len()));
+    }
+}
+
+#[test]
+fn test_query_messages() {
+    let mut timestamp = builder(&[("timestamp", 1.0)]);
+    let packet = build(&mut timestamp);
+    assert_eq!(packet.len(), 20 + 20);
+    let ip = Ipv4Packet::new(&packet).unwrap();
+    let message = IcmpPacket::new(ip.payload()).unwrap();
+    assert_eq!(message.get_icmp_type().0, 13);
+    let originate = u32::from_be_bytes(message.payload()[4..8].try_into().unwrap());
+    assert!(originate < 86_400_000);
+    assert_eq!(&message.payload()[8..16], &[0; 8]);
+
+    let mut mask = builder(&[("address_mask", 1.0)]);
+    let packet = build(&mut mask);
+    assert_eq!(packet.len(), 20 + 12);
+    assert_eq!(IcmpPacket::new(Ipv4Packet::new(&packet).unwrap().payload()).unwrap().get_icmp_type().0, 17);
+}
+
+#[test]
+fn test_error_messages_quote_datagram() {
+    let cases = [
+        ("port_unreachable", 3, Some(3)),
+        ("dest_unreachable", 3, None),
+        ("ttl_exceeded", 11, Some(0)),
+        ("time_exceeded", 11, None),
+        ("parameter_problem:0", 12, Some(0)),
+    ];
+
+    for (spec, icmp_type, code) in cases {
+        let mut builder = builder(&[(spec, 1.0)]);
+        for _ in 0..20 {
+            let packet = build(&mut builder);
+            assert_eq!(packet.len(), 20 + 8 + 28, "{}", spec);
+
+            let ip = Ipv4Packet::new(&packet).unwrap();
+            let message = IcmpPacket::new(ip.payload()).unwrap();
+            assert_eq!(message.get_icmp_type().0, icmp_type);
+            match code {
+                Some(code) => assert_eq!(message.get_icmp_code().0, code, "{}", spec),
+                None => assert!(message.get_icmp_code().0 <= 15),
+            }
+
+            let quoted = Ipv4Packet::new(&message.payload()[4..]).unwrap();
+            assert_eq!(quoted.get_version(), 4);
+            assert_eq!(quoted.get_checksum(), ipv4::checksum(&quoted));
+            assert_eq!(quoted.get_source(), ip.get_destination());
+            assert_eq!(quoted.get_destination(), ip.get_source());
+            assert_eq!(quoted.get_next_level_protocol(), IpNextHeaderProtocols::Udp);
+            let udp = UdpPacket::new(quoted.payload()).unwrap();
+            assert_eq!(udp.get_source(), 53);
+
+            if spec == "ttl_exceeded" {
+                assert_eq!(quoted.get_ttl(), 1);
+            }
+            if spec == "parameter_problem:0" {
+                assert!(message.payload()[0] < 20);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_frag_needed_reports_mtu() {
+    let mut builder = builder(&[("frag_needed", 1.0)]);
+    let packet = build(&mut builder);
+    let ip = Ipv4Packet::new(&packet).unwrap();
+    let message = IcmpPacket::new(ip.payload()).unwrap();
+    assert_eq!(message.get_icmp_code().0, 4);
+
+    let mtu = u16::from_be_bytes(message.payload()[2..4].try_into().unwrap());
+    assert!([576, 1280, 1400, 1492].contains(&mtu));
+    let quoted = Ipv4Packet::new(&message.payload()[4..]).unwrap();
+    assert_eq!(quoted.get_flags(), 2);
+}
+
+#[test]
+fn test_weighted_types() {
+    let mut builder = builder(&[("echo_request", 3.0), ("timestamp", 1.0), ("ttl_exceeded", 0.0)]);
+    let mut counts: HashMap<u8, usize> = HashMap::new();
+    for _ in 0..4000 {
+        let packet = build(&mut builder);
+        let icmp_type = IcmpPacket::new(Ipv4Packet::new(&packet).unwrap().payload()).unwrap().get_icmp_type().0;
+        *counts.entry(icmp_type).or_insert(0) += 1;
+    }
+
+    assert_eq!(counts.len(), 2);
+    assert!((counts[&13] as f64 / 4000.0 - 0.25).abs() < 0.03);
+}
+
+#[test]
+fn test_icmp_types_yaml_and_validation() {
+    let yaml = serde_yaml::to_string(&Config::default()).unwrap();
+    assert!(!yaml.contains("icmp_types"));
+
+    let yaml = yaml.replace(
+        "attack:\n",
+        "attack:\n  icmp_types:\n    echo_request: 0.5\n    timestamp: 0.2\n    \"dest_unreachable:3\": 0.3\n",
+    );
+    let mut config: Config = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(config.attack.icmp_types.len(), 3);
+    assert_eq!(config.attack.icmp_types[&message("port_unreachable")], 0.3);
+    assert!(validate_config(&config).is_ok());
+
+    config.attack.icmp_types.insert(message("timestamp"), -1.0);
+    assert!(validate_config(&config).is_err());
+
+    config.attack.icmp_types = BTreeMap::from([(message("timestamp"), 0.0)]);
+    assert!(validate_config(&config).is_err());
+
+    assert!(serde_yaml::from_str::<BTreeMap<IcmpMessage, f64>>("\"time_exceeded:5\": 1.0").is_err());
+}
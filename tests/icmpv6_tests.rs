@@ -0,0 +1,137 @@
+//! ICMPv6 Neighbor Discovery and MLD tests
+
+use pnet::packet::icmpv6::{self, Icmpv6Packet};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::util::MacAddr;
+use router_flood::config::{validate_config, Config, Icmpv6Kind};
+use router_flood::packet::{
+    Icmpv6MessageMix, PacketBuilder, PacketOptions, PacketSizes, PacketType, SourceAddresses,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, Ipv6Addr};
+
+const TARGET: &str = "2001:db8::12:3456";
+
+fn builder(types: &[(Icmpv6Kind, f64)]) -> PacketBuilder {
+    let types: BTreeMap<Icmpv6Kind, f64> = types.iter().copied().collect();
+    let options = PacketOptions {
+        icmpv6_messages: Icmpv6MessageMix::from_config(&types),
+        source: SourceAddresses { mac: Some(MacAddr::new(0x02, 0, 0, 0, 0, 0x42)), ..Default::default() },
+        ..Default::default()
+    };
+    PacketBuilder::with_options(&PacketSizes::from_config(&Config::default().attack), &options, Default::default())
+}
+
+/// Build an IPv6 ICMP packet, returning it with the offset of the ICMPv6 message
+fn build(builder: &mut PacketBuilder) -> (Vec<u8>, usize) {
+    let target: IpAddr = TARGET.parse().unwrap();
+    let (packet, _) = builder.build_packet(PacketType::Ipv6Icmp, target, 0).unwrap();
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    assert_eq!(ip.get_payload_length() as usize, packet.len() - 40);
+
+    let offset = if ip.get_next_header() == IpNextHeaderProtocols::Hopopt { 48 } else { 40 };
+    let message = Icmpv6Packet::new(&packet[offset..]).unwrap();
+    assert_eq!(message.get_checksum(), icmpv6::checksum(&message, &ip.get_source(), &ip.get_destination()));
+    (packet, offset)
+}
+
+#[test]
+fn test_echo_request_by_default() {
+    let mut builder = builder(&[]);
+    let (packet, offset) = build(&mut builder);
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    assert_eq!(offset, 40);
+    assert_eq!(ip.get_next_header(), IpNextHeaderProtocols::Icmpv6);
+    assert_eq!(ip.get_destination(), TARGET.parse::<Ipv6Addr>().unwrap());
+    assert_eq!(packet[40], 128);
+}
+
+#[test]
+fn test_neighbor_solicitation() {
+    let mut builder = builder(&[(Icmpv6Kind::NeighborSolicitation, 1.0)]);
+    let (packet, _) = build(&mut builder);
+    assert_eq!(packet.len(), 40 + 32);
+
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    assert_eq!(ip.get_hop_limit(), 255);
+    assert_eq!(ip.get_destination(), "ff02::1:ff12:3456".parse::<Ipv6Addr>().unwrap());
+    assert_eq!(packet[40], 135);
+    assert_eq!(packet[41], 0);
+    assert_eq!(&packet[48..64], &TARGET.parse::<Ipv6Addr>().unwrap().octets());
+    assert_eq!(&packet[64..72], &[1, 1, 0x02, 0, 0, 0, 0, 0x42]);
+}
+
+#[test]
+fn test_router_solicitation() {
+    let mut builder = builder(&[(Icmpv6Kind::RouterSolicitation, 1.0)]);
+    let (packet, _) = build(&mut builder);
+    assert_eq!(packet.len(), 40 + 16);
+
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    assert_eq!(ip.get_hop_limit(), 255);
+    assert_eq!(ip.get_destination(), "ff02::2".parse::<Ipv6Addr>().unwrap());
+    assert_eq!(packet[40], 133);
+    assert_eq!(&packet[48..56], &[1, 1, 0x02, 0, 0, 0, 0, 0x42]);
+}
+
+#[test]
+fn test_mld_report() {
+    let mut builder = builder(&[(Icmpv6Kind::MldReport, 1.0)]);
+    let (packet, offset) = build(&mut builder);
+    assert_eq!(offset, 48);
+    assert_eq!(packet.len(), 40 + 8 + 28);
+
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    assert_eq!(ip.get_hop_limit(), 1);
+    assert_eq!(ip.get_destination(), "ff02::16".parse::<Ipv6Addr>().unwrap());
+    // Hop-by-Hop header with a Router Alert option for MLD
+    assert_eq!(&packet[40..48], &[58, 0, 5, 2, 0, 0, 1, 0]);
+
+    let message = &packet[48..];
+    assert_eq!(message[0], 143);
+    assert_eq!(u16::from_be_bytes([message[6], message[7]]), 1);
+    assert_eq!(message[8], 4);
+    assert_eq!(u16::from_be_bytes([message[10], message[11]]), 0);
+    assert_eq!(&message[12..14], &[0xff, 0x05]);
+}
+
+#[test]
+fn test_weighted_types() {
+    let mut builder = builder(&[
+        (Icmpv6Kind::EchoRequest, 1.0),
+        (Icmpv6Kind::NeighborSolicitation, 1.0),
+        (Icmpv6Kind::RouterSolicitation, 0.0),
+        (Icmpv6Kind::MldReport, 2.0),
+    ]);
+    let mut counts: HashMap<u8, usize> = HashMap::new();
+    for _ in 0..4000 {
+        let (packet, offset) = build(&mut builder);
+        *counts.entry(packet[offset]).or_insert(0) += 1;
+    }
+
+    assert_eq!(counts.len(), 3);
+    assert!((counts[&143] as f64 / 4000.0 - 0.5).abs() < 0.03);
+}
+
+#[test]
+fn test_icmpv6_types_yaml_and_validation() {
+    let yaml = serde_yaml::to_string(&Config::default()).unwrap();
+    assert!(!yaml.contains("icmpv6_types"));
+
+    let yaml = yaml.replace(
+        "attack:\n",
+        "attack:\n  icmpv6_types:\n    neighbor_solicitation: 0.7\n    mld_report: 0.3\n",
+    );
+    let mut config: Config = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(config.attack.icmpv6_types[&Icmpv6Kind::NeighborSolicitation], 0.7);
+    assert!(validate_config(&config).is_ok());
+
+    config.attack.icmpv6_types.insert(Icmpv6Kind::RouterSolicitation, f64::NAN);
+    assert!(validate_config(&config).is_err());
+
+    config.attack.icmpv6_types = BTreeMap::from([(Icmpv6Kind::MldReport, 0.0)]);
+    assert!(validate_config(&config).is_err());
+
+    assert!(serde_yaml::from_str::<BTreeMap<Icmpv6Kind, f64>>("router_advertisement: 1.0").is_err());
+}
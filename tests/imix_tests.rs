@@ -0,0 +1,105 @@
+//! IMIX frame size tests
+
+mod common;
+
+use common::{build_packet, frame_table, tally, uniform_sizes};
+use router_flood::cli::parse_imix;
+use router_flood::config::{validate_config, Config, FrameTable, ImixProfile, PayloadSizes, SizeDistribution, TcpFlagSet};
+use router_flood::packet::{PacketBuilder, PacketType, PayloadSizer};
+use router_flood::utils::rng::BatchedRng;
+
+#[test]
+fn test_frame_table_parsing() {
+    let table: FrameTable = "64:7, 594:4,1518:1".parse().unwrap();
+    assert_eq!(table, frame_table(&[(64, 7.0), (594, 4.0), (1518, 1.0)]));
+    assert_eq!(table.to_string(), "64:7, 594:4, 1518:1");
+    assert_eq!(table.to_string().parse::<FrameTable>().unwrap(), table);
+
+    for invalid in ["64", "64:x", "abc:1", "64:7,,1518:1"] {
+        assert!(invalid.parse::<FrameTable>().is_err(), "accepted {:?}", invalid);
+    }
+}
+
+#[test]
+fn test_presets() {
+    assert_eq!(ImixProfile::Simple.frames(), frame_table(&[(64, 7.0), (594, 4.0), (1518, 1.0)]));
+    assert_eq!(ImixProfile::Cisco.frames(), frame_table(&[(64, 7.0), (570, 4.0), (1518, 1.0)]));
+
+    assert_eq!(parse_imix("Cisco").unwrap(), SizeDistribution::imix(ImixProfile::Cisco));
+    assert_eq!(
+        parse_imix("128:1,1024:3").unwrap(),
+        SizeDistribution::Imix { profile: None, frames: Some(frame_table(&[(128, 1.0), (1024, 3.0)])) }
+    );
+    assert!(parse_imix("trimodal").is_err());
+}
+
+#[test]
+fn test_imix_yaml() {
+    let sizes: PayloadSizes = serde_yaml::from_str(
+        "default:\n  type: imix\n  profile: simple\n\
+         udp:\n  type: imix\n  frames: \"64:7, 1518:1\"\n",
+    )
+    .unwrap();
+
+    assert_eq!(sizes.default, Some(SizeDistribution::imix(ImixProfile::Simple)));
+    assert_eq!(sizes.udp.unwrap().frame_sizes(), Some(frame_table(&[(64, 7.0), (1518, 1.0)])));
+}
+
+#[test]
+fn test_imix_validation() {
+    let mut config = Config::default();
+    config.attack.payload_sizes.default = Some(SizeDistribution::imix(ImixProfile::Cisco));
+    assert!(validate_config(&config).is_ok());
+
+    for invalid in [
+        SizeDistribution::Imix { profile: None, frames: None },
+        SizeDistribution::Imix { profile: Some(ImixProfile::Simple), frames: Some(frame_table(&[(64, 1.0)])) },
+        SizeDistribution::Imix { profile: None, frames: Some(frame_table(&[(60, 1.0)])) },
+        SizeDistribution::Imix { profile: None, frames: Some(frame_table(&[(9000, 1.0)])) },
+        SizeDistribution::Imix { profile: None, frames: Some(frame_table(&[(64, 0.0)])) },
+    ] {
+        config.attack.payload_sizes.udp = Some(invalid.clone());
+        assert!(validate_config(&config).is_err(), "accepted {:?}", invalid);
+    }
+}
+
+#[test]
+fn test_frames_become_payloads() {
+    let sizer = PayloadSizer::new(&SizeDistribution::imix(ImixProfile::Simple), 28);
+    let mut rng = BatchedRng::new();
+    let counts = tally(12_000, || sizer.sample(&mut rng));
+
+    // Frame minus Ethernet header, FCS and the 28 header bytes
+    for (payload, expected) in [(18, 7000), (548, 4000), (1472, 1000)] {
+        let count = counts[&payload] as i64;
+        assert!((count - expected).abs() < 400, "payload {}: {} samples", payload, count);
+    }
+    assert_eq!(sizer.max(), 1472);
+}
+
+#[test]
+fn test_ipv4_packets_fill_frames() {
+    let sizes = uniform_sizes(SizeDistribution::imix(ImixProfile::Simple));
+    let mut builder = PacketBuilder::with_sizes(&sizes, Default::default());
+
+    for packet_type in [PacketType::Udp, PacketType::Icmp, PacketType::Tcp(TcpFlagSet::PSH_ACK)] {
+        for _ in 0..200 {
+            let len = build_packet(&mut builder, packet_type, 53).len();
+            assert!([64, 594, 1518].contains(&(len + 18)), "{:?} packet of {} bytes", packet_type, len);
+        }
+    }
+}
+
+#[test]
+fn test_ipv6_packets_fill_frames() {
+    let imix = SizeDistribution::Imix { profile: None, frames: Some(frame_table(&[(128, 1.0), (1518, 1.0)])) };
+    let sizes = uniform_sizes(imix);
+    let mut builder = PacketBuilder::with_sizes(&sizes, Default::default());
+
+    for packet_type in [PacketType::Ipv6Udp, PacketType::Ipv6Icmp] {
+        for _ in 0..100 {
+            let len = build_packet(&mut builder, packet_type, 53).len();
+            assert!([128, 1518].contains(&(len + 18)), "{:?} packet of {} bytes", packet_type, len);
+        }
+    }
+}
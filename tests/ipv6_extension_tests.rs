@@ -0,0 +1,191 @@
+//! IPv6 extension header chain tests
+
+use pnet::packet::icmpv6::Icmpv6Packet;
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::{self, TcpPacket};
+use pnet::packet::udp::{self, UdpPacket};
+use pnet::packet::Packet;
+use router_flood::config::{validate_config, Config, Icmpv6Kind, Ipv6Extension, Ipv6ExtensionChain, TcpFlagSet};
+use router_flood::packet::{Icmpv6MessageMix, Ipv6ExtensionLayout, PacketBuilder, PacketOptions, PacketSizes, PacketType};
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv6Addr};
+
+const TARGET: Ipv6Addr = Ipv6Addr::new(0xfd00, 0, 0, 0, 0, 0, 0, 1);
+
+fn builder(chain: &str) -> PacketBuilder {
+    let options = PacketOptions {
+        ipv6_extensions: Ipv6ExtensionLayout::from_config(&chain.parse().unwrap()),
+        icmpv6_messages: Icmpv6MessageMix::from_config(&BTreeMap::from([(Icmpv6Kind::MldReport, 1.0)])),
+        ..Default::default()
+    };
+    PacketBuilder::with_options(&PacketSizes::from_config(&Config::default().attack), &options, Default::default())
+}
+
+/// Protocol and bytes of one header
+type Header = (u8, Vec<u8>);
+
+/// Walk the extension headers of an IPv6 packet, returning each header's
+/// protocol and bytes, and the upper-layer protocol and bytes
+fn walk(packet: &[u8]) -> (Vec<Header>, u8, Vec<u8>) {
+    let ip = Ipv6Packet::new(packet).unwrap();
+    assert_eq!(ip.get_payload_length() as usize, packet.len() - 40);
+    let mut headers = Vec::new();
+    let mut protocol = ip.get_next_header().0;
+    let mut rest = ip.payload();
+    while matches!(protocol, 0 | 43 | 44 | 60) {
+        let len = if protocol == 44 { 8 } else { (rest[1] as usize + 1) * 8 };
+        headers.push((protocol, rest[..len].to_vec()));
+        protocol = rest[0];
+        rest = &rest[len..];
+    }
+    (headers, protocol, rest.to_vec())
+}
+
+#[test]
+fn test_chain_parsing() {
+    let chain: Ipv6ExtensionChain = "hbh, routing:0:2, dest_opts:3*2, fragment, routing:4".parse().unwrap();
+    assert_eq!(chain.0, vec![
+        Ipv6Extension::HopByHop(1),
+        Ipv6Extension::Routing { routing_type: 0, segments: 2 },
+        Ipv6Extension::DestinationOptions(3),
+        Ipv6Extension::DestinationOptions(3),
+        Ipv6Extension::Fragment,
+        Ipv6Extension::Routing { routing_type: 4, segments: 1 },
+    ]);
+    assert_eq!(chain.encoded_len(), 8 + 40 + 48 + 8 + 24);
+    assert_eq!(
+        chain.to_string(),
+        "hop_by_hop, routing:0:2, destination_options:3*2, fragment, routing:4"
+    );
+    assert_eq!(chain.to_string().parse::<Ipv6ExtensionChain>().unwrap(), chain);
+
+    for invalid in ["routing:1", "routing:2:2", "routing:0:0", "hop_by_hop:0", "hop_by_hop:257", "fragment:1", "fragment*0", "jumbo"] {
+        assert!(invalid.parse::<Ipv6ExtensionChain>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn test_chain_yaml_and_validation() {
+    let yaml = serde_yaml::to_string(&Config::default()).unwrap();
+    assert!(!yaml.contains("ipv6_extension_headers"));
+    let yaml = yaml.replace("attack:\n", "attack:\n  ipv6_extension_headers: \"hop_by_hop, fragment*2\"\n");
+    let mut config: Config = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(config.attack.ipv6_extension_headers.0.len(), 3);
+    assert!(validate_config(&config).is_ok());
+
+    config.attack.ipv6_extension_headers = "destination_options:256*20".parse().unwrap();
+    assert!(validate_config(&config).is_err());
+}
+
+#[test]
+fn test_udp_through_chain() {
+    let mut builder = builder("hop_by_hop, routing:4:3, fragment, destination_options:2");
+    let (packet, _) = builder.build_packet(PacketType::Ipv6Udp, IpAddr::V6(TARGET), 53).unwrap();
+    let (headers, protocol, upper) = walk(&packet);
+
+    let protocols: Vec<u8> = headers.iter().map(|(protocol, _)| *protocol).collect();
+    assert_eq!(protocols, vec![0, 43, 44, 60]);
+    assert_eq!(protocol, IpNextHeaderProtocols::Udp.0);
+
+    // Options headers are filled with padding
+    assert_eq!(&headers[0].1[2..4], &[1, 4]);
+    assert_eq!(headers[3].1.len(), 16);
+
+    // Segment routing header: segments left and last entry, every segment the target
+    let routing = &headers[1].1;
+    assert_eq!(routing.len(), 8 + 3 * 16);
+    assert_eq!(&routing[2..5], &[4, 2, 2]);
+    for segment in routing[8..].chunks(16) {
+        assert_eq!(segment, TARGET.octets());
+    }
+
+    // Atomic fragment
+    assert_eq!(&headers[2].1[2..4], &[0, 0]);
+
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    let udp = UdpPacket::new(&upper).unwrap();
+    assert_eq!(udp.get_length() as usize, upper.len());
+    assert_eq!(udp.get_checksum(), udp::ipv6_checksum(&udp, &ip.get_source(), &TARGET));
+}
+
+#[test]
+fn test_long_chain_before_tcp() {
+    let mut builder = builder("destination_options*100, routing:0:2, routing:2");
+    let (packet, _) = builder.build_packet(PacketType::Ipv6Tcp(TcpFlagSet::SYN), IpAddr::V6(TARGET), 443).unwrap();
+    let (headers, protocol, upper) = walk(&packet);
+
+    assert_eq!(headers.len(), 102);
+    assert!(headers[..100].iter().all(|(protocol, _)| *protocol == 60));
+    assert_eq!(&headers[100].1[2..4], &[0, 2]);
+    assert_eq!(&headers[101].1[1..4], &[2, 2, 1]);
+    assert_eq!(protocol, IpNextHeaderProtocols::Tcp.0);
+
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    let tcp = TcpPacket::new(&upper).unwrap();
+    assert_eq!(tcp.get_flags(), TcpFlagSet::SYN.bits());
+    assert_eq!(tcp.get_checksum(), tcp::ipv6_checksum(&tcp, &ip.get_source(), &TARGET));
+}
+
+#[test]
+fn test_mld_router_alert_leads_chain() {
+    let mut builder = builder("destination_options, routing:0");
+    let (packet, _) = builder.build_packet(PacketType::Ipv6Icmp, IpAddr::V6(TARGET), 0).unwrap();
+    let (headers, protocol, upper) = walk(&packet);
+
+    let protocols: Vec<u8> = headers.iter().map(|(protocol, _)| *protocol).collect();
+    assert_eq!(protocols, vec![0, 60, 43]);
+    // Router Alert option in the first header
+    assert_eq!(headers[0].1[2], 5);
+    assert_eq!(protocol, IpNextHeaderProtocols::Icmpv6.0);
+
+    // Routing headers name the MLD destination, which the checksum covers
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    assert_eq!(&headers[2].1[8..24], &ip.get_destination().octets());
+    let icmp = Icmpv6Packet::new(&upper).unwrap();
+    assert_eq!(icmp.get_icmpv6_type().0, 143);
+    assert_eq!(
+        icmp.get_checksum(),
+        pnet::packet::icmpv6::checksum(&icmp, &ip.get_source(), &ip.get_destination())
+    );
+}
+
+#[test]
+fn test_mld_router_alert_joins_hop_by_hop() {
+    let mut builder = builder("hop_by_hop:2, fragment");
+    let (packet, _) = builder.build_packet(PacketType::Ipv6Icmp, IpAddr::V6(TARGET), 0).unwrap();
+    let (headers, protocol, upper) = walk(&packet);
+
+    // A single Hop-by-Hop header, the configured one, leads with the Router Alert
+    let protocols: Vec<u8> = headers.iter().map(|(protocol, _)| *protocol).collect();
+    assert_eq!(protocols, vec![0, 44]);
+    assert_eq!(headers[0].1.len(), 16);
+    assert_eq!(&headers[0].1[2..6], &[5, 2, 0, 0]);
+    assert_eq!(&headers[0].1[6..8], &[1, 8]);
+    assert_eq!(protocol, IpNextHeaderProtocols::Icmpv6.0);
+
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    let icmp = Icmpv6Packet::new(&upper).unwrap();
+    assert_eq!(icmp.get_icmpv6_type().0, 143);
+    assert_eq!(
+        icmp.get_checksum(),
+        pnet::packet::icmpv6::checksum(&icmp, &ip.get_source(), &ip.get_destination())
+    );
+
+    // Other packets keep the chain as configured
+    let (packet, _) = builder.build_packet(PacketType::Ipv6Udp, IpAddr::V6(TARGET), 53).unwrap();
+    let (headers, _, _) = walk(&packet);
+    assert_eq!(&headers[0].1[2..4], &[1, 12]);
+}
+
+#[test]
+fn test_fragment_identification_varies() {
+    let mut builder = builder("fragment");
+    let identifications: Vec<Vec<u8>> = (0..20)
+        .map(|_| {
+            let (packet, _) = builder.build_packet(PacketType::Ipv6Udp, IpAddr::V6(TARGET), 53).unwrap();
+            walk(&packet).0[0].1[4..8].to_vec()
+        })
+        .collect();
+    assert!(identifications.iter().any(|id| id != &identifications[0]));
+}
@@ -0,0 +1,120 @@
+//! IPv6 protocol mix tests
+
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::{self, TcpPacket};
+use pnet::packet::udp::UdpPacket;
+use pnet::packet::Packet;
+use router_flood::config::{validate_config, Config, Ipv6ProtocolMix, ProtocolMix, SizeDistribution, TcpFlagSet};
+use router_flood::packet::{PacketBuilder, PacketSizes, PacketType};
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+
+fn target() -> IpAddr {
+    "2001:db8::1".parse().unwrap()
+}
+
+fn builder(protocol_mix: ProtocolMix) -> PacketBuilder {
+    PacketBuilder::with_sizes(&PacketSizes::from_config(&Config::default().attack), protocol_mix)
+}
+
+fn ipv6_mix() -> Ipv6ProtocolMix {
+    Ipv6ProtocolMix {
+        udp_ratio: 0.2,
+        tcp_ack_ratio: 0.3,
+        icmp_ratio: 0.1,
+        tcp_flags: BTreeMap::from([(TcpFlagSet::PSH_ACK, 0.4)]),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_ipv6_mix_yaml_and_validation() {
+    let yaml = serde_yaml::to_string(&Config::default()).unwrap();
+    let yaml = yaml.replace(
+        "    custom_ratio: 0.0\n",
+        "    custom_ratio: 0.0\n    ipv6:\n      udp_ratio: 0.5\n      tcp_syn_ratio: 0.25\n      tcp_flags:\n        syn_ack: 0.25\n",
+    );
+    let mut config: Config = serde_yaml::from_str(&yaml).unwrap();
+    let ipv6 = config.target.protocol_mix.ipv6.clone().unwrap();
+    assert_eq!(ipv6.udp_ratio, 0.5);
+    assert_eq!(ipv6.icmp_ratio, 0.0);
+    assert_eq!(ipv6.tcp_flags[&TcpFlagSet::SYN_ACK], 0.25);
+    assert!(validate_config(&config).is_ok());
+
+    // IPv6 ratios must add up on their own
+    config.target.protocol_mix.ipv6.as_mut().unwrap().udp_ratio = 0.3;
+    assert!(validate_config(&config).is_err());
+
+    config.target.protocol_mix.ipv6.as_mut().unwrap().udp_ratio = 0.5;
+    config.target.protocol_mix.ipv6.as_mut().unwrap().tcp_flags.insert(TcpFlagSet::SYN_ACK, -0.25);
+    assert!(validate_config(&config).is_err());
+}
+
+#[test]
+fn test_ipv6_mix_follows_ipv4_ratios_by_default() {
+    let mix = ProtocolMix::default();
+    assert!(mix.ipv6.is_none());
+
+    let ipv6 = mix.ipv6_mix();
+    assert_eq!(ipv6.udp_ratio, mix.udp_ratio + mix.custom_ratio + mix.arp_ratio);
+    assert_eq!(ipv6.tcp_ratios(), mix.tcp_ratios());
+    assert_eq!(ipv6.icmp_ratio, mix.icmp_ratio);
+    assert!((ipv6.total() - 1.0).abs() < 1e-9);
+
+    let explicit = ProtocolMix { ipv6: Some(ipv6_mix()), ..Default::default() };
+    assert_eq!(explicit.ipv6_mix(), ipv6_mix());
+}
+
+#[test]
+fn test_ipv6_tcp_flag_sets() {
+    let mut builder = builder(Default::default());
+    for flags in [TcpFlagSet::SYN, TcpFlagSet::ACK, TcpFlagSet::RST, TcpFlagSet::XMAS, TcpFlagSet::NULL] {
+        let (packet, _) = builder.build_packet(PacketType::Ipv6Tcp(flags), target(), 443).unwrap();
+        let ip = Ipv6Packet::new(&packet).unwrap();
+        assert_eq!(ip.get_next_header(), IpNextHeaderProtocols::Tcp);
+        let tcp = TcpPacket::new(ip.payload()).unwrap();
+        assert_eq!(tcp.get_flags(), flags.bits(), "{}", flags);
+        if !flags.contains(TcpFlagSet::ACK) {
+            assert_eq!(tcp.get_acknowledgement(), 0, "{}", flags);
+        }
+        if !flags.contains(TcpFlagSet::URG) {
+            assert_eq!(tcp.get_urgent_ptr(), 0, "{}", flags);
+        }
+        assert_eq!(tcp.get_checksum(), tcp::ipv6_checksum(&tcp, &ip.get_source(), &ip.get_destination()));
+    }
+}
+
+#[test]
+fn test_ipv6_target_honors_ratios() {
+    let protocol_mix = ProtocolMix { ipv6: Some(ipv6_mix()), ..Default::default() };
+    let mut builder = builder(protocol_mix);
+
+    let mut counts: HashMap<PacketType, usize> = HashMap::new();
+    for _ in 0..10_000 {
+        *counts.entry(builder.next_packet_type_for_ip(target())).or_insert(0) += 1;
+    }
+    assert_eq!(counts.len(), 4);
+    for (packet_type, expected) in [
+        (PacketType::Ipv6Udp, 0.2),
+        (PacketType::Ipv6Tcp(TcpFlagSet::ACK), 0.3),
+        (PacketType::Ipv6Tcp(TcpFlagSet::PSH_ACK), 0.4),
+        (PacketType::Ipv6Icmp, 0.1),
+    ] {
+        let share = counts[&packet_type] as f64 / 10_000.0;
+        assert!((share - expected).abs() < 0.03, "{}: {}", packet_type, share);
+        assert!(builder.build_packet(packet_type, target(), 80).is_ok());
+    }
+}
+
+#[test]
+fn test_ipv6_udp_uses_udp_sizes() {
+    let mut sizes = PacketSizes::from_config(&Config::default().attack);
+    sizes.udp = SizeDistribution::Fixed { size: 321 };
+    let mut builder = PacketBuilder::with_sizes(&sizes, Default::default());
+
+    let (packet, _) = builder.build_packet(PacketType::Ipv6Udp, target(), 53).unwrap();
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    let udp = UdpPacket::new(ip.payload()).unwrap();
+    assert_eq!(udp.payload().len(), 321);
+}
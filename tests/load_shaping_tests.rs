@@ -0,0 +1,127 @@
+//! Load shaping tests (burst patterns and rate profiles)
+
+use router_flood::config::{validate_config, BurstPattern, Config, RateProfile};
+use router_flood::network::burst::{BurstSchedule, LoadPhase};
+use router_flood::network::rate_profile::RateSchedule;
+use router_flood::stats::Stats;
+use std::time::{Duration, Instant};
+
+fn pattern(burst_ms: u64, idle_ms: u64, multiplier: f64) -> BurstPattern {
+    BurstPattern {
+        burst_duration_ms: burst_ms,
+        idle_duration_ms: idle_ms,
+        burst_multiplier: multiplier,
+    }
+}
+
+#[test]
+fn test_burst_schedule_phases() {
+    let epoch = Instant::now();
+    let schedule = BurstSchedule::new(&pattern(50, 150, 4.0), epoch);
+
+    let state = schedule.state_at(epoch + Duration::from_millis(10));
+    assert_eq!(state.phase, LoadPhase::Burst);
+    assert_eq!(state.cycle, 0);
+    assert_eq!(state.remaining, Duration::from_millis(40));
+
+    let state = schedule.state_at(epoch + Duration::from_millis(60));
+    assert_eq!(state.phase, LoadPhase::Idle);
+    assert_eq!(state.remaining, Duration::from_millis(140));
+
+    let state = schedule.state_at(epoch + Duration::from_millis(410));
+    assert_eq!(state.phase, LoadPhase::Burst);
+    assert_eq!(state.cycle, 2);
+    assert_eq!(schedule.multiplier(), 4.0);
+}
+
+#[test]
+fn test_burst_schedules_share_epoch() {
+    let epoch = Instant::now();
+    let a = BurstSchedule::new(&pattern(20, 80, 2.0), epoch);
+    let b = a;
+
+    let probe = epoch + Duration::from_millis(1234);
+    assert_eq!(a.state_at(probe), b.state_at(probe));
+}
+
+#[test]
+fn test_stats_track_burst_phases() {
+    let stats = Stats::new(None);
+    assert_eq!(stats.load_phase(), LoadPhase::Steady);
+
+    let epoch = Instant::now();
+    let schedule = BurstSchedule::new(&pattern(10, 10, 2.0), epoch);
+    stats.record_load_phase(schedule.state_at(epoch + Duration::from_millis(5)));
+    stats.record_load_phase(schedule.state_at(epoch + Duration::from_millis(35)));
+
+    assert_eq!(stats.load_phase(), LoadPhase::Idle);
+    assert_eq!(stats.burst_cycles(), 2);
+    assert_eq!(stats.take_interval_phases(), vec![LoadPhase::Burst, LoadPhase::Idle]);
+    assert!(stats.take_interval_phases().is_empty());
+}
+
+#[test]
+fn test_burst_mode_validation() {
+    let mut config = Config::default();
+    config.attack.burst_mode = true;
+    assert!(validate_config(&config).is_err());
+
+    config.attack.burst_pattern = Some(pattern(0, 100, 2.0));
+    assert!(validate_config(&config).is_err());
+
+    config.attack.burst_pattern = Some(pattern(50, 100, 0.0));
+    assert!(validate_config(&config).is_err());
+
+    config.attack.burst_pattern = Some(pattern(50, 100, 10.0));
+    assert!(validate_config(&config).is_ok());
+}
+
+fn rate_after(profile: RateProfile, secs: f64) -> f64 {
+    RateSchedule::new(profile, 100.0, Instant::now()).rate_after(Duration::from_secs_f64(secs))
+}
+
+#[test]
+fn test_constant_profile_uses_packet_rate() {
+    assert_eq!(rate_after(RateProfile::Constant, 0.0), 100.0);
+    assert_eq!(rate_after(RateProfile::Constant, 500.0), 100.0);
+}
+
+#[test]
+fn test_ramp_profiles() {
+    let linear = RateProfile::LinearRamp { start_rate: 100.0, end_rate: 1100.0, duration_secs: 10 };
+    assert_eq!(rate_after(linear.clone(), 0.0), 100.0);
+    assert!((rate_after(linear.clone(), 5.0) - 600.0).abs() < 1e-9);
+    assert_eq!(rate_after(linear, 60.0), 1100.0);
+
+    let exponential = RateProfile::ExponentialRamp { start_rate: 100.0, end_rate: 10_000.0, duration_secs: 10 };
+    assert!((rate_after(exponential.clone(), 5.0) - 1000.0).abs() < 1e-6);
+    assert!((rate_after(exponential, 20.0) - 10_000.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_step_and_sine_profiles() {
+    let step = RateProfile::Step { start_rate: 100.0, step_rate: 50.0, step_duration_secs: 10, max_rate: 200.0 };
+    assert_eq!(rate_after(step.clone(), 9.9), 100.0);
+    assert_eq!(rate_after(step.clone(), 10.0), 150.0);
+    assert_eq!(rate_after(step, 100.0), 200.0);
+
+    let sine = RateProfile::Sine { min_rate: 100.0, max_rate: 300.0, period_secs: 20 };
+    assert!((rate_after(sine.clone(), 0.0) - 100.0).abs() < 1e-9);
+    assert!((rate_after(sine.clone(), 10.0) - 300.0).abs() < 1e-9);
+    assert!((rate_after(sine, 5.0) - 200.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_rate_profile_validation_and_parsing() {
+    let mut config = Config::default();
+    config.attack.rate_profile = RateProfile::LinearRamp { start_rate: 100.0, end_rate: 500.0, duration_secs: 0 };
+    assert!(validate_config(&config).is_err());
+
+    config.attack.rate_profile = RateProfile::Sine { min_rate: 500.0, max_rate: 100.0, period_secs: 10 };
+    assert!(validate_config(&config).is_err());
+
+    let yaml = "type: step\nstart_rate: 100\nstep_rate: 100\nstep_duration_secs: 30\nmax_rate: 1000\n";
+    let profile: RateProfile = serde_yaml::from_str(yaml).unwrap();
+    config.attack.rate_profile = profile;
+    assert!(validate_config(&config).is_ok());
+}
@@ -0,0 +1,162 @@
+//! Deadline pacer and pacing accuracy tests
+
+use router_flood::config::{validate_config, Config, ExportFormat, LoadConfig, PacingConfig, PacingMode};
+use router_flood::network::pacer::Pacer;
+use router_flood::network::phase::{PhaseControl, PhaseSettings};
+use router_flood::network::worker_manager::Workers;
+use router_flood::stats::{GapHistogram, PacingMeter, Stats};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+#[test]
+fn test_gap_histogram_percentiles() {
+    let mut histogram = GapHistogram::new();
+    assert_eq!(histogram.percentile(0.5), Duration::ZERO);
+
+    for micros in 1..=1000 {
+        histogram.record(Duration::from_micros(micros));
+    }
+
+    assert_eq!(histogram.count(), 1000);
+    assert_eq!(histogram.max(), Duration::from_micros(1000));
+
+    let mean = histogram.mean().as_secs_f64() * 1e6;
+    assert!((mean - 500.5).abs() < 1.0, "mean {}", mean);
+
+    // Buckets are about 12% wide
+    let p50 = histogram.percentile(0.50).as_secs_f64() * 1e6;
+    let p99 = histogram.percentile(0.99).as_secs_f64() * 1e6;
+    assert!((p50 - 500.0).abs() < 65.0, "p50 {}", p50);
+    assert!((p99 - 990.0).abs() < 125.0, "p99 {}", p99);
+    assert!(histogram.percentile(1.0) <= histogram.max());
+}
+
+#[test]
+fn test_pacer_catches_up_overshoot() {
+    let start = Instant::now();
+    let mut pacer = Pacer::new(&PacingConfig::default(), start);
+    let interval = Duration::from_micros(100);
+
+    assert_eq!(pacer.due(start, 100, || interval), 1);
+    assert_eq!(pacer.due(start, 100, || interval), 0);
+
+    // Waking 1 ms late sends everything that became due in the meantime
+    let late = start + Duration::from_micros(1100);
+    assert_eq!(pacer.due(late, 5, || interval), 5);
+    assert_eq!(pacer.due(late, 100, || interval), 6);
+    assert_eq!(pacer.next_send(), start + Duration::from_micros(1200));
+}
+
+#[test]
+fn test_pacer_backlog_is_bounded() {
+    let start = Instant::now();
+    let config = PacingConfig { max_catch_up_ms: 10, ..Default::default() };
+    let mut pacer = Pacer::new(&config, start);
+    let interval = Duration::from_millis(1);
+
+    // A one second stall only catches up the last 10 ms
+    let stalled = start + Duration::from_secs(1);
+    assert_eq!(pacer.due(stalled, 1000, || interval), 11);
+
+    pacer.reset(stalled);
+    assert_eq!(pacer.next_send(), stalled);
+}
+
+#[test]
+fn test_meter_requested_vs_achieved() {
+    let start = Instant::now();
+    let mut meter = PacingMeter::new(start);
+    meter.set_rate(start, 1000.0);
+
+    // Half of the requested packets, evenly spaced 2 ms apart
+    for i in 0..500 {
+        meter.record_send(start + Duration::from_millis(2 * i));
+    }
+
+    // Idle for the second half: nothing requested, no gap recorded
+    let idle = start + Duration::from_secs(1);
+    meter.set_rate(idle, 0.0);
+    meter.break_gap();
+    meter.record_send(start + Duration::from_secs(2));
+
+    let report = meter.report(3, start + Duration::from_secs(2));
+    assert_eq!(report.worker_id, 3);
+    assert_eq!(report.packets, 501);
+    assert!((report.requested_pps - 500.0).abs() < 1.0, "requested {}", report.requested_pps);
+    assert!((report.achieved_pps - 250.5).abs() < 1.0, "achieved {}", report.achieved_pps);
+    assert!((report.gap_p50_us - 2000.0).abs() < 250.0, "p50 {}", report.gap_p50_us);
+    assert!((report.gap_max_us - 2000.0).abs() < 1.0, "max {}", report.gap_max_us);
+}
+
+#[test]
+fn test_pacing_config_parsing_and_validation() {
+    let load: LoadConfig = serde_yaml::from_str(
+        "threads: 2\npacket_rate: 100\npayload_size: 64\nduration: 10\nburst_mode: false\npacing:\n  mode: hybrid\n  spin_threshold_us: 200\n",
+    )
+    .unwrap();
+    assert_eq!(load.pacing.mode, PacingMode::Hybrid);
+    assert_eq!(load.pacing.spin_threshold_us, 200);
+    assert_eq!(load.pacing.max_catch_up_ms, PacingConfig::default().max_catch_up_ms);
+
+    let mut config = Config::default();
+    assert_eq!(config.attack.pacing.mode, PacingMode::Sleep);
+    config.attack.pacing = load.pacing;
+    assert!(validate_config(&config).is_ok());
+
+    config.attack.pacing.spin_threshold_us = 1_000_000;
+    assert!(validate_config(&config).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_workers_report_pacing_accuracy() {
+    let mut config = Config::default();
+    config.attack.threads = 2;
+    config.attack.packet_rate = 2000.0;
+    config.attack.pacing.mode = PacingMode::Hybrid;
+    config.safety.dry_run = true;
+    config.safety.perfect_simulation = true;
+
+    let stats = Arc::new(Stats::new(None));
+    let control = Arc::new(PhaseControl::new(PhaseSettings::from_config(&config, None, Instant::now())));
+    let workers = Workers::new(&config, Arc::clone(&stats), control, "192.168.1.1".parse().unwrap(), None, true).unwrap();
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    workers.stop();
+    workers.join_all().await.unwrap();
+
+    let reports = stats.pacing_reports();
+    assert_eq!(reports.iter().map(|report| report.worker_id).collect::<Vec<_>>(), [0, 1]);
+    for report in reports {
+        assert!((report.requested_pps - 2000.0).abs() < 1.0, "requested {}", report.requested_pps);
+        let accuracy = report.achieved_pps / report.requested_pps;
+        assert!((0.9..=1.1).contains(&accuracy), "worker {} accuracy {:.3}", report.worker_id, accuracy);
+        assert!(report.gap_p50_us > 0.0 && report.gap_p50_us <= report.gap_max_us);
+    }
+}
+
+#[tokio::test]
+async fn test_pacing_exported_per_worker() {
+    let dir = TempDir::new().unwrap();
+    let mut export = Config::default().export;
+    export.enabled = true;
+    export.format = ExportFormat::Csv;
+    export.path = dir.path().to_str().unwrap().to_string();
+
+    let stats = Stats::new(Some(export));
+    let start = Instant::now();
+    let mut meter = PacingMeter::new(start);
+    meter.set_rate(start, 100.0);
+    meter.record_send(start);
+    meter.record_send(start + Duration::from_millis(10));
+    stats.update_pacing(meter.report(0, start + Duration::from_secs(1)));
+    stats.export_stats().await.unwrap();
+
+    let entry = std::fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap();
+    let csv = std::fs::read_to_string(entry.path()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].contains(",worker,requested_pps,gap_mean_us,"));
+    assert!(lines[2].contains(",worker,,"));
+    assert!(lines[2].contains(",0,100,"));
+}
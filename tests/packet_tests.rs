@@ -26,6 +26,9 @@ fn test_packet_builder_creation() {
         tcp_rst_ratio: 0.10,
         icmp_ratio: 0.10,
         custom_ratio: 0.0,
+        arp_ratio: 0.0,
+        ipv6: None,
+        tcp_flags: Default::default(),
     };
     
     let _builder = PacketBuilder::new(PacketSizeRange::new(64, 1400), protocol_mix);
@@ -55,7 +58,7 @@ fn test_build_tcp_syn_packet() {
     let target_ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
     let target_port = 443;
     
-    let result = builder.build_packet(PacketType::TcpSyn, target_ip, target_port);
+    let result = builder.build_packet(PacketType::TCP_SYN, target_ip, target_port);
     assert!(result.is_ok());
     
     let (packet, protocol) = result.unwrap();
@@ -118,7 +121,7 @@ fn test_ipv6_packet_building() {
     assert_eq!(protocol, "IPv6");
     
     // Test IPv6 TCP
-    let result = builder.build_packet(PacketType::Ipv6Tcp, target_ip, target_port);
+    let result = builder.build_packet(PacketType::IPV6_TCP_SYN, target_ip, target_port);
     assert!(result.is_ok());
     let (packet, protocol) = result.unwrap();
     assert!(!packet.is_empty());
@@ -184,8 +187,8 @@ fn test_multiple_packet_types() {
     
     let packet_types = vec![
         PacketType::Udp,
-        PacketType::TcpSyn,
-        PacketType::TcpAck,
+        PacketType::TCP_SYN,
+        PacketType::TCP_ACK,
         PacketType::Icmp,
     ];
     
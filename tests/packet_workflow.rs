@@ -15,8 +15,8 @@ fn test_complete_packet_workflow() {
     // Build different packet types
     let packet_types = vec![
         PacketType::Udp,
-        PacketType::TcpSyn,
-        PacketType::TcpAck,
+        PacketType::TCP_SYN,
+        PacketType::TCP_ACK,
         PacketType::Icmp,
     ];
     
@@ -0,0 +1,169 @@
+//! Payload content pattern tests
+
+use router_flood::config::{validate_config, Config, PayloadPattern, SizeDistribution, TcpFlagSet};
+use router_flood::constants::defaults;
+use router_flood::packet::{
+    InstrumentedHeader, PacketBuilder, PacketOptions, PacketSizes, PacketType, PayloadFill, PayloadWriter,
+};
+use router_flood::utils::rng::BatchedRng;
+use std::io::Write;
+use std::net::IpAddr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tempfile::NamedTempFile;
+
+fn fill(pattern: PayloadPattern) -> PayloadFill {
+    PayloadFill::from_pattern(&pattern).unwrap()
+}
+
+fn write(fill: &PayloadFill, len: usize) -> Vec<u8> {
+    let mut payload = vec![0xAA; len];
+    PayloadWriter::new(fill).write(&mut payload, &mut BatchedRng::new());
+    payload
+}
+
+#[test]
+fn test_simple_patterns() {
+    assert_eq!(write(&fill(PayloadPattern::Zero), 16), vec![0; 16]);
+
+    let incrementing = write(&fill(PayloadPattern::Incrementing), 300);
+    assert_eq!(&incrementing[..4], &[0, 1, 2, 3]);
+    assert_eq!(&incrementing[254..258], &[254, 255, 0, 1]);
+
+    let hex = fill(PayloadPattern::Hex { pattern: "0xDE AD be ef".to_string() });
+    assert_eq!(write(&hex, 10), vec![0xde, 0xad, 0xbe, 0xef, 0xde, 0xad, 0xbe, 0xef, 0xde, 0xad]);
+
+    let random = write(&fill(PayloadPattern::Random), 256);
+    assert!(random.iter().any(|&byte| byte != random[0]));
+}
+
+#[test]
+fn test_pattern_validation() {
+    let mut config = Config::default();
+    assert_eq!(config.attack.payload_pattern, PayloadPattern::Random);
+
+    config.attack.payload_pattern = PayloadPattern::Hex { pattern: "c0ffee".to_string() };
+    assert!(validate_config(&config).is_ok());
+
+    for invalid in ["", "abc", "zz", "0x"] {
+        config.attack.payload_pattern = PayloadPattern::Hex { pattern: invalid.to_string() };
+        assert!(validate_config(&config).is_err(), "accepted {:?}", invalid);
+    }
+
+    config.attack.payload_pattern = PayloadPattern::File { path: " ".to_string() };
+    assert!(validate_config(&config).is_err());
+}
+
+#[test]
+fn test_file_pattern() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(b"GET / HTTP/1.1\r\n").unwrap();
+    let path = file.path().to_string_lossy().to_string();
+
+    let payload = write(&fill(PayloadPattern::File { path }), 20);
+    assert_eq!(&payload, b"GET / HTTP/1.1\r\nGET ");
+
+    let missing = PayloadPattern::File { path: "/nonexistent/payload.bin".to_string() };
+    assert!(PayloadFill::from_pattern(&missing).is_err());
+
+    let empty = NamedTempFile::new().unwrap();
+    let empty = PayloadPattern::File { path: empty.path().to_string_lossy().to_string() };
+    assert!(PayloadFill::from_pattern(&empty).is_err());
+}
+
+#[test]
+fn test_instrumented_header() {
+    let fill = fill(PayloadPattern::instrumented());
+    let mut writer = PayloadWriter::new(&fill);
+    let mut rng = BatchedRng::new();
+    let now_ns = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos() as u64;
+
+    for expected in 0..5 {
+        let mut payload = vec![0xAA; 64];
+        writer.write(&mut payload, &mut rng);
+
+        let header = InstrumentedHeader::parse(&payload, defaults::PAYLOAD_MAGIC).unwrap();
+        assert_eq!(&payload[..4], b"RFLD");
+        assert_eq!(Some(header.stream_id), writer.stream_id());
+        assert_eq!(header.sequence, expected);
+        assert!(header.tx_timestamp_ns >= now_ns);
+        assert!(header.tx_timestamp_ns - now_ns < 10_000_000_000);
+        assert!(payload[24..].iter().all(|&byte| byte == 0));
+        assert_eq!(InstrumentedHeader::parse(&payload, 0x1234_5678), None);
+    }
+
+    // Too short for the header: zero-filled, sequence not used up
+    let mut short = vec![0xAA; 16];
+    writer.write(&mut short, &mut rng);
+    assert_eq!(short, vec![0; 16]);
+
+    let mut payload = vec![0; 24];
+    writer.write(&mut payload, &mut rng);
+    assert_eq!(InstrumentedHeader::parse(&payload, defaults::PAYLOAD_MAGIC).unwrap().sequence, 5);
+}
+
+#[test]
+fn test_stream_ids_are_unique() {
+    let fill = fill(PayloadPattern::Instrumented { magic: 0xCAFE_F00D, stream_base: 1000 });
+    let ids: Vec<u32> = (0..4).map(|_| PayloadWriter::new(&fill).stream_id().unwrap()).collect();
+    assert_eq!(ids, vec![1000, 1001, 1002, 1003]);
+
+    // Each packet builder strategy is a stream of its own
+    let options = PacketOptions { payload: fill.clone(), ..Default::default() };
+    let _builder = PacketBuilder::with_options(&PacketSizes::from_config(&Config::default().attack), &options, Default::default());
+    assert!(PayloadWriter::new(&fill).stream_id().unwrap() > 1004);
+
+    assert_eq!(PayloadWriter::new(&PayloadFill::Zero).stream_id(), None);
+}
+
+#[test]
+fn test_packets_carry_instrumented_payload() {
+    let sizes = PacketSizes {
+        udp: SizeDistribution::Fixed { size: 100 },
+        icmp: SizeDistribution::Fixed { size: 32 },
+        tcp: SizeDistribution::Fixed { size: 64 },
+    };
+    let options = PacketOptions { payload: fill(PayloadPattern::instrumented()), ..Default::default() };
+    let mut builder = PacketBuilder::with_options(&sizes, &options, Default::default());
+    let mut buffer = vec![0u8; 1500];
+
+    let ipv4: IpAddr = "192.168.1.1".parse().unwrap();
+    let ipv6: IpAddr = "2001:db8::1".parse().unwrap();
+    let cases = [
+        (PacketType::Udp, ipv4, 28),
+        (PacketType::Icmp, ipv4, 28),
+        (PacketType::Tcp(TcpFlagSet::PSH_ACK), ipv4, 40),
+        (PacketType::Ipv6Udp, ipv6, 48),
+        (PacketType::Ipv6Icmp, ipv6, 48),
+    ];
+
+    for (packet_type, target, offset) in cases {
+        let mut stream = None;
+        for sequence in 0..3 {
+            let (len, _) = builder.build_packet_into_buffer(&mut buffer, packet_type, target, 9).unwrap();
+            let header = InstrumentedHeader::parse(&buffer[offset..len], defaults::PAYLOAD_MAGIC)
+                .unwrap_or_else(|| panic!("{:?} packet without header", packet_type));
+            assert_eq!(header.sequence, sequence);
+            assert_eq!(*stream.get_or_insert(header.stream_id), header.stream_id);
+        }
+    }
+}
+
+#[test]
+fn test_payload_pattern_yaml() {
+    let parse = |yaml: &str| serde_yaml::from_str::<PayloadPattern>(yaml).unwrap();
+
+    assert_eq!(parse("type: zero"), PayloadPattern::Zero);
+    assert_eq!(parse("type: hex\npattern: deadbeef"), PayloadPattern::Hex { pattern: "deadbeef".to_string() });
+    assert_eq!(parse("type: instrumented"), PayloadPattern::instrumented());
+    assert_eq!(
+        parse("type: instrumented\nmagic: 0x12345678\nstream_base: 7"),
+        PayloadPattern::Instrumented { magic: 0x1234_5678, stream_base: 7 }
+    );
+
+    let attack: router_flood::LoadConfig = serde_yaml::from_str(
+        "threads: 2\npacket_rate: 100\npayload_size: 64\nduration: null\nburst_mode: false\n\
+         payload_pattern:\n  type: file\n  path: payload.bin\n",
+    )
+    .unwrap();
+    assert_eq!(attack.payload_pattern, PayloadPattern::File { path: "payload.bin".to_string() });
+}
@@ -0,0 +1,140 @@
+//! Payload size distribution tests
+
+use router_flood::config::{validate_config, Config, PayloadSizes, ScenarioPhase, SizeDistribution, WeightedSize};
+use router_flood::constants::icmp;
+use router_flood::packet::{PacketBuilder, PacketSizes, PacketType, PayloadSizer};
+use router_flood::utils::rng::BatchedRng;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+fn weighted(table: &[(usize, f64)]) -> SizeDistribution {
+    SizeDistribution::Weighted {
+        sizes: table.iter().map(|&(size, weight)| WeightedSize { size, weight }).collect(),
+    }
+}
+
+#[test]
+fn test_weighted_sizes_follow_weights() {
+    let sizer = PayloadSizer::new(&weighted(&[(64, 7.0), (576, 4.0), (1400, 1.0), (900, 0.0)]), 28);
+    let mut rng = BatchedRng::new();
+
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for _ in 0..12_000 {
+        *counts.entry(sizer.sample(&mut rng)).or_default() += 1;
+    }
+
+    assert_eq!(counts.get(&900), None);
+    for (size, expected) in [(64, 7000), (576, 4000), (1400, 1000)] {
+        let count = counts[&size] as i64;
+        assert!((count - expected).abs() < 400, "size {}: {} samples", size, count);
+    }
+    assert_eq!(sizer.max(), 1400);
+}
+
+#[test]
+fn test_range_and_fixed_sizes() {
+    let mut rng = BatchedRng::new();
+
+    let range = PayloadSizer::new(&SizeDistribution::Range { min: 64, max: 1400 }, 28);
+    let samples: Vec<usize> = (0..2000).map(|_| range.sample(&mut rng)).collect();
+    assert!(samples.iter().all(|size| (64..=1400).contains(size)));
+    // Small, medium and large buckets are all used
+    assert!(samples.iter().any(|&size| size <= 200));
+    assert!(samples.iter().any(|&size| size > 200 && size <= 800));
+    assert!(samples.iter().any(|&size| size > 800));
+
+    let fixed = PayloadSizer::new(&SizeDistribution::Fixed { size: 56 }, 28);
+    assert!((0..100).all(|_| fixed.sample(&mut rng) == 56));
+}
+
+#[test]
+fn test_sizes_resolved_per_protocol() {
+    let mut config = Config::default();
+    config.attack.payload_size = 512;
+
+    // Legacy behavior: fixed UDP payload, ping-sized ICMP
+    let sizes = PacketSizes::from_config(&config.attack);
+    assert_eq!(sizes.udp, SizeDistribution::Fixed { size: 512 });
+    assert_eq!(sizes.icmp, SizeDistribution::Range { min: icmp::MIN_PING_SIZE, max: icmp::MAX_PING_SIZE });
+
+    config.attack.payload_sizes = PayloadSizes {
+        default: Some(SizeDistribution::Range { min: 64, max: 1400 }),
+        udp: None,
+        icmp: Some(SizeDistribution::Fixed { size: 56 }),
+        tcp: None,
+    };
+    let sizes = PacketSizes::from_config(&config.attack);
+    assert_eq!(sizes.udp, SizeDistribution::Range { min: 64, max: 1400 });
+    assert_eq!(sizes.icmp, SizeDistribution::Fixed { size: 56 });
+    assert_eq!(sizes.max_payload(), 1400);
+}
+
+#[test]
+fn test_builder_uses_protocol_sizes() {
+    let sizes = PacketSizes {
+        udp: SizeDistribution::Range { min: 64, max: 1400 },
+        icmp: SizeDistribution::Fixed { size: 56 },
+        tcp: SizeDistribution::Fixed { size: 0 },
+    };
+    let mut builder = PacketBuilder::with_sizes(&sizes, Default::default());
+    let target: IpAddr = "192.168.1.1".parse().unwrap();
+    let mut buffer = vec![0u8; 1500];
+
+    let udp_lengths: Vec<usize> = (0..200)
+        .map(|_| builder.build_packet_into_buffer(&mut buffer, PacketType::Udp, target, 53).unwrap().0)
+        .collect();
+    assert!(udp_lengths.iter().all(|len| (28 + 64..=28 + 1400).contains(len)));
+    assert!(udp_lengths.iter().any(|&len| len != udp_lengths[0]));
+
+    for _ in 0..20 {
+        let (len, protocol) = builder.build_packet_into_buffer(&mut buffer, PacketType::Icmp, target, 0).unwrap();
+        assert_eq!((len, protocol), (20 + 8 + 56, "ICMP"));
+    }
+
+    let ipv6: IpAddr = "2001:db8::1".parse().unwrap();
+    let (len, _) = builder.build_packet_into_buffer(&mut buffer, PacketType::Ipv6Icmp, ipv6, 0).unwrap();
+    assert_eq!(len, 40 + 8 + 56);
+}
+
+#[test]
+fn test_payload_size_validation() {
+    let mut config = Config::default();
+    config.attack.payload_sizes.udp = Some(weighted(&[(64, 7.0), (576, 4.0), (1400, 1.0)]));
+    assert!(validate_config(&config).is_ok());
+
+    for invalid in [
+        SizeDistribution::Range { min: 800, max: 64 },
+        SizeDistribution::Fixed { size: 9000 },
+        weighted(&[]),
+        weighted(&[(64, 0.0)]),
+        weighted(&[(64, -1.0), (128, 2.0)]),
+    ] {
+        config.attack.payload_sizes.icmp = Some(invalid.clone());
+        assert!(validate_config(&config).is_err(), "accepted {:?}", invalid);
+    }
+}
+
+#[test]
+fn test_payload_sizes_yaml() {
+    let sizes: PayloadSizes = serde_yaml::from_str(
+        "udp:\n  type: range\n  min: 64\n  max: 1400\n\
+         icmp:\n  type: fixed\n  size: 56\n\
+         default:\n  type: weighted\n  sizes:\n    - {size: 64, weight: 7}\n    - {size: 1400, weight: 1}\n",
+    )
+    .unwrap();
+
+    assert_eq!(sizes.udp, Some(SizeDistribution::Range { min: 64, max: 1400 }));
+    assert_eq!(sizes.icmp, Some(SizeDistribution::Fixed { size: 56 }));
+    assert_eq!(sizes.default, Some(weighted(&[(64, 7.0), (1400, 1.0)])));
+}
+
+#[test]
+fn test_scenario_phase_payload_size_fixes_udp() {
+    let mut base = Config::default();
+    base.attack.payload_sizes.default = Some(SizeDistribution::Range { min: 64, max: 1400 });
+
+    let phase: ScenarioPhase = serde_yaml::from_str("name: small\nduration_secs: 10\npayload_size: 100\n").unwrap();
+    let sizes = PacketSizes::from_config(&phase.apply_to(&base).attack);
+    assert_eq!(sizes.udp, SizeDistribution::Fixed { size: 100 });
+    assert_eq!(sizes.icmp, SizeDistribution::Range { min: 64, max: 1400 });
+}
@@ -0,0 +1,117 @@
+//! Destination port list and selection tests
+
+mod common;
+
+use common::tally;
+use router_flood::cli::parse_ports;
+use router_flood::config::{format_ports, service_port, validate_config, Config, PortSelection, PortSpec, ScenarioPhase};
+use router_flood::network::target::PortTarget;
+use router_flood::utils::rng::BatchedRng;
+use std::collections::{BTreeMap, HashSet};
+
+#[test]
+fn test_port_list_syntax() {
+    let spec = parse_ports("80, 1000-1004,dns,HTTPS").unwrap();
+    assert_eq!(spec.ports, vec![80, 1000, 1001, 1002, 1003, 1004, 53, 443]);
+    assert!(spec.weights.is_empty());
+
+    let all = parse_ports("all").unwrap();
+    assert_eq!(all.ports.len(), 65535);
+    assert_eq!((all.ports[0], all.ports[65534]), (1, 65535));
+
+    assert_eq!(service_port("http-alt"), Some(8080));
+    assert_eq!(service_port("gopher"), None);
+
+    for invalid in ["", "0", "70000", "2000-1000", "1-", "gopher", "80,,443", "http:0", "http:x"] {
+        assert!(parse_ports(invalid).is_err(), "accepted {:?}", invalid);
+    }
+}
+
+#[test]
+fn test_weighted_entries() {
+    let spec: PortSpec = "http:5,dns,8000-8002:2".parse().unwrap();
+    assert_eq!(spec.ports, vec![80, 53, 8000, 8001, 8002]);
+    assert_eq!(spec.weights, BTreeMap::from([(80, 5), (8000, 2), (8001, 2), (8002, 2)]));
+
+    let target = PortTarget::with_selection(vec![80, 53], PortSelection::Weighted, &BTreeMap::from([(80, 4)]));
+    let mut rng = BatchedRng::new();
+    let counts = tally(10_000, || target.next_port(&mut rng));
+    let share = counts[&80] as f64 / 10_000.0;
+    assert!((share - 0.8).abs() < 0.03, "port 80 share {}", share);
+}
+
+#[test]
+fn test_round_robin_and_sequential() {
+    let mut rng = BatchedRng::new();
+
+    let round_robin = PortTarget::new(vec![443, 80, 443]);
+    let ports: Vec<u16> = (0..6).map(|_| round_robin.next_port(&mut rng)).collect();
+    assert_eq!(ports, vec![443, 80, 443, 443, 80, 443]);
+
+    let sweep = PortTarget::with_selection(vec![443, 80, 443, 22], PortSelection::Sequential, &BTreeMap::new());
+    assert_eq!(sweep.get_ports(), &[22, 80, 443]);
+    let ports: Vec<u16> = (0..5).map(|_| sweep.next_port(&mut rng)).collect();
+    assert_eq!(ports, vec![22, 80, 443, 22, 80]);
+}
+
+#[test]
+fn test_random_selection() {
+    let target = PortTarget::with_selection((1000..1100).collect(), PortSelection::Random, &BTreeMap::new());
+    let mut rng = BatchedRng::new();
+    let counts = tally(10_000, || target.next_port(&mut rng));
+    assert_eq!(counts.len(), 100);
+    assert!(counts.values().all(|&count| count > 50));
+
+    let ports: HashSet<u16> = counts.into_keys().collect();
+    assert_eq!(ports, (1000..1100).collect());
+}
+
+#[test]
+fn test_port_validation() {
+    let mut config = Config::default();
+    config.target.port_selection = PortSelection::Weighted;
+    config.target.port_weights = BTreeMap::from([(443, 3)]);
+    assert!(validate_config(&config).is_ok());
+
+    config.target.port_weights.insert(80, 0);
+    assert!(validate_config(&config).is_err());
+
+    config.target.port_weights = BTreeMap::from([(443, 3)]);
+    config.target.port_selection = PortSelection::RoundRobin;
+    assert!(validate_config(&config).is_err());
+
+    config.target.port_weights.clear();
+    config.target.ports = vec![80, 0];
+    assert!(validate_config(&config).is_err());
+
+    assert_eq!("round-robin".parse::<PortSelection>().unwrap(), PortSelection::RoundRobin);
+    assert!("shuffle".parse::<PortSelection>().is_err());
+}
+
+#[test]
+fn test_ports_yaml() {
+    let config: Config = serde_yaml::from_str(
+        &serde_yaml::to_string(&Config::default())
+            .unwrap()
+            .replace("- 80\n  - 443\n", "- 53\n  - 8000-8002\n  - https\n"),
+    )
+    .unwrap();
+    assert_eq!(config.target.ports, vec![53, 8000, 8001, 8002, 443]);
+
+    let phase: ScenarioPhase = serde_yaml::from_str("name: sweep\nduration_secs: 10\nports: \"ssh,20-21\"\n").unwrap();
+    assert_eq!(phase.ports, Some(vec![22, 20, 21]));
+
+    let phase: ScenarioPhase = serde_yaml::from_str("name: plain\nduration_secs: 10\n").unwrap();
+    assert_eq!(phase.ports, None);
+
+    // Weights belong in port_weights
+    assert!(serde_yaml::from_str::<ScenarioPhase>("name: w\nduration_secs: 10\nports: [\"http:5\"]\n").is_err());
+}
+
+#[test]
+fn test_format_ports() {
+    assert_eq!(format_ports(&[80, 443]), "80, 443");
+    assert_eq!(format_ports(&parse_ports("1000-2000,http").unwrap().ports), "1000-2000, 80");
+    assert_eq!(format_ports(&parse_ports("all").unwrap().ports), "1-65535");
+    assert_eq!(format_ports(&[1, 2, 3, 5]), "1-3, 5");
+}
@@ -0,0 +1,138 @@
+//! Packet and byte quota tests
+
+use router_flood::config::{validate_config, Config};
+use router_flood::network::phase::{PhaseControl, PhaseSettings};
+use router_flood::network::worker_manager::Workers;
+use router_flood::stats::{QuotaGrant, SendQuota, Stats};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_packet_quota_is_exact() {
+    let quota = SendQuota::new(Some(3), None);
+
+    for _ in 0..3 {
+        assert_eq!(quota.reserve(100), QuotaGrant::Granted);
+    }
+    // The last packets are still in flight
+    assert_eq!(quota.reserve(100), QuotaGrant::Busy);
+    assert!(!quota.is_exhausted());
+
+    quota.commit(100);
+    quota.commit(100);
+    // A failed send hands its reservation back
+    quota.release(100);
+    assert_eq!(quota.reserve(100), QuotaGrant::Granted);
+    quota.commit(100);
+
+    assert!(quota.is_exhausted());
+    assert_eq!(quota.reserve(100), QuotaGrant::Exhausted);
+    assert_eq!(quota.committed(), (3, 300));
+}
+
+#[test]
+fn test_byte_quota_never_overshoots() {
+    let quota = SendQuota::new(None, Some(1000));
+
+    assert_eq!(quota.reserve(600), QuotaGrant::Granted);
+    quota.commit(600);
+    // 600 + 500 would exceed the limit and nothing else is in flight
+    assert_eq!(quota.reserve(500), QuotaGrant::Exhausted);
+    assert!(quota.is_exhausted());
+    assert_eq!(quota.committed(), (1, 600));
+}
+
+#[test]
+fn test_concurrent_reservations_hit_quota_exactly() {
+    let quota = Arc::new(SendQuota::new(Some(10_000), None));
+
+    let handles: Vec<_> = (0..8)
+        .map(|worker| {
+            let quota = Arc::clone(&quota);
+            thread::spawn(move || {
+                let mut attempt = 0u64;
+                loop {
+                    match quota.reserve(64) {
+                        QuotaGrant::Granted => {
+                            // Every 7th send fails and must not count
+                            attempt += 1;
+                            if (attempt + worker).is_multiple_of(7) {
+                                quota.release(64);
+                            } else {
+                                quota.commit(64);
+                            }
+                        }
+                        QuotaGrant::Busy => thread::yield_now(),
+                        QuotaGrant::Exhausted => break,
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(quota.committed(), (10_000, 640_000));
+}
+
+#[test]
+fn test_quota_config() {
+    let mut config = Config::default();
+    config.attack.max_packets = Some(10_000_000);
+    config.attack.max_bytes = Some(1 << 30);
+    assert!(validate_config(&config).is_ok());
+
+    config.attack.max_packets = Some(0);
+    assert!(validate_config(&config).is_err());
+
+    let attack: router_flood::LoadConfig = serde_yaml::from_str(
+        "threads: 2\npacket_rate: 100\npayload_size: 64\nduration: null\nburst_mode: false\nmax_packets: 5000\n",
+    )
+    .unwrap();
+    assert_eq!(attack.max_packets, Some(5000));
+    assert_eq!(attack.max_bytes, None);
+}
+
+#[tokio::test]
+async fn test_no_quota_never_completes() {
+    let stats = Stats::new(None);
+    assert!(tokio::time::timeout(Duration::from_millis(50), stats.quota_reached()).await.is_err());
+    assert_eq!(stats.reserve_quota(1500), QuotaGrant::Granted);
+    assert!(!stats.quota_exhausted());
+}
+
+async fn run_until_quota(max_packets: Option<u64>, max_bytes: Option<u64>) -> Arc<Stats> {
+    let mut config = Config::default();
+    config.attack.threads = 4;
+    config.attack.packet_rate = 5000.0;
+    config.safety.dry_run = true;
+    // Simulated send failures must not count against the quota
+    config.safety.perfect_simulation = false;
+
+    let stats = Arc::new(Stats::new(None).with_quota(max_packets, max_bytes));
+    let control = Arc::new(PhaseControl::new(PhaseSettings::from_config(&config, None, Instant::now())));
+    let workers = Workers::new(&config, Arc::clone(&stats), control, "192.168.1.1".parse().unwrap(), None, true).unwrap();
+
+    tokio::time::timeout(Duration::from_secs(10), stats.quota_reached()).await.unwrap();
+    workers.stop();
+    workers.join_all().await.unwrap();
+    stats
+}
+
+#[tokio::test]
+async fn test_workers_stop_at_packet_quota() {
+    let stats = run_until_quota(Some(2500), None).await;
+    assert_eq!(stats.packets_sent(), 2500);
+    assert!(stats.packets_failed() > 0);
+}
+
+#[tokio::test]
+async fn test_workers_stop_at_byte_quota() {
+    let max_bytes = 500_000;
+    let stats = run_until_quota(None, Some(max_bytes)).await;
+    assert!(stats.bytes_sent() <= max_bytes);
+    // Stops at the first packet that no longer fits
+    assert!(stats.bytes_sent() > max_bytes - 1500, "{}", stats.bytes_sent());
+}
@@ -0,0 +1,102 @@
+//! Global rate limiter tests
+
+use router_flood::config::{validate_config, Config};
+use router_flood::network::phase::{PhaseControl, PhaseSettings};
+use router_flood::network::rate_limiter::RateLimiter;
+use router_flood::network::worker_manager::Workers;
+use router_flood::stats::Stats;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_limiter_built_from_safety_settings() {
+    let mut config = Config::default();
+    config.safety.max_packets_per_second = Some(1000.0);
+    assert!(RateLimiter::from_safety(&config.safety).is_some());
+
+    config.safety.rate_limit = false;
+    assert!(RateLimiter::from_safety(&config.safety).is_none());
+
+    config.safety.rate_limit = true;
+    config.safety.max_packets_per_second = None;
+    config.safety.max_bandwidth_mbps = None;
+    assert!(RateLimiter::from_safety(&config.safety).is_none());
+}
+
+#[test]
+fn test_packet_cap_wait() {
+    let limiter = RateLimiter::new(Some(1000.0), None);
+
+    // Within the bucket depth (10 ms at 1000 pps)
+    assert_eq!(limiter.charge(5, 0), Duration::ZERO);
+
+    // A second's worth of packets must be paid back almost entirely
+    let wait = limiter.charge(1000, 0);
+    assert!(wait > Duration::from_millis(950) && wait <= Duration::from_millis(1005), "wait {:?}", wait);
+}
+
+#[test]
+fn test_bandwidth_cap_wait() {
+    let limiter = RateLimiter::new(None, Some(8.0));
+
+    // 1 MB is 8 Mbit, one second at 8 Mbps
+    let wait = limiter.charge(1, 1_000_000);
+    assert!(wait > Duration::from_millis(950) && wait <= Duration::from_secs(1), "wait {:?}", wait);
+}
+
+#[test]
+fn test_limiter_shared_across_threads() {
+    let limiter = Arc::new(RateLimiter::new(Some(1000.0), None));
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let limiter = Arc::clone(&limiter);
+            std::thread::spawn(move || limiter.charge(250, 0))
+        })
+        .collect();
+    let longest = handles.into_iter().map(|handle| handle.join().unwrap()).max().unwrap();
+
+    // Together the threads sent a full second's worth
+    assert!(longest > Duration::from_millis(950), "longest wait {:?}", longest);
+}
+
+#[test]
+fn test_rate_cap_validation() {
+    let mut config = Config::default();
+    config.safety.max_packets_per_second = Some(0.0);
+    assert!(validate_config(&config).is_err());
+
+    config.safety.max_packets_per_second = Some(5000.0);
+    config.safety.max_bandwidth_mbps = Some(-1.0);
+    assert!(validate_config(&config).is_err());
+
+    config.safety.max_bandwidth_mbps = Some(10.0);
+    assert!(validate_config(&config).is_ok());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_workers_respect_aggregate_cap() {
+    let mut config = Config::default();
+    config.attack.threads = 4;
+    config.attack.packet_rate = 10_000.0;
+    config.safety.dry_run = true;
+    config.safety.perfect_simulation = true;
+    config.safety.max_packets_per_second = Some(2000.0);
+
+    let started = Instant::now();
+    let stats = Arc::new(Stats::new(None));
+    let control = Arc::new(PhaseControl::new(PhaseSettings::from_config(&config, None, Instant::now())));
+    let workers = Workers::new(&config, Arc::clone(&stats), control, "192.168.1.1".parse().unwrap(), None, true).unwrap();
+
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    workers.stop();
+    workers.join_all().await.unwrap();
+
+    // Without the cap the workers would send 40000 pps; with it, at most
+    // 2000 pps over the run plus the 10 ms bucket depth
+    let budget = 2000.0 * (started.elapsed().as_secs_f64() + 0.010);
+    let sent = stats.packets_sent();
+    assert!(sent >= 500 && sent as f64 <= budget, "sent {} budget {:.0}", sent, budget);
+    assert!(stats.throttle_events() > 0);
+    assert!(stats.throttled_time() > Duration::ZERO);
+}
@@ -0,0 +1,164 @@
+//! Multi-phase scenario tests
+
+use router_flood::config::{load_scenario, validate_config, Config, ExportFormat, RateProfile, Scenario, SizeDistribution};
+use router_flood::network::phase::{PhaseControl, PhaseSettings};
+use router_flood::stats::Stats;
+use std::io::Write;
+use std::time::Instant;
+use tempfile::{NamedTempFile, TempDir};
+
+const SCENARIO_YAML: &str = r#"
+phases:
+  - name: warmup
+    duration_secs: 30
+    rate_profile:
+      type: linear_ramp
+      start_rate: 50
+      end_rate: 500
+      duration_secs: 30
+  - name: sustain
+    duration_secs: 120
+    packet_rate: 500
+  - name: spike
+    duration_secs: 10
+    packet_rate: 5000
+    payload_size: 64
+    ports: [8080]
+  - name: cooldown
+    duration_secs: 20
+"#;
+
+fn scenario() -> Scenario {
+    serde_yaml::from_str(SCENARIO_YAML).unwrap()
+}
+
+#[test]
+fn test_scenario_phases_fall_back_to_base_config() {
+    let mut base = Config::default();
+    base.attack.rate_profile = RateProfile::Sine { min_rate: 100.0, max_rate: 200.0, period_secs: 10 };
+    let scenario = scenario();
+    assert_eq!(scenario.total_duration_secs(), 180);
+
+    let sustain = scenario.phases[1].apply_to(&base);
+    assert_eq!(sustain.attack.packet_rate, 500.0);
+    assert_eq!(sustain.attack.rate_profile, RateProfile::Constant);
+    assert_eq!(sustain.attack.duration, Some(120));
+    assert_eq!(sustain.target.ports, base.target.ports);
+
+    let spike = scenario.phases[2].apply_to(&base);
+    assert_eq!(spike.attack.payload_size, 64);
+    assert_eq!(spike.target.ports, vec![8080]);
+
+    let cooldown = scenario.phases[3].apply_to(&base);
+    assert_eq!(cooldown.attack.packet_rate, base.attack.packet_rate);
+    assert_eq!(cooldown.attack.rate_profile, base.attack.rate_profile);
+    assert!(cooldown.scenario.is_none());
+}
+
+#[test]
+fn test_scenario_validation() {
+    let mut config = Config { scenario: Some(scenario()), ..Default::default() };
+    assert!(validate_config(&config).is_ok());
+
+    config.scenario = Some(Scenario { phases: Vec::new() });
+    assert!(validate_config(&config).is_err());
+
+    let mut invalid = scenario();
+    invalid.phases[2].packet_rate = Some(0.0);
+    config.scenario = Some(invalid);
+    let message = validate_config(&config).unwrap_err().to_string();
+    assert!(message.contains("spike"), "unexpected error: {}", message);
+
+    let mut invalid = scenario();
+    invalid.phases[0].duration_secs = 0;
+    config.scenario = Some(invalid);
+    assert!(validate_config(&config).is_err());
+}
+
+#[test]
+fn test_load_scenario_file() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(SCENARIO_YAML.as_bytes()).unwrap();
+
+    let scenario = load_scenario(file.path().to_str().unwrap()).unwrap();
+    let names: Vec<&str> = scenario.phases.iter().map(|phase| phase.name.as_str()).collect();
+    assert_eq!(names, ["warmup", "sustain", "spike", "cooldown"]);
+
+    assert!(load_scenario("/nonexistent/scenario.yaml").is_err());
+}
+
+#[test]
+fn test_phase_control_publishes_settings() {
+    let base = Config::default();
+    let scenario = scenario();
+    let control = PhaseControl::new(PhaseSettings::from_config(&base, None, Instant::now()));
+    assert_eq!(control.generation(), 0);
+    assert!(control.current().name.is_none());
+
+    let spike = scenario.phases[2].apply_to(&base);
+    control.publish(PhaseSettings::from_config(&spike, Some("spike".to_string()), Instant::now()));
+
+    let current = control.current();
+    assert_eq!(control.generation(), 1);
+    assert_eq!(current.name.as_deref(), Some("spike"));
+    assert_eq!(current.packet_sizes.udp, SizeDistribution::Fixed { size: 64 });
+    assert_eq!(current.target_port.get_ports(), &[8080]);
+}
+
+#[test]
+fn test_stats_track_phases() {
+    let stats = Stats::new(None);
+    assert!(stats.current_phase().is_none());
+    assert!(stats.end_phase().is_none());
+
+    stats.increment_sent(100, "UDP");
+    stats.begin_phase("warmup");
+    for _ in 0..10 {
+        stats.increment_sent(100, "UDP");
+    }
+    stats.increment_failed();
+
+    stats.begin_phase("spike");
+    for _ in 0..5 {
+        stats.increment_sent(64, "TCP");
+    }
+    let spike = stats.end_phase().unwrap();
+
+    let phases = stats.completed_phases();
+    assert_eq!(phases.len(), 2);
+    assert_eq!(phases[0].name, "warmup");
+    assert_eq!(phases[0].packets_sent, 10);
+    assert_eq!(phases[0].packets_failed, 1);
+    assert_eq!(phases[0].bytes_sent, 1000);
+    assert_eq!(spike.packets_sent, 5);
+    assert_eq!(spike.bytes_sent, 320);
+
+    // The last phase keeps tagging exports after it ends
+    assert_eq!(stats.current_phase().as_deref(), Some("spike"));
+}
+
+#[tokio::test]
+async fn test_exports_tagged_with_phase() {
+    let dir = TempDir::new().unwrap();
+    let mut export = Config::default().export;
+    export.enabled = true;
+    export.format = ExportFormat::Csv;
+    export.path = dir.path().to_str().unwrap().to_string();
+
+    let stats = Stats::new(Some(export));
+    stats.begin_phase("sustain");
+    stats.increment_sent(100, "UDP");
+    stats.end_phase();
+    stats.export_stats().await.unwrap();
+
+    let entry = std::fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap();
+    let filename = entry.file_name().into_string().unwrap();
+    assert!(filename.starts_with("router_flood_stats_sustain_"), "unexpected file {}", filename);
+
+    let csv = std::fs::read_to_string(entry.path()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].contains(",scope,phase,"));
+    assert!(lines[1].contains(",session,sustain,"));
+    assert!(lines[2].contains(",phase,sustain,"));
+}
@@ -0,0 +1,125 @@
+//! Source port policy tests
+
+mod common;
+
+use common::{build_packet, packet_builder, source_port};
+use router_flood::config::{validate_config, Config, SourceConfig, SourcePort};
+use router_flood::constants::{EPHEMERAL_PORT_MAX, EPHEMERAL_PORT_MIN};
+use router_flood::packet::{PacketOptions, PacketType, PortPicker, SourceAddresses};
+use router_flood::utils::rng::BatchedRng;
+use std::collections::HashSet;
+
+#[test]
+fn test_sequential_wraps() {
+    let mut picker = PortPicker::new(SourcePort::Sequential { min: 40000, max: 40002 });
+    let mut rng = BatchedRng::new();
+    let ports: Vec<u16> = (0..7).map(|_| picker.next(&mut rng)).collect();
+    assert_eq!(ports, vec![40000, 40001, 40002, 40000, 40001, 40002, 40000]);
+
+    let mut top = PortPicker::new(SourcePort::Sequential { min: 65534, max: 65535 });
+    let ports: Vec<u16> = (0..3).map(|_| top.next(&mut rng)).collect();
+    assert_eq!(ports, vec![65534, 65535, 65534]);
+}
+
+#[test]
+fn test_range_and_fixed() {
+    let mut rng = BatchedRng::new();
+
+    let mut range = PortPicker::new(SourcePort::Range { min: 5000, max: 5009 });
+    let ports: HashSet<u16> = (0..1000).map(|_| range.next(&mut rng)).collect();
+    assert_eq!(ports, (5000..=5009).collect());
+
+    let mut fixed = PortPicker::new(SourcePort::Fixed { port: 5353 });
+    assert!((0..100).all(|_| fixed.next(&mut rng) == 5353));
+
+    let mut random = PortPicker::new(SourcePort::Random);
+    let ports: HashSet<u16> = (0..1000).map(|_| random.next(&mut rng)).collect();
+    assert!(ports.len() > 900);
+    assert!(ports.iter().all(|&port| port >= EPHEMERAL_PORT_MIN));
+}
+
+#[test]
+fn test_parse_policies() {
+    let parse = |spec: &str| spec.parse::<SourcePort>().unwrap();
+
+    assert_eq!(parse("random"), SourcePort::Random);
+    assert_eq!(parse("5000"), SourcePort::Fixed { port: 5000 });
+    assert_eq!(parse("5000-5009"), SourcePort::Range { min: 5000, max: 5009 });
+    assert_eq!(parse("Sequential"), SourcePort::Sequential { min: EPHEMERAL_PORT_MIN, max: EPHEMERAL_PORT_MAX });
+    assert_eq!(parse("sequential:40000-40999"), SourcePort::Sequential { min: 40000, max: 40999 });
+
+    for invalid in ["", "port", "70000", "5000-", "-5000", "1-2-3", "sequential:40000"] {
+        assert!(invalid.parse::<SourcePort>().is_err(), "accepted {:?}", invalid);
+    }
+}
+
+#[test]
+fn test_port_validation() {
+    let mut config = Config::default();
+    assert_eq!(config.source.port, SourcePort::Random);
+
+    config.source.port = SourcePort::Sequential { min: 1024, max: 1024 };
+    assert!(validate_config(&config).is_ok());
+
+    for invalid in [
+        SourcePort::Fixed { port: 0 },
+        SourcePort::Range { min: 0, max: 100 },
+        SourcePort::Range { min: 2000, max: 1000 },
+        SourcePort::Sequential { min: 40001, max: 40000 },
+    ] {
+        config.source.port = invalid;
+        assert!(validate_config(&config).is_err(), "accepted {:?}", invalid);
+    }
+}
+
+#[test]
+fn test_packets_use_policy() {
+    let source = SourceAddresses { port: SourcePort::Fixed { port: 4242 }, ..Default::default() };
+    let mut builder = packet_builder(&PacketOptions { source, ..Default::default() });
+    for packet_type in [
+        PacketType::Udp,
+        PacketType::TCP_SYN,
+        PacketType::TCP_ACK,
+        PacketType::TCP_FIN,
+        PacketType::TCP_RST,
+        PacketType::Ipv6Udp,
+        PacketType::IPV6_TCP_SYN,
+    ] {
+        assert_eq!(source_port(packet_type, &build_packet(&mut builder, packet_type, 80)), 4242, "{:?}", packet_type);
+    }
+}
+
+#[test]
+fn test_each_stream_counts_on_its_own() {
+    let source = SourceAddresses { port: SourcePort::Sequential { min: 30000, max: 30009 }, ..Default::default() };
+    let mut builder = packet_builder(&PacketOptions { source, ..Default::default() });
+    let mut next_port = |packet_type| source_port(packet_type, &build_packet(&mut builder, packet_type, 80));
+
+    // Interleaving protocols does not disturb each stream's sequence
+    for index in 0..15u16 {
+        let expected = 30000 + index % 10;
+        assert_eq!(next_port(PacketType::Udp), expected);
+        assert_eq!(next_port(PacketType::TCP_SYN), expected);
+        assert_eq!(next_port(PacketType::IPV6_TCP_SYN), expected);
+    }
+}
+
+#[test]
+fn test_source_port_yaml() {
+    let parse = |yaml: &str| serde_yaml::from_str::<SourceConfig>(yaml).unwrap().port;
+
+    assert_eq!(parse("{}"), SourcePort::Random);
+    assert_eq!(parse("port:\n  type: fixed\n  port: 5000\n"), SourcePort::Fixed { port: 5000 });
+    assert_eq!(parse("port:\n  type: range\n  min: 10\n  max: 20\n"), SourcePort::Range { min: 10, max: 20 });
+    assert_eq!(
+        parse("port:\n  type: sequential\n  min: 40000\n"),
+        SourcePort::Sequential { min: 40000, max: EPHEMERAL_PORT_MAX }
+    );
+
+    let resolved = SourceAddresses::resolve(
+        &SourceConfig { port: SourcePort::Fixed { port: 7 }, ..Default::default() },
+        None,
+    )
+    .unwrap();
+    assert_eq!(resolved.port, SourcePort::Fixed { port: 7 });
+}
@@ -0,0 +1,199 @@
+//! Source address configuration tests
+
+use pnet::datalink::NetworkInterface;
+use pnet::packet::arp::ArpPacket;
+use pnet::packet::ethernet::EthernetPacket;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::udp::{self, UdpPacket};
+use pnet::packet::Packet;
+use pnet::util::MacAddr;
+use router_flood::config::{validate_config, Config, SourceAddress, SourceConfig, SourceSelection};
+use router_flood::packet::{PacketBuilder, PacketOptions, PacketSizes, PacketType, SourceAddresses, SourcePool};
+use router_flood::utils::rng::BatchedRng;
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+fn pool(cidr: &str, selection: SourceSelection) -> SourcePool {
+    SourcePool::from_network(SourceAddress::parse_cidr(cidr).unwrap(), selection)
+}
+
+fn builder(source: SourceAddresses) -> PacketBuilder {
+    let options = PacketOptions { source, ..Default::default() };
+    PacketBuilder::with_options(&PacketSizes::from_config(&Config::default().attack), &options, Default::default())
+}
+
+fn interface(mac: Option<MacAddr>, ips: &[&str]) -> NetworkInterface {
+    NetworkInterface {
+        name: "lab0".to_string(),
+        description: String::new(),
+        index: 2,
+        mac,
+        ips: ips.iter().map(|ip| ip.parse().unwrap()).collect(),
+        flags: 0,
+    }
+}
+
+#[test]
+fn test_pool_hosts() {
+    let lan = pool("10.20.30.0/24", SourceSelection::PerFlow);
+    assert!(!lan.contains("10.20.30.0".parse().unwrap()));
+    assert!(lan.contains("10.20.30.1".parse().unwrap()));
+    assert!(lan.contains("10.20.30.254".parse().unwrap()));
+    assert!(!lan.contains("10.20.30.255".parse().unwrap()));
+
+    let point_to_point = pool("172.16.0.0/31", SourceSelection::PerFlow);
+    assert!(point_to_point.contains("172.16.0.0".parse().unwrap()));
+    assert!(point_to_point.contains("172.16.0.1".parse().unwrap()));
+
+    let single = pool("fd00::5/128", SourceSelection::PerPacket);
+    let mut picker = single.picker(&mut BatchedRng::new());
+    assert_eq!(picker.next_ipv6(&mut BatchedRng::new()), "fd00::5".parse::<Ipv6Addr>().unwrap());
+}
+
+#[test]
+fn test_per_packet_and_per_flow_selection() {
+    let mut rng = BatchedRng::new();
+
+    let mut per_packet = pool("10.1.0.0/16", SourceSelection::PerPacket).picker(&mut rng);
+    let addresses: HashSet<Ipv4Addr> = (0..1000).map(|_| per_packet.next_ipv4(&mut rng)).collect();
+    assert!(addresses.len() > 900);
+    assert!(addresses.iter().all(|ip| ip.octets()[..2] == [10, 1]));
+
+    let per_flow = pool("10.1.0.0/16", SourceSelection::PerFlow);
+    let mut flow = per_flow.picker(&mut rng);
+    let first = flow.next_ipv4(&mut rng);
+    assert!((0..100).all(|_| flow.next_ipv4(&mut rng) == first));
+
+    // Different flows spread over the pool
+    let flows: HashSet<Ipv4Addr> = (0..50).map(|_| per_flow.picker(&mut rng).next_ipv4(&mut rng)).collect();
+    assert!(flows.len() > 40);
+}
+
+#[test]
+fn test_source_validation() {
+    let source = SourceConfig {
+        ipv4: Some(SourceAddress::Pool { cidr: "10.0.0.0/8".to_string(), selection: SourceSelection::PerPacket }),
+        ipv6: Some(SourceAddress::Fixed { address: "fd00::1".parse().unwrap() }),
+        ..Default::default()
+    };
+    let mut config = Config { source, ..Default::default() };
+    assert!(validate_config(&config).is_ok());
+
+    for invalid in [
+        SourceAddress::Pool { cidr: "8.8.8.0/24".to_string(), selection: SourceSelection::PerFlow },
+        SourceAddress::Pool { cidr: "10.0.0.0/7".to_string(), selection: SourceSelection::PerFlow },
+        SourceAddress::Pool { cidr: "10.0.0.0/33".to_string(), selection: SourceSelection::PerFlow },
+        SourceAddress::Pool { cidr: "fd00::/64".to_string(), selection: SourceSelection::PerFlow },
+        SourceAddress::Fixed { address: "1.1.1.1".parse().unwrap() },
+        SourceAddress::Fixed { address: "fd00::1".parse().unwrap() },
+    ] {
+        config.source.ipv4 = Some(invalid.clone());
+        assert!(validate_config(&config).is_err(), "accepted {:?}", invalid);
+    }
+
+    config.source.ipv4 = Some(SourceAddress::Interface);
+    config.source.ipv6 = Some(SourceAddress::Pool { cidr: "2001:db8::/64".to_string(), selection: SourceSelection::PerFlow });
+    assert!(validate_config(&config).is_err());
+}
+
+#[test]
+fn test_interface_source() {
+    let config = SourceConfig {
+        ipv4: Some(SourceAddress::Interface),
+        ipv6: Some(SourceAddress::Interface),
+        ..Default::default()
+    };
+    let mac = MacAddr::new(0x00, 0x11, 0x22, 0x33, 0x44, 0x55);
+    let lab = interface(Some(mac), &["fe80::1/64", "10.9.8.7/24", "fd12::7/64"]);
+
+    let resolved = SourceAddresses::resolve(&config, Some(&lab)).unwrap();
+    assert_eq!(resolved.ipv4, SourcePool::fixed("10.9.8.7".parse().unwrap()));
+    // Routable addresses win over link-local ones
+    assert_eq!(resolved.ipv6, SourcePool::fixed("fd12::7".parse().unwrap()));
+    assert_eq!(resolved.mac, Some(mac));
+
+    assert!(SourceAddresses::resolve(&config, None).is_err());
+    assert!(SourceAddresses::resolve(&config, Some(&interface(None, &["10.9.8.7/24"]))).is_err());
+
+    // Unconfigured families keep the old behavior
+    let defaults = SourceAddresses::resolve(&SourceConfig::default(), Some(&lab)).unwrap();
+    assert_eq!(defaults.ipv4, SourcePool::legacy_ipv4());
+    assert_eq!(defaults.mac, None);
+}
+
+#[test]
+fn test_ipv4_packets_use_source() {
+    let source = SourceAddresses {
+        ipv4: pool("172.16.5.0/24", SourceSelection::PerPacket),
+        ..Default::default()
+    };
+    let mut builder = builder(source.clone());
+    let target: IpAddr = "172.16.0.1".parse().unwrap();
+    let mut buffer = vec![0u8; 1500];
+
+    for packet_type in [PacketType::Udp, PacketType::TCP_SYN, PacketType::Icmp] {
+        for _ in 0..20 {
+            let (len, _) = builder.build_packet_into_buffer(&mut buffer, packet_type, target, 53).unwrap();
+            let ip = Ipv4Packet::new(&buffer[..len]).unwrap();
+            assert!(source.ipv4.contains(IpAddr::V4(ip.get_source())), "{:?} from {}", packet_type, ip.get_source());
+
+            if packet_type == PacketType::Udp {
+                let udp = UdpPacket::new(ip.payload()).unwrap();
+                let checksum = udp::ipv4_checksum(&udp, &ip.get_source(), &ip.get_destination());
+                assert_eq!(udp.get_checksum(), checksum);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_arp_uses_interface_mac_and_address() {
+    let mac = MacAddr::new(0x00, 0x11, 0x22, 0x33, 0x44, 0x55);
+    let source = SourceAddresses {
+        ipv4: SourcePool::fixed("10.9.8.7".parse().unwrap()),
+        mac: Some(mac),
+        ..Default::default()
+    };
+    let mut builder = builder(source);
+    let mut buffer = vec![0u8; 1500];
+
+    let (len, _) = builder.build_packet_into_buffer(&mut buffer, PacketType::Arp, "10.9.8.1".parse().unwrap(), 0).unwrap();
+    let frame = EthernetPacket::new(&buffer[..len]).unwrap();
+    let arp = ArpPacket::new(frame.payload()).unwrap();
+    assert_eq!(frame.get_source(), mac);
+    assert_eq!(arp.get_sender_hw_addr(), mac);
+    assert_eq!(arp.get_sender_proto_addr(), "10.9.8.7".parse::<Ipv4Addr>().unwrap());
+}
+
+#[test]
+fn test_ipv6_packets_use_source() {
+    let source = SourceAddresses {
+        ipv6: pool("fd00:1:2:3::/64", SourceSelection::PerPacket),
+        ..Default::default()
+    };
+    let mut builder = builder(source.clone());
+    let target: IpAddr = "fd00::1".parse().unwrap();
+    let mut buffer = vec![0u8; 1500];
+
+    let mut seen = HashSet::new();
+    for packet_type in [PacketType::Ipv6Udp, PacketType::IPV6_TCP_SYN, PacketType::Ipv6Icmp] {
+        for _ in 0..20 {
+            let (len, _) = builder.build_packet_into_buffer(&mut buffer, packet_type, target, 53).unwrap();
+            let ip = Ipv6Packet::new(&buffer[..len]).unwrap();
+            assert!(source.ipv6.contains(IpAddr::V6(ip.get_source())));
+            seen.insert(ip.get_source());
+        }
+    }
+    assert!(seen.len() > 50);
+
+    let yaml: SourceConfig = serde_yaml::from_str(
+        "ipv4:\n  type: interface\nipv6:\n  type: pool\n  cidr: fd00:1:2:3::/64\n  selection: per_packet\n",
+    )
+    .unwrap();
+    assert_eq!(yaml.ipv4, Some(SourceAddress::Interface));
+    assert_eq!(
+        yaml.ipv6,
+        Some(SourceAddress::Pool { cidr: "fd00:1:2:3::/64".to_string(), selection: SourceSelection::PerPacket })
+    );
+}
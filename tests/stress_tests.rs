@@ -32,8 +32,8 @@ fn stress_test_concurrent_packet_generation() {
                 for i in 0..packets_per_thread {
                     let packet_type = match i % 4 {
                         0 => PacketType::Udp,
-                        1 => PacketType::TcpSyn,
-                        2 => PacketType::TcpAck,
+                        1 => PacketType::TCP_SYN,
+                        2 => PacketType::TCP_ACK,
                         _ => PacketType::Icmp,
                     };
                     
@@ -0,0 +1,172 @@
+//! TCP flag set tests
+
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::tcp::{self, TcpPacket};
+use pnet::packet::Packet;
+use router_flood::config::{validate_config, Config, ProtocolMix, TcpFlagSet, TcpOptions, TcpOsProfile, TcpPayload};
+use router_flood::packet::{PacketBuilder, PacketOptions, PacketSizes, PacketType, TcpOptionLayout};
+use router_flood::stats::{BatchStats, Stats};
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+fn builder(mix: ProtocolMix) -> PacketBuilder {
+    PacketBuilder::with_sizes(&PacketSizes::from_config(&Config::default().attack), mix)
+}
+
+fn flags(spec: &str) -> TcpFlagSet {
+    spec.parse().unwrap()
+}
+
+/// Mix of UDP and the given flag sets only
+fn mix(udp_ratio: f64, tcp_flags: &[(&str, f64)]) -> ProtocolMix {
+    ProtocolMix {
+        udp_ratio,
+        tcp_syn_ratio: 0.0,
+        tcp_ack_ratio: 0.0,
+        tcp_fin_ratio: 0.0,
+        tcp_rst_ratio: 0.0,
+        icmp_ratio: 0.0,
+        custom_ratio: 0.0,
+        arp_ratio: 0.0,
+        ipv6: None,
+        tcp_flags: tcp_flags.iter().map(|&(spec, ratio)| (flags(spec), ratio)).collect(),
+    }
+}
+
+#[test]
+fn test_flag_set_parsing() {
+    assert_eq!(flags("syn_ack"), TcpFlagSet::SYN_ACK);
+    assert_eq!(flags("SYN-ACK"), TcpFlagSet::SYN_ACK);
+    assert_eq!(flags("ack+syn"), TcpFlagSet::SYN_ACK);
+    assert_eq!(flags("fin+psh+urg"), TcpFlagSet::XMAS);
+    assert_eq!(flags("null").bits(), 0);
+    assert_eq!(flags("ece_cwr").bits(), 0xc0);
+    assert_eq!(flags("syn+ece+cwr").bits(), 0xc2);
+
+    for invalid in ["", "syn+", "syn+bogus", "christmas", "syn,fin"] {
+        assert!(invalid.parse::<TcpFlagSet>().is_err(), "accepted {:?}", invalid);
+    }
+}
+
+#[test]
+fn test_flag_set_display() {
+    for &(name, preset) in TcpFlagSet::PRESETS {
+        assert_eq!(preset.to_string(), name);
+        assert_eq!(flags(name), preset);
+    }
+
+    let ecn_syn = flags("cwr+syn+ece");
+    assert_eq!(ecn_syn.to_string(), "syn+ece+cwr");
+    assert_eq!(flags(&ecn_syn.to_string()), ecn_syn);
+
+    assert_eq!(PacketType::Tcp(TcpFlagSet::SYN_FIN).to_string(), "TCP-SYN_FIN");
+    assert_eq!(PacketType::TCP_SYN.to_string(), "TCP-SYN");
+    assert_eq!(PacketType::Tcp(ecn_syn).protocol_name(), "TCP");
+}
+
+#[test]
+fn test_headers_carry_flags() {
+    let mut builder = builder(ProtocolMix::default());
+    let target: IpAddr = "192.168.1.1".parse().unwrap();
+
+    for &(name, preset) in TcpFlagSet::PRESETS {
+        let (packet, protocol) = builder.build_packet(PacketType::Tcp(preset), target, 80).unwrap();
+        assert_eq!(protocol, "TCP");
+
+        let ip = Ipv4Packet::new(&packet).unwrap();
+        let tcp = TcpPacket::new(ip.payload()).unwrap();
+        assert_eq!(tcp.get_flags(), preset.bits(), "{}", name);
+        assert_eq!(tcp.get_checksum(), tcp::ipv4_checksum(&tcp, &ip.get_source(), &ip.get_destination()));
+
+        if !preset.contains(TcpFlagSet::ACK) {
+            assert_eq!(tcp.get_acknowledgement(), 0, "{}", name);
+        }
+        if !preset.contains(TcpFlagSet::URG) {
+            assert_eq!(tcp.get_urgent_ptr(), 0, "{}", name);
+        }
+    }
+}
+
+#[test]
+fn test_syn_flag_sets_get_syn_options() {
+    let options = PacketOptions {
+        tcp_options: TcpOptionLayout::from_config(&TcpOptions::Preset { os: TcpOsProfile::Linux }),
+        tcp_payload: TcpPayload::None,
+        ..Default::default()
+    };
+    let mut builder = PacketBuilder::with_options(
+        &PacketSizes::from_config(&Config::default().attack), &options, Default::default(),
+    );
+    let target: IpAddr = "192.168.1.1".parse().unwrap();
+
+    for (spec, options_len) in [("syn_ack", 20), ("syn_fin", 20), ("psh_ack", 12), ("xmas", 12), ("null", 12)] {
+        let (packet, _) = builder.build_packet(PacketType::Tcp(flags(spec)), target, 443).unwrap();
+        assert_eq!(packet.len(), 40 + options_len, "{}", spec);
+    }
+}
+
+#[test]
+fn test_mix_selects_flag_sets() {
+    let mut builder = builder(mix(0.5, &[("xmas", 0.3), ("syn+fin", 0.2)]));
+    let target: IpAddr = "192.168.1.1".parse().unwrap();
+
+    let mut counts: HashMap<PacketType, usize> = HashMap::new();
+    for _ in 0..10_000 {
+        *counts.entry(builder.next_packet_type_for_ip(target)).or_insert(0) += 1;
+    }
+
+    assert_eq!(counts.len(), 3);
+    let share = |packet_type| counts[&packet_type] as f64 / 10_000.0;
+    assert!((share(PacketType::Tcp(TcpFlagSet::XMAS)) - 0.3).abs() < 0.03);
+    assert!((share(PacketType::Tcp(TcpFlagSet::SYN_FIN)) - 0.2).abs() < 0.03);
+
+    // Extra ratios add to the legacy SYN ratio
+    let mut combined = mix(0.5, &[("syn", 0.2)]);
+    combined.tcp_syn_ratio = 0.3;
+    assert_eq!(combined.tcp_ratios(), vec![(TcpFlagSet::SYN, 0.5)]);
+    assert!((combined.total() - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_tcp_flags_yaml_and_validation() {
+    let yaml = serde_yaml::to_string(&Config::default()).unwrap().replace(
+        "custom_ratio: 0.0\n",
+        "custom_ratio: 0.0\n    tcp_flags:\n      null: 0.01\n      \"syn+fin\": 0.01\n",
+    );
+    let mut config: Config = serde_yaml::from_str(&yaml).unwrap();
+    assert_eq!(
+        config.target.protocol_mix.tcp_flags,
+        BTreeMap::from([(TcpFlagSet::NULL, 0.01), (TcpFlagSet::SYN_FIN, 0.01)])
+    );
+
+    // The extra ratios count towards the total
+    assert!(validate_config(&config).is_err());
+    config.target.protocol_mix.udp_ratio -= 0.02;
+    assert!(validate_config(&config).is_ok());
+
+    config.target.protocol_mix.tcp_flags.insert(TcpFlagSet::XMAS, -0.1);
+    config.target.protocol_mix.udp_ratio += 0.1;
+    assert!(validate_config(&config).is_err());
+
+    assert!(!serde_yaml::to_string(&Config::default()).unwrap().contains("tcp_flags"));
+}
+
+#[test]
+fn test_stats_per_flag_set() {
+    let stats = Arc::new(Stats::default());
+    {
+        let mut batch = BatchStats::new(stats.clone(), 100);
+        for spec in ["syn", "xmas", "xmas", "syn+fin"] {
+            batch.increment_sent(40, "TCP");
+            batch.record_tcp_flags(flags(spec));
+        }
+    }
+    stats.increment_tcp_flags(TcpFlagSet::SYN);
+
+    assert_eq!(stats.packets_sent(), 4);
+    assert_eq!(
+        stats.tcp_flag_counts(),
+        BTreeMap::from([(TcpFlagSet::SYN, 2), (TcpFlagSet::XMAS, 2), (TcpFlagSet::SYN_FIN, 1)])
+    );
+}
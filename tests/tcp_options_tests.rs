@@ -0,0 +1,152 @@
+//! TCP option tests
+
+mod common;
+
+use common::{build_packet, packet_builder, tcp_option_kinds};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::{self, TcpOptionNumbers, TcpPacket};
+use pnet::packet::Packet;
+use router_flood::config::{validate_config, Config, TcpOption, TcpOptionList, TcpOptions, TcpOsProfile};
+use router_flood::packet::{PacketOptions, PacketType, TcpOptionLayout};
+
+#[test]
+fn test_presets_on_syn() {
+    let cases = [
+        (TcpOsProfile::Linux, 20, vec![2, 4, 8, 1, 3]),
+        (TcpOsProfile::Windows, 12, vec![2, 1, 3, 1, 1, 4]),
+        // 23 bytes of options plus one EOL byte of padding
+        (TcpOsProfile::Macos, 24, vec![2, 1, 3, 1, 1, 8, 4, 0, 0]),
+    ];
+
+    for (os, options_len, kinds) in cases {
+        let tcp_options = TcpOptionLayout::from_config(&TcpOptions::Preset { os });
+        let mut builder = packet_builder(&PacketOptions { tcp_options, ..Default::default() });
+        let packet = build_packet(&mut builder, PacketType::TCP_SYN, 443);
+        let ip = Ipv4Packet::new(&packet).unwrap();
+        let tcp = TcpPacket::new(ip.payload()).unwrap();
+
+        assert_eq!(packet.len(), 40 + options_len, "{:?}", os);
+        assert_eq!(ip.get_total_length() as usize, packet.len());
+        assert_eq!(tcp.get_data_offset() as usize * 4, 20 + options_len);
+        assert_eq!(tcp_option_kinds(&packet), kinds, "{:?}", os);
+    }
+}
+
+#[test]
+fn test_option_values_and_checksum() {
+    let tcp_options = TcpOptionLayout::from_config(&TcpOptions::Preset { os: TcpOsProfile::Linux });
+    let mut builder = packet_builder(&PacketOptions { tcp_options, ..Default::default() });
+    let packet = build_packet(&mut builder, PacketType::TCP_SYN, 443);
+    let ip = Ipv4Packet::new(&packet).unwrap();
+    let tcp = TcpPacket::new(ip.payload()).unwrap();
+
+    let options: Vec<_> = tcp.get_options_iter().collect();
+    assert_eq!(options[0].get_number(), TcpOptionNumbers::MSS);
+    assert_eq!(options[0].payload(), &1460u16.to_be_bytes());
+    assert_eq!(options[4].get_number(), TcpOptionNumbers::WSCALE);
+    assert_eq!(options[4].payload(), &[7]);
+
+    // SYNs echo no timestamp
+    let timestamps = options[2].payload();
+    assert_eq!(timestamps.len(), 8);
+    assert_eq!(&timestamps[4..], &[0, 0, 0, 0]);
+
+    let checksum = tcp::ipv4_checksum(&tcp, &ip.get_source(), &ip.get_destination());
+    assert_eq!(tcp.get_checksum(), checksum);
+}
+
+#[test]
+fn test_non_syn_segments_carry_only_timestamps() {
+    let tcp_options = TcpOptionLayout::from_config(&TcpOptions::Preset { os: TcpOsProfile::Linux });
+    let mut linux = packet_builder(&PacketOptions { tcp_options, ..Default::default() });
+    for packet_type in [PacketType::TCP_ACK, PacketType::TCP_FIN, PacketType::TCP_RST] {
+        let packet = build_packet(&mut linux, packet_type, 443);
+        assert_eq!(packet.len(), 40 + 12, "{:?}", packet_type);
+        assert_eq!(tcp_option_kinds(&packet), vec![1, 1, 8]);
+    }
+
+    // Without timestamps, only SYNs grow
+    let tcp_options = TcpOptionLayout::from_config(&TcpOptions::Preset { os: TcpOsProfile::Windows });
+    let mut windows = packet_builder(&PacketOptions { tcp_options, ..Default::default() });
+    let packet = build_packet(&mut windows, PacketType::TCP_ACK, 443);
+    assert_eq!(packet.len(), 40);
+    assert!(tcp_option_kinds(&packet).is_empty());
+}
+
+#[test]
+fn test_ipv6_syn_options() {
+    let options: TcpOptions = "mss:1220, sack, ts".parse().unwrap();
+    let tcp_options = TcpOptionLayout::from_config(&options);
+    let mut builder = packet_builder(&PacketOptions { tcp_options, ..Default::default() });
+    let packet = build_packet(&mut builder, PacketType::IPV6_TCP_SYN, 443);
+
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    assert_eq!(ip.get_next_header(), IpNextHeaderProtocols::Tcp);
+    // 16 option bytes: 4 + 2 + 10
+    assert_eq!(ip.get_payload_length(), 36);
+    assert_eq!(packet.len(), 76);
+
+    let tcp = TcpPacket::new(ip.payload()).unwrap();
+    assert_eq!(tcp_option_kinds(&packet), vec![2, 4, 8]);
+    assert_eq!(tcp.get_checksum(), tcp::ipv6_checksum(&tcp, &ip.get_source(), &ip.get_destination()));
+}
+
+#[test]
+fn test_max_packet_size_includes_options() {
+    let layout = TcpOptionLayout::from_config(&TcpOptions::Preset { os: TcpOsProfile::Macos });
+    assert_eq!(layout.len(true), 24);
+    assert_eq!(layout.len(false), 12);
+    assert_eq!(layout.max_len(), 24);
+    assert!(TcpOptionLayout::from_config(&TcpOptions::None).is_empty());
+
+    // An odd-length list is padded to a whole number of words
+    let odd = TcpOptionLayout::from_config(&"ws:3".parse().unwrap());
+    assert_eq!(odd.len(true), 4);
+
+    // Without options the header stays bare
+    let tcp_options = TcpOptionLayout::from_config(&TcpOptions::None);
+    let mut plain = packet_builder(&PacketOptions { tcp_options, ..Default::default() });
+    let packet = build_packet(&mut plain, PacketType::TCP_SYN, 443);
+    assert_eq!(packet.len(), 40);
+    assert!(tcp_option_kinds(&packet).is_empty());
+}
+
+#[test]
+fn test_option_parsing_and_validation() {
+    assert_eq!("none".parse::<TcpOptions>().unwrap(), TcpOptions::None);
+    assert_eq!("Windows".parse::<TcpOptions>().unwrap(), TcpOptions::Preset { os: TcpOsProfile::Windows });
+    assert_eq!(
+        "mss:1400, nop, ws:2, sack_permitted".parse::<TcpOptionList>().unwrap(),
+        TcpOptionList(vec![TcpOption::Mss(1400), TcpOption::Nop, TcpOption::WindowScale(2), TcpOption::SackPermitted])
+    );
+    for invalid in ["mss", "mss:70000", "nop:1", "bsd", "sack,,ts"] {
+        assert!(invalid.parse::<TcpOptions>().is_err(), "accepted {:?}", invalid);
+    }
+
+    let mut config = Config::default();
+    config.attack.tcp_options = TcpOptions::Preset { os: TcpOsProfile::Macos };
+    assert!(validate_config(&config).is_ok());
+
+    for invalid in ["mss:0", "ws:15", "ts,ts,ts,ts,ts"] {
+        config.attack.tcp_options = invalid.parse().unwrap();
+        assert!(validate_config(&config).is_err(), "accepted {:?}", invalid);
+    }
+}
+
+#[test]
+fn test_tcp_options_yaml() {
+    let parse = |yaml: &str| serde_yaml::from_str::<TcpOptions>(yaml).unwrap();
+
+    assert_eq!(parse("type: none"), TcpOptions::None);
+    assert_eq!(parse("type: preset\nos: macos"), TcpOptions::Preset { os: TcpOsProfile::Macos });
+    assert_eq!(
+        parse("type: list\noptions: \"mss:1460, timestamps\""),
+        TcpOptions::List { options: TcpOptionList(vec![TcpOption::Mss(1460), TcpOption::Timestamps]) }
+    );
+
+    let list = TcpOsProfile::Linux.options();
+    assert_eq!(list.to_string(), "mss:1460, sack_permitted, timestamps, nop, window_scale:7");
+    assert_eq!(list.to_string().parse::<TcpOptionList>().unwrap(), list);
+}
@@ -0,0 +1,109 @@
+//! TCP payload tests
+
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::ipv6::Ipv6Packet;
+use pnet::packet::tcp::{self, TcpPacket};
+use pnet::packet::Packet;
+use router_flood::config::{
+    validate_config, Config, PayloadPattern, PayloadSizes, SizeDistribution, TcpFlagSet, TcpPayload,
+};
+use router_flood::packet::{PacketBuilder, PacketOptions, PacketSizes, PacketType, PayloadFill};
+use std::net::IpAddr;
+
+fn builder(tcp_payload: TcpPayload, tcp: SizeDistribution) -> PacketBuilder {
+    let mut sizes = PacketSizes::from_config(&Config::default().attack);
+    sizes.tcp = tcp;
+    let options = PacketOptions {
+        payload: PayloadFill::from_pattern(&PayloadPattern::Incrementing).unwrap(),
+        tcp_payload,
+        ..Default::default()
+    };
+    PacketBuilder::with_options(&sizes, &options, Default::default())
+}
+
+/// Build an IPv4 segment with the given flags, returning it with its TCP payload
+fn build_ipv4(builder: &mut PacketBuilder, flags: TcpFlagSet) -> (Vec<u8>, Vec<u8>) {
+    let target: IpAddr = "192.168.1.1".parse().unwrap();
+    let (packet, _) = builder.build_packet(PacketType::Tcp(flags), target, 80).unwrap();
+    let ip = Ipv4Packet::new(&packet).unwrap();
+    let tcp = TcpPacket::new(ip.payload()).unwrap();
+    assert_eq!(ip.get_total_length() as usize, packet.len());
+    assert_eq!(tcp.get_checksum(), tcp::ipv4_checksum(&tcp, &ip.get_source(), &ip.get_destination()));
+    let payload = tcp.payload().to_vec();
+    (packet, payload)
+}
+
+#[test]
+fn test_push_segments_carry_payload() {
+    let mut builder = builder(TcpPayload::Push, SizeDistribution::Fixed { size: 100 });
+
+    let (packet, payload) = build_ipv4(&mut builder, TcpFlagSet::PSH_ACK);
+    assert_eq!(packet.len(), 40 + 100);
+    assert_eq!(payload, (0..100).collect::<Vec<u8>>());
+
+    // Segments without PSH stay header-only
+    for flags in [TcpFlagSet::SYN, TcpFlagSet::ACK, TcpFlagSet::FIN_ACK, TcpFlagSet::NULL] {
+        let (packet, payload) = build_ipv4(&mut builder, flags);
+        assert_eq!(packet.len(), 40, "{}", flags);
+        assert!(payload.is_empty());
+    }
+
+    // XMAS has PSH set
+    assert_eq!(build_ipv4(&mut builder, TcpFlagSet::XMAS).0.len(), 140);
+}
+
+#[test]
+fn test_payload_modes() {
+    let mut none = builder(TcpPayload::None, SizeDistribution::Fixed { size: 100 });
+    assert_eq!(build_ipv4(&mut none, TcpFlagSet::PSH_ACK).0.len(), 40);
+
+    let mut all = builder(TcpPayload::All, SizeDistribution::Range { min: 200, max: 1200 });
+    for flags in [TcpFlagSet::SYN, TcpFlagSet::ACK, TcpFlagSet::SYN_FIN] {
+        for _ in 0..50 {
+            let (packet, payload) = build_ipv4(&mut all, flags);
+            assert!((200..=1200).contains(&payload.len()), "{}: {}", flags, payload.len());
+            assert_eq!(packet.len(), 40 + payload.len());
+        }
+    }
+
+    let target: IpAddr = "2001:db8::1".parse().unwrap();
+    let (packet, _) = all.build_packet(PacketType::IPV6_TCP_SYN, target, 80).unwrap();
+    let ip = Ipv6Packet::new(&packet).unwrap();
+    let tcp = TcpPacket::new(ip.payload()).unwrap();
+    assert!(tcp.payload().len() >= 200);
+    assert_eq!(ip.get_payload_length() as usize, packet.len() - 40);
+    assert_eq!(tcp.get_checksum(), tcp::ipv6_checksum(&tcp, &ip.get_source(), &ip.get_destination()));
+}
+
+#[test]
+fn test_tcp_sizes_resolved() {
+    let mut config = Config::default();
+    config.attack.payload_size = 512;
+    assert_eq!(config.attack.tcp_payload, TcpPayload::Push);
+    assert_eq!(PacketSizes::from_config(&config.attack).tcp, SizeDistribution::Fixed { size: 512 });
+
+    config.attack.payload_sizes = PayloadSizes {
+        default: Some(SizeDistribution::Range { min: 64, max: 1400 }),
+        tcp: Some(SizeDistribution::Fixed { size: 1200 }),
+        ..Default::default()
+    };
+    let sizes = PacketSizes::from_config(&config.attack);
+    assert_eq!(sizes.tcp, SizeDistribution::Fixed { size: 1200 });
+    assert_eq!(sizes.max_payload(), 1400);
+    assert!(validate_config(&config).is_ok());
+
+    config.attack.payload_sizes.tcp = Some(SizeDistribution::Range { min: 100, max: 10 });
+    assert!(validate_config(&config).is_err());
+}
+
+#[test]
+fn test_tcp_payload_yaml() {
+    let yaml = serde_yaml::to_string(&Config::default()).unwrap();
+    assert!(yaml.contains("tcp_payload: push"));
+
+    let config: Config = serde_yaml::from_str(&yaml.replace("tcp_payload: push", "tcp_payload: all")).unwrap();
+    assert_eq!(config.attack.tcp_payload, TcpPayload::All);
+
+    assert_eq!("PSH".parse::<TcpPayload>().unwrap(), TcpPayload::Push);
+    assert!("some".parse::<TcpPayload>().is_err());
+}
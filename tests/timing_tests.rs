@@ -0,0 +1,114 @@
+//! Inter-arrival timing model tests
+
+use router_flood::config::{validate_config, Config, LoadConfig, TimingModel};
+use router_flood::network::timing::ArrivalProcess;
+use router_flood::utils::rng::BatchedRng;
+use std::time::Duration;
+
+const INTERVAL: Duration = Duration::from_micros(100);
+
+/// Sample `count` gaps in microseconds
+fn sample(model: TimingModel, count: usize) -> Vec<f64> {
+    let mut process = ArrivalProcess::new(model);
+    (0..count)
+        .map(|_| process.next_gap(INTERVAL).as_secs_f64() * 1e6)
+        .collect()
+}
+
+fn mean(gaps: &[f64]) -> f64 {
+    gaps.iter().sum::<f64>() / gaps.len() as f64
+}
+
+fn coefficient_of_variation(gaps: &[f64]) -> f64 {
+    let mean = mean(gaps);
+    let variance = gaps.iter().map(|gap| (gap - mean).powi(2)).sum::<f64>() / gaps.len() as f64;
+    variance.sqrt() / mean
+}
+
+#[test]
+fn test_batched_rng_unit_range() {
+    let mut rng = BatchedRng::with_batch_size(100);
+    let values: Vec<f64> = (0..1000).map(|_| rng.unit()).collect();
+    assert!(values.iter().all(|value| (0.0..1.0).contains(value)));
+    assert!((mean(&values) - 0.5).abs() < 0.05);
+}
+
+#[test]
+fn test_constant_gaps() {
+    let gaps = sample(TimingModel::Constant, 100);
+    assert!(gaps.iter().all(|gap| (gap - 100.0).abs() < 1e-6));
+}
+
+#[test]
+fn test_poisson_gaps_are_exponential() {
+    let gaps = sample(TimingModel::Poisson, 100_000);
+    assert!((mean(&gaps) - 100.0).abs() < 2.0, "mean {}", mean(&gaps));
+
+    // An exponential distribution has a coefficient of variation of 1
+    let cv = coefficient_of_variation(&gaps);
+    assert!((cv - 1.0).abs() < 0.05, "cv {}", cv);
+}
+
+#[test]
+fn test_uniform_jitter_bounds() {
+    let gaps = sample(TimingModel::Uniform { jitter: 0.2 }, 10_000);
+    assert!(gaps.iter().all(|gap| (80.0..=120.0).contains(gap)));
+    assert!((mean(&gaps) - 100.0).abs() < 1.0, "mean {}", mean(&gaps));
+}
+
+#[test]
+fn test_pareto_gaps_are_heavy_tailed() {
+    let gaps = sample(TimingModel::Pareto { shape: 2.5 }, 200_000);
+    assert!((mean(&gaps) - 100.0).abs() < 10.0, "mean {}", mean(&gaps));
+
+    // Minimum is the Pareto scale, the tail reaches far beyond the mean
+    let min = gaps.iter().cloned().fold(f64::MAX, f64::min);
+    let max = gaps.iter().cloned().fold(0.0, f64::max);
+    assert!(min >= 60.0 - 1e-6, "min {}", min);
+    assert!(max > 2000.0, "max {}", max);
+}
+
+#[test]
+fn test_on_off_preserves_mean_rate() {
+    let model = TimingModel::OnOff { shape: 2.5, mean_on_ms: 10.0, mean_off_ms: 10.0 };
+    let gaps = sample(model, 200_000);
+
+    // Packets are 50 us apart while ON and separated by OFF periods
+    let on_gaps = gaps.iter().filter(|gap| (*gap - 50.0).abs() < 1e-6).count();
+    assert!(on_gaps > gaps.len() * 9 / 10, "on gaps {}", on_gaps);
+    assert!(gaps.iter().any(|gap| *gap > 1000.0));
+
+    assert!((mean(&gaps) - 100.0).abs() < 10.0, "mean {}", mean(&gaps));
+}
+
+#[test]
+fn test_timing_config_parsing_and_validation() {
+    let load: LoadConfig = serde_yaml::from_str(
+        "threads: 2\npacket_rate: 100\npayload_size: 64\nduration: 10\nburst_mode: false\ntiming:\n  type: pareto\n  shape: 1.8\n",
+    )
+    .unwrap();
+    assert_eq!(load.timing, TimingModel::Pareto { shape: 1.8 });
+
+    let mut config = Config::default();
+    assert_eq!(config.attack.timing, TimingModel::Constant);
+
+    for valid in [
+        TimingModel::Poisson,
+        TimingModel::Uniform { jitter: 0.5 },
+        TimingModel::Pareto { shape: 1.8 },
+        TimingModel::OnOff { shape: 1.5, mean_on_ms: 100.0, mean_off_ms: 0.0 },
+    ] {
+        config.attack.timing = valid;
+        assert!(validate_config(&config).is_ok(), "{:?}", config.attack.timing);
+    }
+
+    for invalid in [
+        TimingModel::Uniform { jitter: 1.5 },
+        TimingModel::Pareto { shape: 1.0 },
+        TimingModel::OnOff { shape: 2.0, mean_on_ms: 0.0, mean_off_ms: 10.0 },
+        TimingModel::OnOff { shape: 2.0, mean_on_ms: 10.0, mean_off_ms: -1.0 },
+    ] {
+        config.attack.timing = invalid;
+        assert!(validate_config(&config).is_err(), "{:?}", config.attack.timing);
+    }
+}
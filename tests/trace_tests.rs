@@ -0,0 +1,203 @@
+//! Trace-driven rate replay tests
+
+use router_flood::config::{load_trace, validate_config, Config, ExportFormat, ProtocolMix, RateProfile, TracePoint};
+use router_flood::network::phase::{PhaseControl, PhaseSettings};
+use router_flood::network::rate_profile::RateSchedule;
+use router_flood::network::worker_manager::Workers;
+use router_flood::stats::Stats;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::{NamedTempFile, TempDir};
+
+fn trace_file(contents: &str) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(contents.as_bytes()).unwrap();
+    file
+}
+
+fn only(protocol: &str) -> ProtocolMix {
+    let mut mix = ProtocolMix {
+        udp_ratio: 0.0,
+        tcp_syn_ratio: 0.0,
+        tcp_ack_ratio: 0.0,
+        tcp_fin_ratio: 0.0,
+        tcp_rst_ratio: 0.0,
+        icmp_ratio: 0.0,
+        custom_ratio: 0.0,
+        arp_ratio: 0.0,
+        ipv6: None,
+        tcp_flags: Default::default(),
+    };
+    match protocol {
+        "udp" => mix.udp_ratio = 1.0,
+        "icmp" => mix.icmp_ratio = 1.0,
+        _ => unreachable!(),
+    }
+    mix
+}
+
+fn point(offset_secs: f64, pps: f64) -> TracePoint {
+    TracePoint { offset_secs, pps, protocol_mix: None }
+}
+
+#[test]
+fn test_load_trace_with_header_and_mix() {
+    let file = trace_file(
+        "# incident 2024-03-02\n\
+         offset_seconds,pps,udp_ratio,icmp_ratio\n\
+         0,1000,1.0,\n\
+         30, 5000 ,0.5,0.5\n\
+         60,2000,,\n",
+    );
+
+    let points = load_trace(file.path().to_str().unwrap()).unwrap();
+    assert_eq!(points.len(), 3);
+    assert_eq!(points[1].offset_secs, 30.0);
+    assert_eq!(points[1].pps, 5000.0);
+    assert_eq!(points[0].protocol_mix, Some(only("udp")));
+
+    let mix = points[1].protocol_mix.as_ref().unwrap();
+    assert_eq!((mix.udp_ratio, mix.icmp_ratio, mix.tcp_syn_ratio), (0.5, 0.5, 0.0));
+    assert!(points[2].protocol_mix.is_none());
+}
+
+#[test]
+fn test_load_trace_without_header() {
+    let file = trace_file("0,100\n10,200\n20.5,50\n");
+    let points = load_trace(file.path().to_str().unwrap()).unwrap();
+    assert_eq!(points, vec![point(0.0, 100.0), point(10.0, 200.0), point(20.5, 50.0)]);
+}
+
+#[test]
+fn test_load_trace_errors() {
+    assert!(load_trace("/nonexistent/trace.csv").is_err());
+
+    for contents in [
+        "time,pps\n0,100\n",
+        "offset_seconds,pps\n0,fast\n",
+        "offset_seconds,pps,tcp_ratio\n0,100,1.0\n",
+        "0\n",
+    ] {
+        let file = trace_file(contents);
+        assert!(load_trace(file.path().to_str().unwrap()).is_err(), "accepted {:?}", contents);
+    }
+}
+
+#[test]
+fn test_trace_validation() {
+    let mut config = Config::default();
+    config.attack.threads = 4;
+
+    config.attack.rate_profile = RateProfile::Trace { points: vec![point(0.0, 100.0), point(10.0, 20_000.0)] };
+    assert!(validate_config(&config).is_ok());
+
+    let invalid = [
+        vec![],
+        vec![point(10.0, 100.0), point(5.0, 100.0)],
+        vec![point(0.0, -1.0)],
+        // 4 threads at more than 1M pps each
+        vec![point(0.0, 5_000_000.0)],
+        vec![TracePoint { offset_secs: 0.0, pps: 100.0, protocol_mix: Some(ProtocolMix { udp_ratio: 0.5, ..only("icmp") }) }],
+    ];
+    for points in invalid {
+        config.attack.rate_profile = RateProfile::Trace { points: points.clone() };
+        assert!(validate_config(&config).is_err(), "accepted {:?}", points);
+    }
+}
+
+#[test]
+fn test_trace_interpolation_split_across_threads() {
+    let profile = RateProfile::Trace { points: vec![point(10.0, 400.0), point(20.0, 800.0), point(30.0, 0.0)] };
+    assert_eq!(profile.trace_duration_secs(), Some(30));
+
+    let schedule = RateSchedule::new(profile, 100.0, Instant::now()).with_threads(4);
+    let rate = |secs: f64| schedule.rate_after(Duration::from_secs_f64(secs));
+
+    // Held before the first and after the last point
+    assert_eq!(rate(0.0), 100.0);
+    assert_eq!(rate(10.0), 100.0);
+    assert!((rate(15.0) - 150.0).abs() < 1e-9);
+    assert!((rate(25.0) - 100.0).abs() < 1e-9);
+    assert_eq!(rate(60.0), 0.0);
+    assert!(!schedule.is_constant());
+}
+
+#[test]
+fn test_trace_mix_held_until_next_mix() {
+    let points = vec![
+        point(0.0, 100.0),
+        TracePoint { offset_secs: 1.0, pps: 100.0, protocol_mix: Some(only("udp")) },
+        point(2.0, 100.0),
+        TracePoint { offset_secs: 3.0, pps: 100.0, protocol_mix: Some(only("icmp")) },
+    ];
+    let epoch = Instant::now();
+    let schedule = RateSchedule::new(RateProfile::Trace { points }, 100.0, epoch);
+    let mix_at = |secs: f64| {
+        schedule
+            .mix_at(epoch + Duration::from_secs_f64(secs))
+            .map(|(index, mix)| (index, mix.icmp_ratio))
+    };
+
+    assert_eq!(mix_at(0.5), None);
+    assert_eq!(mix_at(1.0), Some((1, 0.0)));
+    assert_eq!(mix_at(2.5), Some((1, 0.0)));
+    assert_eq!(mix_at(10.0), Some((3, 1.0)));
+
+    let constant = RateSchedule::new(RateProfile::Constant, 100.0, epoch);
+    assert!(constant.mix_at(epoch).is_none());
+}
+
+#[tokio::test]
+async fn test_workers_follow_trace_mix() {
+    let dir = TempDir::new().unwrap();
+    let mut config = Config::default();
+    config.attack.threads = 1;
+    config.safety.dry_run = true;
+    config.safety.perfect_simulation = true;
+    config.attack.rate_profile = RateProfile::Trace {
+        points: vec![
+            TracePoint { offset_secs: 0.0, pps: 1000.0, protocol_mix: Some(only("udp")) },
+            TracePoint { offset_secs: 0.2, pps: 1000.0, protocol_mix: Some(only("icmp")) },
+        ],
+    };
+    config.export.enabled = true;
+    config.export.format = ExportFormat::Json;
+    config.export.path = dir.path().to_str().unwrap().to_string();
+
+    let stats = Arc::new(Stats::new(Some(config.export.clone())));
+    let control = Arc::new(PhaseControl::new(PhaseSettings::from_config(&config, None, Instant::now())));
+    let workers = Workers::new(&config, Arc::clone(&stats), control, "192.168.1.1".parse().unwrap(), None, true).unwrap();
+
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    workers.stop();
+    workers.join_all().await.unwrap();
+    stats.export_stats().await.unwrap();
+
+    let entry = std::fs::read_dir(dir.path()).unwrap().next().unwrap().unwrap();
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(entry.path()).unwrap()).unwrap();
+    let breakdown = &json["protocol_breakdown"];
+    assert!(breakdown["UDP"].as_u64().unwrap_or(0) > 0, "{}", breakdown);
+    assert!(breakdown["ICMP"].as_u64().unwrap_or(0) > 0, "{}", breakdown);
+    assert_eq!(breakdown["TCP"].as_u64().unwrap_or(0), 0, "{}", breakdown);
+}
+
+#[tokio::test]
+async fn test_workers_idle_during_zero_trace_section() {
+    let mut config = Config::default();
+    config.attack.threads = 2;
+    config.safety.dry_run = true;
+    config.safety.perfect_simulation = true;
+    // An outage from the start, after which the trace stays at zero
+    config.attack.rate_profile = RateProfile::Trace { points: vec![point(0.0, 0.0), point(0.2, 0.0)] };
+
+    let stats = Arc::new(Stats::new(None));
+    let control = Arc::new(PhaseControl::new(PhaseSettings::from_config(&config, None, Instant::now())));
+    let workers = Workers::new(&config, Arc::clone(&stats), control, "192.168.1.1".parse().unwrap(), None, true).unwrap();
+
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    workers.stop();
+    workers.join_all().await.unwrap();
+    assert_eq!(stats.packets_sent(), 0);
+    assert_eq!(stats.packets_failed(), 0);
+}
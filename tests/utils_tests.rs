@@ -23,8 +23,8 @@ mod protocol_utils_tests {
     #[test]
     fn test_packet_type_to_protocol_name() {
         assert_eq!(ProtocolUtils::packet_type_to_protocol_name(PacketType::Udp), "UDP");
-        assert_eq!(ProtocolUtils::packet_type_to_protocol_name(PacketType::TcpSyn), "TCP");
-        assert_eq!(ProtocolUtils::packet_type_to_protocol_name(PacketType::TcpAck), "TCP");
+        assert_eq!(ProtocolUtils::packet_type_to_protocol_name(PacketType::TCP_SYN), "TCP");
+        assert_eq!(ProtocolUtils::packet_type_to_protocol_name(PacketType::TCP_ACK), "TCP");
         assert_eq!(ProtocolUtils::packet_type_to_protocol_name(PacketType::Icmp), "ICMP");
         assert_eq!(ProtocolUtils::packet_type_to_protocol_name(PacketType::Ipv6Udp), "IPv6");
     }